@@ -0,0 +1,110 @@
+//! Command latency on an account with a long history, with and without snapshots.
+//!
+//! Runs against the database in `DATABASE_URL` with the migrations applied, e.g. after
+//! `make dev`: `cargo bench --bench snapshots`, or in memory with
+//! `DATABASE_URL=memory: cargo bench --bench snapshots`
+
+use cqrs_es::{CqrsFramework, EventStore};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use example_veloxide_api::any_es::{AnyDatabase, AnyEventRepository};
+use example_veloxide_api::application::{
+    BankAccountServices, FxRateTable, HappyPathBankAccountServices, SnapshotPolicy,
+};
+use example_veloxide_api::configuration::{get_db_connection, DatabaseConfiguration};
+use example_veloxide_api::domain::{
+    AccountProduct, BankAccount, BankAccountCommand, BankAccountDepositMoneyCommandData,
+    BankAccountOpenAccountCommandData, Currency, Money, BANK_ACCOUNT_SNAPSHOT_VERSION,
+};
+
+const HISTORY_LENGTHS: [usize; 2] = [1_000, 5_000];
+const SNAPSHOT_EVERY_N_EVENTS: usize = 100;
+
+fn services() -> BankAccountServices {
+    BankAccountServices::new(Box::new(HappyPathBankAccountServices::new(
+        FxRateTable::default(),
+    )))
+}
+
+// Frameworks without queries, so that only loading and committing the aggregate is measured.
+fn framework(
+    db: AnyDatabase,
+    snapshots: SnapshotPolicy,
+) -> CqrsFramework<BankAccount, impl EventStore<BankAccount>> {
+    let events = AnyEventRepository::new(db).with_snapshot_version(BANK_ACCOUNT_SNAPSHOT_VERSION);
+    let event_store = snapshots.event_store(events);
+    CqrsFramework::new(event_store, vec![], services())
+}
+
+fn deposit() -> BankAccountCommand {
+    BankAccountCommand::DepositMoney(BankAccountDepositMoneyCommandData {
+        amount: Money::new(100, Currency::EUR),
+    })
+}
+
+async fn open_account_with_history<ES: EventStore<BankAccount>>(
+    cqrs: &CqrsFramework<BankAccount, ES>,
+    events: usize,
+) -> String {
+    let account_id = uuid::Uuid::new_v4().to_string();
+    cqrs.execute(
+        &account_id,
+        BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+            account_id: account_id.clone(),
+            currency: Currency::EUR,
+            primary_holder_id: "bench-holder".to_string(),
+            product: AccountProduct::Checking,
+        }),
+    )
+    .await
+    .expect("account could not be opened");
+    for _ in 1..events {
+        cqrs.execute(&account_id, deposit())
+            .await
+            .expect("deposit failed");
+    }
+    account_id
+}
+
+fn command_latency(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let db = runtime
+        .block_on(get_db_connection(&DatabaseConfiguration::default()))
+        .expect("could not connect to the database in DATABASE_URL");
+    let with_snapshots = framework(
+        db.clone(),
+        SnapshotPolicy {
+            every_n_events: Some(SNAPSHOT_EVERY_N_EVENTS),
+        },
+    );
+    let without_snapshots = framework(db, SnapshotPolicy::default());
+
+    let mut group = c.benchmark_group("deposit_command_latency");
+    group.sample_size(20);
+    for history_length in HISTORY_LENGTHS {
+        // The history is built with snapshots on, the framework without them ignores the
+        // snapshots and replays every event.
+        let account_id =
+            runtime.block_on(open_account_with_history(&with_snapshots, history_length));
+        group.bench_with_input(
+            BenchmarkId::new("with_snapshots", history_length),
+            &account_id,
+            |b, account_id| {
+                b.to_async(&runtime)
+                    .iter(|| async { with_snapshots.execute(account_id, deposit()).await })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("without_snapshots", history_length),
+            &account_id,
+            |b, account_id| {
+                b.to_async(&runtime)
+                    .iter(|| async { without_snapshots.execute(account_id, deposit()).await })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, command_latency);
+criterion_main!(benches);
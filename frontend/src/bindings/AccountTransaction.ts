@@ -1,3 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountTransactionType } from "./AccountTransactionType";
+import type { Money } from "./Money";
 
-export interface AccountTransaction { description: string, amount: number, }
\ No newline at end of file
+export interface AccountTransaction { transaction_type: AccountTransactionType, description: string, amount: Money, sequence: number | null, reversed: boolean, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface Address { line1: string, line2: string | null, city: string, postal_code: string, country: string, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface BankAccountAccrueInterestCommandData { annual_rate_basis_points: number, period_start: string, period_end: string, }
\ No newline at end of file
@@ -1,7 +1,28 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BankAccountAccrueInterestCommandData } from "./BankAccountAccrueInterestCommandData";
+import type { BankAccountAddHolderCommandData } from "./BankAccountAddHolderCommandData";
+import type { BankAccountBounceCheckCommandData } from "./BankAccountBounceCheckCommandData";
+import type { BankAccountCancelStandingOrderCommandData } from "./BankAccountCancelStandingOrderCommandData";
+import type { BankAccountCaptureHoldCommandData } from "./BankAccountCaptureHoldCommandData";
+import type { BankAccountChargeMaintenanceFeeCommandData } from "./BankAccountChargeMaintenanceFeeCommandData";
+import type { BankAccountClearCheckCommandData } from "./BankAccountClearCheckCommandData";
+import type { BankAccountCloseAccountCommandData } from "./BankAccountCloseAccountCommandData";
+import type { BankAccountCompleteTransferCommandData } from "./BankAccountCompleteTransferCommandData";
+import type { BankAccountCreateStandingOrderCommandData } from "./BankAccountCreateStandingOrderCommandData";
 import type { BankAccountDepositMoneyCommandData } from "./BankAccountDepositMoneyCommandData";
+import type { BankAccountExecuteStandingOrderCommandData } from "./BankAccountExecuteStandingOrderCommandData";
+import type { BankAccountFreezeAccountCommandData } from "./BankAccountFreezeAccountCommandData";
 import type { BankAccountOpenAccountCommandData } from "./BankAccountOpenAccountCommandData";
+import type { BankAccountPlaceHoldCommandData } from "./BankAccountPlaceHoldCommandData";
+import type { BankAccountRefundTransferCommandData } from "./BankAccountRefundTransferCommandData";
+import type { BankAccountReleaseHoldCommandData } from "./BankAccountReleaseHoldCommandData";
+import type { BankAccountRemoveHolderCommandData } from "./BankAccountRemoveHolderCommandData";
+import type { BankAccountReverseTransactionCommandData } from "./BankAccountReverseTransactionCommandData";
+import type { BankAccountSetOverdraftLimitCommandData } from "./BankAccountSetOverdraftLimitCommandData";
+import type { BankAccountTransferInCommandData } from "./BankAccountTransferInCommandData";
+import type { BankAccountTransferOutCommandData } from "./BankAccountTransferOutCommandData";
+import type { BankAccountUnfreezeAccountCommandData } from "./BankAccountUnfreezeAccountCommandData";
 import type { BankAccountWithdrawMoneyCommandData } from "./BankAccountWithdrawMoneyCommandData";
 import type { BankAccountWriteCheckCommandData } from "./BankAccountWriteCheckCommandData";
 
-export type BankAccountCommand = { OpenAccount: BankAccountOpenAccountCommandData } | { DepositMoney: BankAccountDepositMoneyCommandData } | { WithdrawMoney: BankAccountWithdrawMoneyCommandData } | { WriteCheck: BankAccountWriteCheckCommandData };
\ No newline at end of file
+export type BankAccountCommand = { OpenAccount: BankAccountOpenAccountCommandData } | { DepositMoney: BankAccountDepositMoneyCommandData } | { WithdrawMoney: BankAccountWithdrawMoneyCommandData } | { WriteCheck: BankAccountWriteCheckCommandData } | { FreezeAccount: BankAccountFreezeAccountCommandData } | { UnfreezeAccount: BankAccountUnfreezeAccountCommandData } | { CloseAccount: BankAccountCloseAccountCommandData } | { SetOverdraftLimit: BankAccountSetOverdraftLimitCommandData } | { AccrueInterest: BankAccountAccrueInterestCommandData } | { TransferOut: BankAccountTransferOutCommandData } | { TransferIn: BankAccountTransferInCommandData } | { RefundTransfer: BankAccountRefundTransferCommandData } | { CompleteTransfer: BankAccountCompleteTransferCommandData } | { PlaceHold: BankAccountPlaceHoldCommandData } | { CaptureHold: BankAccountCaptureHoldCommandData } | { ReleaseHold: BankAccountReleaseHoldCommandData } | { AddHolder: BankAccountAddHolderCommandData } | { RemoveHolder: BankAccountRemoveHolderCommandData } | { ClearCheck: BankAccountClearCheckCommandData } | { BounceCheck: BankAccountBounceCheckCommandData } | { ReverseTransaction: BankAccountReverseTransactionCommandData } | { ChargeMaintenanceFee: BankAccountChargeMaintenanceFeeCommandData } | { CreateStandingOrder: BankAccountCreateStandingOrderCommandData } | { CancelStandingOrder: BankAccountCancelStandingOrderCommandData } | { ExecuteStandingOrder: BankAccountExecuteStandingOrderCommandData };
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Money } from "./Money";
+
+export interface BankAccountCreateStandingOrderCommandData { standing_order_id: string, amount: Money, day_of_month: number, description: string, }
\ No newline at end of file
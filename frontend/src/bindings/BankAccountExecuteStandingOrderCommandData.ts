@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface BankAccountExecuteStandingOrderCommandData { standing_order_id: string, due_date: string, }
\ No newline at end of file
@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountProduct } from "./AccountProduct";
 
-export interface BankAccountOpenAccountCommandData { account_id: string, }
\ No newline at end of file
+export interface BankAccountOpenAccountCommandData { account_id: string, currency: string, primary_holder_id: string, product: AccountProduct, }
\ No newline at end of file
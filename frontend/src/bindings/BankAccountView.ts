@@ -1,4 +1,9 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountProduct } from "./AccountProduct";
+import type { AccountStatus } from "./AccountStatus";
 import type { AccountTransaction } from "./AccountTransaction";
+import type { CheckRecord } from "./CheckRecord";
+import type { Money } from "./Money";
+import type { PendingHold } from "./PendingHold";
 
-export interface BankAccountView { account_id: string | null, balance: number, written_checks: Array<string>, account_transactions: Array<AccountTransaction>, }
\ No newline at end of file
+export interface BankAccountView { account_id: string | null, product: AccountProduct, primary_holder_id: string | null, holder_ids: Array<string>, status: AccountStatus, balance: Money, overdraft_limit: Money, available_balance: Money, written_checks: Array<CheckRecord>, account_transactions: Array<AccountTransaction>, pending_holds: Array<PendingHold>, }
\ No newline at end of file
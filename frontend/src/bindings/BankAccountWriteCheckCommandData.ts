@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Money } from "./Money";
 
-export interface BankAccountWriteCheckCommandData { check_number: string, amount: number, }
\ No newline at end of file
+export interface BankAccountWriteCheckCommandData { check_number: string, amount: Money, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckStatus } from "./CheckStatus";
+import type { Money } from "./Money";
+
+export interface CheckRecord { check_number: string, amount: Money, status: CheckStatus, }
\ No newline at end of file
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CustomerChangeAddressCommandData } from "./CustomerChangeAddressCommandData";
+import type { CustomerRegisterCustomerCommandData } from "./CustomerRegisterCustomerCommandData";
+import type { CustomerRejectKycCommandData } from "./CustomerRejectKycCommandData";
+import type { CustomerSubmitKycCommandData } from "./CustomerSubmitKycCommandData";
+import type { CustomerVerifyKycCommandData } from "./CustomerVerifyKycCommandData";
+
+export type CustomerCommand = { RegisterCustomer: CustomerRegisterCustomerCommandData } | { ChangeAddress: CustomerChangeAddressCommandData } | { SubmitKyc: CustomerSubmitKycCommandData } | { VerifyKyc: CustomerVerifyKycCommandData } | { RejectKyc: CustomerRejectKycCommandData };
\ No newline at end of file
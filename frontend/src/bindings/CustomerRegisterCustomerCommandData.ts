@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Address } from "./Address";
+
+export interface CustomerRegisterCustomerCommandData { name: string, email: string, address: Address, }
\ No newline at end of file
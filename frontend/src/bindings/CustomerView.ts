@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Address } from "./Address";
+import type { KycStatus } from "./KycStatus";
+
+export interface CustomerView { customer_id: string, name: string, email: string, address: Address | null, kyc_status: KycStatus, kyc_rejection_reason: string | null, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LedgerAccount } from "./LedgerAccount";
+import type { Money } from "./Money";
+
+export interface LedgerBalance { ledger_account: LedgerAccount, debits: Money, credits: Money, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface StandingOrderPayment { due_date: string, occurred_at: string, failure_reason: string | null, }
\ No newline at end of file
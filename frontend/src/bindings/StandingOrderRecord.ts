@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Money } from "./Money";
+import type { StandingOrderPayment } from "./StandingOrderPayment";
+import type { StandingOrderStatus } from "./StandingOrderStatus";
+
+export interface StandingOrderRecord { standing_order_id: string, amount: Money, day_of_month: number, description: string, status: StandingOrderStatus, next_due_date: string | null, payments: Array<StandingOrderPayment>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StandingOrderRecord } from "./StandingOrderRecord";
+
+export interface StandingOrdersView { account_id: string | null, standing_orders: Array<StandingOrderRecord>, }
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Money } from "./Money";
+import type { StatementLine } from "./StatementLine";
+import type { StatementTotal } from "./StatementTotal";
+
+export interface Statement { period: string, opening_balance: Money, closing_balance: Money, totals: Array<StatementTotal>, lines: Array<StatementLine>, }
\ No newline at end of file
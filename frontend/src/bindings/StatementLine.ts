@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountTransactionType } from "./AccountTransactionType";
+import type { Money } from "./Money";
+
+export interface StatementLine { occurred_at: string, transaction_type: AccountTransactionType, description: string, amount: Money, balance: Money, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountTransactionType } from "./AccountTransactionType";
+import type { Money } from "./Money";
+
+export interface StatementTotal { transaction_type: AccountTransactionType, amount: Money, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Money } from "./Money";
+
+export interface TransferRequestTransferCommandData { source_account_id: string, destination_account_id: string, amount: Money, }
\ No newline at end of file
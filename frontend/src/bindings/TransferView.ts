@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Money } from "./Money";
+import type { TransferStatus } from "./TransferStatus";
+
+export interface TransferView { transfer_id: string, source_account_id: string, destination_account_id: string, amount: Money, status: TransferStatus, failure_reason: string | null, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LedgerBalance } from "./LedgerBalance";
+import type { TrialBalanceTotal } from "./TrialBalanceTotal";
+
+export interface TrialBalance { accounts: Array<LedgerBalance>, totals: Array<TrialBalanceTotal>, }
\ No newline at end of file
@@ -0,0 +1,11 @@
+-- Aggregate snapshots written by the mysql-es event store, see SnapshotPolicy.
+CREATE TABLE IF NOT EXISTS snapshots
+(
+    aggregate_type   varchar(255)                        NOT NULL,
+    aggregate_id     varchar(255)                        NOT NULL,
+    last_sequence    bigint CHECK (last_sequence >= 0)    NOT NULL,
+    current_snapshot bigint CHECK (current_snapshot >= 0) NOT NULL,
+    payload          json                                NOT NULL,
+    `timestamp`      timestamp DEFAULT (CURRENT_TIMESTAMP),
+    PRIMARY KEY (aggregate_type, aggregate_id, last_sequence)
+);
@@ -0,0 +1,15 @@
+-- Idempotency keys of commands and their outcomes, see IdempotentCommands. Keys are only
+-- unique within their scope. The request id is recorded with the events the request commits,
+-- and the key is claimed again when retrying an attempt that was released or abandoned.
+CREATE TABLE IF NOT EXISTS idempotency_keys
+(
+    scope           varchar(255) NOT NULL,
+    idempotency_key varchar(255) NOT NULL,
+    request         text         NOT NULL,
+    request_id      varchar(255) NOT NULL,
+    outcome         text,
+    created_at      timestamp(6) NOT NULL,
+    claimed_at      timestamp(6),
+    PRIMARY KEY (scope, idempotency_key),
+    INDEX idempotency_keys_created_at (created_at)
+);
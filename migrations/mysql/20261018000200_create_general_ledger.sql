@@ -0,0 +1,22 @@
+-- The general ledger, see GeneralLedger. One entry per bank account event that moves money.
+CREATE TABLE IF NOT EXISTS ledger_entries
+(
+    account_id varchar(255) NOT NULL,
+    sequence   bigint       NOT NULL,
+    reference  varchar(255),
+    PRIMARY KEY (account_id, sequence),
+    INDEX ledger_entries_reference (account_id, reference)
+);
+
+-- Debits are positive and credits negative, the postings of an entry sum to zero.
+CREATE TABLE IF NOT EXISTS ledger_postings
+(
+    account_id     varchar(255) NOT NULL,
+    sequence       bigint       NOT NULL,
+    line           int          NOT NULL,
+    ledger_account varchar(64)  NOT NULL,
+    currency       char(3)      NOT NULL,
+    amount         bigint       NOT NULL,
+    PRIMARY KEY (account_id, sequence, line),
+    FOREIGN KEY (account_id, sequence) REFERENCES ledger_entries (account_id, sequence)
+);
@@ -0,0 +1,8 @@
+-- Bank account events that could not be posted to the general ledger yet, see GeneralLedger.
+CREATE TABLE IF NOT EXISTS ledger_unposted_events
+(
+    account_id varchar(255) NOT NULL,
+    sequence   bigint       NOT NULL,
+    error      text         NOT NULL,
+    PRIMARY KEY (account_id, sequence)
+);
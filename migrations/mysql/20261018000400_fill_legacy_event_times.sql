@@ -0,0 +1,13 @@
+-- Bank account events written before version 2.0 did not record when they happened, they
+-- are given the time they were stored instead, see bank_account_event_upcasters.
+UPDATE events
+SET payload = JSON_SET(
+        payload,
+        CONCAT('$.', event_type, '.occurred_at'),
+        DATE_FORMAT(CONVERT_TZ(`timestamp`, @@session.time_zone, '+00:00'), '%Y-%m-%dT%H:%i:%sZ')
+    )
+WHERE aggregate_type = 'account'
+  AND event_type IN ('AccountOpened', 'CustomerDepositedMoney', 'CustomerWithdrewCash', 'CustomerWroteCheck')
+  AND event_version LIKE '1.%'
+  AND `timestamp` IS NOT NULL
+  AND JSON_EXTRACT(payload, CONCAT('$.', event_type, '.occurred_at')) IS NULL;
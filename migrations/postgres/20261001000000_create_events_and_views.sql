@@ -0,0 +1,54 @@
+-- Events stored by the postgres-es event repository.
+CREATE TABLE IF NOT EXISTS events
+(
+    aggregate_type text                        NOT NULL,
+    aggregate_id   text                        NOT NULL,
+    sequence       bigint CHECK (sequence >= 0) NOT NULL,
+    event_type     text                        NOT NULL,
+    event_version  text                        NOT NULL,
+    payload        json                        NOT NULL,
+    metadata       json                        NOT NULL,
+    timestamp      timestamp with time zone DEFAULT (CURRENT_TIMESTAMP),
+    PRIMARY KEY (aggregate_type, aggregate_id, sequence)
+);
+
+-- Views stored by the postgres-es view repository.
+CREATE TABLE IF NOT EXISTS account_query
+(
+    view_id text                        NOT NULL,
+    version bigint CHECK (version >= 0) NOT NULL,
+    payload json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
+CREATE TABLE IF NOT EXISTS statement_query
+(
+    view_id text                        NOT NULL,
+    version bigint CHECK (version >= 0) NOT NULL,
+    payload json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
+CREATE TABLE IF NOT EXISTS standing_order_query
+(
+    view_id text                        NOT NULL,
+    version bigint CHECK (version >= 0) NOT NULL,
+    payload json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
+CREATE TABLE IF NOT EXISTS transfer_query
+(
+    view_id text                        NOT NULL,
+    version bigint CHECK (version >= 0) NOT NULL,
+    payload json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
+
+CREATE TABLE IF NOT EXISTS customer_query
+(
+    view_id text                        NOT NULL,
+    version bigint CHECK (version >= 0) NOT NULL,
+    payload json                        NOT NULL,
+    PRIMARY KEY (view_id)
+);
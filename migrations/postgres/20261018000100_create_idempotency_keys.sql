@@ -0,0 +1,16 @@
+-- Idempotency keys of commands and their outcomes, see IdempotentCommands. Keys are only
+-- unique within their scope. The request id is recorded with the events the request commits,
+-- and the key is claimed again when retrying an attempt that was released or abandoned.
+CREATE TABLE IF NOT EXISTS idempotency_keys
+(
+    scope           text                     NOT NULL,
+    idempotency_key text                     NOT NULL,
+    request         text                     NOT NULL,
+    request_id      text                     NOT NULL,
+    outcome         text,
+    created_at      timestamp with time zone NOT NULL,
+    claimed_at      timestamp with time zone,
+    PRIMARY KEY (scope, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_at ON idempotency_keys (created_at);
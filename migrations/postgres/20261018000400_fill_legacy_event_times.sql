@@ -0,0 +1,13 @@
+-- Bank account events written before version 2.0 did not record when they happened, they
+-- are given the time they were stored instead, see bank_account_event_upcasters.
+UPDATE events
+SET payload = jsonb_set(
+        payload::jsonb,
+        ARRAY [event_type, 'occurred_at'],
+        to_jsonb(to_char("timestamp" AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'))
+    )::json
+WHERE aggregate_type = 'account'
+  AND event_type IN ('AccountOpened', 'CustomerDepositedMoney', 'CustomerWithdrewCash', 'CustomerWroteCheck')
+  AND event_version LIKE '1.%'
+  AND "timestamp" IS NOT NULL
+  AND payload -> event_type -> 'occurred_at' IS NULL;
@@ -0,0 +1,23 @@
+-- The general ledger, see GeneralLedger. One entry per bank account event that moves money.
+CREATE TABLE IF NOT EXISTS ledger_entries
+(
+    account_id text   NOT NULL,
+    sequence   bigint NOT NULL,
+    reference  text,
+    PRIMARY KEY (account_id, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS ledger_entries_reference ON ledger_entries (account_id, reference);
+
+-- Debits are positive and credits negative, the postings of an entry sum to zero.
+CREATE TABLE IF NOT EXISTS ledger_postings
+(
+    account_id     text   NOT NULL,
+    sequence       bigint NOT NULL,
+    line           int    NOT NULL,
+    ledger_account text   NOT NULL,
+    currency       text   NOT NULL,
+    amount         bigint NOT NULL,
+    PRIMARY KEY (account_id, sequence, line),
+    FOREIGN KEY (account_id, sequence) REFERENCES ledger_entries (account_id, sequence)
+);
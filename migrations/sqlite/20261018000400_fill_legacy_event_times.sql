@@ -0,0 +1,13 @@
+-- Bank account events written before version 2.0 did not record when they happened, they
+-- are given the time they were stored instead, see bank_account_event_upcasters.
+UPDATE events
+SET payload = json_set(
+        payload,
+        '$.' || event_type || '.occurred_at',
+        strftime('%Y-%m-%dT%H:%M:%SZ', timestamp)
+    )
+WHERE aggregate_type = 'account'
+  AND event_type IN ('AccountOpened', 'CustomerDepositedMoney', 'CustomerWithdrewCash', 'CustomerWroteCheck')
+  AND event_version LIKE '1.%'
+  AND timestamp IS NOT NULL
+  AND json_extract(payload, '$.' || event_type || '.occurred_at') IS NULL;
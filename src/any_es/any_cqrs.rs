@@ -0,0 +1,19 @@
+use cqrs_es::persist::PersistedEventStore;
+use cqrs_es::{Aggregate, CqrsFramework, Query};
+
+use super::{AnyDatabase, AnyEventRepository};
+
+pub type AnyCqrs<A> = CqrsFramework<A, PersistedEventStore<AnyEventRepository, A>>;
+
+// A framework without snapshots, for aggregates with short event streams.
+pub fn any_cqrs<A>(
+    db: AnyDatabase,
+    queries: Vec<Box<dyn Query<A>>>,
+    services: A::Services,
+) -> AnyCqrs<A>
+where
+    A: Aggregate,
+{
+    let event_store = PersistedEventStore::new_event_store(AnyEventRepository::new(db));
+    CqrsFramework::new(event_store, queries, services)
+}
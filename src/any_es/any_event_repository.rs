@@ -0,0 +1,310 @@
+use async_trait::async_trait;
+use cqrs_es::persist::{
+    PersistedEventRepository, PersistenceError, ReplayStream, SerializedEvent, SerializedSnapshot,
+};
+use cqrs_es::Aggregate;
+use serde_json::{json, Value};
+
+#[cfg(feature = "in-memory")]
+use crate::memory_es::MemEventRepository;
+#[cfg(feature = "sqlite")]
+use crate::sqlite_es::SqliteEventRepository;
+#[cfg(feature = "mysql")]
+use mysql_es::MysqlEventRepository;
+#[cfg(feature = "postgres")]
+use postgres_es::PostgresEventRepository;
+
+use super::AnyDatabase;
+use crate::application::{RequestAlreadyCommitted, REQUEST_ID_METADATA};
+
+// Aggregates that number their events keep the sequence of the last one applied in this
+// field. It is restored from the snapshot record, which the backend keeps in step with the
+// events, rather than trusted from the snapshotted state.
+const LAST_SEQUENCE_FIELD: &str = "last_sequence";
+
+// Events and snapshots in the database the service runs against, with the table layout and
+// optimistic locking of its backend.
+pub struct AnyEventRepository {
+    backend: AnyEventBackend,
+    snapshot_version: Option<u32>,
+}
+
+enum AnyEventBackend {
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresEventRepository),
+    #[cfg(feature = "mysql")]
+    Mysql(MysqlEventRepository),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteEventRepository),
+    #[cfg(feature = "in-memory")]
+    InMemory(MemEventRepository),
+}
+
+impl AnyEventRepository {
+    pub fn new(db: AnyDatabase) -> Self {
+        let backend = match db {
+            #[cfg(feature = "postgres")]
+            AnyDatabase::Postgres(pool) => {
+                AnyEventBackend::Postgres(PostgresEventRepository::new(pool))
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabase::Mysql(pool) => AnyEventBackend::Mysql(MysqlEventRepository::new(pool)),
+            #[cfg(feature = "sqlite")]
+            AnyDatabase::Sqlite(pool) => AnyEventBackend::Sqlite(SqliteEventRepository::new(pool)),
+            #[cfg(feature = "in-memory")]
+            AnyDatabase::InMemory(db) => AnyEventBackend::InMemory(MemEventRepository::new(db)),
+        };
+        Self {
+            backend,
+            snapshot_version: None,
+        }
+    }
+
+    // Stores snapshots along with the version of the aggregate's state they were taken of.
+    // Snapshots of any other version, including those stored without one, are not loaded:
+    // the aggregate is rebuilt from all of its events and its next snapshot replaces them.
+    pub fn with_snapshot_version(mut self, version: u32) -> Self {
+        self.snapshot_version = Some(version);
+        self
+    }
+}
+
+// Unwraps a snapshot stored with `version`, or starts the aggregate over from its first
+// event if it was stored with another one.
+fn versioned_snapshot<A: Aggregate>(
+    version: u32,
+    mut snapshot: SerializedSnapshot,
+) -> Result<SerializedSnapshot, PersistenceError> {
+    if snapshot.aggregate["version"].as_u64() == Some(version.into()) {
+        let mut state = snapshot.aggregate["state"].take();
+        if let Some(last_sequence) = state.get_mut(LAST_SEQUENCE_FIELD) {
+            *last_sequence = json!(snapshot.current_sequence);
+        }
+        snapshot.aggregate = state;
+        return Ok(snapshot);
+    }
+    tracing::warn!(
+        aggregate_type = %A::aggregate_type(),
+        aggregate_id = %snapshot.aggregate_id,
+        "snapshot taken of another version of the aggregate, rebuilding it from its events"
+    );
+    snapshot.aggregate = serde_json::to_value(A::default())
+        .map_err(|err| PersistenceError::UnknownError(Box::new(err)))?;
+    snapshot.current_sequence = 0;
+    Ok(snapshot)
+}
+
+#[async_trait]
+impl PersistedEventRepository for AnyEventRepository {
+    async fn get_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<Vec<SerializedEvent>, PersistenceError> {
+        dispatch!(AnyEventBackend, &self.backend, repository => {
+            repository.get_events::<A>(aggregate_id).await
+        })
+    }
+
+    async fn get_last_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+        last_sequence: usize,
+    ) -> Result<Vec<SerializedEvent>, PersistenceError> {
+        dispatch!(AnyEventBackend, &self.backend, repository => {
+            repository.get_last_events::<A>(aggregate_id, last_sequence).await
+        })
+    }
+
+    async fn get_snapshot<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<Option<SerializedSnapshot>, PersistenceError> {
+        let snapshot = dispatch!(AnyEventBackend, &self.backend, repository => {
+            repository.get_snapshot::<A>(aggregate_id).await
+        })?;
+        match (self.snapshot_version, snapshot) {
+            (Some(version), Some(snapshot)) => versioned_snapshot::<A>(version, snapshot).map(Some),
+            (_, snapshot) => Ok(snapshot),
+        }
+    }
+
+    async fn persist<A: Aggregate>(
+        &self,
+        events: &[SerializedEvent],
+        snapshot_update: Option<(String, Value, usize)>,
+    ) -> Result<(), PersistenceError> {
+        let snapshot_update = match self.snapshot_version {
+            Some(version) => snapshot_update.map(|(aggregate_id, state, current_snapshot)| {
+                let snapshot = json!({ "version": version, "state": state });
+                (aggregate_id, snapshot, current_snapshot)
+            }),
+            None => snapshot_update,
+        };
+        // Events committed for a request that committed events to the aggregate before are
+        // refused. No other commit can slip in between the check and this one, it would have
+        // taken the sequence numbers of these events.
+        if let Some(event) = events.first() {
+            if let Some(request_id) = event.metadata[REQUEST_ID_METADATA].as_str() {
+                let committed = self.get_events::<A>(&event.aggregate_id).await?;
+                if committed
+                    .iter()
+                    .any(|committed| committed.metadata[REQUEST_ID_METADATA] == request_id)
+                {
+                    return Err(PersistenceError::UnknownError(Box::new(
+                        RequestAlreadyCommitted,
+                    )));
+                }
+            }
+        }
+        dispatch!(AnyEventBackend, &self.backend, repository => {
+            repository.persist::<A>(events, snapshot_update).await
+        })
+    }
+
+    async fn stream_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<ReplayStream, PersistenceError> {
+        dispatch!(AnyEventBackend, &self.backend, repository => {
+            repository.stream_events::<A>(aggregate_id).await
+        })
+    }
+
+    async fn stream_all_events<A: Aggregate>(&self) -> Result<ReplayStream, PersistenceError> {
+        dispatch!(AnyEventBackend, &self.backend, repository => {
+            repository.stream_all_events::<A>().await
+        })
+    }
+}
+
+#[cfg(all(test, feature = "in-memory"))]
+mod tests {
+    use super::*;
+    use crate::domain::{BankAccount, Transfer};
+    use crate::memory_es::MemDatabase;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn events_are_persisted_to_the_wrapped_repository() {
+        let db = MemDatabase::default();
+        let event = SerializedEvent {
+            aggregate_id: "transfer-1".to_string(),
+            sequence: 1,
+            aggregate_type: "transfer".to_string(),
+            event_type: "SourceAccountDebited".to_string(),
+            event_version: "1.0".to_string(),
+            payload: json!({ "SourceAccountDebited": { "account_id": "1234" } }),
+            metadata: json!({}),
+        };
+        AnyEventRepository::new(AnyDatabase::InMemory(db.clone()))
+            .persist::<Transfer>(std::slice::from_ref(&event), None)
+            .await
+            .unwrap();
+
+        let events = MemEventRepository::new(db)
+            .get_events::<Transfer>("transfer-1")
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event]);
+    }
+
+    fn deposit(sequence: usize) -> SerializedEvent {
+        SerializedEvent {
+            aggregate_id: "1234".to_string(),
+            sequence,
+            aggregate_type: "account".to_string(),
+            event_type: "CustomerDepositedMoney".to_string(),
+            event_version: "1.0".to_string(),
+            payload: json!({ "CustomerDepositedMoney": {} }),
+            metadata: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn events_of_a_request_that_was_already_committed_are_refused() {
+        let repo = AnyEventRepository::new(AnyDatabase::InMemory(MemDatabase::default()));
+        let for_request = |sequence| SerializedEvent {
+            metadata: json!({ REQUEST_ID_METADATA: "request-1" }),
+            ..deposit(sequence)
+        };
+        repo.persist::<BankAccount>(&[for_request(1)], None)
+            .await
+            .unwrap();
+
+        let refused = repo.persist::<BankAccount>(&[for_request(2)], None).await;
+        assert!(matches!(
+            refused,
+            Err(PersistenceError::UnknownError(err)) if err.is::<RequestAlreadyCommitted>()
+        ));
+        repo.persist::<BankAccount>(&[deposit(2)], None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn snapshots_are_loaded_with_the_sequence_they_were_taken_at() {
+        let repo = AnyEventRepository::new(AnyDatabase::InMemory(MemDatabase::default()))
+            .with_snapshot_version(2);
+        let state = json!({ "account_id": "1234", "last_sequence": 1 });
+        repo.persist::<BankAccount>(
+            &[deposit(1), deposit(2)],
+            Some(("1234".to_string(), state, 1)),
+        )
+        .await
+        .unwrap();
+
+        let snapshot = repo
+            .get_snapshot::<BankAccount>("1234")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.current_sequence, 2);
+        assert_eq!(snapshot.current_snapshot, 1);
+        assert_eq!(
+            snapshot.aggregate,
+            json!({ "account_id": "1234", "last_sequence": 2 })
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshots_of_other_versions_are_rebuilt_from_the_events() {
+        let db = MemDatabase::default();
+        let state = json!({ "account_id": "1234", "last_sequence": 2 });
+        AnyEventRepository::new(AnyDatabase::InMemory(db.clone()))
+            .persist::<BankAccount>(
+                &[deposit(1), deposit(2)],
+                Some(("1234".to_string(), state, 1)),
+            )
+            .await
+            .unwrap();
+
+        let repo = AnyEventRepository::new(AnyDatabase::InMemory(db)).with_snapshot_version(2);
+        let snapshot = repo
+            .get_snapshot::<BankAccount>("1234")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.current_sequence, 0);
+        assert_eq!(snapshot.current_snapshot, 1);
+        assert_eq!(
+            snapshot.aggregate,
+            serde_json::to_value(BankAccount::default()).unwrap()
+        );
+
+        // The rebuilt aggregate's next snapshot replaces the stale one.
+        repo.persist::<BankAccount>(
+            &[deposit(3)],
+            Some(("1234".to_string(), json!({ "last_sequence": 3 }), 2)),
+        )
+        .await
+        .unwrap();
+        let snapshot = repo
+            .get_snapshot::<BankAccount>("1234")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.current_sequence, 3);
+        assert_eq!(snapshot.aggregate, json!({ "last_sequence": 3 }));
+    }
+}
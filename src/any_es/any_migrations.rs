@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+
+use sqlx::migrate::{Migrate, Migrator};
+
+use super::AnyDatabase;
+
+// The migrations of each backend, embedded in the binary so that it can migrate the database
+// it runs against without the sources at hand.
+#[cfg(feature = "postgres")]
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+#[cfg(feature = "mysql")]
+pub static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("./migrations/mysql");
+#[cfg(feature = "sqlite")]
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+impl AnyDatabase {
+    // Applies the migrations of the backend that were not applied yet.
+    pub async fn migrate(&self) -> crate::prelude::Result<()> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => POSTGRES_MIGRATOR.run(pool).await?,
+            #[cfg(feature = "mysql")]
+            Self::Mysql(pool) => MYSQL_MIGRATOR.run(pool).await?,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await?,
+            // Tables are created in memory as they are first written to.
+            #[cfg(feature = "in-memory")]
+            Self::InMemory(_) => {}
+        }
+        Ok(())
+    }
+
+    // The versions of the migrations that were not applied yet, in the order they apply in.
+    pub async fn pending_migrations(&self) -> crate::prelude::Result<Vec<i64>> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => {
+                pending_migrations(&POSTGRES_MIGRATOR, &mut *pool.acquire().await?).await
+            }
+            #[cfg(feature = "mysql")]
+            Self::Mysql(pool) => {
+                pending_migrations(&MYSQL_MIGRATOR, &mut *pool.acquire().await?).await
+            }
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => {
+                pending_migrations(&SQLITE_MIGRATOR, &mut *pool.acquire().await?).await
+            }
+            #[cfg(feature = "in-memory")]
+            Self::InMemory(_) => Ok(Vec::new()),
+        }
+    }
+}
+
+async fn pending_migrations(
+    migrator: &Migrator,
+    connection: &mut (impl Migrate + Send),
+) -> crate::prelude::Result<Vec<i64>> {
+    connection.ensure_migrations_table().await?;
+    let applied: HashSet<i64> = connection
+        .list_applied_migrations()
+        .await?
+        .iter()
+        .map(|migration| migration.version)
+        .collect();
+    Ok(migrator
+        .iter()
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[cfg(feature = "sqlite")]
+    fn versions(migrator: &Migrator) -> Vec<i64> {
+        migrator.iter().map(|migration| migration.version).collect()
+    }
+
+    #[cfg(all(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    #[test]
+    fn every_backend_has_the_same_migrations() {
+        assert_eq!(versions(&POSTGRES_MIGRATOR), versions(&SQLITE_MIGRATOR));
+        assert_eq!(versions(&MYSQL_MIGRATOR), versions(&SQLITE_MIGRATOR));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn migrations_are_pending_until_they_are_applied() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let db = AnyDatabase::Sqlite(pool);
+        assert_eq!(
+            db.pending_migrations().await.unwrap(),
+            versions(&SQLITE_MIGRATOR)
+        );
+
+        db.migrate().await.unwrap();
+        assert_eq!(db.pending_migrations().await.unwrap(), Vec::<i64>::new());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn legacy_events_are_given_the_time_they_were_stored() {
+        use sqlx::Executor;
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // An event stored by an earlier version, before the migrations that followed it.
+        pool.execute(include_str!(
+            "../../migrations/sqlite/20261001000000_create_events_and_views.sql"
+        ))
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO events VALUES ('account', '1234', 1, 'CustomerDepositedMoney', '1.0', ?, '{}', '2023-03-01 09:00:00')",
+        )
+        .bind(r#"{"CustomerDepositedMoney": {"amount": 1000.5, "balance": 1000.5}}"#)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let db = AnyDatabase::Sqlite(pool.clone());
+        db.migrate().await.unwrap();
+        let occurred_at: String = sqlx::query_scalar(
+            "SELECT json_extract(payload, '$.CustomerDepositedMoney.occurred_at') FROM events",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(occurred_at, "2023-03-01T09:00:00Z");
+    }
+}
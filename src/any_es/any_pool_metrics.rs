@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use super::AnyDatabase;
+
+// How often the connection pool is sampled for its gauges.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+impl AnyDatabase {
+    // Keeps the connection pool gauges up to date, they are rendered by the `/metrics`
+    // endpoint alongside the request metrics of `PrometheusMetricLayer`.
+    pub async fn record_pool_metrics(self) {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.record_pool_gauges();
+        }
+    }
+
+    fn record_pool_gauges(&self) {
+        let (size, idle) = match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres(pool) => (pool.size(), pool.num_idle()),
+            #[cfg(feature = "mysql")]
+            Self::Mysql(pool) => (pool.size(), pool.num_idle()),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => (pool.size(), pool.num_idle()),
+            // There are no connections to keep track of.
+            #[cfg(feature = "in-memory")]
+            Self::InMemory(_) => return,
+        };
+        let backend = self.backend().to_string();
+        metrics::gauge!("db_pool_connections", size as f64, "backend" => backend.clone());
+        metrics::gauge!("db_pool_idle_connections", idle as f64, "backend" => backend);
+    }
+}
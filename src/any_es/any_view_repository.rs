@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use cqrs_es::persist::{PersistenceError, ViewContext, ViewRepository};
+use cqrs_es::{Aggregate, View};
+
+#[cfg(feature = "in-memory")]
+use crate::memory_es::MemViewRepository;
+#[cfg(feature = "sqlite")]
+use crate::sqlite_es::SqliteViewRepository;
+#[cfg(feature = "mysql")]
+use mysql_es::MysqlViewRepository;
+#[cfg(feature = "postgres")]
+use postgres_es::PostgresViewRepository;
+
+use super::AnyDatabase;
+
+// Views in a table of the database the service runs against, named after the view.
+pub enum AnyViewRepository<V, A>
+where
+    V: View<A>,
+    A: Aggregate,
+{
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresViewRepository<V, A>),
+    #[cfg(feature = "mysql")]
+    Mysql(MysqlViewRepository<V, A>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteViewRepository<V, A>),
+    #[cfg(feature = "in-memory")]
+    InMemory(MemViewRepository<V, A>),
+}
+
+impl<V, A> AnyViewRepository<V, A>
+where
+    V: View<A>,
+    A: Aggregate,
+{
+    pub fn new(view_name: &str, db: AnyDatabase) -> Self {
+        match db {
+            #[cfg(feature = "postgres")]
+            AnyDatabase::Postgres(pool) => {
+                Self::Postgres(PostgresViewRepository::new(view_name, pool))
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabase::Mysql(pool) => Self::Mysql(MysqlViewRepository::new(view_name, pool)),
+            #[cfg(feature = "sqlite")]
+            AnyDatabase::Sqlite(pool) => Self::Sqlite(SqliteViewRepository::new(view_name, pool)),
+            #[cfg(feature = "in-memory")]
+            AnyDatabase::InMemory(db) => Self::InMemory(MemViewRepository::new(view_name, db)),
+        }
+    }
+}
+
+#[async_trait]
+impl<V, A> ViewRepository<V, A> for AnyViewRepository<V, A>
+where
+    V: View<A>,
+    A: Aggregate,
+{
+    async fn load(&self, view_id: &str) -> Result<Option<V>, PersistenceError> {
+        dispatch!(AnyViewRepository, self, repository => repository.load(view_id).await)
+    }
+
+    async fn load_with_context(
+        &self,
+        view_id: &str,
+    ) -> Result<Option<(V, ViewContext)>, PersistenceError> {
+        dispatch!(AnyViewRepository, self, repository => {
+            repository.load_with_context(view_id).await
+        })
+    }
+
+    async fn update_view(&self, view: V, context: ViewContext) -> Result<(), PersistenceError> {
+        dispatch!(AnyViewRepository, self, repository => {
+            repository.update_view(view, context).await
+        })
+    }
+}
@@ -0,0 +1,42 @@
+//! An event store and view repository over whichever database the service was started
+//! against, so that one binary serves every backend it was built with. Each backend has a
+//! feature of its own, see `Backend` for how one is chosen at startup.
+
+#[cfg(not(any(
+    feature = "postgres",
+    feature = "mysql",
+    feature = "sqlite",
+    feature = "in-memory"
+)))]
+compile_error!("Must enable at least one of the mysql, postgres, sqlite or in-memory features");
+
+// Calls the same method on whichever backend an `AnyDatabase`, `AnyEventRepository` or
+// `AnyViewRepository` wraps.
+macro_rules! dispatch {
+    ($any:ident, $value:expr, $inner:ident => $call:expr) => {
+        match $value {
+            #[cfg(feature = "postgres")]
+            $any::Postgres($inner) => $call,
+            #[cfg(feature = "mysql")]
+            $any::Mysql($inner) => $call,
+            #[cfg(feature = "sqlite")]
+            $any::Sqlite($inner) => $call,
+            #[cfg(feature = "in-memory")]
+            $any::InMemory($inner) => $call,
+        }
+    };
+}
+
+pub mod any_cqrs;
+pub mod any_database;
+pub mod any_event_repository;
+pub mod any_migrations;
+pub mod any_pool_metrics;
+pub mod any_view_repository;
+
+// Re-exports
+pub use any_cqrs::*;
+pub use any_database::*;
+pub use any_event_repository::*;
+pub use any_migrations::*;
+pub use any_view_repository::*;
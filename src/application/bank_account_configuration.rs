@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+use crate::application::{FeeEngine, InterestConfiguration, SnapshotPolicy};
+use crate::domain::{
+    CheckClearingPolicy, HoldPolicy, OverdraftFeeSchedule, ProductCatalogue, WithdrawalLimits,
+};
+
+// Business rules for bank accounts that can be tuned without a code change, loaded
+// from the `bank_account` section of the application configuration file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BankAccountConfiguration {
+    #[serde(default)]
+    pub overdraft_fees: OverdraftFeeSchedule,
+    #[serde(default)]
+    pub interest: InterestConfiguration,
+    #[serde(default)]
+    pub holds: HoldPolicy,
+    #[serde(default)]
+    pub checks: CheckClearingPolicy,
+    #[serde(default)]
+    pub withdrawal_limits: WithdrawalLimits,
+    #[serde(default)]
+    pub fees: FeeEngine,
+    #[serde(default)]
+    pub products: ProductCatalogue,
+    // Not a business rule, but tuned per aggregate all the same.
+    #[serde(default)]
+    pub snapshots: SnapshotPolicy,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::FeeRule;
+    use crate::domain::{FeeAmount, ProductRules};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn missing_sections_fall_back_to_defaults() {
+        let config: BankAccountConfiguration = serde_yaml::from_str("{}").unwrap();
+        assert_eq!(config, BankAccountConfiguration::default());
+    }
+
+    #[test]
+    fn can_parse_overdraft_fee_schedule() {
+        let config: BankAccountConfiguration =
+            serde_yaml::from_str("overdraft_fees: { flat_fee: 2500, basis_points: 100 }").unwrap();
+        assert_eq!(
+            config.overdraft_fees,
+            OverdraftFeeSchedule {
+                flat_fee: 2500,
+                basis_points: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_interest_configuration() {
+        let config: BankAccountConfiguration =
+            serde_yaml::from_str("interest: { enabled: true, catch_up_days: 3 }").unwrap();
+        assert_eq!(
+            config.interest,
+            InterestConfiguration {
+                enabled: true,
+                catch_up_days: 3,
+                ..InterestConfiguration::default()
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_hold_policy() {
+        let config: BankAccountConfiguration =
+            serde_yaml::from_str("holds: { ttl_seconds: 86400 }").unwrap();
+        assert_eq!(config.holds, HoldPolicy { ttl_seconds: 86400 });
+    }
+
+    #[test]
+    fn can_parse_check_clearing_policy() {
+        let config: BankAccountConfiguration =
+            serde_yaml::from_str("checks: { returned_item_fee: 3500 }").unwrap();
+        assert_eq!(
+            config.checks,
+            CheckClearingPolicy {
+                returned_item_fee: FeeAmount::Flat(3500),
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_withdrawal_limits() {
+        let config: BankAccountConfiguration =
+            serde_yaml::from_str("withdrawal_limits: { daily_limit: 100000 }").unwrap();
+        assert_eq!(
+            config.withdrawal_limits,
+            WithdrawalLimits {
+                daily_limit: Some(100000),
+                daily_limit_per_atm: None,
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_fee_rules() {
+        let config: BankAccountConfiguration = serde_yaml::from_str(
+            "fees: { rules: [{ type: atm_withdrawal, atm_ids: [ATM-EXT-1], fee: 250 }] }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.fees,
+            FeeEngine {
+                rules: vec![FeeRule::AtmWithdrawal {
+                    atm_ids: vec!["ATM-EXT-1".to_string()],
+                    fee: FeeAmount::Flat(250),
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_product_catalogue() {
+        let config: BankAccountConfiguration = serde_yaml::from_str(
+            "products: { savings: { checks_allowed: false, monthly_withdrawal_limit: 3 } }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.products.savings,
+            ProductRules {
+                checks_allowed: false,
+                overdraft_allowed: true,
+                monthly_withdrawal_limit: Some(3),
+                annual_interest_rate_basis_points: None,
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_snapshot_policy() {
+        let config: BankAccountConfiguration =
+            serde_yaml::from_str("snapshots: { every_n_events: 100 }").unwrap();
+        assert_eq!(
+            config.snapshots,
+            SnapshotPolicy {
+                every_n_events: Some(100),
+            }
+        );
+    }
+}
@@ -2,19 +2,43 @@
 use mockall::{automock, predicate::*};
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use derivative::Derivative;
 
+use crate::application::{BankAccountConfiguration, FxRateTable};
+use crate::domain::{Currency, ExchangeRate, KycStatus, Money};
+
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct BankAccountServices {
     #[derivative(Debug = "ignore")]
     pub services: Box<dyn BankAccountApi>,
+    #[derivative(Debug = "ignore")]
+    pub customers: Box<dyn CustomerKycLookup>,
+    pub configuration: BankAccountConfiguration,
 }
 
 #[cfg_attr(test, automock)]
 impl BankAccountServices {
     pub fn new(services: Box<dyn BankAccountApi>) -> Self {
-        Self { services }
+        Self {
+            services,
+            customers: Box::new(UnknownCustomersKycLookup),
+            configuration: BankAccountConfiguration::default(),
+        }
+    }
+}
+
+impl BankAccountServices {
+    pub fn with_configuration(self, configuration: BankAccountConfiguration) -> Self {
+        Self {
+            configuration,
+            ..self
+        }
+    }
+
+    pub fn with_customers(self, customers: Box<dyn CustomerKycLookup>) -> Self {
+        Self { customers, ..self }
     }
 }
 
@@ -22,19 +46,69 @@ impl BankAccountServices {
 #[async_trait]
 #[cfg_attr(test, automock)]
 pub trait BankAccountApi: Sync + Send {
-    async fn atm_withdrawal(&self, atm_id: &str, amount: f64) -> Result<(), AtmError>;
+    async fn atm_withdrawal(&self, atm_id: &str, amount: &Money) -> Result<(), AtmError>;
     async fn validate_check(&self, account_id: &str, check: &str) -> Result<(), CheckingError>;
+    async fn fx_rate(&self, from: Currency, to: Currency) -> Result<ExchangeRate, FxRateError>;
+    fn now(&self) -> DateTime<Utc>;
 }
 pub struct AtmError;
 pub struct CheckingError;
+pub struct FxRateError;
+
+// Accounts may only be opened for customers who passed KYC, the customer aggregate lives
+// outside the bank account so its status is looked up while opening the account.
+#[async_trait]
+pub trait CustomerKycLookup: Sync + Send {
+    // `None` when there is no such customer.
+    async fn kyc_status(&self, customer_id: &str)
+        -> Result<Option<KycStatus>, CustomerLookupError>;
+}
+pub struct CustomerLookupError;
 
-// A very simple "happy path" set of services that always succeed.
-pub struct HappyPathBankAccountServices;
+// Knows no customers, so no account can be opened until the customer records are looked
+// up with `with_customers`.
+pub struct UnknownCustomersKycLookup;
+
+#[async_trait]
+impl CustomerKycLookup for UnknownCustomersKycLookup {
+    async fn kyc_status(
+        &self,
+        _customer_id: &str,
+    ) -> Result<Option<KycStatus>, CustomerLookupError> {
+        Ok(None)
+    }
+}
+
+// Treats every customer as verified, for setups without customer records.
+pub struct HappyPathCustomerKycLookup;
+
+#[async_trait]
+impl CustomerKycLookup for HappyPathCustomerKycLookup {
+    async fn kyc_status(
+        &self,
+        _customer_id: &str,
+    ) -> Result<Option<KycStatus>, CustomerLookupError> {
+        Ok(Some(KycStatus::Verified))
+    }
+}
+
+// A very simple "happy path" set of services that always succeed, apart from currency
+// conversions, which are only possible for the currency pairs in its rate table.
+#[derive(Default)]
+pub struct HappyPathBankAccountServices {
+    fx_rates: FxRateTable,
+}
+
+impl HappyPathBankAccountServices {
+    pub fn new(fx_rates: FxRateTable) -> Self {
+        Self { fx_rates }
+    }
+}
 
 #[async_trait]
 #[cfg_attr(test, automock)]
 impl BankAccountApi for HappyPathBankAccountServices {
-    async fn atm_withdrawal(&self, _atm_id: &str, _amount: f64) -> Result<(), AtmError> {
+    async fn atm_withdrawal(&self, _atm_id: &str, _amount: &Money) -> Result<(), AtmError> {
         Ok(())
     }
 
@@ -45,6 +119,14 @@ impl BankAccountApi for HappyPathBankAccountServices {
     ) -> Result<(), CheckingError> {
         Ok(())
     }
+
+    async fn fx_rate(&self, from: Currency, to: Currency) -> Result<ExchangeRate, FxRateError> {
+        self.fx_rates.rate(from, to).ok_or(FxRateError)
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
 }
 
 #[cfg(test)]
@@ -53,15 +135,41 @@ mod tests {
 
     #[tokio::test]
     async fn happy_path_bank_account_services_atm_withdrawal_returns_ok() {
-        let services = HappyPathBankAccountServices;
-        let result = services.atm_withdrawal("123", 100.0).await;
+        let services = HappyPathBankAccountServices::default();
+        let result = services
+            .atm_withdrawal("123", &Money::new(10000, Currency::EUR))
+            .await;
         assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn happy_path_bank_account_services_validate_check_returns_ok() {
-        let services = HappyPathBankAccountServices;
+        let services = HappyPathBankAccountServices::default();
         let result = services.validate_check("123", "123").await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn unknown_customers_kyc_lookup_knows_no_customers() {
+        let status = UnknownCustomersKycLookup.kyc_status("customer-1").await;
+        assert!(matches!(status, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn happy_path_customer_kyc_lookup_treats_customers_as_verified() {
+        let status = HappyPathCustomerKycLookup.kyc_status("customer-1").await;
+        assert!(matches!(status, Ok(Some(KycStatus::Verified))));
+    }
+
+    #[tokio::test]
+    async fn happy_path_bank_account_services_fx_rate_uses_rate_table() {
+        let fx_rates = FxRateTable::from_yaml_str("- { from: USD, to: EUR, rate: \"0.92\" }")
+            .expect("Expected the rate table to parse");
+        let services = HappyPathBankAccountServices::new(fx_rates);
+
+        let rate = services.fx_rate(Currency::USD, Currency::EUR).await;
+        assert!(rate.is_ok());
+        let rate = services.fx_rate(Currency::GBP, Currency::EUR).await;
+        assert!(rate.is_err());
+    }
 }
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Currency, FeeAmount, FeeReason, Money};
+
+// A fee charged for every transaction the rule applies to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeeRule {
+    /// Charged for cash withdrawals, only at the listed ATMs when any are listed
+    AtmWithdrawal {
+        #[serde(default)]
+        atm_ids: Vec<String>,
+        fee: FeeAmount,
+    },
+
+    /// Charged for every check written
+    Check { fee: FeeAmount },
+
+    /// Charged once for every calendar month the account is open
+    MonthlyMaintenance { fee: FeeAmount },
+}
+
+impl FeeRule {
+    fn applies_to(&self, reason: &FeeReason) -> bool {
+        match (self, reason) {
+            (FeeRule::AtmWithdrawal { atm_ids, .. }, FeeReason::AtmWithdrawal { atm_id }) => {
+                atm_ids.is_empty() || atm_ids.contains(atm_id)
+            }
+            (FeeRule::Check { .. }, FeeReason::Check { .. }) => true,
+            (FeeRule::MonthlyMaintenance { .. }, FeeReason::MonthlyMaintenance { .. }) => true,
+            _ => false,
+        }
+    }
+
+    fn fee(&self) -> &FeeAmount {
+        match self {
+            FeeRule::AtmWithdrawal { fee, .. }
+            | FeeRule::Check { fee }
+            | FeeRule::MonthlyMaintenance { fee } => fee,
+        }
+    }
+}
+
+// Works out the fees due from the rules in the `fees` section of the bank account
+// configuration. Every rule that applies is charged as a fee of its own, e.g. a fee at any
+// ATM on top of a surcharge at some.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeEngine {
+    #[serde(default)]
+    pub rules: Vec<FeeRule>,
+}
+
+impl FeeEngine {
+    pub fn fees_for(&self, reason: &FeeReason, currency: Currency) -> Vec<Money> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.applies_to(reason))
+            .map(|rule| rule.fee().in_currency(currency))
+            .filter(|fee| !fee.is_zero())
+            .collect()
+    }
+
+    pub fn charges_maintenance_fees(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| matches!(rule, FeeRule::MonthlyMaintenance { fee } if !fee.is_zero()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn fee_engine() -> FeeEngine {
+        serde_yaml::from_str(
+            "rules:
+               - { type: atm_withdrawal, fee: 100 }
+               - { type: atm_withdrawal, atm_ids: [ATM-EXT-1], fee: 250 }
+               - { type: check, fee: 50 }
+               - { type: monthly_maintenance, fee: { GBP: 400 } }",
+        )
+        .unwrap()
+    }
+
+    fn atm_withdrawal(atm_id: &str) -> FeeReason {
+        FeeReason::AtmWithdrawal {
+            atm_id: atm_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn every_rule_that_applies_is_charged_separately() {
+        assert_eq!(
+            fee_engine().fees_for(&atm_withdrawal("ATM-EXT-1"), Currency::EUR),
+            vec![
+                Money::new(100, Currency::EUR),
+                Money::new(250, Currency::EUR)
+            ]
+        );
+        assert_eq!(
+            fee_engine().fees_for(&atm_withdrawal("ATM-1"), Currency::EUR),
+            vec![Money::new(100, Currency::EUR)]
+        );
+    }
+
+    #[test]
+    fn nothing_is_charged_without_a_matching_rule() {
+        let reason = FeeReason::MonthlyMaintenance {
+            period: "2023-03".to_string(),
+        };
+        assert!(fee_engine().fees_for(&reason, Currency::EUR).is_empty());
+    }
+
+    #[test]
+    fn fees_can_differ_by_currency() {
+        let reason = FeeReason::MonthlyMaintenance {
+            period: "2023-03".to_string(),
+        };
+        assert_eq!(
+            fee_engine().fees_for(&reason, Currency::GBP),
+            vec![Money::new(400, Currency::GBP)]
+        );
+        assert!(fee_engine().charges_maintenance_fees());
+    }
+
+    #[test]
+    fn negative_fees_are_refused() {
+        assert!(serde_yaml::from_str::<FeeEngine>("rules: [{ type: check, fee: -50 }]").is_err());
+    }
+}
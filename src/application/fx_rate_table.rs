@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use tracing::{instrument, Level};
+
+use crate::domain::{Currency, ExchangeRate};
+
+// A deterministic set of exchange rates loaded from a YAML file, so that currency
+// conversion behaves the same way every time and can be exercised offline.
+#[derive(Debug, Default, Clone)]
+pub struct FxRateTable {
+    rates: HashMap<(Currency, Currency), ExchangeRate>,
+}
+
+#[derive(Deserialize)]
+struct FxRateTableEntry {
+    from: Currency,
+    to: Currency,
+    rate: String,
+}
+
+impl FxRateTable {
+    #[instrument]
+    pub fn load(path: &str) -> crate::prelude::Result<Self> {
+        tracing::event!(Level::INFO, "loading fx rates from {path}");
+        let contents = std::fs::read_to_string(path)?;
+        Self::from_yaml_str(&contents)
+    }
+
+    pub fn from_yaml_str(yaml: &str) -> crate::prelude::Result<Self> {
+        let entries: Vec<FxRateTableEntry> = serde_yaml::from_str(yaml)?;
+        let mut rates = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            let rate = ExchangeRate::from_decimal_str(entry.from, entry.to, &entry.rate)?;
+            rates.insert((entry.from, entry.to), rate);
+        }
+        Ok(Self { rates })
+    }
+
+    pub fn rate(&self, from: Currency, to: Currency) -> Option<ExchangeRate> {
+        self.rates.get(&(from, to)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Money;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn can_load_the_bundled_fx_rates_file() {
+        let table = FxRateTable::load(concat!(env!("CARGO_MANIFEST_DIR"), "/fx-rates.yaml"))
+            .expect("Expected the bundled fx rates file to load");
+        let rate = table
+            .rate(Currency::USD, Currency::EUR)
+            .expect("Expected a USD to EUR rate");
+        assert_eq!(
+            rate.convert(&Money::new(10000, Currency::USD)).unwrap(),
+            Money::new(9200, Currency::EUR)
+        );
+    }
+
+    #[test]
+    fn missing_currency_pairs_have_no_rate() {
+        let table = FxRateTable::from_yaml_str("- { from: USD, to: EUR, rate: \"0.92\" }").unwrap();
+        assert_eq!(table.rate(Currency::EUR, Currency::USD), None);
+    }
+
+    #[test]
+    fn invalid_rates_are_rejected() {
+        let result = FxRateTable::from_yaml_str("- { from: USD, to: EUR, rate: \"-0.92\" }");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,932 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cqrs_es::{EventEnvelope, Query};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use crate::domain::{BankAccount, BankAccountEvent, Money};
+
+// Events that could not be posted are retried every minute, e.g. once the database is back.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+// The bank's chart of accounts, as far as bank account events touch it. Postings are signed:
+// debits are positive and credits negative, so the postings of every entry sum to zero.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema, TS,
+)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum LedgerAccount {
+    /// What the bank owes its customers, the sum of all bank account balances
+    CustomerDeposits,
+
+    /// Cash held at branches
+    Cash,
+
+    /// Cash held in ATMs
+    CashAtAtms,
+
+    /// Checks written by customers that have not been presented by the payee's bank yet
+    ChecksInClearing,
+
+    /// Money debited from one bank account and not yet credited to another
+    TransfersInTransit,
+
+    /// The bank's account for settling with other banks, e.g. for cleared checks and card payments
+    Settlement,
+
+    /// The bank's position in each currency from converting customers' money between currencies
+    ForeignExchange,
+
+    FeeIncome,
+
+    InterestExpense,
+}
+
+impl LedgerAccount {
+    pub fn code(&self) -> &'static str {
+        match self {
+            LedgerAccount::CustomerDeposits => "customer_deposits",
+            LedgerAccount::Cash => "cash",
+            LedgerAccount::CashAtAtms => "cash_at_atms",
+            LedgerAccount::ChecksInClearing => "checks_in_clearing",
+            LedgerAccount::TransfersInTransit => "transfers_in_transit",
+            LedgerAccount::Settlement => "settlement",
+            LedgerAccount::ForeignExchange => "foreign_exchange",
+            LedgerAccount::FeeIncome => "fee_income",
+            LedgerAccount::InterestExpense => "interest_expense",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        [
+            LedgerAccount::CustomerDeposits,
+            LedgerAccount::Cash,
+            LedgerAccount::CashAtAtms,
+            LedgerAccount::ChecksInClearing,
+            LedgerAccount::TransfersInTransit,
+            LedgerAccount::Settlement,
+            LedgerAccount::ForeignExchange,
+            LedgerAccount::FeeIncome,
+            LedgerAccount::InterestExpense,
+        ]
+        .into_iter()
+        .find(|account| account.code() == code)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerPosting {
+    pub ledger_account: LedgerAccount,
+    // Positive for a debit, negative for a credit.
+    pub amount: Money,
+}
+
+// The postings for one bank account event, keyed by the event's place in the account's stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub account_id: String,
+    pub sequence: usize,
+    // What the entry is for, e.g. a check, so that later entries for it can find it.
+    pub reference: Option<String>,
+    pub postings: Vec<LedgerPosting>,
+}
+
+impl JournalEntry {
+    // The entry undoing this one.
+    fn reversed(&self, sequence: usize) -> crate::prelude::Result<JournalEntry> {
+        let postings = self
+            .postings
+            .iter()
+            .map(|posting| {
+                Ok(LedgerPosting {
+                    ledger_account: posting.ledger_account,
+                    amount: posting.amount.checked_neg()?,
+                })
+            })
+            .collect::<crate::prelude::Result<_>>()?;
+        Ok(JournalEntry {
+            account_id: self.account_id.clone(),
+            sequence,
+            reference: self.reference.clone(),
+            postings,
+        })
+    }
+
+    fn amount_posted_to(&self, ledger_account: LedgerAccount) -> Option<Money> {
+        self.postings
+            .iter()
+            .find(|posting| posting.ledger_account == ledger_account)
+            .map(|posting| posting.amount)
+    }
+}
+
+// The total debits and credits of a ledger account in one currency.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct LedgerBalance {
+    pub ledger_account: LedgerAccount,
+    pub debits: Money,
+    pub credits: Money,
+}
+
+// The debits and credits of the whole ledger in one currency, which are always equal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct TrialBalanceTotal {
+    pub debits: Money,
+    pub credits: Money,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct TrialBalance {
+    pub accounts: Vec<LedgerBalance>,
+    pub totals: Vec<TrialBalanceTotal>,
+}
+
+impl TrialBalance {
+    pub fn new(accounts: Vec<LedgerBalance>) -> crate::prelude::Result<Self> {
+        let mut totals: BTreeMap<String, TrialBalanceTotal> = BTreeMap::new();
+        for balance in &accounts {
+            let currency = balance.debits.currency();
+            let total = totals
+                .entry(currency.code().to_string())
+                .or_insert_with(|| TrialBalanceTotal {
+                    debits: Money::new(0, currency),
+                    credits: Money::new(0, currency),
+                });
+            total.debits = total.debits.checked_add(&balance.debits)?;
+            total.credits = total.credits.checked_add(&balance.credits)?;
+        }
+        Ok(Self {
+            accounts,
+            totals: totals.into_values().collect(),
+        })
+    }
+}
+
+// A bank account event that could not be posted to the general ledger, and why. It is
+// retried until it is posted, and the trial balance is incomplete until then.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct UnpostedEvent {
+    pub account_id: String,
+    pub sequence: usize,
+    pub error: String,
+}
+
+// Persists the journal entries of the general ledger.
+#[async_trait]
+pub trait LedgerStore: Send + Sync {
+    // Recording an entry that is already in the ledger leaves the ledger unchanged.
+    async fn record(&self, entry: &JournalEntry) -> crate::prelude::Result<()>;
+
+    async fn entry(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<Option<JournalEntry>>;
+
+    // The first entry of the account with the reference.
+    async fn entry_with_reference(
+        &self,
+        account_id: &str,
+        reference: &str,
+    ) -> crate::prelude::Result<Option<JournalEntry>>;
+
+    async fn trial_balance(&self) -> crate::prelude::Result<TrialBalance>;
+
+    // Recording an event that is already unposted replaces why it could not be posted.
+    async fn record_unposted(&self, event: &UnpostedEvent) -> crate::prelude::Result<()>;
+
+    async fn remove_unposted(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<()>;
+
+    // Sorted by account and sequence number, the order they are to be posted in.
+    async fn unposted_events(&self) -> crate::prelude::Result<Vec<UnpostedEvent>>;
+}
+
+// Finds a stored bank account event, for events that were stored before the general ledger
+// was and so were never posted to it.
+#[async_trait]
+pub trait BankAccountEventLookup: Send + Sync {
+    async fn event(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<Option<BankAccountEvent>>;
+}
+
+// Turns every bank account event that moves money into a balanced journal entry, so that the
+// bank's books can be balanced across all accounts. Events that cannot be posted when they
+// are committed are recorded as unposted and retried in the background.
+pub struct GeneralLedger {
+    store: Arc<dyn LedgerStore>,
+    events: Arc<dyn BankAccountEventLookup>,
+}
+
+impl GeneralLedger {
+    pub fn new(store: Arc<dyn LedgerStore>, events: Arc<dyn BankAccountEventLookup>) -> Self {
+        Self { store, events }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(RETRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.post_unposted_events().await;
+        }
+    }
+
+    // Posts the event to the ledger. Posting an event that is already posted changes nothing,
+    // so events can be posted again, e.g. to backfill the ledger from the event store.
+    pub async fn post(
+        &self,
+        account_id: &str,
+        event: &EventEnvelope<BankAccount>,
+    ) -> crate::prelude::Result<()> {
+        match self.journal_entry(account_id, event).await? {
+            Some(entry) => self.store.record(&entry).await,
+            None => Ok(()),
+        }
+    }
+
+    // Retries the events that could not be posted and returns how many were posted.
+    pub async fn post_unposted_events(&self) -> usize {
+        let unposted = match self.store.unposted_events().await {
+            Ok(unposted) => unposted,
+            Err(err) => {
+                tracing::error!(
+                    "could not look up the events unposted to the general ledger: {err}"
+                );
+                return 0;
+            }
+        };
+        let mut posted = 0;
+        for event in unposted {
+            match self.post_stored(&event.account_id, event.sequence).await {
+                Ok(()) => posted += 1,
+                Err(err) => {
+                    self.record_unposted(&event.account_id, event.sequence, err)
+                        .await
+                }
+            }
+        }
+        if posted > 0 {
+            tracing::info!("posted {posted} events to the general ledger late");
+        }
+        posted
+    }
+
+    async fn post_stored(&self, account_id: &str, sequence: usize) -> crate::prelude::Result<()> {
+        let Some(payload) = self.events.event(account_id, sequence).await? else {
+            return Err(crate::prelude::Error::Generic(format!(
+                "event {sequence} of account {account_id} is not stored"
+            )));
+        };
+        let event = EventEnvelope {
+            aggregate_id: account_id.to_string(),
+            sequence,
+            payload,
+            metadata: HashMap::new(),
+        };
+        self.post(account_id, &event).await?;
+        self.store.remove_unposted(account_id, sequence).await
+    }
+
+    async fn record_unposted(&self, account_id: &str, sequence: usize, err: crate::prelude::Error) {
+        tracing::error!(
+            "could not post event {sequence} of account {account_id} to the general ledger: {err}"
+        );
+        let unposted = UnpostedEvent {
+            account_id: account_id.to_string(),
+            sequence,
+            error: err.to_string(),
+        };
+        if let Err(err) = self.store.record_unposted(&unposted).await {
+            tracing::error!(
+                "could not record event {sequence} of account {account_id} as unposted: {err}"
+            );
+        }
+    }
+
+    // The entry of the reversed transaction. Transactions from before the general ledger have
+    // none, so their entry is posted late from the stored event, and then reversed.
+    async fn reversed_entry(
+        &self,
+        account_id: &str,
+        reversed_sequence: usize,
+    ) -> crate::prelude::Result<JournalEntry> {
+        if let Some(entry) = self.store.entry(account_id, reversed_sequence).await? {
+            return Ok(entry);
+        }
+        let postings = match self.events.event(account_id, reversed_sequence).await? {
+            Some(event) => ledger_postings(&event)?,
+            None => None,
+        };
+        let Some((reference, postings)) = postings else {
+            return Err(crate::prelude::Error::Generic(format!(
+                "event {reversed_sequence} of account {account_id} is not a transaction that can be reversed"
+            )));
+        };
+        let entry = JournalEntry {
+            account_id: account_id.to_string(),
+            sequence: reversed_sequence,
+            reference,
+            postings,
+        };
+        tracing::warn!(
+            "posting event {reversed_sequence} of account {account_id} to the general ledger late, to reverse it"
+        );
+        self.store.record(&entry).await?;
+        Ok(entry)
+    }
+
+    async fn journal_entry(
+        &self,
+        account_id: &str,
+        event: &EventEnvelope<BankAccount>,
+    ) -> crate::prelude::Result<Option<JournalEntry>> {
+        match &event.payload {
+            BankAccountEvent::TransactionReversed {
+                reversed_sequence, ..
+            } => {
+                let reversed = self.reversed_entry(account_id, *reversed_sequence).await?;
+                Ok(Some(reversed.reversed(event.sequence)?))
+            }
+            // The refund undoes the debit, at the rate it was converted at if it was.
+            BankAccountEvent::TransferRefunded { transfer_id, .. } => {
+                let debited = self
+                    .store
+                    .entry_with_reference(account_id, transfer_id)
+                    .await?;
+                let Some(debited) = debited else {
+                    return Err(crate::prelude::Error::Generic(format!(
+                        "no journal entry for transfer {transfer_id} of account {account_id}"
+                    )));
+                };
+                Ok(Some(debited.reversed(event.sequence)?))
+            }
+            BankAccountEvent::CheckCleared { check_number, .. } => {
+                let reference = check_reference(check_number);
+                let written = self
+                    .store
+                    .entry_with_reference(account_id, &reference)
+                    .await?;
+                let Some(amount) = written
+                    .and_then(|entry| entry.amount_posted_to(LedgerAccount::ChecksInClearing))
+                else {
+                    return Err(crate::prelude::Error::Generic(format!(
+                        "no journal entry for check {check_number} of account {account_id}"
+                    )));
+                };
+                // The check was credited to clearing when it was written.
+                let amount = amount.checked_neg()?;
+                Ok(Some(JournalEntry {
+                    account_id: account_id.to_string(),
+                    sequence: event.sequence,
+                    reference: Some(reference),
+                    postings: postings(
+                        amount,
+                        LedgerAccount::ChecksInClearing,
+                        LedgerAccount::Settlement,
+                    )?,
+                }))
+            }
+            payload => Ok(
+                ledger_postings(payload)?.map(|(reference, postings)| JournalEntry {
+                    account_id: account_id.to_string(),
+                    sequence: event.sequence,
+                    reference,
+                    postings,
+                }),
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl Query<BankAccount> for GeneralLedger {
+    async fn dispatch(&self, account_id: &str, events: &[EventEnvelope<BankAccount>]) {
+        for event in events {
+            if let Err(err) = self.post(account_id, event).await {
+                self.record_unposted(account_id, event.sequence, err).await;
+            }
+        }
+    }
+}
+
+fn check_reference(check_number: &str) -> String {
+    format!("check {check_number}")
+}
+
+// Debits one ledger account and credits another with the amount.
+fn postings(
+    amount: Money,
+    debit: LedgerAccount,
+    credit: LedgerAccount,
+) -> crate::prelude::Result<Vec<LedgerPosting>> {
+    Ok(vec![
+        LedgerPosting {
+            ledger_account: debit,
+            amount,
+        },
+        LedgerPosting {
+            ledger_account: credit,
+            amount: amount.checked_neg()?,
+        },
+    ])
+}
+
+// Debits one ledger account and credits another with an amount converted between currencies.
+// The conversion goes through the foreign exchange account, so that the postings in each
+// currency balance on their own.
+fn exchange_postings(
+    debit_amount: Money,
+    debit: LedgerAccount,
+    credit_amount: Money,
+    credit: LedgerAccount,
+) -> crate::prelude::Result<Vec<LedgerPosting>> {
+    if debit_amount.currency() == credit_amount.currency() {
+        return postings(debit_amount, debit, credit);
+    }
+    let mut exchanged = postings(debit_amount, debit, LedgerAccount::ForeignExchange)?;
+    exchanged.extend(postings(
+        credit_amount,
+        LedgerAccount::ForeignExchange,
+        credit,
+    )?);
+    Ok(exchanged)
+}
+
+// The reference and postings of the events that can be posted on their own, `None` for the
+// events that do not move money.
+fn ledger_postings(
+    event: &BankAccountEvent,
+) -> crate::prelude::Result<Option<(Option<String>, Vec<LedgerPosting>)>> {
+    use LedgerAccount::*;
+
+    let (reference, amount, debit, credit) = match event {
+        BankAccountEvent::CustomerDepositedMoney { amount, .. } => {
+            (None, amount, Cash, CustomerDeposits)
+        }
+        BankAccountEvent::CustomerWithdrewCash { amount, atm_id, .. } => {
+            let cash = if atm_id.is_some() { CashAtAtms } else { Cash };
+            (atm_id.clone(), amount, CustomerDeposits, cash)
+        }
+        BankAccountEvent::CustomerWroteCheck {
+            check_number,
+            amount,
+            ..
+        } => (
+            Some(check_reference(check_number)),
+            amount,
+            CustomerDeposits,
+            ChecksInClearing,
+        ),
+        BankAccountEvent::CheckBounced {
+            check_number,
+            amount,
+            ..
+        } => (
+            Some(check_reference(check_number)),
+            amount,
+            ChecksInClearing,
+            CustomerDeposits,
+        ),
+        BankAccountEvent::OverdraftFeeCharged { fee, .. }
+        | BankAccountEvent::ReturnedItemFeeCharged { fee, .. }
+        | BankAccountEvent::FeeCharged { fee, .. } => (None, fee, CustomerDeposits, FeeIncome),
+        BankAccountEvent::InterestPosted { amount, .. } => {
+            (None, amount, InterestExpense, CustomerDeposits)
+        }
+        // Transfers are in transit in the currency they were requested in, which both
+        // accounts may have converted from.
+        BankAccountEvent::TransferDebited {
+            transfer_id,
+            amount,
+            conversion,
+            ..
+        } => {
+            let in_transit = conversion
+                .as_ref()
+                .map_or(*amount, |conversion| conversion.original_amount);
+            let postings =
+                exchange_postings(*amount, CustomerDeposits, in_transit, TransfersInTransit)?;
+            return Ok(Some((Some(transfer_id.clone()), postings)));
+        }
+        BankAccountEvent::TransferCredited {
+            transfer_id,
+            amount,
+            conversion,
+            ..
+        } => {
+            let in_transit = conversion
+                .as_ref()
+                .map_or(*amount, |conversion| conversion.original_amount);
+            let postings =
+                exchange_postings(in_transit, TransfersInTransit, *amount, CustomerDeposits)?;
+            return Ok(Some((Some(transfer_id.clone()), postings)));
+        }
+        BankAccountEvent::HoldCaptured {
+            hold_id, amount, ..
+        } => (Some(hold_id.clone()), amount, CustomerDeposits, Settlement),
+        BankAccountEvent::StandingOrderExecuted {
+            standing_order_id,
+            amount,
+            ..
+        } => (
+            Some(standing_order_id.clone()),
+            amount,
+            CustomerDeposits,
+            Settlement,
+        ),
+        _ => return Ok(None),
+    };
+    Ok(Some((reference, postings(*amount, debit, credit)?)))
+}
+
+#[cfg(all(test, feature = "in-memory"))]
+mod tests {
+    use super::*;
+    use crate::domain::{Currency, CurrencyConversion, ExchangeRate, FeeReason};
+    use crate::memory_es::MemDatabase;
+    use crate::presentation::MemLedgerStore;
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn eur(amount: i64) -> Money {
+        Money::new(amount, Currency::EUR)
+    }
+
+    fn usd(amount: i64) -> Money {
+        Money::new(amount, Currency::USD)
+    }
+
+    // The in-memory store, refusing journal entries while it is unavailable.
+    struct FlakyLedgerStore {
+        store: MemLedgerStore,
+        unavailable: AtomicBool,
+    }
+
+    #[async_trait]
+    impl LedgerStore for FlakyLedgerStore {
+        async fn record(&self, entry: &JournalEntry) -> crate::prelude::Result<()> {
+            if self.unavailable.load(Ordering::SeqCst) {
+                return Err(crate::prelude::Error::Generic(
+                    "ledger unavailable".to_string(),
+                ));
+            }
+            self.store.record(entry).await
+        }
+
+        async fn entry(
+            &self,
+            account_id: &str,
+            sequence: usize,
+        ) -> crate::prelude::Result<Option<JournalEntry>> {
+            self.store.entry(account_id, sequence).await
+        }
+
+        async fn entry_with_reference(
+            &self,
+            account_id: &str,
+            reference: &str,
+        ) -> crate::prelude::Result<Option<JournalEntry>> {
+            self.store.entry_with_reference(account_id, reference).await
+        }
+
+        async fn trial_balance(&self) -> crate::prelude::Result<TrialBalance> {
+            self.store.trial_balance().await
+        }
+
+        async fn record_unposted(&self, event: &UnpostedEvent) -> crate::prelude::Result<()> {
+            self.store.record_unposted(event).await
+        }
+
+        async fn remove_unposted(
+            &self,
+            account_id: &str,
+            sequence: usize,
+        ) -> crate::prelude::Result<()> {
+            self.store.remove_unposted(account_id, sequence).await
+        }
+
+        async fn unposted_events(&self) -> crate::prelude::Result<Vec<UnpostedEvent>> {
+            self.store.unposted_events().await
+        }
+    }
+
+    // Knows the events that were stored before the general ledger.
+    #[derive(Default)]
+    struct StoredEvents(Vec<BankAccountEvent>);
+
+    #[async_trait]
+    impl BankAccountEventLookup for StoredEvents {
+        async fn event(
+            &self,
+            _account_id: &str,
+            sequence: usize,
+        ) -> crate::prelude::Result<Option<BankAccountEvent>> {
+            Ok(sequence
+                .checked_sub(1)
+                .and_then(|index| self.0.get(index))
+                .cloned())
+        }
+    }
+
+    fn envelope(sequence: usize, payload: BankAccountEvent) -> EventEnvelope<BankAccount> {
+        EventEnvelope {
+            aggregate_id: "1234".to_string(),
+            sequence,
+            payload,
+            metadata: HashMap::new(),
+        }
+    }
+
+    async fn post(events: Vec<BankAccountEvent>) -> Arc<MemLedgerStore> {
+        let store = Arc::new(MemLedgerStore::new(MemDatabase::default()));
+        let ledger = GeneralLedger::new(store.clone(), Arc::new(StoredEvents(events.clone())));
+        let events: Vec<_> = events
+            .into_iter()
+            .enumerate()
+            .map(|(index, payload)| envelope(index + 1, payload))
+            .collect();
+        ledger.dispatch("1234", &events).await;
+        store
+    }
+
+    #[test]
+    fn ledger_account_codes_round_trip() {
+        for account in [
+            LedgerAccount::CustomerDeposits,
+            LedgerAccount::CashAtAtms,
+            LedgerAccount::InterestExpense,
+        ] {
+            assert_eq!(LedgerAccount::from_code(account.code()), Some(account));
+        }
+        assert_eq!(LedgerAccount::from_code("goodwill"), None);
+    }
+
+    #[tokio::test]
+    async fn trial_balance_debits_equal_credits() {
+        let at = DateTime::default();
+        let store = post(vec![
+            BankAccountEvent::CustomerDepositedMoney {
+                amount: eur(20000),
+                balance: eur(20000),
+                conversion: None,
+                occurred_at: at,
+            },
+            BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(5000),
+                balance: eur(15000),
+                atm_id: Some("atm-1".to_string()),
+                conversion: None,
+                occurred_at: at,
+            },
+            BankAccountEvent::CustomerWroteCheck {
+                check_number: "1170".to_string(),
+                amount: eur(3000),
+                balance: eur(12000),
+                occurred_at: at,
+            },
+            BankAccountEvent::CheckCleared {
+                check_number: "1170".to_string(),
+                occurred_at: at,
+            },
+            BankAccountEvent::FeeCharged {
+                reason: FeeReason::AtmWithdrawal {
+                    atm_id: "atm-1".to_string(),
+                },
+                fee: eur(200),
+                balance: eur(11800),
+                occurred_at: at,
+            },
+            BankAccountEvent::TransactionReversed {
+                reversed_sequence: 2,
+                amount: eur(5000),
+                balance: eur(16800),
+                reason: "cash not dispensed".to_string(),
+                occurred_at: at,
+            },
+        ])
+        .await;
+
+        let trial_balance = store.trial_balance().await.unwrap();
+        assert_eq!(
+            trial_balance.totals,
+            vec![TrialBalanceTotal {
+                debits: eur(36200),
+                credits: eur(36200),
+            }]
+        );
+        let deposits = &trial_balance.accounts[0];
+        assert_eq!(deposits.ledger_account, LedgerAccount::CustomerDeposits);
+        // The balance of the bank account, owed to the customer.
+        assert_eq!(
+            deposits.credits.checked_sub(&deposits.debits).unwrap(),
+            eur(16800)
+        );
+    }
+
+    #[tokio::test]
+    async fn cleared_checks_leave_clearing_for_settlement() {
+        let at = DateTime::default();
+        let store = post(vec![
+            BankAccountEvent::CustomerWroteCheck {
+                check_number: "1170".to_string(),
+                amount: eur(3000),
+                balance: eur(-3000),
+                occurred_at: at,
+            },
+            BankAccountEvent::CheckCleared {
+                check_number: "1170".to_string(),
+                occurred_at: at,
+            },
+        ])
+        .await;
+
+        let cleared = store.entry("1234", 2).await.unwrap().unwrap();
+        assert_eq!(
+            cleared.postings,
+            vec![
+                LedgerPosting {
+                    ledger_account: LedgerAccount::ChecksInClearing,
+                    amount: eur(3000),
+                },
+                LedgerPosting {
+                    ledger_account: LedgerAccount::Settlement,
+                    amount: eur(-3000),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn events_that_do_not_move_money_are_not_posted() {
+        let store = post(vec![BankAccountEvent::AccountFrozen {
+            reason: "suspected fraud".to_string(),
+        }])
+        .await;
+        assert_eq!(store.entry("1234", 1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn transactions_from_before_the_ledger_are_posted_when_reversed() {
+        let at = DateTime::default();
+        let deposit = BankAccountEvent::CustomerDepositedMoney {
+            amount: eur(20000),
+            balance: eur(20000),
+            conversion: None,
+            occurred_at: at,
+        };
+        let store = Arc::new(MemLedgerStore::new(MemDatabase::default()));
+        let ledger = GeneralLedger::new(store.clone(), Arc::new(StoredEvents(vec![deposit])));
+        let reversal = BankAccountEvent::TransactionReversed {
+            reversed_sequence: 1,
+            amount: eur(20000),
+            balance: eur(0),
+            reason: "deposited twice".to_string(),
+            occurred_at: at,
+        };
+        ledger.dispatch("1234", &[envelope(2, reversal)]).await;
+
+        assert!(store.entry("1234", 1).await.unwrap().is_some());
+        let trial_balance = store.trial_balance().await.unwrap();
+        let deposits = &trial_balance.accounts[0];
+        assert_eq!(deposits.ledger_account, LedgerAccount::CustomerDeposits);
+        assert_eq!(deposits.debits, deposits.credits);
+    }
+
+    #[tokio::test]
+    async fn events_that_cannot_be_posted_are_retried() {
+        let deposit = BankAccountEvent::CustomerDepositedMoney {
+            amount: eur(20000),
+            balance: eur(20000),
+            conversion: None,
+            occurred_at: DateTime::default(),
+        };
+        let store = Arc::new(FlakyLedgerStore {
+            store: MemLedgerStore::new(MemDatabase::default()),
+            unavailable: AtomicBool::new(false),
+        });
+        let ledger =
+            GeneralLedger::new(store.clone(), Arc::new(StoredEvents(vec![deposit.clone()])));
+        store.unavailable.store(true, Ordering::SeqCst);
+        ledger.dispatch("1234", &[envelope(1, deposit)]).await;
+
+        let unposted = store.unposted_events().await.unwrap();
+        assert_eq!(unposted.len(), 1);
+        assert_eq!(unposted[0].sequence, 1);
+        assert_eq!(ledger.post_unposted_events().await, 0);
+        assert_eq!(store.unposted_events().await.unwrap().len(), 1);
+
+        store.unavailable.store(false, Ordering::SeqCst);
+        assert_eq!(ledger.post_unposted_events().await, 1);
+        assert!(store.entry("1234", 1).await.unwrap().is_some());
+        assert_eq!(store.unposted_events().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn cross_currency_transfers_leave_nothing_in_transit() {
+        let at = DateTime::default();
+        // 100 USD from a dollar account to a euro account, at 0.90 EUR to the dollar.
+        let debited = BankAccountEvent::TransferDebited {
+            transfer_id: "t-1".to_string(),
+            destination_account_id: "5678".to_string(),
+            amount: usd(10000),
+            balance: usd(0),
+            conversion: None,
+            occurred_at: at,
+        };
+        let credited = BankAccountEvent::TransferCredited {
+            transfer_id: "t-1".to_string(),
+            source_account_id: "1234".to_string(),
+            amount: eur(9000),
+            balance: eur(9000),
+            conversion: Some(CurrencyConversion {
+                original_amount: usd(10000),
+                rate: ExchangeRate::new(Currency::USD, Currency::EUR, 90_000_000).unwrap(),
+            }),
+            occurred_at: at,
+        };
+        let store = Arc::new(MemLedgerStore::new(MemDatabase::default()));
+        let ledger = GeneralLedger::new(store.clone(), Arc::new(StoredEvents::default()));
+        ledger.dispatch("1234", &[envelope(1, debited)]).await;
+        ledger.dispatch("5678", &[envelope(1, credited)]).await;
+
+        let trial_balance = store.trial_balance().await.unwrap();
+        for total in &trial_balance.totals {
+            assert_eq!(total.debits, total.credits);
+        }
+        for balance in &trial_balance.accounts {
+            if balance.ledger_account == LedgerAccount::TransfersInTransit {
+                assert_eq!(balance.debits, balance.credits);
+            }
+        }
+        // The dollars in transit, and the euros they were converted to.
+        let exchanged: Vec<_> = trial_balance
+            .accounts
+            .into_iter()
+            .filter(|balance| balance.ledger_account == LedgerAccount::ForeignExchange)
+            .collect();
+        assert_eq!(
+            exchanged,
+            vec![
+                LedgerBalance {
+                    ledger_account: LedgerAccount::ForeignExchange,
+                    debits: eur(9000),
+                    credits: eur(0),
+                },
+                LedgerBalance {
+                    ledger_account: LedgerAccount::ForeignExchange,
+                    debits: usd(0),
+                    credits: usd(10000),
+                },
+            ]
+        );
+        assert_eq!(store.unposted_events().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn refunds_undo_the_converted_debit() {
+        let at = DateTime::default();
+        let store = post(vec![
+            BankAccountEvent::TransferDebited {
+                transfer_id: "t-1".to_string(),
+                destination_account_id: "5678".to_string(),
+                amount: eur(9000),
+                balance: eur(0),
+                conversion: Some(CurrencyConversion {
+                    original_amount: usd(10000),
+                    rate: ExchangeRate::new(Currency::USD, Currency::EUR, 90_000_000).unwrap(),
+                }),
+                occurred_at: at,
+            },
+            BankAccountEvent::TransferRefunded {
+                transfer_id: "t-1".to_string(),
+                amount: eur(9000),
+                balance: eur(9000),
+                reason: "destination account closed".to_string(),
+                occurred_at: at,
+            },
+        ])
+        .await;
+
+        let trial_balance = store.trial_balance().await.unwrap();
+        assert_eq!(trial_balance.totals.len(), 2);
+        for balance in &trial_balance.accounts {
+            assert_eq!(balance.debits, balance.credits);
+        }
+    }
+}
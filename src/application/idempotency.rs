@@ -0,0 +1,608 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use cqrs_es::AggregateError;
+use serde::{Deserialize, Serialize};
+
+// Expired keys are only reused once they have lapsed anyway, forgetting them every ten minutes
+// keeps the table small without deleting on every request.
+const CLEANUP_INTERVAL: StdDuration = StdDuration::from_secs(10 * 60);
+
+// The command metadata entry carrying the idempotency key supplied by the client.
+pub const IDEMPOTENCY_KEY_METADATA: &str = "idempotency_key";
+
+// The command metadata entry carrying the id of the request the events were committed for.
+// Retries of a request are given its id, so the events show whether it was committed.
+pub const REQUEST_ID_METADATA: &str = "request_id";
+
+// Refuses to commit events for a request that already committed events to the aggregate,
+// e.g. when a retry ran while the request was still in progress.
+#[derive(thiserror::Error, Debug)]
+#[error("the request has already been committed")]
+pub struct RequestAlreadyCommitted;
+
+// How long an idempotency key is remembered for, loaded from the `idempotency` section of
+// the application configuration. A key can be reused for a new request once it has lapsed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IdempotencyPolicy {
+    #[serde(default = "default_retention_seconds")]
+    pub retention_seconds: i64,
+    // A request still in progress after this long is taken to have been abandoned, e.g.
+    // because the service stopped while executing it, and the key is given to its retry.
+    // The retry only executes the command if the events show it was not committed.
+    #[serde(default = "default_in_progress_timeout_seconds")]
+    pub in_progress_timeout_seconds: i64,
+}
+
+fn default_retention_seconds() -> i64 {
+    24 * 60 * 60
+}
+
+fn default_in_progress_timeout_seconds() -> i64 {
+    60
+}
+
+impl Default for IdempotencyPolicy {
+    fn default() -> Self {
+        Self {
+            retention_seconds: default_retention_seconds(),
+            in_progress_timeout_seconds: default_in_progress_timeout_seconds(),
+        }
+    }
+}
+
+// What happened to a command, as replayed to clients retrying the same request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CommandOutcome {
+    Succeeded,
+    Rejected(String),
+}
+
+// A request claiming an idempotency key, under an id of its own unless a previous attempt
+// at the same request already holds the key.
+pub struct IdempotencyClaim<'a> {
+    pub scope: &'a str,
+    pub key: &'a str,
+    pub request: &'a str,
+    pub request_id: &'a str,
+}
+
+pub enum IdempotencyReservation {
+    // The key is now held for the request until its outcome is recorded. A key taken over
+    // from an abandoned or failed attempt at the same request keeps the id of that attempt,
+    // which may have committed its events before it stopped.
+    Reserved {
+        request_id: String,
+        reclaimed: bool,
+    },
+    // The key is already in use. There is no outcome while the first request is in progress.
+    Existing {
+        request: String,
+        request_id: String,
+        outcome: Option<CommandOutcome>,
+    },
+}
+
+// Persists idempotency keys along with the request they were first used for. Keys are only
+// unique within their scope, so that clients cannot see each other's outcomes.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    // Claims the key at `now`. Keys claimed before `expired_before` are claimed afresh, and
+    // attempts at the same request without an outcome that were released, or last claimed
+    // before `abandoned_before`, are taken over.
+    async fn reserve(
+        &self,
+        claim: &IdempotencyClaim<'_>,
+        now: DateTime<Utc>,
+        expired_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> crate::prelude::Result<IdempotencyReservation>;
+
+    async fn record_outcome(
+        &self,
+        scope: &str,
+        key: &str,
+        outcome: &CommandOutcome,
+    ) -> crate::prelude::Result<()>;
+
+    // Lets the request be retried straight away. The key stays with the request and its id,
+    // as the attempt may have committed its events regardless.
+    async fn release(&self, scope: &str, key: &str) -> crate::prelude::Result<()>;
+
+    // Forgets the keys claimed before `expired_before` and returns how many there were.
+    async fn forget_expired(&self, expired_before: DateTime<Utc>) -> crate::prelude::Result<u64>;
+}
+
+// Tells whether a request committed events to an aggregate, from the request ids recorded
+// in the metadata of its events.
+#[async_trait]
+pub trait CommittedRequests: Send + Sync {
+    async fn is_committed(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: &str,
+        request_id: &str,
+    ) -> crate::prelude::Result<bool>;
+}
+
+// A command to be executed at most once per idempotency key.
+pub struct IdempotentRequest<'a> {
+    pub aggregate_type: &'a str,
+    // Commands for no aggregate in particular create one, which is given the request id.
+    pub aggregate_id: Option<&'a str>,
+    // Who the key belongs to, e.g. the account and caller of the command.
+    pub scope: &'a str,
+    // What was asked for, so that a key reused for a different request is rejected.
+    pub request: &'a str,
+    // The metadata of the command, with the idempotency key if the client supplied one.
+    pub metadata: HashMap<String, String>,
+}
+
+// What happened to a request, and the id it was executed under.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdempotentOutcome {
+    pub request_id: String,
+    pub outcome: CommandOutcome,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum IdempotencyError {
+    #[error("idempotency key has already been used for a different request")]
+    KeyReused,
+    #[error("a request with this idempotency key is still being processed")]
+    InProgress,
+    // The command could not be carried out, e.g. because of a concurrent change to the
+    // aggregate, so the key is released for the client to retry.
+    #[error("{0}")]
+    CommandFailed(String),
+    #[error("idempotency keys could not be accessed: {0}")]
+    Store(String),
+}
+
+// Executes commands at most once per idempotency key, replaying the original outcome to
+// retried requests instead. The events committed for a request carry its id, which settles
+// whether an attempt that stopped before recording its outcome was committed. Expired keys
+// are forgotten in the background.
+pub struct IdempotentCommands {
+    store: Arc<dyn IdempotencyStore>,
+    committed: Arc<dyn CommittedRequests>,
+    policy: IdempotencyPolicy,
+}
+
+impl IdempotentCommands {
+    pub fn new(
+        store: Arc<dyn IdempotencyStore>,
+        committed: Arc<dyn CommittedRequests>,
+        policy: IdempotencyPolicy,
+    ) -> Self {
+        Self {
+            store,
+            committed,
+            policy,
+        }
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.forget_expired_keys(Utc::now()).await;
+        }
+    }
+
+    // Returns the number of keys that had expired by `now`.
+    pub async fn forget_expired_keys(&self, now: DateTime<Utc>) -> u64 {
+        let expired_before = now - Duration::seconds(self.policy.retention_seconds);
+        match self.store.forget_expired(expired_before).await {
+            Ok(forgotten) => forgotten,
+            Err(err) => {
+                tracing::error!("could not forget expired idempotency keys: {err}");
+                0
+            }
+        }
+    }
+
+    // Commands without a key are always executed. `execute` is given the id of the request,
+    // and the metadata to commit the events with.
+    pub async fn execute<F, Fut, E>(
+        &self,
+        request: IdempotentRequest<'_>,
+        execute: F,
+    ) -> Result<IdempotentOutcome, IdempotencyError>
+    where
+        F: FnOnce(String, HashMap<String, String>) -> Fut,
+        Fut: Future<Output = Result<(), AggregateError<E>>>,
+        E: std::error::Error,
+    {
+        let IdempotentRequest {
+            aggregate_type,
+            aggregate_id,
+            scope,
+            request,
+            mut metadata,
+        } = request;
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let Some(key) = metadata.get(IDEMPOTENCY_KEY_METADATA).cloned() else {
+            let outcome = command_outcome(execute(request_id.clone(), metadata).await)?;
+            return Ok(IdempotentOutcome {
+                request_id,
+                outcome,
+            });
+        };
+        let now = Utc::now();
+        let expired_before = now - Duration::seconds(self.policy.retention_seconds);
+        let abandoned_before = now - Duration::seconds(self.policy.in_progress_timeout_seconds);
+        let claim = IdempotencyClaim {
+            scope,
+            key: &key,
+            request,
+            request_id: &request_id,
+        };
+        let request_id = match self
+            .store
+            .reserve(&claim, now, expired_before, abandoned_before)
+            .await
+            .map_err(store_error)?
+        {
+            IdempotencyReservation::Reserved {
+                request_id,
+                reclaimed: false,
+            } => request_id,
+            IdempotencyReservation::Reserved {
+                request_id,
+                reclaimed: true,
+            } => {
+                let aggregate_id = aggregate_id.unwrap_or(&request_id);
+                let committed = self
+                    .committed
+                    .is_committed(aggregate_type, aggregate_id, &request_id)
+                    .await
+                    .map_err(store_error)?;
+                if committed {
+                    return self
+                        .record_outcome(scope, &key, request_id, CommandOutcome::Succeeded)
+                        .await;
+                }
+                request_id
+            }
+            IdempotencyReservation::Existing {
+                request: original_request,
+                ..
+            } if original_request != request => return Err(IdempotencyError::KeyReused),
+            IdempotencyReservation::Existing {
+                request_id,
+                outcome: Some(outcome),
+                ..
+            } => {
+                return Ok(IdempotentOutcome {
+                    request_id,
+                    outcome,
+                })
+            }
+            IdempotencyReservation::Existing { outcome: None, .. } => {
+                return Err(IdempotencyError::InProgress)
+            }
+        };
+
+        metadata.insert(REQUEST_ID_METADATA.to_string(), request_id.clone());
+        match command_outcome(execute(request_id.clone(), metadata).await) {
+            Ok(outcome) => self.record_outcome(scope, &key, request_id, outcome).await,
+            Err(err) => {
+                if let Err(release_err) = self.store.release(scope, &key).await {
+                    tracing::error!("could not release idempotency key {key}: {release_err}");
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn record_outcome(
+        &self,
+        scope: &str,
+        key: &str,
+        request_id: String,
+        outcome: CommandOutcome,
+    ) -> Result<IdempotentOutcome, IdempotencyError> {
+        self.store
+            .record_outcome(scope, key, &outcome)
+            .await
+            .map_err(store_error)?;
+        Ok(IdempotentOutcome {
+            request_id,
+            outcome,
+        })
+    }
+}
+
+fn store_error(err: crate::prelude::Error) -> IdempotencyError {
+    IdempotencyError::Store(err.to_string())
+}
+
+// Commands rejected by the aggregate have a final outcome, anything else may succeed if
+// the command is retried.
+fn command_outcome<E: std::error::Error>(
+    result: Result<(), AggregateError<E>>,
+) -> Result<CommandOutcome, IdempotencyError> {
+    match result {
+        Ok(()) => Ok(CommandOutcome::Succeeded),
+        Err(err @ AggregateError::UserError(_)) => Ok(CommandOutcome::Rejected(err.to_string())),
+        // Another attempt at the same request committed its events first.
+        Err(AggregateError::UnexpectedError(err))
+            if err.downcast_ref::<RequestAlreadyCommitted>().is_some() =>
+        {
+            Ok(CommandOutcome::Succeeded)
+        }
+        Err(err) => Err(IdempotencyError::CommandFailed(err.to_string())),
+    }
+}
+
+#[cfg(all(test, feature = "in-memory"))]
+mod tests {
+    use super::*;
+    use crate::any_es::AnyDatabase;
+    use crate::domain::{BankAccount, BankAccountError};
+    use crate::memory_es::{MemDatabase, MemEventRepository};
+    use crate::presentation::{EventStoreCommittedRequests, MemIdempotencyStore};
+    use cqrs_es::persist::{PersistedEventRepository, SerializedEvent};
+    use cqrs_es::Aggregate;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn idempotent_commands() -> IdempotentCommands {
+        idempotent_commands_in(&MemDatabase::default(), IdempotencyPolicy::default())
+    }
+
+    fn idempotent_commands_in(db: &MemDatabase, policy: IdempotencyPolicy) -> IdempotentCommands {
+        IdempotentCommands::new(
+            Arc::new(MemIdempotencyStore::new(db.clone())),
+            Arc::new(EventStoreCommittedRequests::new(AnyDatabase::InMemory(
+                db.clone(),
+            ))),
+            policy,
+        )
+    }
+
+    // Leaves the key held by an attempt at the request that stopped at `reserved_at`.
+    async fn abandon_request(db: &MemDatabase, request_id: &str, reserved_at: DateTime<Utc>) {
+        let claim = IdempotencyClaim {
+            scope: "account-1",
+            key: "key-1",
+            request: "deposit",
+            request_id,
+        };
+        MemIdempotencyStore::new(db.clone())
+            .reserve(&claim, reserved_at, reserved_at, reserved_at)
+            .await
+            .unwrap();
+    }
+
+    async fn execute(
+        commands: &IdempotentCommands,
+        key: Option<&str>,
+        request: &str,
+        executions: &AtomicUsize,
+        result: fn() -> Result<(), AggregateError<BankAccountError>>,
+    ) -> Result<CommandOutcome, IdempotencyError> {
+        execute_in_scope(commands, "account-1", key, request, executions, result).await
+    }
+
+    async fn execute_in_scope(
+        commands: &IdempotentCommands,
+        scope: &str,
+        key: Option<&str>,
+        request: &str,
+        executions: &AtomicUsize,
+        result: fn() -> Result<(), AggregateError<BankAccountError>>,
+    ) -> Result<CommandOutcome, IdempotencyError> {
+        let mut metadata = HashMap::new();
+        if let Some(key) = key {
+            metadata.insert(IDEMPOTENCY_KEY_METADATA.to_string(), key.to_string());
+        }
+        let request = IdempotentRequest {
+            aggregate_type: &BankAccount::aggregate_type(),
+            aggregate_id: Some("1234"),
+            scope,
+            request,
+            metadata,
+        };
+        commands
+            .execute(request, |_, _| async {
+                executions.fetch_add(1, Ordering::SeqCst);
+                result()
+            })
+            .await
+            .map(|executed| executed.outcome)
+    }
+
+    #[tokio::test]
+    async fn retried_requests_replay_the_outcome_without_executing_again() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        for _ in 0..2 {
+            let outcome =
+                execute(&commands, Some("key-1"), "deposit", &executions, || Ok(())).await;
+            assert_eq!(outcome, Ok(CommandOutcome::Succeeded));
+        }
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_commands_are_replayed_as_rejected() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        let rejected = || {
+            Err(AggregateError::UserError(
+                BankAccountError::InsufficientFunds,
+            ))
+        };
+        let first = execute(&commands, Some("key-1"), "withdraw", &executions, rejected).await;
+        let retry = execute(&commands, Some("key-1"), "withdraw", &executions, || Ok(())).await;
+        assert!(matches!(first, Ok(CommandOutcome::Rejected(_))));
+        assert_eq!(first, retry);
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn keys_reused_for_a_different_request_are_a_conflict() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        execute(&commands, Some("key-1"), "deposit", &executions, || Ok(()))
+            .await
+            .unwrap();
+        let outcome = execute(&commands, Some("key-1"), "withdraw", &executions, || Ok(())).await;
+        assert_eq!(outcome, Err(IdempotencyError::KeyReused));
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn keys_are_released_when_the_command_could_not_be_carried_out() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        let conflict = || Err(AggregateError::AggregateConflict);
+        let first = execute(&commands, Some("key-1"), "deposit", &executions, conflict).await;
+        assert!(matches!(first, Err(IdempotencyError::CommandFailed(_))));
+        let retry = execute(&commands, Some("key-1"), "deposit", &executions, || Ok(())).await;
+        assert_eq!(retry, Ok(CommandOutcome::Succeeded));
+        assert_eq!(executions.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn requests_without_a_key_are_always_executed() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        for _ in 0..2 {
+            execute(&commands, None, "deposit", &executions, || Ok(()))
+                .await
+                .unwrap();
+        }
+        assert_eq!(executions.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn keys_are_only_reused_within_their_scope() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        for scope in ["account-1", "account-2"] {
+            let outcome = execute_in_scope(
+                &commands,
+                scope,
+                Some("key-1"),
+                "deposit",
+                &executions,
+                || Ok(()),
+            )
+            .await;
+            assert_eq!(outcome, Ok(CommandOutcome::Succeeded));
+        }
+        assert_eq!(executions.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn abandoned_requests_are_retried_once_they_time_out() {
+        let db = MemDatabase::default();
+        abandon_request(&db, "request-1", Utc::now() - Duration::seconds(120)).await;
+        let executions = AtomicUsize::new(0);
+
+        let policy = IdempotencyPolicy {
+            in_progress_timeout_seconds: 600,
+            ..IdempotencyPolicy::default()
+        };
+        let commands = idempotent_commands_in(&db, policy);
+        let outcome = execute(&commands, Some("key-1"), "deposit", &executions, || Ok(())).await;
+        assert_eq!(outcome, Err(IdempotencyError::InProgress));
+
+        let commands = idempotent_commands_in(&db, IdempotencyPolicy::default());
+        let outcome = execute(&commands, Some("key-1"), "deposit", &executions, || Ok(())).await;
+        assert_eq!(outcome, Ok(CommandOutcome::Succeeded));
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn abandoned_requests_that_committed_their_events_are_not_executed_again() {
+        let db = MemDatabase::default();
+        abandon_request(&db, "request-1", Utc::now() - Duration::seconds(120)).await;
+        let event = SerializedEvent::new(
+            "1234".to_string(),
+            1,
+            BankAccount::aggregate_type(),
+            "CustomerDepositedMoney".to_string(),
+            "2.0".to_string(),
+            json!({}),
+            json!({ REQUEST_ID_METADATA: "request-1" }),
+        );
+        MemEventRepository::new(db.clone())
+            .persist::<BankAccount>(&[event], None)
+            .await
+            .unwrap();
+        let executions = AtomicUsize::new(0);
+
+        let commands = idempotent_commands_in(&db, IdempotencyPolicy::default());
+        let outcome = execute(&commands, Some("key-1"), "deposit", &executions, || Ok(())).await;
+        assert_eq!(outcome, Ok(CommandOutcome::Succeeded));
+        assert_eq!(executions.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn requests_committed_by_another_attempt_succeed() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        let already_committed = || {
+            Err(AggregateError::UnexpectedError(Box::new(
+                RequestAlreadyCommitted,
+            )))
+        };
+        let outcome = execute(
+            &commands,
+            Some("key-1"),
+            "deposit",
+            &executions,
+            already_committed,
+        )
+        .await;
+        assert_eq!(outcome, Ok(CommandOutcome::Succeeded));
+    }
+
+    #[tokio::test]
+    async fn retries_are_given_the_id_of_the_original_request() {
+        let commands = idempotent_commands();
+        let mut request_ids = Vec::new();
+        for _ in 0..2 {
+            let metadata =
+                HashMap::from([(IDEMPOTENCY_KEY_METADATA.to_string(), "key-1".to_string())]);
+            let request = IdempotentRequest {
+                aggregate_type: &BankAccount::aggregate_type(),
+                aggregate_id: None,
+                scope: "account-1",
+                request: "transfer",
+                metadata,
+            };
+            let executed = commands
+                .execute(request, |request_id, metadata| async move {
+                    assert_eq!(metadata.get(REQUEST_ID_METADATA), Some(&request_id));
+                    Ok::<_, AggregateError<BankAccountError>>(())
+                })
+                .await
+                .unwrap();
+            request_ids.push(executed.request_id);
+        }
+        assert_eq!(request_ids[0], request_ids[1]);
+    }
+
+    #[tokio::test]
+    async fn expired_keys_are_forgotten() {
+        let commands = idempotent_commands();
+        let executions = AtomicUsize::new(0);
+        execute(&commands, Some("key-1"), "deposit", &executions, || Ok(()))
+            .await
+            .unwrap();
+        assert_eq!(commands.forget_expired_keys(Utc::now()).await, 0);
+        let retention = Duration::seconds(IdempotencyPolicy::default().retention_seconds);
+        let lapsed = Utc::now() + retention + Duration::seconds(1);
+        assert_eq!(commands.forget_expired_keys(lapsed).await, 1);
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use cqrs_es::{AggregateError, CqrsFramework, EventStore};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::domain::{
+    AccountProduct, BankAccount, BankAccountAccrueInterestCommandData, BankAccountCommand,
+    BankAccountError, ProductCatalogue,
+};
+
+// How and when interest is accrued for open accounts, loaded from the `interest` section
+// of the bank account configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InterestConfiguration {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How many days back each run looks, so that days missed while the service was
+    /// down are still accrued. Days already accrued are skipped by the aggregate.
+    #[serde(default = "default_catch_up_days")]
+    pub catch_up_days: u32,
+
+    #[serde(default = "default_run_interval_seconds")]
+    pub run_interval_seconds: u64,
+}
+
+fn default_catch_up_days() -> u32 {
+    7
+}
+
+fn default_run_interval_seconds() -> u64 {
+    60 * 60
+}
+
+impl Default for InterestConfiguration {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            catch_up_days: default_catch_up_days(),
+            run_interval_seconds: default_run_interval_seconds(),
+        }
+    }
+}
+
+// An account that is open or frozen, along with the product it was opened as.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenAccount {
+    pub account_id: String,
+    pub product: AccountProduct,
+}
+
+// Finds the accounts that should accrue interest, i.e. those that are open or frozen.
+#[async_trait]
+pub trait OpenAccountsLookup: Send + Sync {
+    async fn open_accounts(&self) -> crate::prelude::Result<Vec<OpenAccount>>;
+}
+
+// Periodically issues `AccrueInterest` commands for every open account, covering the days
+// up to (but not including) today at the rate of the account's product.
+pub struct InterestAccrualScheduler<ES: EventStore<BankAccount>> {
+    cqrs: Arc<CqrsFramework<BankAccount, ES>>,
+    accounts: Arc<dyn OpenAccountsLookup>,
+    products: ProductCatalogue,
+    configuration: InterestConfiguration,
+}
+
+impl<ES: EventStore<BankAccount>> InterestAccrualScheduler<ES> {
+    pub fn new(
+        cqrs: Arc<CqrsFramework<BankAccount, ES>>,
+        accounts: Arc<dyn OpenAccountsLookup>,
+        products: ProductCatalogue,
+        configuration: InterestConfiguration,
+    ) -> Self {
+        Self {
+            cqrs,
+            accounts,
+            products,
+            configuration,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.configuration.run_interval_seconds));
+        loop {
+            interval.tick().await;
+            self.accrue_interest_for_open_accounts().await;
+        }
+    }
+
+    // Returns the number of accounts that interest was accrued for.
+    #[instrument(skip(self))]
+    pub async fn accrue_interest_for_open_accounts(&self) -> usize {
+        let today = Utc::now().date_naive();
+        let accounts = match self.accounts.open_accounts().await {
+            Ok(accounts) => accounts,
+            Err(err) => {
+                tracing::error!("could not look up open accounts: {err}");
+                return 0;
+            }
+        };
+
+        let mut accrued = 0;
+        for OpenAccount {
+            account_id,
+            product,
+        } in accounts
+        {
+            // Products without a rate earn no interest, so there is nothing to accrue.
+            let Some(annual_rate_basis_points) = self
+                .products
+                .rules_for(product)
+                .annual_interest_rate_basis_points
+            else {
+                continue;
+            };
+            let command = BankAccountAccrueInterestCommandData {
+                annual_rate_basis_points,
+                period_start: today
+                    - chrono::Duration::days(self.configuration.catch_up_days.into()),
+                period_end: today,
+            };
+            match self
+                .cqrs
+                .execute(&account_id, BankAccountCommand::AccrueInterest(command))
+                .await
+            {
+                Ok(()) => accrued += 1,
+                Err(AggregateError::UserError(
+                    BankAccountError::InterestAlreadyAccrued | BankAccountError::InterestNotEarned,
+                )) => {}
+                Err(err) => {
+                    tracing::error!("could not accrue interest for account {account_id}: {err}");
+                }
+            }
+        }
+        tracing::info!("accrued interest for {accrued} accounts");
+        accrued
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        BankAccountServices, HappyPathBankAccountServices, HappyPathCustomerKycLookup,
+    };
+    use crate::domain::{BankAccountOpenAccountCommandData, Currency};
+    use cqrs_es::mem_store::MemStore;
+    use pretty_assertions::assert_eq;
+
+    struct FixedOpenAccounts(Vec<OpenAccount>);
+
+    #[async_trait]
+    impl OpenAccountsLookup for FixedOpenAccounts {
+        async fn open_accounts(&self) -> crate::prelude::Result<Vec<OpenAccount>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn accrues_interest_once_per_day_for_open_accounts() {
+        let services = BankAccountServices::new(Box::<HappyPathBankAccountServices>::default())
+            .with_customers(Box::new(HappyPathCustomerKycLookup));
+        let cqrs = Arc::new(CqrsFramework::new(
+            MemStore::<BankAccount>::default(),
+            vec![],
+            services,
+        ));
+        cqrs.execute(
+            "1234",
+            BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: "customer-1".to_string(),
+                product: AccountProduct::Savings,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let scheduler = InterestAccrualScheduler::new(
+            cqrs,
+            Arc::new(FixedOpenAccounts(vec![OpenAccount {
+                account_id: "1234".to_string(),
+                product: AccountProduct::Savings,
+            }])),
+            ProductCatalogue::default(),
+            InterestConfiguration {
+                enabled: true,
+                ..InterestConfiguration::default()
+            },
+        );
+        assert_eq!(scheduler.accrue_interest_for_open_accounts().await, 1);
+        assert_eq!(scheduler.accrue_interest_for_open_accounts().await, 0);
+    }
+}
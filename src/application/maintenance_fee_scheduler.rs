@@ -0,0 +1,157 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use cqrs_es::{AggregateError, CqrsFramework, EventStore};
+use tracing::instrument;
+
+use crate::application::OpenAccountsLookup;
+use crate::domain::{
+    BankAccount, BankAccountChargeMaintenanceFeeCommandData, BankAccountCommand, BankAccountError,
+};
+
+// Fees only need charging once a month, checking hourly catches up soon after downtime.
+const RUN_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Periodically issues `ChargeMaintenanceFee` commands for the previous calendar month to
+// every open account. Months that have already been charged, or that ended before the account
+// was opened, are refused by the aggregate and skipped.
+pub struct MaintenanceFeeScheduler<ES: EventStore<BankAccount>> {
+    cqrs: Arc<CqrsFramework<BankAccount, ES>>,
+    accounts: Arc<dyn OpenAccountsLookup>,
+}
+
+impl<ES: EventStore<BankAccount>> MaintenanceFeeScheduler<ES> {
+    pub fn new(
+        cqrs: Arc<CqrsFramework<BankAccount, ES>>,
+        accounts: Arc<dyn OpenAccountsLookup>,
+    ) -> Self {
+        Self { cqrs, accounts }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(RUN_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.charge_open_accounts(Utc::now().date_naive()).await;
+        }
+    }
+
+    // Returns the number of accounts that were charged for the month before `today`.
+    #[instrument(skip(self))]
+    pub async fn charge_open_accounts(&self, today: NaiveDate) -> usize {
+        let command = BankAccountChargeMaintenanceFeeCommandData {
+            period: previous_month(today),
+        };
+        let account_ids = match self.accounts.open_accounts().await {
+            Ok(accounts) => accounts.into_iter().map(|account| account.account_id),
+            Err(err) => {
+                tracing::error!("could not look up open accounts: {err}");
+                return 0;
+            }
+        };
+
+        let mut charged = 0;
+        for account_id in account_ids {
+            match self
+                .cqrs
+                .execute(
+                    &account_id,
+                    BankAccountCommand::ChargeMaintenanceFee(command.clone()),
+                )
+                .await
+            {
+                Ok(()) => charged += 1,
+                Err(AggregateError::UserError(
+                    BankAccountError::MaintenanceFeeAlreadyCharged
+                    | BankAccountError::MaintenanceFeePeriodBeforeOpening,
+                )) => {}
+                Err(err) => {
+                    tracing::error!(
+                        "could not charge maintenance fee for account {account_id}: {err}"
+                    );
+                }
+            }
+        }
+        tracing::info!("charged maintenance fees for {charged} accounts");
+        charged
+    }
+}
+
+fn previous_month(today: NaiveDate) -> String {
+    let last_month = today.with_day(1).unwrap_or(today) - chrono::Duration::days(1);
+    last_month.format("%Y-%m").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        BankAccountConfiguration, BankAccountServices, FeeEngine, FeeRule,
+        HappyPathBankAccountServices, HappyPathCustomerKycLookup, OpenAccount,
+    };
+    use crate::domain::{AccountProduct, BankAccountOpenAccountCommandData, Currency, FeeAmount};
+    use async_trait::async_trait;
+    use cqrs_es::mem_store::MemStore;
+    use pretty_assertions::assert_eq;
+
+    struct FixedOpenAccounts(Vec<OpenAccount>);
+
+    #[async_trait]
+    impl OpenAccountsLookup for FixedOpenAccounts {
+        async fn open_accounts(&self) -> crate::prelude::Result<Vec<OpenAccount>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn previous_month_crosses_year_boundaries() {
+        let day = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        assert_eq!(previous_month(day(2023, 3, 15)), "2023-02");
+        assert_eq!(previous_month(day(2023, 1, 1)), "2022-12");
+    }
+
+    #[tokio::test]
+    async fn charges_open_accounts_once_per_month() {
+        let services = BankAccountServices::new(Box::<HappyPathBankAccountServices>::default())
+            .with_customers(Box::new(HappyPathCustomerKycLookup))
+            .with_configuration(BankAccountConfiguration {
+                fees: FeeEngine {
+                    rules: vec![FeeRule::MonthlyMaintenance {
+                        fee: FeeAmount::Flat(500),
+                    }],
+                },
+                ..BankAccountConfiguration::default()
+            });
+        let cqrs = Arc::new(CqrsFramework::new(
+            MemStore::<BankAccount>::default(),
+            vec![],
+            services,
+        ));
+        cqrs.execute(
+            "1234",
+            BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: "customer-1".to_string(),
+                product: AccountProduct::Checking,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let scheduler = MaintenanceFeeScheduler::new(
+            cqrs,
+            Arc::new(FixedOpenAccounts(vec![OpenAccount {
+                account_id: "1234".to_string(),
+                product: AccountProduct::Checking,
+            }])),
+        );
+        // The account was opened this month, so nothing is due for the previous one.
+        let today = Utc::now().date_naive();
+        assert_eq!(scheduler.charge_open_accounts(today).await, 0);
+        let next_month = today.with_day(1).unwrap() + chrono::Duration::days(31);
+        assert_eq!(scheduler.charge_open_accounts(next_month).await, 1);
+        assert_eq!(scheduler.charge_open_accounts(next_month).await, 0);
+    }
+}
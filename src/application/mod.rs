@@ -1,4 +1,24 @@
+pub mod bank_account_configuration;
 pub mod bank_account_service;
+pub mod fee_engine;
+pub mod fx_rate_table;
+pub mod general_ledger;
+pub mod idempotency;
+pub mod interest_accrual_scheduler;
+pub mod maintenance_fee_scheduler;
+pub mod snapshot_policy;
+pub mod standing_order_scheduler;
+pub mod transfer_process_manager;
 
 // Re-exports
+pub use bank_account_configuration::*;
 pub use bank_account_service::*;
+pub use fee_engine::*;
+pub use fx_rate_table::*;
+pub use general_ledger::*;
+pub use idempotency::*;
+pub use interest_accrual_scheduler::*;
+pub use maintenance_fee_scheduler::*;
+pub use snapshot_policy::*;
+pub use standing_order_scheduler::*;
+pub use transfer_process_manager::*;
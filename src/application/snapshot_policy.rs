@@ -0,0 +1,39 @@
+use cqrs_es::persist::{PersistedEventRepository, PersistedEventStore};
+use cqrs_es::Aggregate;
+use serde::{Deserialize, Serialize};
+
+// How often the state of an aggregate is snapshotted, so that loading it only replays the
+// events committed since the latest snapshot rather than its whole history.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotPolicy {
+    /// Take a snapshot every this many events, snapshots are not taken when not set
+    #[serde(default)]
+    pub every_n_events: Option<usize>,
+}
+
+impl SnapshotPolicy {
+    pub fn event_store<R, A>(&self, repository: R) -> PersistedEventStore<R, A>
+    where
+        R: PersistedEventRepository,
+        A: Aggregate,
+    {
+        match self.every_n_events {
+            Some(every_n_events) if every_n_events > 0 => {
+                PersistedEventStore::new_snapshot_store(repository, every_n_events)
+            }
+            _ => PersistedEventStore::new_event_store(repository),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn snapshots_are_disabled_by_default() {
+        let policy: SnapshotPolicy = serde_yaml::from_str("{}").unwrap();
+        assert_eq!(policy.every_n_events, None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use cqrs_es::{AggregateError, CqrsFramework, EventStore};
+use tracing::instrument;
+
+use crate::domain::{
+    BankAccount, BankAccountCommand, BankAccountError, BankAccountExecuteStandingOrderCommandData,
+};
+
+// Payments are due on a day rather than at a time, checking every few minutes pays them
+// early in the day and soon after downtime.
+const RUN_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+// A standing order payment that has fallen due and not been made yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DueStandingOrder {
+    pub account_id: String,
+    pub standing_order_id: String,
+    pub due_date: NaiveDate,
+}
+
+// Finds the standing order payments due by a day, across all accounts.
+#[async_trait]
+pub trait DueStandingOrdersLookup: Send + Sync {
+    async fn due_standing_orders(
+        &self,
+        today: NaiveDate,
+    ) -> crate::prelude::Result<Vec<DueStandingOrder>>;
+}
+
+// Periodically issues `ExecuteStandingOrder` commands for the payments that have fallen due.
+// The schedule lives in the event store, so nothing is lost on a restart: payments missed
+// while the service was down are made on the next run, and payments already made are
+// refused by the aggregate.
+pub struct StandingOrderScheduler<ES: EventStore<BankAccount>> {
+    cqrs: Arc<CqrsFramework<BankAccount, ES>>,
+    standing_orders: Arc<dyn DueStandingOrdersLookup>,
+}
+
+impl<ES: EventStore<BankAccount>> StandingOrderScheduler<ES> {
+    pub fn new(
+        cqrs: Arc<CqrsFramework<BankAccount, ES>>,
+        standing_orders: Arc<dyn DueStandingOrdersLookup>,
+    ) -> Self {
+        Self {
+            cqrs,
+            standing_orders,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(RUN_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.execute_due_standing_orders(Utc::now().date_naive())
+                .await;
+        }
+    }
+
+    // Returns the number of payments processed. Whether each was made or recorded as failed
+    // is up to the aggregate.
+    #[instrument(skip(self))]
+    pub async fn execute_due_standing_orders(&self, today: NaiveDate) -> usize {
+        let due = match self.standing_orders.due_standing_orders(today).await {
+            Ok(due) => due,
+            Err(err) => {
+                tracing::error!("could not look up due standing orders: {err}");
+                return 0;
+            }
+        };
+
+        let mut processed = 0;
+        for payment in due {
+            let command = BankAccountCommand::ExecuteStandingOrder(
+                BankAccountExecuteStandingOrderCommandData {
+                    standing_order_id: payment.standing_order_id.clone(),
+                    due_date: payment.due_date,
+                },
+            );
+            match self.cqrs.execute(&payment.account_id, command).await {
+                Ok(()) => processed += 1,
+                // The lookup may lag behind the aggregate, e.g. right after a payment.
+                Err(AggregateError::UserError(
+                    BankAccountError::StandingOrderNotDue | BankAccountError::UnknownStandingOrder,
+                )) => {}
+                Err(err) => {
+                    tracing::error!(
+                        "could not execute standing order {} for account {}: {err}",
+                        payment.standing_order_id,
+                        payment.account_id
+                    );
+                }
+            }
+        }
+        tracing::info!("processed {processed} standing order payments");
+        processed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        BankAccountServices, HappyPathBankAccountServices, HappyPathCustomerKycLookup,
+    };
+    use crate::domain::{
+        AccountProduct, BankAccountCreateStandingOrderCommandData,
+        BankAccountDepositMoneyCommandData, BankAccountOpenAccountCommandData, Currency, Money,
+    };
+    use cqrs_es::mem_store::MemStore;
+    use pretty_assertions::assert_eq;
+
+    // Due payments as a projection that has not caught up with them being made would see them.
+    struct FixedDueStandingOrders(Vec<DueStandingOrder>);
+
+    #[async_trait]
+    impl DueStandingOrdersLookup for FixedDueStandingOrders {
+        async fn due_standing_orders(
+            &self,
+            _today: NaiveDate,
+        ) -> crate::prelude::Result<Vec<DueStandingOrder>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn executes_due_payments_once() {
+        let services = BankAccountServices::new(Box::<HappyPathBankAccountServices>::default())
+            .with_customers(Box::new(HappyPathCustomerKycLookup));
+        let cqrs = Arc::new(CqrsFramework::new(
+            MemStore::<BankAccount>::default(),
+            vec![],
+            services,
+        ));
+        let today = Utc::now().date_naive();
+        for command in [
+            BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: "customer-1".to_string(),
+                product: AccountProduct::Checking,
+            }),
+            BankAccountCommand::DepositMoney(BankAccountDepositMoneyCommandData {
+                amount: Money::new(20000, Currency::EUR),
+            }),
+            BankAccountCommand::CreateStandingOrder(BankAccountCreateStandingOrderCommandData {
+                standing_order_id: "so-1".to_string(),
+                amount: Money::new(5000, Currency::EUR),
+                day_of_month: chrono::Datelike::day(&today),
+                description: "rent".to_string(),
+            }),
+        ] {
+            cqrs.execute("1234", command).await.unwrap();
+        }
+
+        let scheduler = StandingOrderScheduler::new(
+            cqrs,
+            Arc::new(FixedDueStandingOrders(vec![DueStandingOrder {
+                account_id: "1234".to_string(),
+                standing_order_id: "so-1".to_string(),
+                due_date: today,
+            }])),
+        );
+        assert_eq!(scheduler.execute_due_standing_orders(today).await, 1);
+        assert_eq!(scheduler.execute_due_standing_orders(today).await, 0);
+    }
+}
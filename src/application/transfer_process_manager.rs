@@ -0,0 +1,555 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cqrs_es::{AggregateError, CqrsFramework, EventEnvelope, EventStore, Query};
+use tokio::sync::Notify;
+use tracing::instrument;
+
+use crate::domain::{
+    BankAccount, BankAccountCommand, BankAccountCompleteTransferCommandData, BankAccountError,
+    BankAccountRefundTransferCommandData, BankAccountTransferInCommandData,
+    BankAccountTransferOutCommandData, Money, Transfer, TransferCommand, TransferEvent,
+    TransferStatus,
+};
+
+// How often transfers that were left unfinished are picked up again, e.g. after a restart or
+// when the refund of a transfer that could not be credited failed.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+// A transfer that has been requested and still needs money to be moved, or refunded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnfinishedTransfer {
+    pub transfer_id: String,
+    pub source_account_id: String,
+    pub destination_account_id: String,
+    pub amount: Money,
+    // One of `Requested`, `SourceDebited` and `Compensating`.
+    pub status: TransferStatus,
+    pub failure_reason: Option<String>,
+}
+
+// Finds the transfers that have been requested and are neither completed nor given up on.
+#[async_trait]
+pub trait UnfinishedTransfersLookup: Send + Sync {
+    async fn unfinished_transfers(&self) -> crate::prelude::Result<Vec<UnfinishedTransfer>>;
+}
+
+// Wakes the `TransferSaga` up as transfer requests are dispatched by the transfer cqrs
+// framework, so that they are carried out straight away rather than on the next retry.
+pub struct TransferProcessManager {
+    transfer_requested: Arc<Notify>,
+}
+
+impl TransferProcessManager {
+    pub fn new(transfer_requested: Arc<Notify>) -> Self {
+        Self { transfer_requested }
+    }
+}
+
+#[async_trait]
+impl Query<Transfer> for TransferProcessManager {
+    async fn dispatch(&self, _transfer_id: &str, events: &[EventEnvelope<Transfer>]) {
+        if events
+            .iter()
+            .any(|event| matches!(event.payload, TransferEvent::TransferRequested { .. }))
+        {
+            self.transfer_requested.notify_one();
+        }
+    }
+}
+
+// Moves the money for a transfer: debits the source account, credits the destination account
+// and, if the credit fails, refunds the source account. The outcome of every step is recorded
+// on the `Transfer` aggregate, which is where the saga picks a transfer up again after a
+// restart or a failure. Every step can be retried, the bank accounts refuse to debit, credit
+// or refund the same transfer twice.
+pub struct TransferSaga<AES, TES>
+where
+    AES: EventStore<BankAccount>,
+    TES: EventStore<Transfer>,
+{
+    accounts: Arc<CqrsFramework<BankAccount, AES>>,
+    transfers: Arc<CqrsFramework<Transfer, TES>>,
+    unfinished_transfers: Arc<dyn UnfinishedTransfersLookup>,
+}
+
+impl<AES, TES> TransferSaga<AES, TES>
+where
+    AES: EventStore<BankAccount>,
+    TES: EventStore<Transfer>,
+{
+    pub fn new(
+        accounts: Arc<CqrsFramework<BankAccount, AES>>,
+        transfers: Arc<CqrsFramework<Transfer, TES>>,
+        unfinished_transfers: Arc<dyn UnfinishedTransfersLookup>,
+    ) -> Self {
+        Self {
+            accounts,
+            transfers,
+            unfinished_transfers,
+        }
+    }
+
+    pub async fn run(self, transfer_requested: Arc<Notify>) {
+        let mut interval = tokio::time::interval(RETRY_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = transfer_requested.notified() => {}
+            }
+            self.carry_out_unfinished_transfers().await;
+        }
+    }
+
+    // Returns the number of transfers that were carried out to the end, whether completed,
+    // failed or compensated.
+    #[instrument(skip(self))]
+    pub async fn carry_out_unfinished_transfers(&self) -> usize {
+        let transfers = match self.unfinished_transfers.unfinished_transfers().await {
+            Ok(transfers) => transfers,
+            Err(err) => {
+                tracing::error!("could not look up unfinished transfers: {err}");
+                return 0;
+            }
+        };
+
+        let mut finished = 0;
+        for transfer in transfers {
+            if let TransferStatus::Completed
+            | TransferStatus::Failed
+            | TransferStatus::Compensated = self.execute(transfer).await
+            {
+                finished += 1;
+            }
+        }
+        finished
+    }
+
+    // Carries the transfer on from its status until it is finished, or until a step fails
+    // in a way that is worth retrying. Returns the status reached.
+    #[instrument(skip(self))]
+    pub async fn execute(&self, mut transfer: UnfinishedTransfer) -> TransferStatus {
+        loop {
+            let status = match transfer.status {
+                TransferStatus::Requested => self.debit_source(&transfer).await,
+                TransferStatus::SourceDebited => self.credit_destination(&mut transfer).await,
+                TransferStatus::Compensating => self.refund_source(&transfer).await,
+                status => return status,
+            };
+            if status == transfer.status {
+                return status;
+            }
+            transfer.status = status;
+        }
+    }
+
+    async fn debit_source(&self, transfer: &UnfinishedTransfer) -> TransferStatus {
+        let debit = BankAccountCommand::TransferOut(BankAccountTransferOutCommandData {
+            transfer_id: transfer.transfer_id.clone(),
+            destination_account_id: transfer.destination_account_id.clone(),
+            amount: transfer.amount,
+        });
+        match self
+            .accounts
+            .execute(&transfer.source_account_id, debit)
+            .await
+        {
+            Ok(()) | Err(AggregateError::UserError(BankAccountError::DuplicateTransfer)) => {
+                self.record(
+                    transfer,
+                    TransferCommand::RecordSourceDebited,
+                    TransferStatus::SourceDebited,
+                )
+                .await
+            }
+            Err(AggregateError::UserError(err)) => {
+                let reason = err.to_string();
+                self.record(
+                    transfer,
+                    TransferCommand::RecordSourceDebitFailed { reason },
+                    TransferStatus::Failed,
+                )
+                .await
+            }
+            Err(err) => {
+                tracing::error!(
+                    "could not debit transfer {} from account {}, retrying in {RETRY_INTERVAL:?}: {err}",
+                    transfer.transfer_id,
+                    transfer.source_account_id
+                );
+                transfer.status
+            }
+        }
+    }
+
+    async fn credit_destination(&self, transfer: &mut UnfinishedTransfer) -> TransferStatus {
+        let credit = BankAccountCommand::TransferIn(BankAccountTransferInCommandData {
+            transfer_id: transfer.transfer_id.clone(),
+            source_account_id: transfer.source_account_id.clone(),
+            amount: transfer.amount,
+        });
+        match self
+            .accounts
+            .execute(&transfer.destination_account_id, credit)
+            .await
+        {
+            Ok(()) | Err(AggregateError::UserError(BankAccountError::DuplicateTransfer)) => {
+                if !self.complete(transfer).await {
+                    return transfer.status;
+                }
+                self.record(
+                    transfer,
+                    TransferCommand::RecordDestinationCredited,
+                    TransferStatus::Completed,
+                )
+                .await
+            }
+            Err(AggregateError::UserError(err)) => {
+                let reason = err.to_string();
+                transfer.failure_reason = Some(reason.clone());
+                self.record(
+                    transfer,
+                    TransferCommand::RecordDestinationCreditFailed { reason },
+                    TransferStatus::Compensating,
+                )
+                .await
+            }
+            Err(err) => {
+                tracing::error!(
+                    "could not credit transfer {} to account {}, retrying in {RETRY_INTERVAL:?}: {err}",
+                    transfer.transfer_id,
+                    transfer.destination_account_id
+                );
+                transfer.status
+            }
+        }
+    }
+
+    // Settles the debit on the source account, so that it can no longer be refunded. A
+    // transfer that is unknown to the source account has been settled already.
+    async fn complete(&self, transfer: &UnfinishedTransfer) -> bool {
+        let complete =
+            BankAccountCommand::CompleteTransfer(BankAccountCompleteTransferCommandData {
+                transfer_id: transfer.transfer_id.clone(),
+            });
+        match self
+            .accounts
+            .execute(&transfer.source_account_id, complete)
+            .await
+        {
+            Ok(()) | Err(AggregateError::UserError(BankAccountError::UnknownTransfer)) => true,
+            Err(err) => {
+                tracing::error!(
+                    "could not complete transfer {} on account {}, retrying in {RETRY_INTERVAL:?}: {err}",
+                    transfer.transfer_id,
+                    transfer.source_account_id
+                );
+                false
+            }
+        }
+    }
+
+    // A transfer that is unknown to the source account has been refunded already.
+    async fn refund_source(&self, transfer: &UnfinishedTransfer) -> TransferStatus {
+        let refund = BankAccountCommand::RefundTransfer(BankAccountRefundTransferCommandData {
+            transfer_id: transfer.transfer_id.clone(),
+            reason: transfer
+                .failure_reason
+                .clone()
+                .unwrap_or_else(|| "destination account could not be credited".to_string()),
+        });
+        match self
+            .accounts
+            .execute(&transfer.source_account_id, refund)
+            .await
+        {
+            Ok(()) | Err(AggregateError::UserError(BankAccountError::UnknownTransfer)) => {
+                self.record(
+                    transfer,
+                    TransferCommand::RecordSourceRefunded,
+                    TransferStatus::Compensated,
+                )
+                .await
+            }
+            Err(err) => {
+                tracing::error!(
+                    "could not refund transfer {} to account {}, retrying in {RETRY_INTERVAL:?}: {err}",
+                    transfer.transfer_id,
+                    transfer.source_account_id
+                );
+                transfer.status
+            }
+        }
+    }
+
+    // Returns the status reached, which is the status the transfer was in if the outcome of
+    // the step could not be recorded. The step is then retried and found to be done already.
+    async fn record(
+        &self,
+        transfer: &UnfinishedTransfer,
+        command: TransferCommand,
+        reached: TransferStatus,
+    ) -> TransferStatus {
+        match self.transfers.execute(&transfer.transfer_id, command).await {
+            Ok(()) => reached,
+            Err(err) => {
+                tracing::error!(
+                    "could not record progress of transfer {}: {err}",
+                    transfer.transfer_id
+                );
+                transfer.status
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        BankAccountServices, HappyPathBankAccountServices, HappyPathCustomerKycLookup,
+    };
+    use crate::domain::{
+        AccountProduct, BankAccountCloseAccountCommandData, BankAccountDepositMoneyCommandData,
+        BankAccountOpenAccountCommandData, Currency, TransferRequestTransferCommandData,
+    };
+    use cqrs_es::mem_store::MemStore;
+    use pretty_assertions::assert_eq;
+
+    type AccountsCqrs = CqrsFramework<BankAccount, MemStore<BankAccount>>;
+    type TransfersCqrs = CqrsFramework<Transfer, MemStore<Transfer>>;
+
+    struct FixedUnfinishedTransfers(Vec<UnfinishedTransfer>);
+
+    #[async_trait]
+    impl UnfinishedTransfersLookup for FixedUnfinishedTransfers {
+        async fn unfinished_transfers(&self) -> crate::prelude::Result<Vec<UnfinishedTransfer>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    async fn open_account(accounts: &AccountsCqrs, account_id: &str, balance: i64) {
+        accounts
+            .execute(
+                account_id,
+                BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+                    account_id: account_id.to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: "customer-1".to_string(),
+                    product: AccountProduct::Checking,
+                }),
+            )
+            .await
+            .unwrap();
+        accounts
+            .execute(
+                account_id,
+                BankAccountCommand::DepositMoney(BankAccountDepositMoneyCommandData {
+                    amount: Money::new(balance, Currency::EUR),
+                }),
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn close_account(accounts: &AccountsCqrs, account_id: &str) {
+        accounts
+            .execute(
+                account_id,
+                BankAccountCommand::CloseAccount(BankAccountCloseAccountCommandData {
+                    reason: "customer request".to_string(),
+                }),
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn saga_with_accounts(
+        transfer: &UnfinishedTransfer,
+    ) -> (
+        Arc<AccountsCqrs>,
+        Arc<TransfersCqrs>,
+        TransferSaga<MemStore<BankAccount>, MemStore<Transfer>>,
+    ) {
+        let accounts = Arc::new(CqrsFramework::new(
+            MemStore::<BankAccount>::default(),
+            vec![],
+            BankAccountServices::new(Box::<HappyPathBankAccountServices>::default())
+                .with_customers(Box::new(HappyPathCustomerKycLookup)),
+        ));
+        let transfers: Arc<TransfersCqrs> = Arc::new(CqrsFramework::new(
+            MemStore::<Transfer>::default(),
+            vec![],
+            (),
+        ));
+        open_account(&accounts, &transfer.source_account_id, 10000).await;
+        transfers
+            .execute(
+                &transfer.transfer_id,
+                TransferCommand::RequestTransfer(TransferRequestTransferCommandData {
+                    source_account_id: transfer.source_account_id.clone(),
+                    destination_account_id: transfer.destination_account_id.clone(),
+                    amount: transfer.amount,
+                }),
+            )
+            .await
+            .unwrap();
+        let saga = TransferSaga::new(
+            accounts.clone(),
+            transfers.clone(),
+            Arc::new(FixedUnfinishedTransfers(vec![transfer.clone()])),
+        );
+        (accounts, transfers, saga)
+    }
+
+    fn requested_transfer(amount: i64) -> UnfinishedTransfer {
+        UnfinishedTransfer {
+            transfer_id: "t-1".to_string(),
+            source_account_id: "1234".to_string(),
+            destination_account_id: "5678".to_string(),
+            amount: Money::new(amount, Currency::EUR),
+            status: TransferStatus::Requested,
+            failure_reason: None,
+        }
+    }
+
+    // Debits what is left of the source account after the transfer, which only succeeds if
+    // the transfer was debited exactly once.
+    async fn assert_balance_left(accounts: &AccountsCqrs, balance: i64) {
+        let debit = |amount| {
+            BankAccountCommand::TransferOut(BankAccountTransferOutCommandData {
+                transfer_id: format!("check-{amount}"),
+                destination_account_id: "5678".to_string(),
+                amount: Money::new(amount, Currency::EUR),
+            })
+        };
+        assert!(accounts.execute("1234", debit(balance + 1)).await.is_err());
+        assert!(accounts.execute("1234", debit(balance)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn transfer_between_open_accounts_completes() {
+        let transfer = requested_transfer(2500);
+        let (accounts, _, saga) = saga_with_accounts(&transfer).await;
+        open_account(&accounts, "5678", 0).await;
+
+        assert_eq!(saga.execute(transfer).await, TransferStatus::Completed);
+
+        // The debit was settled, so a completed transfer cannot be refunded as well.
+        let result = accounts
+            .execute(
+                "1234",
+                BankAccountCommand::RefundTransfer(BankAccountRefundTransferCommandData {
+                    transfer_id: "t-1".to_string(),
+                    reason: "duplicate".to_string(),
+                }),
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn transfer_fails_when_source_cannot_be_debited() {
+        let transfer = requested_transfer(20000);
+        let (accounts, _, saga) = saga_with_accounts(&transfer).await;
+        open_account(&accounts, "5678", 0).await;
+
+        assert_eq!(saga.execute(transfer).await, TransferStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn transfer_is_compensated_when_destination_cannot_be_credited() {
+        let transfer = requested_transfer(2500);
+        let (accounts, _, saga) = saga_with_accounts(&transfer).await;
+        open_account(&accounts, "5678", 0).await;
+        close_account(&accounts, "5678").await;
+
+        assert_eq!(saga.execute(transfer).await, TransferStatus::Compensated);
+
+        // The refund restored the full balance of the source account.
+        assert_balance_left(&accounts, 10000).await;
+    }
+
+    #[tokio::test]
+    async fn transfers_interrupted_after_the_debit_are_not_debited_again() {
+        let transfer = requested_transfer(2500);
+        let (accounts, _, saga) = saga_with_accounts(&transfer).await;
+        open_account(&accounts, "5678", 0).await;
+        // The source account was debited, but the service stopped before recording it.
+        accounts
+            .execute(
+                "1234",
+                BankAccountCommand::TransferOut(BankAccountTransferOutCommandData {
+                    transfer_id: "t-1".to_string(),
+                    destination_account_id: "5678".to_string(),
+                    amount: Money::new(2500, Currency::EUR),
+                }),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(saga.carry_out_unfinished_transfers().await, 1);
+        assert_balance_left(&accounts, 7500).await;
+    }
+
+    #[tokio::test]
+    async fn transfers_left_compensating_are_refunded_on_the_next_run() {
+        let transfer = UnfinishedTransfer {
+            status: TransferStatus::Compensating,
+            failure_reason: Some("account closed".to_string()),
+            ..requested_transfer(2500)
+        };
+        let (accounts, transfers, saga) = saga_with_accounts(&transfer).await;
+        // The credit failed, and the refund did not go through before the service stopped.
+        accounts
+            .execute(
+                "1234",
+                BankAccountCommand::TransferOut(BankAccountTransferOutCommandData {
+                    transfer_id: "t-1".to_string(),
+                    destination_account_id: "5678".to_string(),
+                    amount: Money::new(2500, Currency::EUR),
+                }),
+            )
+            .await
+            .unwrap();
+        for command in [
+            TransferCommand::RecordSourceDebited,
+            TransferCommand::RecordDestinationCreditFailed {
+                reason: "account closed".to_string(),
+            },
+        ] {
+            transfers.execute("t-1", command).await.unwrap();
+        }
+
+        assert_eq!(saga.carry_out_unfinished_transfers().await, 1);
+        assert_balance_left(&accounts, 10000).await;
+    }
+
+    #[tokio::test]
+    async fn process_manager_wakes_the_saga_when_a_transfer_is_requested() {
+        let transfer_requested = Arc::new(Notify::new());
+        let transfers = CqrsFramework::new(
+            MemStore::<Transfer>::default(),
+            vec![Box::new(TransferProcessManager::new(
+                transfer_requested.clone(),
+            ))],
+            (),
+        );
+        transfers
+            .execute(
+                "t-1",
+                TransferCommand::RequestTransfer(TransferRequestTransferCommandData {
+                    source_account_id: "1234".to_string(),
+                    destination_account_id: "5678".to_string(),
+                    amount: Money::new(2500, Currency::EUR),
+                }),
+            )
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(1), transfer_requested.notified())
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,41 @@
+use super::*;
+use crate::any_es::{AnyDatabase, Backend};
+
+// Connects to the backend set in the configuration, or else the one that `DATABASE_URL`
+// points at, so that the same build can run against any of the backends it was built with.
+#[instrument]
+pub async fn get_db_connection(
+    configuration: &DatabaseConfiguration,
+) -> crate::prelude::Result<AnyDatabase> {
+    let backend = match configuration.backend {
+        Some(backend) => backend,
+        None => Backend::from_url(&get_database_environment_variable().await)?,
+    };
+    tracing::event!(Level::INFO, "using the {backend} backend");
+    match backend {
+        #[cfg(feature = "postgres")]
+        Backend::Postgres => Ok(AnyDatabase::Postgres(
+            get_db_connection_postgres_sqlx(&configuration.pool).await?,
+        )),
+        #[cfg(feature = "mysql")]
+        Backend::Mysql => Ok(AnyDatabase::Mysql(
+            get_db_connection_mysql_sqlx(&configuration.pool).await?,
+        )),
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => Ok(AnyDatabase::Sqlite(
+            get_db_connection_sqlite_sqlx(&configuration.pool).await?,
+        )),
+        #[cfg(feature = "in-memory")]
+        Backend::InMemory => {
+            tracing::warn!("using the in-memory backend, nothing is kept after a restart");
+            Ok(AnyDatabase::InMemory(
+                crate::memory_es::MemDatabase::default(),
+            ))
+        }
+        #[allow(unreachable_patterns)]
+        backend => Err(crate::prelude::Error::Generic(format!(
+            "the {backend} backend is not part of this build, enable the `{}` feature",
+            backend.feature()
+        ))),
+    }
+}
@@ -1,3 +1,6 @@
+use crate::any_es::Backend;
+use crate::application::{BankAccountConfiguration, IdempotencyPolicy};
+use crate::configuration::PoolConfiguration;
 use crate::presentation::graphql::GraphQlConfiguration;
 
 use serde::{Deserialize, Serialize};
@@ -5,6 +8,25 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AppConfiguration {
     pub graphql: GraphQlConfiguration,
+    #[serde(default)]
+    pub bank_account: BankAccountConfiguration,
+    #[serde(default)]
+    pub idempotency: IdempotencyPolicy,
+    #[serde(default)]
+    pub database: DatabaseConfiguration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DatabaseConfiguration {
+    /// The backend to run against, taken from the scheme of `DATABASE_URL` when not set
+    #[serde(default)]
+    pub backend: Option<Backend>,
+    /// Apply pending migrations when the service starts, rather than with the `migrate` command
+    #[serde(default)]
+    pub run_migrations: bool,
+    /// The size and timeouts of the connection pool
+    #[serde(default)]
+    pub pool: PoolConfiguration,
 }
 
 #[cfg(test)]
@@ -26,4 +48,44 @@ mod tests {
             "127.0.0.1:8080".parse::<SocketAddr>().unwrap()
         );
     }
+
+    #[test]
+    fn bank_account_section_is_optional() {
+        let config: AppConfiguration =
+            serde_yaml::from_str("graphql: { enabled: true, port: 8080 }").unwrap();
+        assert_eq!(config.bank_account, BankAccountConfiguration::default());
+    }
+
+    #[test]
+    fn can_parse_idempotency_policy() {
+        let config: AppConfiguration = serde_yaml::from_str(
+            "graphql: { enabled: true, port: 8080 }\nidempotency: { retention_seconds: 3600 }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.idempotency,
+            IdempotencyPolicy {
+                retention_seconds: 3600,
+                ..IdempotencyPolicy::default()
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_database_backend() {
+        let config: AppConfiguration = serde_yaml::from_str(
+            "graphql: { enabled: true, port: 8080 }\ndatabase: { backend: sqlite }",
+        )
+        .unwrap();
+        assert_eq!(config.database.backend, Some(Backend::Sqlite));
+    }
+
+    #[test]
+    fn migrations_are_not_run_at_startup_by_default() {
+        let config: AppConfiguration = serde_yaml::from_str(
+            "graphql: { enabled: true, port: 8080 }\ndatabase: { backend: sqlite }",
+        )
+        .unwrap();
+        assert!(!config.database.run_migrations);
+    }
 }
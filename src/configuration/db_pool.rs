@@ -0,0 +1,205 @@
+use std::future::Future;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sqlx::pool::PoolOptions;
+use sqlx::Database;
+
+// The longest wait between two attempts at connecting, however many attempts were made.
+const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+// Sizes and timeouts of the connection pool, loaded from the `database.pool` section of the
+// application configuration, and how often to try connecting before giving up at startup.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PoolConfiguration {
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    // Connections kept open while idle, so that a burst of requests does not wait on new ones
+    #[serde(default)]
+    pub min_connections: u32,
+    #[serde(default = "default_acquire_timeout_seconds")]
+    pub acquire_timeout_seconds: u64,
+    // Idle connections above `min_connections` are closed after this long, or never if null
+    #[serde(default = "default_idle_timeout_seconds")]
+    pub idle_timeout_seconds: Option<u64>,
+    // Statements running for longer are cancelled by postgres and mysql, sqlite has no limit
+    #[serde(default)]
+    pub statement_timeout_seconds: Option<u64>,
+    #[serde(default = "default_connect_attempts")]
+    pub connect_attempts: u32,
+    // The wait before the second attempt, doubling with every attempt after it
+    #[serde(default = "default_connect_backoff_millis")]
+    pub connect_backoff_millis: u64,
+}
+
+fn default_max_connections() -> u32 {
+    5
+}
+
+fn default_acquire_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_idle_timeout_seconds() -> Option<u64> {
+    Some(10 * 60)
+}
+
+fn default_connect_attempts() -> u32 {
+    5
+}
+
+fn default_connect_backoff_millis() -> u64 {
+    500
+}
+
+impl Default for PoolConfiguration {
+    fn default() -> Self {
+        Self {
+            max_connections: default_max_connections(),
+            min_connections: 0,
+            acquire_timeout_seconds: default_acquire_timeout_seconds(),
+            idle_timeout_seconds: default_idle_timeout_seconds(),
+            statement_timeout_seconds: None,
+            connect_attempts: default_connect_attempts(),
+            connect_backoff_millis: default_connect_backoff_millis(),
+        }
+    }
+}
+
+impl PoolConfiguration {
+    pub fn pool_options<DB: Database>(&self) -> PoolOptions<DB> {
+        PoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout_seconds))
+            .idle_timeout(self.idle_timeout_seconds.map(Duration::from_secs))
+    }
+
+    pub fn statement_timeout(&self) -> Option<Duration> {
+        self.statement_timeout_seconds.map(Duration::from_secs)
+    }
+
+    // How long to wait after the given failed attempt, counting from 1.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(self.connect_backoff_millis)
+            .saturating_mul(factor)
+            .min(MAX_CONNECT_BACKOFF)
+    }
+}
+
+// Tries to connect until it succeeds or the attempts run out, e.g. while the database
+// container is still starting up. A connection string that cannot be parsed is not retried.
+pub async fn connect_with_retry<T, F, Fut>(
+    configuration: &PoolConfiguration,
+    mut connect: F,
+) -> crate::prelude::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut attempt = 1;
+    loop {
+        match connect().await {
+            Ok(pool) => return Ok(pool),
+            Err(err @ sqlx::Error::Configuration(_)) => return Err(err.into()),
+            Err(err) if attempt >= configuration.connect_attempts => return Err(err.into()),
+            Err(err) => {
+                let backoff = configuration.backoff(attempt);
+                tracing::warn!(
+                    "could not connect to the database (attempt {attempt} of {}), retrying in {backoff:?}: {err}",
+                    configuration.connect_attempts
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn configuration(connect_attempts: u32) -> PoolConfiguration {
+        PoolConfiguration {
+            connect_attempts,
+            connect_backoff_millis: 1,
+            ..PoolConfiguration::default()
+        }
+    }
+
+    fn connection_refused() -> sqlx::Error {
+        sqlx::Error::Io(std::io::ErrorKind::ConnectionRefused.into())
+    }
+
+    #[test]
+    fn can_parse_pool_configuration() {
+        let pool: PoolConfiguration =
+            serde_yaml::from_str("max_connections: 20\nidle_timeout_seconds: null").unwrap();
+        assert_eq!(
+            pool,
+            PoolConfiguration {
+                max_connections: 20,
+                idle_timeout_seconds: None,
+                ..PoolConfiguration::default()
+            }
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_a_limit() {
+        let backoffs = [1, 2, 3, 20].map(|attempt| PoolConfiguration::default().backoff(attempt));
+        assert_eq!(
+            backoffs,
+            [
+                Duration::from_millis(500),
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                MAX_CONNECT_BACKOFF
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn connecting_is_retried_until_it_succeeds() {
+        let mut attempts = 0;
+        let connected = connect_with_retry(&configuration(3), || {
+            attempts += 1;
+            let result = if attempts < 3 {
+                Err(connection_refused())
+            } else {
+                Ok(attempts)
+            };
+            async move { result }
+        })
+        .await
+        .unwrap();
+        assert_eq!(connected, 3);
+    }
+
+    #[tokio::test]
+    async fn connecting_gives_up_when_the_attempts_run_out() {
+        let mut attempts = 0;
+        let result = connect_with_retry(&configuration(3), || {
+            attempts += 1;
+            async { Err::<(), _>(connection_refused()) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn invalid_connection_strings_are_not_retried() {
+        let mut attempts = 0;
+        let result = connect_with_retry(&configuration(3), || {
+            attempts += 1;
+            async { Err::<(), _>(sqlx::Error::Configuration("invalid port".into())) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
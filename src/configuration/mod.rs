@@ -1,12 +1,24 @@
+pub mod any_db;
 pub mod config;
+pub mod db_pool;
+#[cfg(feature = "mysql")]
 pub mod mysql_db_sqlx;
+#[cfg(feature = "postgres")]
 pub mod postgres_db_sqlx;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_db_sqlx;
 pub mod tracing_config;
 
 // Re-exports
+pub use any_db::*;
 pub use config::*;
+pub use db_pool::*;
+#[cfg(feature = "mysql")]
 pub use mysql_db_sqlx::*;
+#[cfg(feature = "postgres")]
 pub use postgres_db_sqlx::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_db_sqlx::*;
 pub use tracing::*;
 
 use tracing::{instrument, Level};
@@ -1,18 +1,34 @@
 use super::*;
-use sqlx::{mysql::MySqlPoolOptions, MySql, Pool};
+use sqlx::{Executor, MySql, Pool};
 
 #[instrument]
-pub async fn get_db_connection_mysql_sqlx() -> crate::prelude::Result<Pool<MySql>> {
+pub async fn get_db_connection_mysql_sqlx(
+    configuration: &PoolConfiguration,
+) -> crate::prelude::Result<Pool<MySql>> {
     let db_connection_url = get_database_environment_variable().await;
 
     tracing::event!(
         Level::INFO,
         "connecting to mysql db with connection string: {db_connection_url}"
     );
-    let pool = MySqlPoolOptions::new()
-        .max_connections(5)
-        .connect(db_connection_url.as_str())
-        .await?;
+    let statement_timeout = configuration.statement_timeout();
+    let pool = connect_with_retry(configuration, || {
+        configuration
+            .pool_options::<MySql>()
+            .after_connect(move |connection, _| {
+                Box::pin(async move {
+                    // MySQL only limits how long a `SELECT` may run
+                    if let Some(timeout) = statement_timeout {
+                        let query =
+                            format!("SET SESSION max_execution_time = {}", timeout.as_millis());
+                        connection.execute(query.as_str()).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .connect(db_connection_url.as_str())
+    })
+    .await?;
 
     Ok(pool)
 }
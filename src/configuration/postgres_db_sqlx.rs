@@ -1,18 +1,32 @@
 use super::*;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use sqlx::{Executor, Pool, Postgres};
 
 #[instrument]
-pub async fn get_db_connection_postgres_sqlx() -> crate::prelude::Result<Pool<Postgres>> {
+pub async fn get_db_connection_postgres_sqlx(
+    configuration: &PoolConfiguration,
+) -> crate::prelude::Result<Pool<Postgres>> {
     let db_connection_url = get_database_environment_variable().await;
 
     tracing::event!(
         Level::INFO,
         "connecting to postgres db with connection string: {db_connection_url}"
     );
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(db_connection_url.as_str())
-        .await?;
+    let statement_timeout = configuration.statement_timeout();
+    let pool = connect_with_retry(configuration, || {
+        configuration
+            .pool_options::<Postgres>()
+            .after_connect(move |connection, _| {
+                Box::pin(async move {
+                    if let Some(timeout) = statement_timeout {
+                        let query = format!("SET statement_timeout = {}", timeout.as_millis());
+                        connection.execute(query.as_str()).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .connect(db_connection_url.as_str())
+    })
+    .await?;
 
     Ok(pool)
 }
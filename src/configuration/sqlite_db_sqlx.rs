@@ -0,0 +1,40 @@
+use super::*;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
+use std::str::FromStr;
+
+// Connects to the database file in `DATABASE_URL`, creating it if need be, e.g.
+// `sqlite://veloxide.db` or `sqlite::memory:`.
+#[instrument]
+pub async fn get_db_connection_sqlite_sqlx(
+    configuration: &PoolConfiguration,
+) -> crate::prelude::Result<Pool<Sqlite>> {
+    let db_connection_url = get_database_environment_variable().await;
+
+    tracing::event!(
+        Level::INFO,
+        "connecting to sqlite db with connection string: {db_connection_url}"
+    );
+    let options = SqliteConnectOptions::from_str(&db_connection_url)?.create_if_missing(true);
+    // Every connection to an in-memory database gets a database of its own, so the pool
+    // keeps a single connection open for the lifetime of the service.
+    let in_memory = db_connection_url.contains(":memory:");
+    let pool = if in_memory {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+    } else {
+        configuration.pool_options::<Sqlite>()
+    }
+    .connect_with(options)
+    .await?;
+
+    // An in-memory database starts out empty every time, so it cannot have been migrated
+    // beforehand.
+    if in_memory {
+        crate::any_es::SQLITE_MIGRATOR.run(&pool).await?;
+    }
+
+    Ok(pool)
+}
@@ -1,15 +1,78 @@
 use super::*;
+use std::collections::{HashMap, HashSet};
+
 use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use cqrs_es::Aggregate;
 use serde::{Deserialize, Serialize};
 use tracing::*;
 
 use crate::application::BankAccountServices;
+use crate::domain::{CurrencyConversion, KycStatus, Money, MoneyError};
+
+// The version of the state snapshots are taken of. Bump it whenever a field changes meaning
+// or shape, so that accounts are rebuilt from their events instead of loading snapshots that
+// no longer fit. Added fields are `#[serde(default)]` instead and don't need a bump.
+pub const BANK_ACCOUNT_SNAPSHOT_VERSION: u32 = 1;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BankAccount {
     account_id: String,
-    balance: f64,
+    #[serde(default)]
+    product: AccountProduct,
+    balance: Money,
+    #[serde(default)]
+    status: AccountStatus,
+    #[serde(default)]
+    overdraft_limit: Money,
+    // Every balance change since the end of the last interest accrual period, along with the
+    // last change before it, so that end of day balances can be worked out for the next one.
+    #[serde(default)]
+    balance_history: Vec<(DateTime<Utc>, Money)>,
+    #[serde(default)]
+    interest_accrued_through: Option<NaiveDate>,
+    #[serde(default)]
+    unposted_interest_micro_units: i64,
+    // Amounts debited for outgoing transfers, by transfer id, so they can be refunded until
+    // the transfer is completed.
+    #[serde(default)]
+    transfers_out: HashMap<String, Money>,
+    // Incoming transfers credited, by transfer id, so that a retried credit is refused.
+    #[serde(default)]
+    transfers_in: HashSet<String>,
+    // Authorization holds by hold id, until they are captured, released or recorded as expired.
+    #[serde(default)]
+    holds: HashMap<String, Hold>,
+    // Every check written against the account, by check number.
+    #[serde(default)]
+    checks: HashMap<String, WrittenCheck>,
+    // Cash withdrawals inside the rolling window of the withdrawal limits.
+    #[serde(default)]
+    cash_withdrawals: Vec<CashWithdrawal>,
+    #[serde(default)]
+    primary_holder_id: Option<String>,
+    // Everyone who holds the account, including the primary holder.
+    #[serde(default)]
+    holder_ids: Vec<String>,
+    // The sequence number of the last event applied. Events are numbered from 1 in the
+    // order they were committed, which is how operations refer to a transaction.
+    #[serde(default)]
+    last_sequence: usize,
+    // Deposits and cash withdrawals by the sequence number of the event that recorded them.
+    #[serde(default)]
+    reversible_transactions: HashMap<usize, ReversibleTransaction>,
+    // The last calendar month a maintenance fee was charged for, formatted as YYYY-MM.
+    #[serde(default)]
+    maintenance_fee_charged_through: Option<String>,
+    // When the account was opened, no maintenance fee is due for the months before.
+    #[serde(default)]
+    opened_at: DateTime<Utc>,
+    // Standing orders by id, until they are cancelled.
+    #[serde(default)]
+    standing_orders: HashMap<String, StandingOrder>,
+    // Withdrawals counted against the monthly withdrawal limit of the account product.
+    #[serde(default)]
+    monthly_withdrawals: MonthlyWithdrawals,
 }
 
 #[async_trait]
@@ -53,27 +116,339 @@ impl Aggregate for BankAccount {
                 self.handle_write_check_command(services, check_number, amount)
                     .await
             }
+            BankAccountCommand::FreezeAccount(command) => {
+                self.handle_freeze_account_command(services, command).await
+            }
+            BankAccountCommand::UnfreezeAccount(command) => {
+                self.handle_unfreeze_account_command(services, command)
+                    .await
+            }
+            BankAccountCommand::CloseAccount(command) => {
+                self.handle_close_account_command(services, command).await
+            }
+            BankAccountCommand::SetOverdraftLimit(BankAccountSetOverdraftLimitCommandData {
+                limit,
+            }) => {
+                self.handle_set_overdraft_limit_command(services, limit)
+                    .await
+            }
+            BankAccountCommand::AccrueInterest(command) => {
+                self.handle_accrue_interest_command(services, command).await
+            }
+            BankAccountCommand::TransferOut(command) => {
+                self.handle_transfer_out_command(services, command).await
+            }
+            BankAccountCommand::TransferIn(command) => {
+                self.handle_transfer_in_command(services, command).await
+            }
+            BankAccountCommand::RefundTransfer(command) => {
+                self.handle_refund_transfer_command(services, command).await
+            }
+            BankAccountCommand::CompleteTransfer(command) => {
+                self.handle_complete_transfer_command(services, command)
+                    .await
+            }
+            BankAccountCommand::PlaceHold(command) => {
+                self.handle_place_hold_command(services, command).await
+            }
+            BankAccountCommand::CaptureHold(command) => {
+                self.handle_capture_hold_command(services, command).await
+            }
+            BankAccountCommand::ReleaseHold(command) => {
+                self.handle_release_hold_command(services, command).await
+            }
+            BankAccountCommand::AddHolder(command) => {
+                self.handle_add_holder_command(services, command).await
+            }
+            BankAccountCommand::RemoveHolder(command) => {
+                self.handle_remove_holder_command(services, command).await
+            }
+            BankAccountCommand::ClearCheck(command) => {
+                self.handle_clear_check_command(services, command).await
+            }
+            BankAccountCommand::BounceCheck(command) => {
+                self.handle_bounce_check_command(services, command).await
+            }
+            BankAccountCommand::ReverseTransaction(command) => {
+                self.handle_reverse_transaction_command(services, command)
+                    .await
+            }
+            BankAccountCommand::ChargeMaintenanceFee(command) => {
+                self.handle_charge_maintenance_fee_command(services, command)
+                    .await
+            }
+            BankAccountCommand::CreateStandingOrder(command) => {
+                self.handle_create_standing_order_command(services, command)
+                    .await
+            }
+            BankAccountCommand::CancelStandingOrder(command) => {
+                self.handle_cancel_standing_order_command(services, command)
+                    .await
+            }
+            BankAccountCommand::ExecuteStandingOrder(command) => {
+                self.handle_execute_standing_order_command(services, command)
+                    .await
+            }
         }
     }
 
     fn apply(&mut self, event: Self::Event) {
+        self.last_sequence += 1;
         match event {
-            BankAccountEvent::AccountOpened { account_id } => {
+            BankAccountEvent::AccountOpened {
+                account_id,
+                currency,
+                primary_holder_id,
+                product,
+                occurred_at,
+            } => {
+                self.holder_ids.extend(primary_holder_id.clone());
+                self.primary_holder_id = primary_holder_id;
                 self.account_id = account_id;
+                self.product = product;
+                self.balance = Money::zero(currency);
+                self.overdraft_limit = Money::zero(currency);
+                self.status = AccountStatus::Open;
+                self.opened_at = occurred_at;
             }
-            BankAccountEvent::CustomerDepositedMoney { amount: _, balance } => {
-                self.balance = balance;
+            BankAccountEvent::CustomerDepositedMoney {
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.record_reversible_transaction(ReversibleTransactionKind::Deposit, amount);
+                self.record_balance(occurred_at, balance);
             }
-            BankAccountEvent::CustomerWithdrewCash { amount: _, balance } => {
-                self.balance = balance;
+            BankAccountEvent::CustomerWithdrewCash {
+                amount,
+                balance,
+                atm_id,
+                occurred_at,
+                ..
+            } => {
+                let window_start = withdrawal_limit_window_start(occurred_at);
+                self.cash_withdrawals
+                    .retain(|withdrawal| withdrawal.occurred_at > window_start);
+                self.cash_withdrawals.push(CashWithdrawal {
+                    occurred_at,
+                    atm_id,
+                    amount,
+                });
+                self.record_reversible_transaction(ReversibleTransactionKind::Withdrawal, amount);
+                self.monthly_withdrawals.record(occurred_at);
+                self.record_balance(occurred_at, balance);
             }
             BankAccountEvent::CustomerWroteCheck {
-                check_number: _,
-                amount: _,
+                check_number,
+                amount,
+                balance,
+                occurred_at,
+            } => {
+                self.checks.insert(
+                    check_number,
+                    WrittenCheck {
+                        amount,
+                        status: CheckStatus::Pending,
+                    },
+                );
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::AccountFrozen { .. } => {
+                self.status = AccountStatus::Frozen;
+            }
+            BankAccountEvent::AccountUnfrozen { .. } => {
+                self.status = AccountStatus::Open;
+            }
+            BankAccountEvent::AccountClosed { .. } => {
+                self.status = AccountStatus::Closed;
+            }
+            BankAccountEvent::OverdraftLimitSet { limit } => {
+                self.overdraft_limit = limit;
+            }
+            BankAccountEvent::OverdraftFeeCharged {
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::InterestAccrued {
+                period_end,
+                unposted_micro_units,
+                ..
+            } => {
+                self.interest_accrued_through = Some(period_end);
+                self.unposted_interest_micro_units = unposted_micro_units;
+                self.prune_balance_history();
+            }
+            BankAccountEvent::InterestPosted {
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::TransferDebited {
+                transfer_id,
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.transfers_out.insert(transfer_id, amount);
+                self.monthly_withdrawals.record(occurred_at);
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::TransferCredited {
+                transfer_id,
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.transfers_in.insert(transfer_id);
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::TransferRefunded {
+                transfer_id,
                 balance,
+                occurred_at,
+                ..
             } => {
-                self.balance = balance;
+                self.transfers_out.remove(&transfer_id);
+                self.record_balance(occurred_at, balance);
             }
+            BankAccountEvent::TransferCompleted { transfer_id, .. } => {
+                self.transfers_out.remove(&transfer_id);
+            }
+            BankAccountEvent::HoldPlaced {
+                hold_id,
+                amount,
+                expires_at,
+                ..
+            } => {
+                self.holds.insert(hold_id, Hold { amount, expires_at });
+            }
+            BankAccountEvent::HoldCaptured {
+                hold_id,
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.holds.remove(&hold_id);
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::HoldReleased { hold_id, .. }
+            | BankAccountEvent::HoldExpired { hold_id, .. } => {
+                self.holds.remove(&hold_id);
+            }
+            BankAccountEvent::HolderAdded { holder_id } => {
+                self.holder_ids.push(holder_id);
+            }
+            BankAccountEvent::HolderRemoved { holder_id } => {
+                self.holder_ids.retain(|existing| *existing != holder_id);
+            }
+            BankAccountEvent::CheckCleared { check_number, .. } => {
+                self.set_check_status(&check_number, CheckStatus::Cleared);
+            }
+            BankAccountEvent::CheckBounced {
+                check_number,
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.set_check_status(&check_number, CheckStatus::Bounced);
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::ReturnedItemFeeCharged {
+                balance,
+                occurred_at,
+                ..
+            } => {
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::TransactionReversed {
+                reversed_sequence,
+                balance,
+                occurred_at,
+                ..
+            } => {
+                if let Some(transaction) = self.reversible_transactions.get_mut(&reversed_sequence)
+                {
+                    transaction.reversed = true;
+                }
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::FeeCharged {
+                reason,
+                balance,
+                occurred_at,
+                ..
+            } => {
+                if let FeeReason::MonthlyMaintenance { period } = reason {
+                    self.maintenance_fee_charged_through = Some(period);
+                }
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::StandingOrderCreated {
+                standing_order_id,
+                amount,
+                day_of_month,
+                first_due_date,
+                ..
+            } => {
+                self.standing_orders.insert(
+                    standing_order_id,
+                    StandingOrder {
+                        amount,
+                        day_of_month,
+                        next_due_date: first_due_date,
+                    },
+                );
+            }
+            BankAccountEvent::StandingOrderCancelled { standing_order_id } => {
+                self.standing_orders.remove(&standing_order_id);
+            }
+            BankAccountEvent::StandingOrderExecuted {
+                standing_order_id,
+                balance,
+                next_due_date,
+                occurred_at,
+                ..
+            } => {
+                self.set_next_due_date(&standing_order_id, next_due_date);
+                self.monthly_withdrawals.record(occurred_at);
+                self.record_balance(occurred_at, balance);
+            }
+            BankAccountEvent::StandingOrderFailed {
+                standing_order_id,
+                next_due_date,
+                ..
+            } => {
+                self.set_next_due_date(&standing_order_id, next_due_date);
+            }
+        }
+    }
+}
+
+// Every holder of an account, not only the one who opened it, must have passed KYC.
+async fn ensure_kyc_verified(
+    services: &BankAccountServices,
+    customer_id: &str,
+) -> Result<(), BankAccountError> {
+    match services.customers.kyc_status(customer_id).await {
+        Ok(Some(KycStatus::Verified)) => Ok(()),
+        Ok(Some(status)) => {
+            error!("customer kyc status is {:?}", status);
+            Err(BankAccountError::KycNotPassed)
+        }
+        Ok(None) => {
+            error!("unknown customer");
+            Err(BankAccountError::UnknownCustomer)
+        }
+        Err(_) => {
+            error!("customer lookup unavailable");
+            Err(BankAccountError::CustomerLookupUnavailable)
         }
     }
 }
@@ -85,30 +460,41 @@ impl BankAccount {
         services: &BankAccountServices,
         command: BankAccountOpenAccountCommandData,
     ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
-        if !self.account_id.is_empty() {
+        if self.status != AccountStatus::NotOpened {
             return Err(BankAccountError::AccountAlreadyOpen);
         }
+        if command.primary_holder_id.is_empty() {
+            error!("invalid holder id");
+            return Err(BankAccountError::InvalidHolderId);
+        }
+        ensure_kyc_verified(services, &command.primary_holder_id).await?;
         Ok(vec![BankAccountEvent::AccountOpened {
             account_id: command.account_id,
+            currency: command.currency,
+            primary_holder_id: Some(command.primary_holder_id),
+            product: command.product,
+            occurred_at: services.services.now(),
         }])
     }
 
     #[instrument]
     pub async fn handle_deposit_money_command(
         &self,
-        _services: &BankAccountServices,
-        amount: f64,
+        services: &BankAccountServices,
+        amount: Money,
     ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
-        if self.account_id.is_empty() {
-            return Err(BankAccountError::AccountNotOpen);
-        }
-        if amount < 0_f64 {
+        self.ensure_account_accepts_credits()?;
+        if amount.is_negative() {
             return Err(BankAccountError::CannotDepositNegativeAmount);
         }
-        let balance = self.balance + amount;
+        let (converted_amount, conversion) =
+            self.convert_to_account_currency(services, amount).await?;
+        let balance = self.balance.checked_add(&converted_amount)?;
         Ok(vec![BankAccountEvent::CustomerDepositedMoney {
-            amount,
+            amount: converted_amount,
             balance,
+            conversion,
+            occurred_at: services.services.now(),
         }])
     }
 
@@ -116,34 +502,42 @@ impl BankAccount {
     pub async fn handle_withdraw_money_command(
         &self,
         services: &BankAccountServices,
-        amount: f64,
+        amount: Money,
         atm_id: String,
     ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
-        if self.account_id.is_empty() {
-            return Err(BankAccountError::AccountNotOpen);
-        }
-        if amount < 0_f64 {
+        self.ensure_account_accepts_debits()?;
+        if amount.is_negative() {
             error!("cannot withdraw negative amount");
             return Err(BankAccountError::CannotWithdrawNegativeAmount);
         }
-        let balance = self.balance - amount;
-        if balance < 0_f64 {
-            error!("insufficient funds");
-            return Err(BankAccountError::InsufficientFunds);
-        }
+        let (converted_amount, conversion) =
+            self.convert_to_account_currency(services, amount).await?;
+        let balance = self.balance.checked_sub(&converted_amount)?;
+        self.ensure_within_overdraft_limit(services, &balance)?;
+        self.ensure_within_withdrawal_limits(services, &atm_id, &converted_amount)?;
+        self.ensure_within_monthly_withdrawal_limit(services)?;
         if services
             .services
-            .atm_withdrawal(&atm_id, amount)
+            .atm_withdrawal(&atm_id, &amount)
             .await
             .is_err()
         {
             error!("atm rule violation");
             return Err(BankAccountError::AtmRuleViolation);
         };
-        Ok(vec![BankAccountEvent::CustomerWithdrewCash {
-            amount,
+        let fee_reason = FeeReason::AtmWithdrawal {
+            atm_id: atm_id.clone(),
+        };
+        let mut events = vec![BankAccountEvent::CustomerWithdrewCash {
+            amount: converted_amount,
             balance,
-        }])
+            atm_id: Some(atm_id),
+            conversion,
+            occurred_at: services.services.now(),
+        }];
+        let balance = self.charge_fees(services, fee_reason, balance, &mut events)?;
+        events.extend(self.overdraft_fee_for_debit(services, &balance)?);
+        Ok(events)
     }
 
     #[instrument]
@@ -151,20 +545,23 @@ impl BankAccount {
         &self,
         services: &BankAccountServices,
         check_number: String,
-        amount: f64,
+        amount: Money,
     ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
-        if self.account_id.is_empty() {
-            return Err(BankAccountError::AccountNotOpen);
+        self.ensure_account_accepts_debits()?;
+        if !self.product_rules(services).checks_allowed {
+            error!("checks not allowed");
+            return Err(BankAccountError::ChecksNotAllowed);
         }
-        if amount < 0_f64 {
+        if amount.is_negative() {
             error!("cannot write negative check amount");
             return Err(BankAccountError::CannotWriteNegativeCheckAmount);
         }
-        let balance = self.balance - amount;
-        if balance < 0_f64 {
-            error!("insufficient funds");
-            return Err(BankAccountError::InsufficientFunds);
+        if check_number.is_empty() || self.checks.contains_key(&check_number) {
+            error!("invalid check number");
+            return Err(BankAccountError::InvalidCheckNumber);
         }
+        let balance = self.balance.checked_sub(&amount)?;
+        self.ensure_within_overdraft_limit(services, &balance)?;
         if services
             .services
             .validate_check(&self.account_id, &check_number)
@@ -174,283 +571,3315 @@ impl BankAccount {
             error!("invalid check");
             return Err(BankAccountError::InvalidCheck);
         };
-        Ok(vec![BankAccountEvent::CustomerWroteCheck {
+        let fee_reason = FeeReason::Check {
+            check_number: check_number.clone(),
+        };
+        let mut events = vec![BankAccountEvent::CustomerWroteCheck {
             check_number,
             amount,
             balance,
-        }])
+            occurred_at: services.services.now(),
+        }];
+        let balance = self.charge_fees(services, fee_reason, balance, &mut events)?;
+        events.extend(self.overdraft_fee_for_debit(services, &balance)?);
+        Ok(events)
     }
-}
 
-impl Default for BankAccount {
-    fn default() -> Self {
-        BankAccount {
-            account_id: "".to_string(),
-            balance: 0_f64,
+    #[instrument]
+    pub async fn handle_freeze_account_command(
+        &self,
+        _services: &BankAccountServices,
+        command: BankAccountFreezeAccountCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        match self.status {
+            AccountStatus::NotOpened => Err(BankAccountError::AccountNotOpen),
+            AccountStatus::Closed => Err(BankAccountError::AccountClosed),
+            AccountStatus::Frozen => Err(BankAccountError::AccountFrozen),
+            AccountStatus::Open => Ok(vec![BankAccountEvent::AccountFrozen {
+                reason: command.reason,
+            }]),
         }
     }
-}
 
-// The aggregate tests are the most important part of a CQRS system.
-// The simplicity and flexibility of these tests are a good part of what
-// makes an event sourced system so friendly to changing business requirements.
-#[cfg(test)]
-mod aggregate_tests {
-    use async_trait::async_trait;
-    use coverage_helper::test;
-    use cqrs_es::test::TestFramework;
-    use std::sync::Mutex;
+    #[instrument]
+    pub async fn handle_unfreeze_account_command(
+        &self,
+        _services: &BankAccountServices,
+        command: BankAccountUnfreezeAccountCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        match self.status {
+            AccountStatus::NotOpened => Err(BankAccountError::AccountNotOpen),
+            AccountStatus::Closed => Err(BankAccountError::AccountClosed),
+            AccountStatus::Open => Err(BankAccountError::AccountNotFrozen),
+            AccountStatus::Frozen => Ok(vec![BankAccountEvent::AccountUnfrozen {
+                reason: command.reason,
+            }]),
+        }
+    }
 
-    use super::*;
+    #[instrument]
+    pub async fn handle_close_account_command(
+        &self,
+        _services: &BankAccountServices,
+        command: BankAccountCloseAccountCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        match self.status {
+            AccountStatus::NotOpened => return Err(BankAccountError::AccountNotOpen),
+            AccountStatus::Closed => return Err(BankAccountError::AccountClosed),
+            AccountStatus::Open | AccountStatus::Frozen => {}
+        }
+        if !self.balance.is_zero() {
+            error!("cannot close account with non-zero balance");
+            return Err(BankAccountError::CannotCloseAccountWithNonZeroBalance);
+        }
+        Ok(vec![BankAccountEvent::AccountClosed {
+            reason: command.reason,
+        }])
+    }
 
-    use crate::application::{AtmError, BankAccountApi, BankAccountServices, CheckingError};
-    use crate::domain::bank_account_aggregate::BankAccount;
-    use crate::domain::bank_account_commands::BankAccountCommand;
-    use crate::domain::bank_account_events::BankAccountEvent;
+    #[instrument]
+    pub async fn handle_set_overdraft_limit_command(
+        &self,
+        services: &BankAccountServices,
+        limit: Money,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_credits()?;
+        if limit.is_negative() {
+            error!("cannot set negative overdraft limit");
+            return Err(BankAccountError::CannotSetNegativeOverdraftLimit);
+        }
+        if !limit.is_zero() && !self.product_rules(services).overdraft_allowed {
+            error!("overdraft not allowed");
+            return Err(BankAccountError::OverdraftNotAllowed);
+        }
+        if limit.currency() != self.balance.currency() {
+            error!("overdraft limit must be in the account currency");
+            return Err(MoneyError::CurrencyMismatch {
+                expected: self.balance.currency().to_string(),
+                found: limit.currency().to_string(),
+            }
+            .into());
+        }
+        Ok(vec![BankAccountEvent::OverdraftLimitSet { limit }])
+    }
 
-    // A test framework that will apply our events and command
-    // and verify that the logic works as expected.
-    type AccountTestFramework = TestFramework<BankAccount>;
+    #[instrument]
+    pub async fn handle_transfer_out_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountTransferOutCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        // Checked first, so that a retried debit is recognized whatever became of the account.
+        if self.transfers_out.contains_key(&command.transfer_id) {
+            error!("duplicate transfer");
+            return Err(BankAccountError::DuplicateTransfer);
+        }
+        self.ensure_account_accepts_debits()?;
+        if command.amount.is_negative() {
+            error!("cannot transfer negative amount");
+            return Err(BankAccountError::CannotTransferNegativeAmount);
+        }
+        let (converted_amount, conversion) = self
+            .convert_to_account_currency(services, command.amount)
+            .await?;
+        let balance = self.balance.checked_sub(&converted_amount)?;
+        self.ensure_within_overdraft_limit(services, &balance)?;
+        self.ensure_within_monthly_withdrawal_limit(services)?;
+        let mut events = vec![BankAccountEvent::TransferDebited {
+            transfer_id: command.transfer_id,
+            destination_account_id: command.destination_account_id,
+            amount: converted_amount,
+            balance,
+            conversion,
+            occurred_at: services.services.now(),
+        }];
+        events.extend(self.overdraft_fee_for_debit(services, &balance)?);
+        Ok(events)
+    }
 
-    pub struct MockBankAccountServices {
-        atm_withdrawal_response: Mutex<Option<Result<(), AtmError>>>,
-        validate_check_response: Mutex<Option<Result<(), CheckingError>>>,
+    #[instrument]
+    pub async fn handle_transfer_in_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountTransferInCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        if self.transfers_in.contains(&command.transfer_id) {
+            error!("duplicate transfer");
+            return Err(BankAccountError::DuplicateTransfer);
+        }
+        self.ensure_account_accepts_credits()?;
+        if command.amount.is_negative() {
+            error!("cannot transfer negative amount");
+            return Err(BankAccountError::CannotTransferNegativeAmount);
+        }
+        let (converted_amount, conversion) = self
+            .convert_to_account_currency(services, command.amount)
+            .await?;
+        let balance = self.balance.checked_add(&converted_amount)?;
+        Ok(vec![BankAccountEvent::TransferCredited {
+            transfer_id: command.transfer_id,
+            source_account_id: command.source_account_id,
+            amount: converted_amount,
+            balance,
+            conversion,
+            occurred_at: services.services.now(),
+        }])
     }
 
-    impl Default for MockBankAccountServices {
-        fn default() -> Self {
-            Self {
-                atm_withdrawal_response: Mutex::new(None),
-                validate_check_response: Mutex::new(None),
+    // Refunds compensate for a transfer that could not be completed, so they are accepted
+    // whatever the status of the account, and return exactly the amount that was debited.
+    #[instrument]
+    pub async fn handle_refund_transfer_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountRefundTransferCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        let amount = match self.transfers_out.get(&command.transfer_id) {
+            Some(amount) => *amount,
+            None => {
+                error!("unknown transfer");
+                return Err(BankAccountError::UnknownTransfer);
             }
+        };
+        let balance = self.balance.checked_add(&amount)?;
+        Ok(vec![BankAccountEvent::TransferRefunded {
+            transfer_id: command.transfer_id,
+            amount,
+            balance,
+            reason: command.reason,
+            occurred_at: services.services.now(),
+        }])
+    }
+
+    // Once the destination account has been credited the transfer is settled, and the amount
+    // debited can no longer be refunded.
+    #[instrument]
+    pub async fn handle_complete_transfer_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountCompleteTransferCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        if !self.transfers_out.contains_key(&command.transfer_id) {
+            error!("unknown transfer");
+            return Err(BankAccountError::UnknownTransfer);
         }
+        Ok(vec![BankAccountEvent::TransferCompleted {
+            transfer_id: command.transfer_id,
+            occurred_at: services.services.now(),
+        }])
     }
 
-    impl MockBankAccountServices {
-        fn set_atm_withdrawal_response(&self, response: Result<(), AtmError>) {
-            *self.atm_withdrawal_response.lock().unwrap() = Some(response);
+    // A hold reserves funds for a later debit, e.g. a card authorization. It counts against
+    // the available funds until it is captured, released or expires.
+    #[instrument]
+    pub async fn handle_place_hold_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountPlaceHoldCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_debits()?;
+        if command.amount.is_negative() || command.amount.is_zero() {
+            error!("invalid hold amount");
+            return Err(BankAccountError::InvalidAmount);
         }
-        fn set_validate_check_response(&self, response: Result<(), CheckingError>) {
-            *self.validate_check_response.lock().unwrap() = Some(response);
+        if self.holds.contains_key(&command.hold_id) {
+            error!("duplicate hold");
+            return Err(BankAccountError::DuplicateHold);
         }
+        let (converted_amount, conversion) = self
+            .convert_to_account_currency(services, command.amount)
+            .await?;
+        let balance_if_captured = self.balance.checked_sub(&converted_amount)?;
+        self.ensure_within_overdraft_limit(services, &balance_if_captured)?;
+        let now = services.services.now();
+        let mut events = self.expired_holds(now);
+        events.push(BankAccountEvent::HoldPlaced {
+            hold_id: command.hold_id,
+            amount: converted_amount,
+            conversion,
+            expires_at: services.configuration.holds.expires_at(now),
+            occurred_at: now,
+        });
+        Ok(events)
     }
 
-    #[async_trait]
-    impl BankAccountApi for MockBankAccountServices {
-        async fn atm_withdrawal(&self, _atm_id: &str, _amount: f64) -> Result<(), AtmError> {
-            self.atm_withdrawal_response.lock().unwrap().take().unwrap()
+    // Capturing debits the account by up to the amount held and releases the rest, so the
+    // funds were already reserved and the overdraft limit is not checked again.
+    #[instrument]
+    pub async fn handle_capture_hold_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountCaptureHoldCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_debits()?;
+        let now = services.services.now();
+        let hold = self.active_hold(&command.hold_id, now)?;
+        let amount = command.amount.unwrap_or(hold.amount);
+        if amount.is_negative() {
+            error!("invalid capture amount");
+            return Err(BankAccountError::InvalidAmount);
+        }
+        if hold.amount.checked_sub(&amount)?.is_negative() {
+            error!("capture exceeds amount held");
+            return Err(BankAccountError::CaptureExceedsHold);
         }
+        let balance = self.balance.checked_sub(&amount)?;
+        let mut events = self.expired_holds(now);
+        events.push(BankAccountEvent::HoldCaptured {
+            hold_id: command.hold_id,
+            amount,
+            balance,
+            occurred_at: now,
+        });
+        events.extend(self.overdraft_fee_for_debit(services, &balance)?);
+        Ok(events)
+    }
 
-        async fn validate_check(
-            &self,
-            _account_id: &str,
-            _check_number: &str,
-        ) -> Result<(), CheckingError> {
-            self.validate_check_response.lock().unwrap().take().unwrap()
+    #[instrument]
+    pub async fn handle_release_hold_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountReleaseHoldCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        let now = services.services.now();
+        let mut events = self.expired_holds(now);
+        match self.holds.get(&command.hold_id) {
+            Some(hold) if hold.is_active(now) => events.push(BankAccountEvent::HoldReleased {
+                hold_id: command.hold_id,
+                amount: hold.amount,
+            }),
+            // Releasing a hold that has lapsed records its expiry instead.
+            Some(_) => {}
+            None => {
+                error!("unknown hold");
+                return Err(BankAccountError::UnknownHold);
+            }
         }
+        Ok(events)
     }
 
-    #[test]
-    fn deposit_money() {
-        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
-        AccountTestFramework::with(services)
-            .given(vec![BankAccountEvent::AccountOpened {
-                account_id: "1234".to_string(),
-            }])
-            .when(BankAccountCommand::DepositMoney(
-                BankAccountDepositMoneyCommandData { amount: 200.0 },
-            ))
-            .then_expect_events(vec![BankAccountEvent::CustomerDepositedMoney {
-                amount: 200.0,
-                balance: 200.0,
-            }]);
+    #[instrument]
+    pub async fn handle_add_holder_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountAddHolderCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_credits()?;
+        if command.holder_id.is_empty() {
+            error!("invalid holder id");
+            return Err(BankAccountError::InvalidHolderId);
+        }
+        if self.holder_ids.contains(&command.holder_id) {
+            error!("holder already added");
+            return Err(BankAccountError::DuplicateHolder);
+        }
+        ensure_kyc_verified(services, &command.holder_id).await?;
+        Ok(vec![BankAccountEvent::HolderAdded {
+            holder_id: command.holder_id,
+        }])
+    }
+
+    // The primary holder owns the account and stays with it until it is closed.
+    #[instrument]
+    pub async fn handle_remove_holder_command(
+        &self,
+        _services: &BankAccountServices,
+        command: BankAccountRemoveHolderCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_credits()?;
+        if self.primary_holder_id.as_ref() == Some(&command.holder_id) {
+            error!("cannot remove primary holder");
+            return Err(BankAccountError::CannotRemovePrimaryHolder);
+        }
+        if !self.holder_ids.contains(&command.holder_id) {
+            error!("unknown holder");
+            return Err(BankAccountError::UnknownHolder);
+        }
+        Ok(vec![BankAccountEvent::HolderRemoved {
+            holder_id: command.holder_id,
+        }])
+    }
+
+    // Checks are debited when written and stay pending until the bank they were paid into
+    // presents them. Clearing only settles the check, the money has already left.
+    #[instrument]
+    pub async fn handle_clear_check_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountClearCheckCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.pending_check(&command.check_number)?;
+        Ok(vec![BankAccountEvent::CheckCleared {
+            check_number: command.check_number,
+            occurred_at: services.services.now(),
+        }])
+    }
+
+    // A bounced check is credited back and charged the returned item fee. Like a transfer
+    // refund this is accepted whatever the status of the account.
+    #[instrument]
+    pub async fn handle_bounce_check_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountBounceCheckCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        let check = self.pending_check(&command.check_number)?;
+        let now = services.services.now();
+        let balance = self.balance.checked_add(&check.amount)?;
+        let mut events = vec![BankAccountEvent::CheckBounced {
+            check_number: command.check_number.clone(),
+            amount: check.amount,
+            balance,
+            reason: command.reason,
+            occurred_at: now,
+        }];
+        let fee = services
+            .configuration
+            .checks
+            .returned_item_fee
+            .in_currency(self.balance.currency());
+        if !fee.is_zero() {
+            events.push(BankAccountEvent::ReturnedItemFeeCharged {
+                check_number: command.check_number,
+                fee,
+                balance: balance.checked_sub(&fee)?,
+                occurred_at: now,
+            });
+        }
+        Ok(events)
+    }
+
+    // Reversals correct a deposit or cash withdrawal recorded in error by moving exactly the
+    // original amount back, without fees or limit checks. Like a transfer refund this is
+    // accepted on frozen accounts.
+    #[instrument]
+    pub async fn handle_reverse_transaction_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountReverseTransactionCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_credits()?;
+        let transaction = match self.reversible_transactions.get(&command.sequence) {
+            Some(transaction) if transaction.reversed => {
+                error!("transaction already reversed");
+                return Err(BankAccountError::TransactionAlreadyReversed);
+            }
+            Some(transaction) => *transaction,
+            None => {
+                error!("unknown transaction");
+                return Err(BankAccountError::UnknownTransaction);
+            }
+        };
+        let balance = match transaction.kind {
+            ReversibleTransactionKind::Deposit => self.balance.checked_sub(&transaction.amount)?,
+            ReversibleTransactionKind::Withdrawal => {
+                self.balance.checked_add(&transaction.amount)?
+            }
+        };
+        Ok(vec![BankAccountEvent::TransactionReversed {
+            reversed_sequence: command.sequence,
+            amount: transaction.amount,
+            balance,
+            reason: command.reason,
+            occurred_at: services.services.now(),
+        }])
+    }
+
+    // Maintenance fees are charged in arrears, at most once for each calendar month.
+    #[instrument]
+    pub async fn handle_charge_maintenance_fee_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountChargeMaintenanceFeeCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_credits()?;
+        let now = services.services.now();
+        let is_valid_period = command.period.len() == 7
+            && NaiveDate::parse_from_str(&format!("{}-01", command.period), "%Y-%m-%d").is_ok()
+            && command.period <= now.format("%Y-%m").to_string();
+        if !is_valid_period {
+            error!("invalid fee period");
+            return Err(BankAccountError::InvalidFeePeriod);
+        }
+        if self
+            .maintenance_fee_charged_through
+            .as_ref()
+            .is_some_and(|charged_through| command.period <= *charged_through)
+        {
+            error!("maintenance fee already charged");
+            return Err(BankAccountError::MaintenanceFeeAlreadyCharged);
+        }
+        if command.period < self.opened_at.format("%Y-%m").to_string() {
+            error!("maintenance fee period before the account was opened");
+            return Err(BankAccountError::MaintenanceFeePeriodBeforeOpening);
+        }
+        let mut events = Vec::new();
+        self.charge_fees(
+            services,
+            FeeReason::MonthlyMaintenance {
+                period: command.period,
+            },
+            self.balance,
+            &mut events,
+        )?;
+        Ok(events)
+    }
+
+    #[instrument]
+    pub async fn handle_create_standing_order_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountCreateStandingOrderCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_debits()?;
+        if self
+            .standing_orders
+            .contains_key(&command.standing_order_id)
+        {
+            error!("duplicate standing order");
+            return Err(BankAccountError::DuplicateStandingOrder);
+        }
+        if command.amount.is_negative() || command.amount.is_zero() {
+            error!("invalid standing order amount");
+            return Err(BankAccountError::InvalidAmount);
+        }
+        if command.amount.currency() != self.balance.currency() {
+            error!("standing order must be in the account currency");
+            return Err(MoneyError::CurrencyMismatch {
+                expected: self.balance.currency().to_string(),
+                found: command.amount.currency().to_string(),
+            }
+            .into());
+        }
+        if !is_valid_day_of_month(command.day_of_month) {
+            error!("invalid day of month");
+            return Err(BankAccountError::InvalidDayOfMonth);
+        }
+        let today = services.services.now().date_naive();
+        Ok(vec![BankAccountEvent::StandingOrderCreated {
+            standing_order_id: command.standing_order_id,
+            amount: command.amount,
+            day_of_month: command.day_of_month,
+            description: command.description,
+            first_due_date: first_due_date(today, command.day_of_month),
+        }])
+    }
+
+    #[instrument]
+    pub async fn handle_cancel_standing_order_command(
+        &self,
+        _services: &BankAccountServices,
+        command: BankAccountCancelStandingOrderCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        if !self
+            .standing_orders
+            .contains_key(&command.standing_order_id)
+        {
+            error!("unknown standing order");
+            return Err(BankAccountError::UnknownStandingOrder);
+        }
+        Ok(vec![BankAccountEvent::StandingOrderCancelled {
+            standing_order_id: command.standing_order_id,
+        }])
+    }
+
+    // Payments are debited like cash withdrawals. A payment that can't be made is recorded as
+    // failed rather than refused, so that the order moves on to its next payment, but orders
+    // on closed accounts are refused outright.
+    #[instrument]
+    pub async fn handle_execute_standing_order_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountExecuteStandingOrderCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_credits()?;
+        let Some(order) = self.standing_orders.get(&command.standing_order_id) else {
+            error!("unknown standing order");
+            return Err(BankAccountError::UnknownStandingOrder);
+        };
+        let now = services.services.now();
+        if command.due_date != order.next_due_date || command.due_date > now.date_naive() {
+            error!("standing order not due");
+            return Err(BankAccountError::StandingOrderNotDue);
+        }
+        let next_due_date = following_due_date(command.due_date, order.day_of_month);
+        let balance = match self.standing_order_debit(services, &order.amount) {
+            Ok(balance) => balance,
+            Err(err) => {
+                return Ok(vec![BankAccountEvent::StandingOrderFailed {
+                    standing_order_id: command.standing_order_id,
+                    due_date: command.due_date,
+                    reason: err.to_string(),
+                    next_due_date,
+                    occurred_at: now,
+                }]);
+            }
+        };
+        let mut events = vec![BankAccountEvent::StandingOrderExecuted {
+            standing_order_id: command.standing_order_id,
+            due_date: command.due_date,
+            amount: order.amount,
+            balance,
+            next_due_date,
+            occurred_at: now,
+        }];
+        events.extend(self.overdraft_fee_for_debit(services, &balance)?);
+        Ok(events)
+    }
+
+    // Interest is accrued day by day on the end of day balance and posted straight away.
+    // Days that have already been accrued are skipped, so overlapping periods are safe to
+    // request, e.g. by a scheduled job catching up after downtime.
+    #[instrument]
+    pub async fn handle_accrue_interest_command(
+        &self,
+        services: &BankAccountServices,
+        command: BankAccountAccrueInterestCommandData,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.ensure_account_accepts_credits()?;
+        if self
+            .product_rules(services)
+            .annual_interest_rate_basis_points
+            .is_none()
+        {
+            error!("interest not earned");
+            return Err(BankAccountError::InterestNotEarned);
+        }
+        let now = services.services.now();
+        if command.period_end <= command.period_start || command.period_end > now.date_naive() {
+            error!("invalid interest period");
+            return Err(BankAccountError::InvalidInterestPeriod);
+        }
+        let period_start = match self.interest_accrued_through {
+            Some(accrued_through) if accrued_through > command.period_start => accrued_through,
+            _ => command.period_start,
+        };
+        if period_start >= command.period_end {
+            error!("interest already accrued");
+            return Err(BankAccountError::InterestAlreadyAccrued);
+        }
+        let end_of_day_balances = period_start
+            .iter_days()
+            .take_while(|day| *day < command.period_end)
+            .map(|day| self.end_of_day_balance(day));
+        let accrued_micro_units =
+            accrue_daily_interest(end_of_day_balances, command.annual_rate_basis_points)?
+                .checked_add(self.unposted_interest_micro_units)
+                .ok_or(MoneyError::Overflow)?;
+        let amount = Money::new(
+            accrued_micro_units / INTEREST_MICRO_UNITS_PER_MINOR_UNIT,
+            self.balance.currency(),
+        );
+        let mut events = vec![BankAccountEvent::InterestAccrued {
+            period_start,
+            period_end: command.period_end,
+            annual_rate_basis_points: command.annual_rate_basis_points,
+            amount,
+            unposted_micro_units: accrued_micro_units % INTEREST_MICRO_UNITS_PER_MINOR_UNIT,
+        }];
+        if !amount.is_zero() {
+            events.push(BankAccountEvent::InterestPosted {
+                amount,
+                balance: self.balance.checked_add(&amount)?,
+                occurred_at: now,
+            });
+        }
+        Ok(events)
+    }
+
+    // Frozen accounts still accept credits, closed accounts accept nothing.
+    fn ensure_account_accepts_credits(&self) -> Result<(), BankAccountError> {
+        match self.status {
+            AccountStatus::NotOpened => Err(BankAccountError::AccountNotOpen),
+            AccountStatus::Closed => Err(BankAccountError::AccountClosed),
+            AccountStatus::Open | AccountStatus::Frozen => Ok(()),
+        }
+    }
+
+    fn ensure_account_accepts_debits(&self) -> Result<(), BankAccountError> {
+        if self.status == AccountStatus::Frozen {
+            error!("account frozen");
+            return Err(BankAccountError::AccountFrozen);
+        }
+        self.ensure_account_accepts_credits()
+    }
+
+    fn pending_check(&self, check_number: &str) -> Result<WrittenCheck, BankAccountError> {
+        match self.checks.get(check_number) {
+            Some(check) if check.status == CheckStatus::Pending => Ok(*check),
+            Some(_) => {
+                error!("check already settled");
+                Err(BankAccountError::CheckAlreadySettled)
+            }
+            None => {
+                error!("unknown check");
+                Err(BankAccountError::UnknownCheck)
+            }
+        }
+    }
+
+    // The balance once a standing order payment is debited, if the payment can be made.
+    fn standing_order_debit(
+        &self,
+        services: &BankAccountServices,
+        amount: &Money,
+    ) -> Result<Money, BankAccountError> {
+        self.ensure_account_accepts_debits()?;
+        let balance = self.balance.checked_sub(amount)?;
+        self.ensure_within_overdraft_limit(services, &balance)?;
+        self.ensure_within_monthly_withdrawal_limit(services)?;
+        Ok(balance)
+    }
+
+    fn set_next_due_date(&mut self, standing_order_id: &str, next_due_date: NaiveDate) {
+        if let Some(order) = self.standing_orders.get_mut(standing_order_id) {
+            order.next_due_date = next_due_date;
+        }
+    }
+
+    // Recorded under the sequence number of the event being applied.
+    fn record_reversible_transaction(&mut self, kind: ReversibleTransactionKind, amount: Money) {
+        self.reversible_transactions.insert(
+            self.last_sequence,
+            ReversibleTransaction {
+                kind,
+                amount,
+                reversed: false,
+            },
+        );
+    }
+
+    fn set_check_status(&mut self, check_number: &str, status: CheckStatus) {
+        if let Some(check) = self.checks.get_mut(check_number) {
+            check.status = status;
+        }
+    }
+
+    fn record_balance(&mut self, occurred_at: DateTime<Utc>, balance: Money) {
+        self.balance = balance;
+        self.balance_history.push((occurred_at, balance));
+    }
+
+    // The balance at the end of a day is the one set by the last change on or before that day.
+    fn end_of_day_balance(&self, day: NaiveDate) -> Money {
+        self.balance_history
+            .iter()
+            .rev()
+            .find(|(occurred_at, _)| occurred_at.date_naive() <= day)
+            .map(|(_, balance)| *balance)
+            .unwrap_or_else(|| Money::zero(self.balance.currency()))
+    }
+
+    fn prune_balance_history(&mut self) {
+        let Some(accrued_through) = self.interest_accrued_through else {
+            return;
+        };
+        if let Some(last_before) = self
+            .balance_history
+            .iter()
+            .rposition(|(occurred_at, _)| occurred_at.date_naive() < accrued_through)
+        {
+            self.balance_history.drain(..last_before);
+        }
+    }
+
+    // A debit may take the balance below zero, but never further than the overdraft limit,
+    // less whatever is reserved by active holds and the overdraft fee the debit would be
+    // charged. Products without an overdraft ignore any limit set before the catalogue changed.
+    fn ensure_within_overdraft_limit(
+        &self,
+        services: &BankAccountServices,
+        balance: &Money,
+    ) -> Result<(), BankAccountError> {
+        let held = self.active_holds_total(services.services.now())?;
+        let overdraft_limit = if self.product_rules(services).overdraft_allowed {
+            self.overdraft_limit
+        } else {
+            Money::zero(self.balance.currency())
+        };
+        let overdraft_fee = self.overdraft_fee(services, balance)?;
+        if balance
+            .checked_add(&overdraft_limit)?
+            .checked_sub(&held)?
+            .checked_sub(&overdraft_fee)?
+            .is_negative()
+        {
+            error!("insufficient funds");
+            return Err(BankAccountError::InsufficientFunds);
+        }
+        Ok(())
+    }
+
+    // Limits are checked against the cash withdrawn over the rolling window ending now,
+    // first across all ATMs and then at the ATM being used.
+    fn ensure_within_withdrawal_limits(
+        &self,
+        services: &BankAccountServices,
+        atm_id: &str,
+        amount: &Money,
+    ) -> Result<(), BankAccountError> {
+        let limits = &services.configuration.withdrawal_limits;
+        let window_start = withdrawal_limit_window_start(services.services.now());
+        let in_window = || {
+            self.cash_withdrawals
+                .iter()
+                .filter(move |withdrawal| withdrawal.occurred_at > window_start)
+        };
+        let currency = self.balance.currency();
+        if let Some(limit) = limits.daily_limit {
+            let remaining =
+                remaining_withdrawal_allowance(Money::new(limit, currency), in_window())?;
+            if remaining.checked_sub(amount)?.is_negative() {
+                error!("daily withdrawal limit exceeded");
+                return Err(BankAccountError::WithdrawalLimitExceeded {
+                    atm_id: None,
+                    remaining,
+                });
+            }
+        }
+        if let Some(limit) = limits.daily_limit_per_atm {
+            let remaining = remaining_withdrawal_allowance(
+                Money::new(limit, currency),
+                in_window().filter(|withdrawal| withdrawal.atm_id.as_deref() == Some(atm_id)),
+            )?;
+            if remaining.checked_sub(amount)?.is_negative() {
+                error!("daily withdrawal limit exceeded at atm {atm_id}");
+                return Err(BankAccountError::WithdrawalLimitExceeded {
+                    atm_id: Some(atm_id.to_string()),
+                    remaining,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn ensure_within_monthly_withdrawal_limit(
+        &self,
+        services: &BankAccountServices,
+    ) -> Result<(), BankAccountError> {
+        let Some(limit) = self.product_rules(services).monthly_withdrawal_limit else {
+            return Ok(());
+        };
+        if self.monthly_withdrawals.count_in(services.services.now()) >= limit {
+            error!("monthly withdrawal limit reached");
+            return Err(BankAccountError::MonthlyWithdrawalLimitReached);
+        }
+        Ok(())
+    }
+
+    fn product_rules<'a>(&self, services: &'a BankAccountServices) -> &'a ProductRules {
+        services.configuration.products.rules_for(self.product)
+    }
+
+    fn active_holds_total(&self, now: DateTime<Utc>) -> Result<Money, MoneyError> {
+        self.holds
+            .values()
+            .filter(|hold| hold.is_active(now))
+            .try_fold(Money::zero(self.balance.currency()), |total, hold| {
+                total.checked_add(&hold.amount)
+            })
+    }
+
+    fn active_hold(&self, hold_id: &str, now: DateTime<Utc>) -> Result<Hold, BankAccountError> {
+        match self.holds.get(hold_id) {
+            Some(hold) if hold.is_active(now) => Ok(*hold),
+            Some(_) => {
+                error!("hold expired");
+                Err(BankAccountError::HoldExpired)
+            }
+            None => {
+                error!("unknown hold");
+                Err(BankAccountError::UnknownHold)
+            }
+        }
+    }
+
+    // Holds that have lapsed but are not yet recorded as expired, in hold id order.
+    fn expired_holds(&self, now: DateTime<Utc>) -> Vec<BankAccountEvent> {
+        let mut expired: Vec<_> = self
+            .holds
+            .iter()
+            .filter(|(_, hold)| !hold.is_active(now))
+            .collect();
+        expired.sort_by_key(|(hold_id, _)| *hold_id);
+        expired
+            .into_iter()
+            .map(|(hold_id, hold)| BankAccountEvent::HoldExpired {
+                hold_id: hold_id.clone(),
+                amount: hold.amount,
+            })
+            .collect()
+    }
+
+    // The overdraft fee is charged once, when a debit takes the balance from zero or above
+    // to below zero. Further debits while already overdrawn are not charged again.
+    fn overdraft_fee(
+        &self,
+        services: &BankAccountServices,
+        balance: &Money,
+    ) -> Result<Money, BankAccountError> {
+        if self.balance.is_negative() || !balance.is_negative() {
+            return Ok(Money::zero(self.balance.currency()));
+        }
+        Ok(services
+            .configuration
+            .overdraft_fees
+            .fee_for(&balance.checked_neg()?)?)
+    }
+
+    fn overdraft_fee_for_debit(
+        &self,
+        services: &BankAccountServices,
+        balance: &Money,
+    ) -> Result<Option<BankAccountEvent>, BankAccountError> {
+        let fee = self.overdraft_fee(services, balance)?;
+        if fee.is_zero() {
+            return Ok(None);
+        }
+        let balance = balance.checked_sub(&fee)?;
+        Ok(Some(BankAccountEvent::OverdraftFeeCharged {
+            fee,
+            balance,
+            occurred_at: services.services.now(),
+        }))
+    }
+
+    // Every fee the fee engine finds due is charged as an event of its own, following the
+    // events for the transaction. Like overdraft fees they are charged whatever the overdraft
+    // limit. Returns the balance once they are charged.
+    fn charge_fees(
+        &self,
+        services: &BankAccountServices,
+        reason: FeeReason,
+        mut balance: Money,
+        events: &mut Vec<BankAccountEvent>,
+    ) -> Result<Money, BankAccountError> {
+        for fee in services
+            .configuration
+            .fees
+            .fees_for(&reason, self.balance.currency())
+        {
+            balance = balance.checked_sub(&fee)?;
+            events.push(BankAccountEvent::FeeCharged {
+                reason: reason.clone(),
+                fee,
+                balance,
+                occurred_at: services.services.now(),
+            });
+        }
+        Ok(balance)
+    }
+
+    // Amounts in a foreign currency are converted into the account's currency using the
+    // rate provided by the FX service, which is recorded alongside the converted amount.
+    async fn convert_to_account_currency(
+        &self,
+        services: &BankAccountServices,
+        amount: Money,
+    ) -> Result<(Money, Option<CurrencyConversion>), BankAccountError> {
+        let account_currency = self.balance.currency();
+        if amount.currency() == account_currency {
+            return Ok((amount, None));
+        }
+        let rate = match services
+            .services
+            .fx_rate(amount.currency(), account_currency)
+            .await
+        {
+            Ok(rate) => rate,
+            Err(_) => {
+                error!("fx rate unavailable");
+                return Err(BankAccountError::FxRateUnavailable);
+            }
+        };
+        let converted_amount = rate.convert(&amount)?;
+        Ok((
+            converted_amount,
+            Some(CurrencyConversion {
+                original_amount: amount,
+                rate,
+            }),
+        ))
+    }
+}
+
+impl Default for BankAccount {
+    fn default() -> Self {
+        BankAccount {
+            account_id: "".to_string(),
+            product: AccountProduct::default(),
+            balance: Money::default(),
+            status: AccountStatus::default(),
+            overdraft_limit: Money::default(),
+            balance_history: Vec::new(),
+            interest_accrued_through: None,
+            unposted_interest_micro_units: 0,
+            transfers_out: HashMap::new(),
+            transfers_in: HashSet::new(),
+            holds: HashMap::new(),
+            checks: HashMap::new(),
+            cash_withdrawals: Vec::new(),
+            primary_holder_id: None,
+            holder_ids: Vec::new(),
+            last_sequence: 0,
+            reversible_transactions: HashMap::new(),
+            maintenance_fee_charged_through: None,
+            opened_at: DateTime::default(),
+            standing_orders: HashMap::new(),
+            monthly_withdrawals: MonthlyWithdrawals::default(),
+        }
+    }
+}
+
+// The aggregate tests are the most important part of a CQRS system.
+// The simplicity and flexibility of these tests are a good part of what
+// makes an event sourced system so friendly to changing business requirements.
+#[cfg(test)]
+mod aggregate_tests {
+    use async_trait::async_trait;
+    use coverage_helper::test;
+    use cqrs_es::test::TestFramework;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    use crate::application::{
+        AtmError, BankAccountApi, BankAccountConfiguration, BankAccountServices, CheckingError,
+        CustomerKycLookup, CustomerLookupError, FeeEngine, FeeRule, FxRateError,
+        HappyPathCustomerKycLookup,
+    };
+    use crate::domain::bank_account_aggregate::BankAccount;
+    use crate::domain::bank_account_commands::BankAccountCommand;
+    use crate::domain::bank_account_events::BankAccountEvent;
+    use crate::domain::{Currency, ExchangeRate, Money};
+
+    fn eur(minor_units: i64) -> Money {
+        Money::new(minor_units, Currency::EUR)
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2023-03-15T12:00:00Z".parse().unwrap()
+    }
+
+    fn day(day_of_month: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 3, day_of_month).unwrap()
+    }
+
+    // A test framework that will apply our events and command
+    // and verify that the logic works as expected.
+    type AccountTestFramework = TestFramework<BankAccount>;
+
+    pub struct MockBankAccountServices {
+        atm_withdrawal_response: Mutex<Option<Result<(), AtmError>>>,
+        validate_check_response: Mutex<Option<Result<(), CheckingError>>>,
+        fx_rate_response: Mutex<Option<Result<ExchangeRate, FxRateError>>>,
+    }
+
+    impl Default for MockBankAccountServices {
+        fn default() -> Self {
+            Self {
+                atm_withdrawal_response: Mutex::new(None),
+                validate_check_response: Mutex::new(None),
+                fx_rate_response: Mutex::new(None),
+            }
+        }
+    }
+
+    impl MockBankAccountServices {
+        fn set_atm_withdrawal_response(&self, response: Result<(), AtmError>) {
+            *self.atm_withdrawal_response.lock().unwrap() = Some(response);
+        }
+        fn set_validate_check_response(&self, response: Result<(), CheckingError>) {
+            *self.validate_check_response.lock().unwrap() = Some(response);
+        }
+        fn set_fx_rate_response(&self, response: Result<ExchangeRate, FxRateError>) {
+            *self.fx_rate_response.lock().unwrap() = Some(response);
+        }
+    }
+
+    #[async_trait]
+    impl BankAccountApi for MockBankAccountServices {
+        async fn atm_withdrawal(&self, _atm_id: &str, _amount: &Money) -> Result<(), AtmError> {
+            self.atm_withdrawal_response.lock().unwrap().take().unwrap()
+        }
+
+        async fn validate_check(
+            &self,
+            _account_id: &str,
+            _check_number: &str,
+        ) -> Result<(), CheckingError> {
+            self.validate_check_response.lock().unwrap().take().unwrap()
+        }
+
+        async fn fx_rate(
+            &self,
+            _from: Currency,
+            _to: Currency,
+        ) -> Result<ExchangeRate, FxRateError> {
+            self.fx_rate_response.lock().unwrap().take().unwrap()
+        }
+
+        fn now(&self) -> DateTime<Utc> {
+            now()
+        }
+    }
+
+    #[test]
+    fn deposit_money() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: Some("customer-1".to_string()),
+                product: AccountProduct::Checking,
+                occurred_at: DateTime::default(),
+            }])
+            .when(BankAccountCommand::DepositMoney(
+                BankAccountDepositMoneyCommandData { amount: eur(20000) },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerDepositedMoney {
+                amount: eur(20000),
+                balance: eur(20000),
+                conversion: None,
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_deposit_money_into_account_that_does_not_exist() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given_no_previous_events()
+            .when(BankAccountCommand::DepositMoney(
+                BankAccountDepositMoneyCommandData { amount: eur(20000) },
+            ))
+            .then_expect_error(BankAccountError::AccountNotOpen);
+    }
+
+    #[test]
+    fn can_deposit_money_with_balance() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+
+        AccountTestFramework::with(services)
+            .given(vec![
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: Some("customer-1".to_string()),
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::DepositMoney(
+                BankAccountDepositMoneyCommandData { amount: eur(20000) },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerDepositedMoney {
+                amount: eur(20000),
+                balance: eur(40000),
+                conversion: None,
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn deposit_in_a_foreign_currency_is_converted_into_the_account_currency() {
+        let rate = ExchangeRate::from_decimal_str(Currency::USD, Currency::EUR, "0.92").unwrap();
+        let services = MockBankAccountServices::default();
+        services.set_fx_rate_response(Ok(rate));
+
+        AccountTestFramework::with(BankAccountServices::new(Box::new(services)))
+            .given(vec![BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: Some("customer-1".to_string()),
+                product: AccountProduct::Checking,
+                occurred_at: DateTime::default(),
+            }])
+            .when(BankAccountCommand::DepositMoney(
+                BankAccountDepositMoneyCommandData {
+                    amount: Money::new(10000, Currency::USD),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerDepositedMoney {
+                amount: eur(9200),
+                balance: eur(9200),
+                conversion: Some(CurrencyConversion {
+                    original_amount: Money::new(10000, Currency::USD),
+                    rate,
+                }),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_deposit_in_a_foreign_currency_without_an_fx_rate() {
+        let services = MockBankAccountServices::default();
+        services.set_fx_rate_response(Err(FxRateError));
+
+        AccountTestFramework::with(BankAccountServices::new(Box::new(services)))
+            .given(vec![BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: Some("customer-1".to_string()),
+                product: AccountProduct::Checking,
+                occurred_at: DateTime::default(),
+            }])
+            .when(BankAccountCommand::DepositMoney(
+                BankAccountDepositMoneyCommandData {
+                    amount: Money::new(10000, Currency::USD),
+                },
+            ))
+            .then_expect_error(BankAccountError::FxRateUnavailable);
+    }
+
+    #[test]
+    fn withdrawal_in_a_foreign_currency_is_converted_into_the_account_currency() {
+        let rate = ExchangeRate::from_decimal_str(Currency::EUR, Currency::USD, "1.087").unwrap();
+        let services = MockBankAccountServices::default();
+        services.set_fx_rate_response(Ok(rate));
+        services.set_atm_withdrawal_response(Ok(()));
+
+        AccountTestFramework::with(BankAccountServices::new(Box::new(services)))
+            .given(vec![
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::USD,
+                    primary_holder_id: Some("customer-1".to_string()),
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: Money::new(20000, Currency::USD),
+                    balance: Money::new(20000, Currency::USD),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(5000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerWithdrewCash {
+                amount: Money::new(5435, Currency::USD),
+                balance: Money::new(14565, Currency::USD),
+                atm_id: Some("ATM34f1ba3c".to_string()),
+                conversion: Some(CurrencyConversion {
+                    original_amount: eur(5000),
+                    rate,
+                }),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn can_withdraw_money_when_atm_withdrawal_response_is_ok() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+
+        AccountTestFramework::with(BankAccountServices::new(Box::new(services)))
+            .given(vec![
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: Some("customer-1".to_string()),
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(10000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(10000),
+                balance: eur(10000),
+                atm_id: Some("ATM34f1ba3c".to_string()),
+                conversion: None,
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_withdraw_money_when_there_is_an_atm_error() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Err(AtmError));
+        let services = BankAccountServices::new(Box::new(services));
+
+        AccountTestFramework::with(services)
+            .given(vec![
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: Some("customer-1".to_string()),
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(10000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_error_message(BankAccountError::AtmRuleViolation.to_string().as_str());
+    }
+
+    #[test]
+    fn withdraw_money_funds_not_available_returns_insufficient_funds_error() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: Some("customer-1".to_string()),
+                product: AccountProduct::Checking,
+                occurred_at: DateTime::default(),
+            }])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(20000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            // Here we expect an error rather than any events
+            .then_expect_error_message(BankAccountError::InsufficientFunds.to_string().as_str());
+    }
+
+    #[test]
+    fn wrote_check() {
+        let services = MockBankAccountServices::default();
+        services.set_validate_check_response(Ok(()));
+        let services = BankAccountServices::new(Box::new(services));
+
+        AccountTestFramework::with(services)
+            .given(vec![
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: Some("customer-1".to_string()),
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::WriteCheck(
+                BankAccountWriteCheckCommandData {
+                    check_number: "1170".to_string(),
+                    amount: eur(10000),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerWroteCheck {
+                check_number: "1170".to_string(),
+                amount: eur(10000),
+                balance: eur(10000),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn wrote_check_bad_check() {
+        let services = MockBankAccountServices::default();
+        services.set_validate_check_response(Err(CheckingError));
+        let services = BankAccountServices::new(Box::new(services));
+
+        AccountTestFramework::with(services)
+            .given(vec![
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: Some("customer-1".to_string()),
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::WriteCheck(
+                BankAccountWriteCheckCommandData {
+                    check_number: "1170".to_string(),
+                    amount: eur(10000),
+                },
+            ))
+            .then_expect_error_message(BankAccountError::InvalidCheck.to_string().as_str());
+    }
+
+    #[test]
+    fn wrote_check_funds_not_available() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: Some("customer-1".to_string()),
+                product: AccountProduct::Checking,
+                occurred_at: DateTime::default(),
+            }])
+            .when(BankAccountCommand::WriteCheck(
+                BankAccountWriteCheckCommandData {
+                    check_number: "1170".to_string(),
+                    amount: eur(10000),
+                },
+            ))
+            .then_expect_error_message(BankAccountError::InsufficientFunds.to_string().as_str())
+    }
+
+    #[test]
+    fn open_account() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_customers(Box::new(HappyPathCustomerKycLookup));
+        AccountTestFramework::with(services)
+            .given_no_previous_events()
+            .when(BankAccountCommand::OpenAccount(
+                BankAccountOpenAccountCommandData {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: "customer-1".to_string(),
+                    product: AccountProduct::Checking,
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: Some("customer-1".to_string()),
+                product: AccountProduct::Checking,
+                occurred_at: now(),
+            }]);
+    }
+
+    // A customer lookup that knows a single customer, "customer-1", in the given KYC status.
+    struct FixedCustomerKycLookup(KycStatus);
+
+    #[async_trait]
+    impl CustomerKycLookup for FixedCustomerKycLookup {
+        async fn kyc_status(
+            &self,
+            customer_id: &str,
+        ) -> Result<Option<KycStatus>, CustomerLookupError> {
+            Ok((customer_id == "customer-1").then_some(self.0))
+        }
+    }
+
+    fn open_account_for(primary_holder_id: &str) -> BankAccountCommand {
+        BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+            account_id: "1234".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: primary_holder_id.to_string(),
+            product: AccountProduct::Checking,
+        })
+    }
+
+    #[test]
+    fn cannot_open_account_for_customer_without_kyc() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_customers(Box::new(FixedCustomerKycLookup(KycStatus::Pending)));
+        AccountTestFramework::with(services)
+            .given_no_previous_events()
+            .when(open_account_for("customer-1"))
+            .then_expect_error(BankAccountError::KycNotPassed);
+    }
+
+    #[test]
+    fn cannot_open_account_for_unknown_customer() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_customers(Box::new(FixedCustomerKycLookup(KycStatus::Verified)));
+        AccountTestFramework::with(services)
+            .given_no_previous_events()
+            .when(open_account_for("customer-2"))
+            .then_expect_error(BankAccountError::UnknownCustomer);
+    }
+
+    #[test]
+    fn open_account_for_verified_customer() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_customers(Box::new(FixedCustomerKycLookup(KycStatus::Verified)));
+        AccountTestFramework::with(services)
+            .given_no_previous_events()
+            .when(open_account_for("customer-1"))
+            .then_expect_events(vec![opened_now(opened_account())]);
+    }
+
+    // Accounts opened by a command are opened at the time the services report.
+    fn opened_now(event: BankAccountEvent) -> BankAccountEvent {
+        match event {
+            BankAccountEvent::AccountOpened {
+                account_id,
+                currency,
+                primary_holder_id,
+                product,
+                ..
+            } => BankAccountEvent::AccountOpened {
+                account_id,
+                currency,
+                primary_holder_id,
+                product,
+                occurred_at: now(),
+            },
+            event => event,
+        }
+    }
+
+    fn opened_account() -> BankAccountEvent {
+        BankAccountEvent::AccountOpened {
+            account_id: "1234".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: Some("customer-1".to_string()),
+            product: AccountProduct::Checking,
+            occurred_at: DateTime::default(),
+        }
+    }
+
+    fn account_frozen() -> BankAccountEvent {
+        BankAccountEvent::AccountFrozen {
+            reason: "suspected fraud".to_string(),
+        }
+    }
+
+    #[test]
+    fn freeze_account() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::FreezeAccount(
+                BankAccountFreezeAccountCommandData {
+                    reason: "suspected fraud".to_string(),
+                },
+            ))
+            .then_expect_events(vec![account_frozen()]);
+    }
+
+    #[test]
+    fn cannot_freeze_account_that_is_not_open() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given_no_previous_events()
+            .when(BankAccountCommand::FreezeAccount(
+                BankAccountFreezeAccountCommandData {
+                    reason: "suspected fraud".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::AccountNotOpen);
+    }
+
+    #[test]
+    fn cannot_freeze_account_that_is_already_frozen() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), account_frozen()])
+            .when(BankAccountCommand::FreezeAccount(
+                BankAccountFreezeAccountCommandData {
+                    reason: "suspected fraud".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::AccountFrozen);
+    }
+
+    #[test]
+    fn frozen_account_accepts_deposits() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), account_frozen()])
+            .when(BankAccountCommand::DepositMoney(
+                BankAccountDepositMoneyCommandData { amount: eur(20000) },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerDepositedMoney {
+                amount: eur(20000),
+                balance: eur(20000),
+                conversion: None,
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn frozen_account_rejects_withdrawals() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+                account_frozen(),
+            ])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(10000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::AccountFrozen);
+    }
+
+    #[test]
+    fn frozen_account_rejects_checks() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+                account_frozen(),
+            ])
+            .when(BankAccountCommand::WriteCheck(
+                BankAccountWriteCheckCommandData {
+                    check_number: "1170".to_string(),
+                    amount: eur(10000),
+                },
+            ))
+            .then_expect_error(BankAccountError::AccountFrozen);
+    }
+
+    #[test]
+    fn unfreeze_account() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), account_frozen()])
+            .when(BankAccountCommand::UnfreezeAccount(
+                BankAccountUnfreezeAccountCommandData {
+                    reason: "investigation complete".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::AccountUnfrozen {
+                reason: "investigation complete".to_string(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_unfreeze_account_that_is_not_frozen() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::UnfreezeAccount(
+                BankAccountUnfreezeAccountCommandData {
+                    reason: "investigation complete".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::AccountNotFrozen);
+    }
+
+    #[test]
+    fn unfrozen_account_accepts_withdrawals() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+
+        AccountTestFramework::with(BankAccountServices::new(Box::new(services)))
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+                account_frozen(),
+                BankAccountEvent::AccountUnfrozen {
+                    reason: "investigation complete".to_string(),
+                },
+            ])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(10000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(10000),
+                balance: eur(10000),
+                atm_id: Some("ATM34f1ba3c".to_string()),
+                conversion: None,
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn close_account_with_zero_balance() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::CloseAccount(
+                BankAccountCloseAccountCommandData {
+                    reason: "customer request".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::AccountClosed {
+                reason: "customer request".to_string(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_close_account_with_non_zero_balance() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::CloseAccount(
+                BankAccountCloseAccountCommandData {
+                    reason: "customer request".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::CannotCloseAccountWithNonZeroBalance);
+    }
+
+    #[test]
+    fn closed_account_rejects_deposits() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::AccountClosed {
+                    reason: "customer request".to_string(),
+                },
+            ])
+            .when(BankAccountCommand::DepositMoney(
+                BankAccountDepositMoneyCommandData { amount: eur(20000) },
+            ))
+            .then_expect_error(BankAccountError::AccountClosed);
+    }
+
+    #[test]
+    fn cannot_reopen_closed_account() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::AccountClosed {
+                    reason: "customer request".to_string(),
+                },
+            ])
+            .when(BankAccountCommand::OpenAccount(
+                BankAccountOpenAccountCommandData {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: "customer-1".to_string(),
+                    product: AccountProduct::Checking,
+                },
+            ))
+            .then_expect_error(BankAccountError::AccountAlreadyOpen);
+    }
+
+    fn overdraft_limit_set(minor_units: i64) -> BankAccountEvent {
+        BankAccountEvent::OverdraftLimitSet {
+            limit: eur(minor_units),
+        }
+    }
+
+    fn services_with_overdraft_fee(
+        services: MockBankAccountServices,
+        flat_fee: i64,
+    ) -> BankAccountServices {
+        BankAccountServices::new(Box::new(services)).with_configuration(BankAccountConfiguration {
+            overdraft_fees: OverdraftFeeSchedule {
+                flat_fee,
+                basis_points: 0,
+            },
+            ..BankAccountConfiguration::default()
+        })
+    }
+
+    #[test]
+    fn set_overdraft_limit() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::SetOverdraftLimit(
+                BankAccountSetOverdraftLimitCommandData { limit: eur(50000) },
+            ))
+            .then_expect_events(vec![overdraft_limit_set(50000)]);
+    }
+
+    #[test]
+    fn cannot_set_negative_overdraft_limit() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::SetOverdraftLimit(
+                BankAccountSetOverdraftLimitCommandData { limit: eur(-1) },
+            ))
+            .then_expect_error(BankAccountError::CannotSetNegativeOverdraftLimit);
+    }
+
+    #[test]
+    fn cannot_set_overdraft_limit_in_a_different_currency() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::SetOverdraftLimit(
+                BankAccountSetOverdraftLimitCommandData {
+                    limit: Money::new(50000, Currency::USD),
+                },
+            ))
+            .then_expect_error(BankAccountError::Money(MoneyError::CurrencyMismatch {
+                expected: "EUR".to_string(),
+                found: "USD".to_string(),
+            }));
+    }
+
+    #[test]
+    fn withdrawal_into_overdraft_charges_overdraft_fee() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+
+        AccountTestFramework::with(services_with_overdraft_fee(services, 2500))
+            .given(vec![opened_account(), overdraft_limit_set(50000)])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(10000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_events(vec![
+                BankAccountEvent::CustomerWithdrewCash {
+                    amount: eur(10000),
+                    balance: eur(-10000),
+                    atm_id: Some("ATM34f1ba3c".to_string()),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+                BankAccountEvent::OverdraftFeeCharged {
+                    fee: eur(2500),
+                    balance: eur(-12500),
+                    occurred_at: now(),
+                },
+            ]);
+    }
+
+    #[test]
+    fn withdrawal_while_already_overdrawn_does_not_charge_another_fee() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+
+        AccountTestFramework::with(services_with_overdraft_fee(services, 2500))
+            .given(vec![
+                opened_account(),
+                overdraft_limit_set(50000),
+                BankAccountEvent::CustomerWithdrewCash {
+                    amount: eur(10000),
+                    balance: eur(-10000),
+                    atm_id: Some("ATM34f1ba3c".to_string()),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(10000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(10000),
+                balance: eur(-20000),
+                atm_id: Some("ATM34f1ba3c".to_string()),
+                conversion: None,
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_withdraw_beyond_overdraft_limit() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+
+        AccountTestFramework::with(services_with_overdraft_fee(services, 2500))
+            .given(vec![opened_account(), overdraft_limit_set(50000)])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(50001),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::InsufficientFunds);
+    }
+
+    #[test]
+    fn cannot_withdraw_when_the_overdraft_fee_would_exceed_the_limit() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+
+        AccountTestFramework::with(services_with_overdraft_fee(services, 2500))
+            .given(vec![opened_account(), overdraft_limit_set(50000)])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(47501),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::InsufficientFunds);
+    }
+
+    #[test]
+    fn check_into_overdraft_charges_overdraft_fee() {
+        let services = MockBankAccountServices::default();
+        services.set_validate_check_response(Ok(()));
+
+        AccountTestFramework::with(services_with_overdraft_fee(services, 1000))
+            .given(vec![
+                opened_account(),
+                overdraft_limit_set(50000),
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(5000),
+                    balance: eur(5000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::WriteCheck(
+                BankAccountWriteCheckCommandData {
+                    check_number: "1170".to_string(),
+                    amount: eur(8000),
+                },
+            ))
+            .then_expect_events(vec![
+                BankAccountEvent::CustomerWroteCheck {
+                    check_number: "1170".to_string(),
+                    amount: eur(8000),
+                    balance: eur(-3000),
+                    occurred_at: now(),
+                },
+                BankAccountEvent::OverdraftFeeCharged {
+                    fee: eur(1000),
+                    balance: eur(-4000),
+                    occurred_at: now(),
+                },
+            ]);
+    }
+
+    fn deposited_on_march_1st(minor_units: i64) -> BankAccountEvent {
+        BankAccountEvent::CustomerDepositedMoney {
+            amount: eur(minor_units),
+            balance: eur(minor_units),
+            conversion: None,
+            occurred_at: "2023-03-01T09:00:00Z".parse().unwrap(),
+        }
+    }
+
+    fn accrue_interest(
+        annual_rate_basis_points: u32,
+        period_start: NaiveDate,
+        period_end: NaiveDate,
+    ) -> BankAccountCommand {
+        BankAccountCommand::AccrueInterest(BankAccountAccrueInterestCommandData {
+            annual_rate_basis_points,
+            period_start,
+            period_end,
+        })
+    }
+
+    #[test]
+    fn accrue_interest_posts_daily_interest() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_savings_account(),
+                deposited_on_march_1st(365_000),
+            ])
+            .when(accrue_interest(1000, day(1), day(11)))
+            .then_expect_events(vec![
+                BankAccountEvent::InterestAccrued {
+                    period_start: day(1),
+                    period_end: day(11),
+                    annual_rate_basis_points: 1000,
+                    amount: eur(1000),
+                    unposted_micro_units: 0,
+                },
+                BankAccountEvent::InterestPosted {
+                    amount: eur(1000),
+                    balance: eur(366_000),
+                    occurred_at: now(),
+                },
+            ]);
+    }
+
+    #[test]
+    fn accrue_interest_uses_the_balance_at_the_end_of_each_day() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_savings_account(),
+                deposited_on_march_1st(365_000),
+                BankAccountEvent::CustomerWithdrewCash {
+                    amount: eur(182_500),
+                    balance: eur(182_500),
+                    atm_id: Some("ATM34f1ba3c".to_string()),
+                    conversion: None,
+                    occurred_at: "2023-03-06T17:30:00Z".parse().unwrap(),
+                },
+            ])
+            .when(accrue_interest(1000, day(1), day(11)))
+            .then_expect_events(vec![
+                BankAccountEvent::InterestAccrued {
+                    period_start: day(1),
+                    period_end: day(11),
+                    annual_rate_basis_points: 1000,
+                    amount: eur(750),
+                    unposted_micro_units: 0,
+                },
+                BankAccountEvent::InterestPosted {
+                    amount: eur(750),
+                    balance: eur(183_250),
+                    occurred_at: now(),
+                },
+            ]);
+    }
+
+    #[test]
+    fn accrue_interest_skips_days_already_accrued() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_savings_account(),
+                deposited_on_march_1st(365_000),
+                BankAccountEvent::InterestAccrued {
+                    period_start: day(1),
+                    period_end: day(6),
+                    annual_rate_basis_points: 1000,
+                    amount: eur(0),
+                    unposted_micro_units: 0,
+                },
+            ])
+            .when(accrue_interest(1000, day(1), day(11)))
+            .then_expect_events(vec![
+                BankAccountEvent::InterestAccrued {
+                    period_start: day(6),
+                    period_end: day(11),
+                    annual_rate_basis_points: 1000,
+                    amount: eur(500),
+                    unposted_micro_units: 0,
+                },
+                BankAccountEvent::InterestPosted {
+                    amount: eur(500),
+                    balance: eur(365_500),
+                    occurred_at: now(),
+                },
+            ]);
+    }
+
+    #[test]
+    fn interest_too_small_to_post_is_carried_forward() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_savings_account(), deposited_on_march_1st(100)])
+            .when(accrue_interest(100, day(1), day(2)))
+            .then_expect_events(vec![BankAccountEvent::InterestAccrued {
+                period_start: day(1),
+                period_end: day(2),
+                annual_rate_basis_points: 100,
+                amount: eur(0),
+                unposted_micro_units: 2_739,
+            }]);
+    }
+
+    #[test]
+    fn cannot_accrue_interest_twice_for_the_same_period() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_savings_account(),
+                BankAccountEvent::InterestAccrued {
+                    period_start: day(1),
+                    period_end: day(11),
+                    annual_rate_basis_points: 1000,
+                    amount: eur(0),
+                    unposted_micro_units: 0,
+                },
+            ])
+            .when(accrue_interest(1000, day(1), day(11)))
+            .then_expect_error(BankAccountError::InterestAlreadyAccrued);
+    }
+
+    #[test]
+    fn products_without_an_interest_rate_earn_no_interest() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), deposited_on_march_1st(365_000)])
+            .when(accrue_interest(1000, day(1), day(11)))
+            .then_expect_error(BankAccountError::InterestNotEarned);
+    }
+
+    #[test]
+    fn cannot_accrue_interest_for_days_that_have_not_ended() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_savings_account()])
+            .when(accrue_interest(1000, day(14), day(16)))
+            .then_expect_error(BankAccountError::InvalidInterestPeriod);
+    }
+
+    fn transfer_debited(transfer_id: &str, amount: i64, balance: i64) -> BankAccountEvent {
+        BankAccountEvent::TransferDebited {
+            transfer_id: transfer_id.to_string(),
+            destination_account_id: "5678".to_string(),
+            amount: eur(amount),
+            balance: eur(balance),
+            conversion: None,
+            occurred_at: now(),
+        }
+    }
+
+    #[test]
+    fn transfer_out() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), deposited_on_march_1st(20000)])
+            .when(BankAccountCommand::TransferOut(
+                BankAccountTransferOutCommandData {
+                    transfer_id: "t-1".to_string(),
+                    destination_account_id: "5678".to_string(),
+                    amount: eur(5000),
+                },
+            ))
+            .then_expect_events(vec![transfer_debited("t-1", 5000, 15000)]);
+    }
+
+    #[test]
+    fn cannot_transfer_out_more_than_available() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), deposited_on_march_1st(2000)])
+            .when(BankAccountCommand::TransferOut(
+                BankAccountTransferOutCommandData {
+                    transfer_id: "t-1".to_string(),
+                    destination_account_id: "5678".to_string(),
+                    amount: eur(5000),
+                },
+            ))
+            .then_expect_error(BankAccountError::InsufficientFunds);
+    }
+
+    #[test]
+    fn cannot_debit_the_same_transfer_twice() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                transfer_debited("t-1", 5000, 15000),
+            ])
+            .when(BankAccountCommand::TransferOut(
+                BankAccountTransferOutCommandData {
+                    transfer_id: "t-1".to_string(),
+                    destination_account_id: "5678".to_string(),
+                    amount: eur(5000),
+                },
+            ))
+            .then_expect_error(BankAccountError::DuplicateTransfer);
+    }
+
+    #[test]
+    fn cannot_credit_the_same_transfer_twice() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::TransferCredited {
+                    transfer_id: "t-1".to_string(),
+                    source_account_id: "9012".to_string(),
+                    amount: eur(5000),
+                    balance: eur(5000),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::TransferIn(
+                BankAccountTransferInCommandData {
+                    transfer_id: "t-1".to_string(),
+                    source_account_id: "9012".to_string(),
+                    amount: eur(5000),
+                },
+            ))
+            .then_expect_error(BankAccountError::DuplicateTransfer);
+    }
+
+    #[test]
+    fn transfer_in() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::TransferIn(
+                BankAccountTransferInCommandData {
+                    transfer_id: "t-1".to_string(),
+                    source_account_id: "9012".to_string(),
+                    amount: eur(5000),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::TransferCredited {
+                transfer_id: "t-1".to_string(),
+                source_account_id: "9012".to_string(),
+                amount: eur(5000),
+                balance: eur(5000),
+                conversion: None,
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_transfer_in_to_a_closed_account() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::AccountClosed {
+                    reason: "customer request".to_string(),
+                },
+            ])
+            .when(BankAccountCommand::TransferIn(
+                BankAccountTransferInCommandData {
+                    transfer_id: "t-1".to_string(),
+                    source_account_id: "9012".to_string(),
+                    amount: eur(5000),
+                },
+            ))
+            .then_expect_error(BankAccountError::AccountClosed);
+    }
+
+    #[test]
+    fn refund_transfer_returns_the_debited_amount() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                transfer_debited("t-1", 5000, 15000),
+                account_frozen(),
+            ])
+            .when(BankAccountCommand::RefundTransfer(
+                BankAccountRefundTransferCommandData {
+                    transfer_id: "t-1".to_string(),
+                    reason: "account closed".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::TransferRefunded {
+                transfer_id: "t-1".to_string(),
+                amount: eur(5000),
+                balance: eur(20000),
+                reason: "account closed".to_string(),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_refund_a_transfer_twice() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                transfer_debited("t-1", 5000, 15000),
+                BankAccountEvent::TransferRefunded {
+                    transfer_id: "t-1".to_string(),
+                    amount: eur(5000),
+                    balance: eur(20000),
+                    reason: "account closed".to_string(),
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::RefundTransfer(
+                BankAccountRefundTransferCommandData {
+                    transfer_id: "t-1".to_string(),
+                    reason: "account closed".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::UnknownTransfer);
+    }
+
+    #[test]
+    fn complete_transfer() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                transfer_debited("t-1", 5000, 15000),
+            ])
+            .when(BankAccountCommand::CompleteTransfer(
+                BankAccountCompleteTransferCommandData {
+                    transfer_id: "t-1".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::TransferCompleted {
+                transfer_id: "t-1".to_string(),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_refund_a_completed_transfer() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                transfer_debited("t-1", 5000, 15000),
+                BankAccountEvent::TransferCompleted {
+                    transfer_id: "t-1".to_string(),
+                    occurred_at: now(),
+                },
+            ])
+            .when(BankAccountCommand::RefundTransfer(
+                BankAccountRefundTransferCommandData {
+                    transfer_id: "t-1".to_string(),
+                    reason: "account closed".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::UnknownTransfer);
+    }
+
+    #[test]
+    fn cannot_complete_an_unknown_transfer() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::CompleteTransfer(
+                BankAccountCompleteTransferCommandData {
+                    transfer_id: "t-1".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::UnknownTransfer);
+    }
+
+    fn hold_placed(hold_id: &str, amount: i64) -> BankAccountEvent {
+        BankAccountEvent::HoldPlaced {
+            hold_id: hold_id.to_string(),
+            amount: eur(amount),
+            conversion: None,
+            expires_at: HoldPolicy::default().expires_at(now()),
+            occurred_at: now(),
+        }
+    }
+
+    fn hold_placed_yesterday(hold_id: &str, amount: i64) -> BankAccountEvent {
+        BankAccountEvent::HoldPlaced {
+            hold_id: hold_id.to_string(),
+            amount: eur(amount),
+            conversion: None,
+            expires_at: now() - chrono::Duration::hours(1),
+            occurred_at: now() - chrono::Duration::days(1),
+        }
+    }
+
+    fn place_hold(hold_id: &str, amount: i64) -> BankAccountCommand {
+        BankAccountCommand::PlaceHold(BankAccountPlaceHoldCommandData {
+            hold_id: hold_id.to_string(),
+            amount: eur(amount),
+        })
+    }
+
+    fn capture_hold(hold_id: &str, amount: Option<i64>) -> BankAccountCommand {
+        BankAccountCommand::CaptureHold(BankAccountCaptureHoldCommandData {
+            hold_id: hold_id.to_string(),
+            amount: amount.map(eur),
+        })
+    }
+
+    #[test]
+    fn places_a_hold() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), deposited_on_march_1st(20000)])
+            .when(place_hold("h-1", 5000))
+            .then_expect_events(vec![hold_placed("h-1", 5000)]);
+    }
+
+    #[test]
+    fn hold_expiry_uses_the_configured_ttl() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_configuration(BankAccountConfiguration {
+                holds: HoldPolicy { ttl_seconds: 60 },
+                ..BankAccountConfiguration::default()
+            });
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), deposited_on_march_1st(20000)])
+            .when(place_hold("h-1", 5000))
+            .then_expect_events(vec![BankAccountEvent::HoldPlaced {
+                hold_id: "h-1".to_string(),
+                amount: eur(5000),
+                conversion: None,
+                expires_at: now() + chrono::Duration::seconds(60),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_place_hold_for_more_than_available() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed("h-1", 15000),
+            ])
+            .when(place_hold("h-2", 10000))
+            .then_expect_error(BankAccountError::InsufficientFunds);
+    }
+
+    #[test]
+    fn cannot_place_the_same_hold_twice() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed("h-1", 5000),
+            ])
+            .when(place_hold("h-1", 5000))
+            .then_expect_error(BankAccountError::DuplicateHold);
+    }
+
+    #[test]
+    fn active_holds_reduce_the_funds_available_for_withdrawal() {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+        AccountTestFramework::with(BankAccountServices::new(Box::new(services)))
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed("h-1", 15000),
+            ])
+            .when(BankAccountCommand::WithdrawMoney(
+                BankAccountWithdrawMoneyCommandData {
+                    amount: eur(10000),
+                    atm_id: "ATM34f1ba3c".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::InsufficientFunds);
+    }
+
+    #[test]
+    fn expired_holds_do_not_reserve_funds() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed_yesterday("h-1", 15000),
+            ])
+            .when(place_hold("h-2", 10000))
+            .then_expect_events(vec![
+                BankAccountEvent::HoldExpired {
+                    hold_id: "h-1".to_string(),
+                    amount: eur(15000),
+                },
+                hold_placed("h-2", 10000),
+            ]);
+    }
+
+    #[test]
+    fn capture_hold_debits_the_amount_held() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed("h-1", 5000),
+            ])
+            .when(capture_hold("h-1", None))
+            .then_expect_events(vec![BankAccountEvent::HoldCaptured {
+                hold_id: "h-1".to_string(),
+                amount: eur(5000),
+                balance: eur(15000),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn capture_hold_for_less_than_the_amount_held() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed("h-1", 5000),
+            ])
+            .when(capture_hold("h-1", Some(4250)))
+            .then_expect_events(vec![BankAccountEvent::HoldCaptured {
+                hold_id: "h-1".to_string(),
+                amount: eur(4250),
+                balance: eur(15750),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_capture_more_than_the_amount_held() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed("h-1", 5000),
+            ])
+            .when(capture_hold("h-1", Some(5001)))
+            .then_expect_error(BankAccountError::CaptureExceedsHold);
+    }
+
+    #[test]
+    fn cannot_capture_an_expired_hold() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed_yesterday("h-1", 5000),
+            ])
+            .when(capture_hold("h-1", None))
+            .then_expect_error(BankAccountError::HoldExpired);
+    }
+
+    #[test]
+    fn release_hold() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                hold_placed("h-1", 5000),
+            ])
+            .when(BankAccountCommand::ReleaseHold(
+                BankAccountReleaseHoldCommandData {
+                    hold_id: "h-1".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::HoldReleased {
+                hold_id: "h-1".to_string(),
+                amount: eur(5000),
+            }]);
+    }
+
+    #[test]
+    fn cannot_release_an_unknown_hold() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::ReleaseHold(
+                BankAccountReleaseHoldCommandData {
+                    hold_id: "h-1".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::UnknownHold);
+    }
+
+    fn check_written(check_number: &str, amount: i64, balance: i64) -> BankAccountEvent {
+        BankAccountEvent::CustomerWroteCheck {
+            check_number: check_number.to_string(),
+            amount: eur(amount),
+            balance: eur(balance),
+            occurred_at: now(),
+        }
+    }
+
+    fn bounce_check(check_number: &str) -> BankAccountCommand {
+        BankAccountCommand::BounceCheck(BankAccountBounceCheckCommandData {
+            check_number: check_number.to_string(),
+            reason: "insufficient funds at drawer".to_string(),
+        })
+    }
+
+    #[test]
+    fn cannot_write_the_same_check_number_twice() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                check_written("1170", 5000, 15000),
+            ])
+            .when(BankAccountCommand::WriteCheck(
+                BankAccountWriteCheckCommandData {
+                    check_number: "1170".to_string(),
+                    amount: eur(5000),
+                },
+            ))
+            .then_expect_error(BankAccountError::InvalidCheckNumber);
+    }
+
+    #[test]
+    fn clear_check() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                check_written("1170", 5000, 15000),
+            ])
+            .when(BankAccountCommand::ClearCheck(
+                BankAccountClearCheckCommandData {
+                    check_number: "1170".to_string(),
+                },
+            ))
+            .then_expect_events(vec![BankAccountEvent::CheckCleared {
+                check_number: "1170".to_string(),
+                occurred_at: now(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_clear_an_unknown_check() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::ClearCheck(
+                BankAccountClearCheckCommandData {
+                    check_number: "1170".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::UnknownCheck);
+    }
+
+    #[test]
+    fn bounced_check_is_credited_back_and_charged_a_returned_item_fee() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_configuration(BankAccountConfiguration {
+                checks: CheckClearingPolicy {
+                    returned_item_fee: FeeAmount::Flat(3500),
+                },
+                ..BankAccountConfiguration::default()
+            });
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                check_written("1170", 5000, 15000),
+            ])
+            .when(bounce_check("1170"))
+            .then_expect_events(vec![
+                BankAccountEvent::CheckBounced {
+                    check_number: "1170".to_string(),
+                    amount: eur(5000),
+                    balance: eur(20000),
+                    reason: "insufficient funds at drawer".to_string(),
+                    occurred_at: now(),
+                },
+                BankAccountEvent::ReturnedItemFeeCharged {
+                    check_number: "1170".to_string(),
+                    fee: eur(3500),
+                    balance: eur(16500),
+                    occurred_at: now(),
+                },
+            ]);
+    }
+
+    #[test]
+    fn cannot_bounce_a_cleared_check() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                check_written("1170", 5000, 15000),
+                BankAccountEvent::CheckCleared {
+                    check_number: "1170".to_string(),
+                    occurred_at: now(),
+                },
+            ])
+            .when(bounce_check("1170"))
+            .then_expect_error(BankAccountError::CheckAlreadySettled);
+    }
+
+    fn cash_withdrawn(atm_id: &str, amount: i64, occurred_at: DateTime<Utc>) -> BankAccountEvent {
+        BankAccountEvent::CustomerWithdrewCash {
+            amount: eur(amount),
+            // Large enough for the overdraft limit not to get in the way.
+            balance: eur(50000),
+            atm_id: Some(atm_id.to_string()),
+            conversion: None,
+            occurred_at,
+        }
+    }
+
+    fn services_with_withdrawal_limits(
+        daily_limit: Option<i64>,
+        daily_limit_per_atm: Option<i64>,
+    ) -> BankAccountServices {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+        BankAccountServices::new(Box::new(services)).with_configuration(BankAccountConfiguration {
+            withdrawal_limits: WithdrawalLimits {
+                daily_limit,
+                daily_limit_per_atm,
+            },
+            ..BankAccountConfiguration::default()
+        })
+    }
+
+    fn withdraw_at(atm_id: &str, amount: i64) -> BankAccountCommand {
+        BankAccountCommand::WithdrawMoney(BankAccountWithdrawMoneyCommandData {
+            amount: eur(amount),
+            atm_id: atm_id.to_string(),
+        })
+    }
+
+    #[test]
+    fn cannot_withdraw_beyond_the_daily_limit() {
+        AccountTestFramework::with(services_with_withdrawal_limits(Some(50000), None))
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(100000),
+                cash_withdrawn("ATM-1", 30000, now() - chrono::Duration::hours(2)),
+            ])
+            .when(withdraw_at("ATM-2", 25000))
+            .then_expect_error(BankAccountError::WithdrawalLimitExceeded {
+                atm_id: None,
+                remaining: eur(20000),
+            });
+    }
+
+    #[test]
+    fn withdrawals_older_than_a_day_do_not_count_towards_the_limit() {
+        AccountTestFramework::with(services_with_withdrawal_limits(Some(50000), None))
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(100000),
+                cash_withdrawn("ATM-1", 30000, now() - chrono::Duration::hours(25)),
+            ])
+            .when(withdraw_at("ATM-1", 25000))
+            .then_expect_events(vec![BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(25000),
+                balance: eur(25000),
+                atm_id: Some("ATM-1".to_string()),
+                conversion: None,
+                occurred_at: now(),
+            }]);
     }
 
     #[test]
-    fn cannot_deposit_money_into_account_that_does_not_exist() {
+    fn cannot_withdraw_beyond_the_daily_limit_at_a_single_atm() {
+        AccountTestFramework::with(services_with_withdrawal_limits(None, Some(20000)))
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(100000),
+                cash_withdrawn("ATM-1", 15000, now() - chrono::Duration::hours(1)),
+                cash_withdrawn("ATM-2", 15000, now() - chrono::Duration::hours(1)),
+            ])
+            .when(withdraw_at("ATM-1", 10000))
+            .then_expect_error(BankAccountError::WithdrawalLimitExceeded {
+                atm_id: Some("ATM-1".to_string()),
+                remaining: eur(5000),
+            });
+    }
+
+    #[test]
+    fn cannot_open_account_without_a_primary_holder() {
         let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
         AccountTestFramework::with(services)
             .given_no_previous_events()
-            .when(BankAccountCommand::DepositMoney(
-                BankAccountDepositMoneyCommandData { amount: 200.0 },
+            .when(BankAccountCommand::OpenAccount(
+                BankAccountOpenAccountCommandData {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: "".to_string(),
+                    product: AccountProduct::Checking,
+                },
             ))
-            .then_expect_error(BankAccountError::AccountNotOpen);
+            .then_expect_error(BankAccountError::InvalidHolderId);
     }
 
     #[test]
-    fn can_deposit_money_with_balance() {
-        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
-
+    fn add_holder() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_customers(Box::new(HappyPathCustomerKycLookup));
         AccountTestFramework::with(services)
-            .given(vec![
-                BankAccountEvent::AccountOpened {
-                    account_id: "1234".to_string(),
-                },
-                BankAccountEvent::CustomerDepositedMoney {
-                    amount: 200.0,
-                    balance: 200.0,
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::AddHolder(
+                BankAccountAddHolderCommandData {
+                    holder_id: "customer-2".to_string(),
                 },
-            ])
-            .when(BankAccountCommand::DepositMoney(
-                BankAccountDepositMoneyCommandData { amount: 200.0 },
             ))
-            .then_expect_events(vec![BankAccountEvent::CustomerDepositedMoney {
-                amount: 200.0,
-                balance: 400.0,
+            .then_expect_events(vec![BankAccountEvent::HolderAdded {
+                holder_id: "customer-2".to_string(),
             }]);
     }
 
     #[test]
-    fn can_withdraw_money_when_atm_withdrawal_response_is_ok() {
-        let services = MockBankAccountServices::default();
-        services.set_atm_withdrawal_response(Ok(()));
+    fn cannot_add_unknown_customer_as_holder() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_customers(Box::new(FixedCustomerKycLookup(KycStatus::Verified)));
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::AddHolder(
+                BankAccountAddHolderCommandData {
+                    holder_id: "customer-2".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::UnknownCustomer);
+    }
 
-        AccountTestFramework::with(BankAccountServices::new(Box::new(services)))
-            .given(vec![
-                BankAccountEvent::AccountOpened {
-                    account_id: "1234".to_string(),
+    #[test]
+    fn cannot_add_the_primary_holder_again() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::AddHolder(
+                BankAccountAddHolderCommandData {
+                    holder_id: "customer-1".to_string(),
                 },
-                BankAccountEvent::CustomerDepositedMoney {
-                    amount: 200.0,
-                    balance: 200.0,
+            ))
+            .then_expect_error(BankAccountError::DuplicateHolder);
+    }
+
+    #[test]
+    fn remove_holder() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                BankAccountEvent::HolderAdded {
+                    holder_id: "customer-2".to_string(),
                 },
             ])
-            .when(BankAccountCommand::WithdrawMoney(
-                BankAccountWithdrawMoneyCommandData {
-                    amount: 100.0,
-                    atm_id: "ATM34f1ba3c".to_string(),
+            .when(BankAccountCommand::RemoveHolder(
+                BankAccountRemoveHolderCommandData {
+                    holder_id: "customer-2".to_string(),
                 },
             ))
-            .then_expect_events(vec![BankAccountEvent::CustomerWithdrewCash {
-                amount: 100.0,
-                balance: 100.0,
+            .then_expect_events(vec![BankAccountEvent::HolderRemoved {
+                holder_id: "customer-2".to_string(),
             }]);
     }
 
     #[test]
-    fn cannot_withdraw_money_when_there_is_an_atm_error() {
-        let services = MockBankAccountServices::default();
-        services.set_atm_withdrawal_response(Err(AtmError));
-        let services = BankAccountServices::new(Box::new(services));
-
+    fn cannot_remove_the_primary_holder() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
         AccountTestFramework::with(services)
-            .given(vec![
-                BankAccountEvent::AccountOpened {
-                    account_id: "1234".to_string(),
-                },
-                BankAccountEvent::CustomerDepositedMoney {
-                    amount: 200.0,
-                    balance: 200.0,
-                },
-            ])
-            .when(BankAccountCommand::WithdrawMoney(
-                BankAccountWithdrawMoneyCommandData {
-                    amount: 100.0,
-                    atm_id: "ATM34f1ba3c".to_string(),
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::RemoveHolder(
+                BankAccountRemoveHolderCommandData {
+                    holder_id: "customer-1".to_string(),
                 },
             ))
-            .then_expect_error_message(BankAccountError::AtmRuleViolation.to_string().as_str());
+            .then_expect_error(BankAccountError::CannotRemovePrimaryHolder);
     }
 
     #[test]
-    fn withdraw_money_funds_not_available_returns_insufficient_funds_error() {
+    fn cannot_remove_an_unknown_holder() {
         let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
         AccountTestFramework::with(services)
-            .given(vec![BankAccountEvent::AccountOpened {
-                account_id: "1234".to_string(),
-            }])
-            .when(BankAccountCommand::WithdrawMoney(
-                BankAccountWithdrawMoneyCommandData {
-                    amount: 200.0,
-                    atm_id: "ATM34f1ba3c".to_string(),
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::RemoveHolder(
+                BankAccountRemoveHolderCommandData {
+                    holder_id: "customer-2".to_string(),
                 },
             ))
-            // Here we expect an error rather than any events
-            .then_expect_error_message(BankAccountError::InsufficientFunds.to_string().as_str());
+            .then_expect_error(BankAccountError::UnknownHolder);
+    }
+
+    fn reverse_transaction(sequence: usize) -> BankAccountCommand {
+        BankAccountCommand::ReverseTransaction(BankAccountReverseTransactionCommandData {
+            sequence,
+            reason: "recorded in error".to_string(),
+        })
+    }
+
+    fn transaction_reversed(
+        reversed_sequence: usize,
+        amount: i64,
+        balance: i64,
+    ) -> BankAccountEvent {
+        BankAccountEvent::TransactionReversed {
+            reversed_sequence,
+            amount: eur(amount),
+            balance: eur(balance),
+            reason: "recorded in error".to_string(),
+            occurred_at: now(),
+        }
     }
 
     #[test]
-    fn wrote_check() {
-        let services = MockBankAccountServices::default();
-        services.set_validate_check_response(Ok(()));
-        let services = BankAccountServices::new(Box::new(services));
+    fn reversing_a_deposit_debits_the_amount_deposited() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), deposited_on_march_1st(20000)])
+            .when(reverse_transaction(2))
+            .then_expect_events(vec![transaction_reversed(2, 20000, 0)]);
+    }
 
+    #[test]
+    fn reversing_a_withdrawal_credits_the_amount_withdrawn() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
         AccountTestFramework::with(services)
             .given(vec![
-                BankAccountEvent::AccountOpened {
-                    account_id: "1234".to_string(),
-                },
-                BankAccountEvent::CustomerDepositedMoney {
-                    amount: 200.0,
-                    balance: 200.0,
-                },
+                opened_account(),
+                deposited_on_march_1st(100000),
+                cash_withdrawn("ATM-1", 30000, now()),
+            ])
+            .when(reverse_transaction(3))
+            .then_expect_events(vec![transaction_reversed(3, 30000, 80000)]);
+    }
+
+    #[test]
+    fn reversals_are_accepted_on_frozen_accounts() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                account_frozen(),
+            ])
+            .when(reverse_transaction(2))
+            .then_expect_events(vec![transaction_reversed(2, 20000, 0)]);
+    }
+
+    #[test]
+    fn cannot_reverse_the_same_transaction_twice() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                transaction_reversed(2, 20000, 0),
+            ])
+            .when(reverse_transaction(2))
+            .then_expect_error(BankAccountError::TransactionAlreadyReversed);
+    }
+
+    #[test]
+    fn cannot_reverse_an_event_that_is_not_a_deposit_or_withdrawal() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                check_written("1170", 5000, 15000),
+            ])
+            .when(reverse_transaction(3))
+            .then_expect_error(BankAccountError::UnknownTransaction);
+    }
+
+    #[test]
+    fn cannot_reverse_a_reversal() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                transaction_reversed(2, 20000, 0),
             ])
+            .when(reverse_transaction(3))
+            .then_expect_error(BankAccountError::UnknownTransaction);
+    }
+
+    fn services_with_fee_rules(rules: Vec<FeeRule>) -> BankAccountServices {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+        services.set_validate_check_response(Ok(()));
+        BankAccountServices::new(Box::new(services)).with_configuration(BankAccountConfiguration {
+            fees: FeeEngine { rules },
+            ..BankAccountConfiguration::default()
+        })
+    }
+
+    fn charge_maintenance_fee(period: &str) -> BankAccountCommand {
+        BankAccountCommand::ChargeMaintenanceFee(BankAccountChargeMaintenanceFeeCommandData {
+            period: period.to_string(),
+        })
+    }
+
+    fn maintenance_fee_charged(period: &str, fee: i64, balance: i64) -> BankAccountEvent {
+        BankAccountEvent::FeeCharged {
+            reason: FeeReason::MonthlyMaintenance {
+                period: period.to_string(),
+            },
+            fee: eur(fee),
+            balance: eur(balance),
+            occurred_at: now(),
+        }
+    }
+
+    #[test]
+    fn atm_fees_are_charged_after_the_withdrawal() {
+        let rules = vec![
+            FeeRule::AtmWithdrawal {
+                atm_ids: vec![],
+                fee: FeeAmount::Flat(100),
+            },
+            FeeRule::AtmWithdrawal {
+                atm_ids: vec!["ATM-EXT-1".to_string()],
+                fee: FeeAmount::Flat(250),
+            },
+        ];
+        let atm_fee = |fee, balance| BankAccountEvent::FeeCharged {
+            reason: FeeReason::AtmWithdrawal {
+                atm_id: "ATM-EXT-1".to_string(),
+            },
+            fee: eur(fee),
+            balance: eur(balance),
+            occurred_at: now(),
+        };
+        AccountTestFramework::with(services_with_fee_rules(rules))
+            .given(vec![opened_account(), deposited_on_march_1st(20000)])
+            .when(withdraw_at("ATM-EXT-1", 5000))
+            .then_expect_events(vec![
+                BankAccountEvent::CustomerWithdrewCash {
+                    amount: eur(5000),
+                    balance: eur(15000),
+                    atm_id: Some("ATM-EXT-1".to_string()),
+                    conversion: None,
+                    occurred_at: now(),
+                },
+                atm_fee(100, 14900),
+                atm_fee(250, 14650),
+            ]);
+    }
+
+    #[test]
+    fn check_fees_are_charged_after_the_check() {
+        let rules = vec![FeeRule::Check {
+            fee: FeeAmount::Flat(50),
+        }];
+        AccountTestFramework::with(services_with_fee_rules(rules))
+            .given(vec![opened_account(), deposited_on_march_1st(20000)])
             .when(BankAccountCommand::WriteCheck(
                 BankAccountWriteCheckCommandData {
                     check_number: "1170".to_string(),
-                    amount: 100.0,
+                    amount: eur(5000),
                 },
             ))
-            .then_expect_events(vec![BankAccountEvent::CustomerWroteCheck {
-                check_number: "1170".to_string(),
-                amount: 100.0,
-                balance: 100.0,
+            .then_expect_events(vec![
+                check_written("1170", 5000, 15000),
+                BankAccountEvent::FeeCharged {
+                    reason: FeeReason::Check {
+                        check_number: "1170".to_string(),
+                    },
+                    fee: eur(50),
+                    balance: eur(14950),
+                    occurred_at: now(),
+                },
+            ]);
+    }
+
+    #[test]
+    fn charges_the_maintenance_fee() {
+        let rules = vec![FeeRule::MonthlyMaintenance {
+            fee: FeeAmount::Flat(500),
+        }];
+        AccountTestFramework::with(services_with_fee_rules(rules))
+            .given(vec![opened_account(), deposited_on_march_1st(20000)])
+            .when(charge_maintenance_fee("2023-02"))
+            .then_expect_events(vec![maintenance_fee_charged("2023-02", 500, 19500)]);
+    }
+
+    #[test]
+    fn cannot_charge_the_maintenance_fee_twice_for_a_month() {
+        let rules = vec![FeeRule::MonthlyMaintenance {
+            fee: FeeAmount::Flat(500),
+        }];
+        AccountTestFramework::with(services_with_fee_rules(rules))
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                maintenance_fee_charged("2023-02", 500, 19500),
+            ])
+            .when(charge_maintenance_fee("2023-02"))
+            .then_expect_error(BankAccountError::MaintenanceFeeAlreadyCharged);
+    }
+
+    #[test]
+    fn cannot_charge_the_maintenance_fee_for_a_future_month() {
+        let rules = vec![FeeRule::MonthlyMaintenance {
+            fee: FeeAmount::Flat(500),
+        }];
+        AccountTestFramework::with(services_with_fee_rules(rules))
+            .given(vec![opened_account()])
+            .when(charge_maintenance_fee("2023-04"))
+            .then_expect_error(BankAccountError::InvalidFeePeriod);
+    }
+
+    #[test]
+    fn no_maintenance_fee_is_charged_for_months_before_the_account_was_opened() {
+        let rules = vec![FeeRule::MonthlyMaintenance {
+            fee: FeeAmount::Flat(500),
+        }];
+        let opened_on_march_1st = BankAccountEvent::AccountOpened {
+            account_id: "1234".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: Some("customer-1".to_string()),
+            product: AccountProduct::Checking,
+            occurred_at: "2023-03-01T09:00:00Z".parse().unwrap(),
+        };
+        AccountTestFramework::with(services_with_fee_rules(rules.clone()))
+            .given(vec![opened_on_march_1st.clone()])
+            .when(charge_maintenance_fee("2023-02"))
+            .then_expect_error(BankAccountError::MaintenanceFeePeriodBeforeOpening);
+        AccountTestFramework::with(services_with_fee_rules(rules))
+            .given(vec![opened_on_march_1st, deposited_on_march_1st(20000)])
+            .when(charge_maintenance_fee("2023-03"))
+            .then_expect_events(vec![maintenance_fee_charged("2023-03", 500, 19500)]);
+    }
+
+    fn standing_order_created(day_of_month: u32, first_due_date: NaiveDate) -> BankAccountEvent {
+        BankAccountEvent::StandingOrderCreated {
+            standing_order_id: "so-1".to_string(),
+            amount: eur(5000),
+            day_of_month,
+            description: "rent".to_string(),
+            first_due_date,
+        }
+    }
+
+    fn execute_standing_order(due_date: NaiveDate) -> BankAccountCommand {
+        BankAccountCommand::ExecuteStandingOrder(BankAccountExecuteStandingOrderCommandData {
+            standing_order_id: "so-1".to_string(),
+            due_date,
+        })
+    }
+
+    #[test]
+    fn create_standing_order() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::CreateStandingOrder(
+                BankAccountCreateStandingOrderCommandData {
+                    standing_order_id: "so-1".to_string(),
+                    amount: eur(5000),
+                    day_of_month: 1,
+                    description: "rent".to_string(),
+                },
+            ))
+            .then_expect_events(vec![standing_order_created(
+                1,
+                NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+            )]);
+    }
+
+    #[test]
+    fn cannot_create_a_standing_order_for_a_day_no_month_has() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account()])
+            .when(BankAccountCommand::CreateStandingOrder(
+                BankAccountCreateStandingOrderCommandData {
+                    standing_order_id: "so-1".to_string(),
+                    amount: eur(5000),
+                    day_of_month: 32,
+                    description: "rent".to_string(),
+                },
+            ))
+            .then_expect_error(BankAccountError::InvalidDayOfMonth);
+    }
+
+    #[test]
+    fn executing_a_due_standing_order_debits_the_account() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                deposited_on_march_1st(20000),
+                standing_order_created(1, day(1)),
+            ])
+            .when(execute_standing_order(day(1)))
+            .then_expect_events(vec![BankAccountEvent::StandingOrderExecuted {
+                standing_order_id: "so-1".to_string(),
+                due_date: day(1),
+                amount: eur(5000),
+                balance: eur(15000),
+                next_due_date: NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+                occurred_at: now(),
             }]);
     }
 
     #[test]
-    fn wrote_check_bad_check() {
-        let services = MockBankAccountServices::default();
-        services.set_validate_check_response(Err(CheckingError));
-        let services = BankAccountServices::new(Box::new(services));
+    fn standing_order_payments_without_funds_are_recorded_as_failed() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), standing_order_created(1, day(1))])
+            .when(execute_standing_order(day(1)))
+            .then_expect_events(vec![BankAccountEvent::StandingOrderFailed {
+                standing_order_id: "so-1".to_string(),
+                due_date: day(1),
+                reason: "insufficient funds".to_string(),
+                next_due_date: NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+                occurred_at: now(),
+            }]);
+    }
 
+    #[test]
+    fn cannot_execute_a_standing_order_payment_twice() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
         AccountTestFramework::with(services)
             .given(vec![
-                BankAccountEvent::AccountOpened {
-                    account_id: "1234".to_string(),
+                opened_account(),
+                deposited_on_march_1st(20000),
+                standing_order_created(1, day(1)),
+                BankAccountEvent::StandingOrderExecuted {
+                    standing_order_id: "so-1".to_string(),
+                    due_date: day(1),
+                    amount: eur(5000),
+                    balance: eur(15000),
+                    next_due_date: NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+                    occurred_at: now(),
                 },
-                BankAccountEvent::CustomerDepositedMoney {
-                    amount: 200.0,
-                    balance: 200.0,
+            ])
+            .when(execute_standing_order(day(1)))
+            .then_expect_error(BankAccountError::StandingOrderNotDue);
+    }
+
+    #[test]
+    fn cannot_execute_a_standing_order_before_it_is_due() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![opened_account(), standing_order_created(20, day(20))])
+            .when(execute_standing_order(day(20)))
+            .then_expect_error(BankAccountError::StandingOrderNotDue);
+    }
+
+    #[test]
+    fn cancelled_standing_orders_are_not_executed() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
+        AccountTestFramework::with(services)
+            .given(vec![
+                opened_account(),
+                standing_order_created(1, day(1)),
+                BankAccountEvent::StandingOrderCancelled {
+                    standing_order_id: "so-1".to_string(),
                 },
             ])
-            .when(BankAccountCommand::WriteCheck(
-                BankAccountWriteCheckCommandData {
-                    check_number: "1170".to_string(),
-                    amount: 100.0,
+            .when(execute_standing_order(day(1)))
+            .then_expect_error(BankAccountError::UnknownStandingOrder);
+    }
+
+    fn opened_savings_account() -> BankAccountEvent {
+        BankAccountEvent::AccountOpened {
+            account_id: "1234".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: Some("customer-1".to_string()),
+            product: AccountProduct::Savings,
+            occurred_at: DateTime::default(),
+        }
+    }
+
+    fn services_with_savings_withdrawal_limit(limit: u32) -> BankAccountServices {
+        let services = MockBankAccountServices::default();
+        services.set_atm_withdrawal_response(Ok(()));
+        BankAccountServices::new(Box::new(services)).with_configuration(BankAccountConfiguration {
+            products: ProductCatalogue {
+                savings: ProductRules {
+                    monthly_withdrawal_limit: Some(limit),
+                    ..ProductCatalogue::default().savings
+                },
+                ..ProductCatalogue::default()
+            },
+            ..BankAccountConfiguration::default()
+        })
+    }
+
+    #[test]
+    fn open_savings_account() {
+        let services = BankAccountServices::new(Box::<MockBankAccountServices>::default())
+            .with_customers(Box::new(HappyPathCustomerKycLookup));
+        AccountTestFramework::with(services)
+            .given_no_previous_events()
+            .when(BankAccountCommand::OpenAccount(
+                BankAccountOpenAccountCommandData {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: "customer-1".to_string(),
+                    product: AccountProduct::Savings,
                 },
             ))
-            .then_expect_error_message(BankAccountError::InvalidCheck.to_string().as_str());
+            .then_expect_events(vec![opened_now(opened_savings_account())]);
     }
 
     #[test]
-    fn wrote_check_funds_not_available() {
+    fn cannot_write_checks_from_savings_accounts() {
         let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
         AccountTestFramework::with(services)
-            .given(vec![BankAccountEvent::AccountOpened {
-                account_id: "1234".to_string(),
-            }])
+            .given(vec![
+                opened_savings_account(),
+                deposited_on_march_1st(20000),
+            ])
             .when(BankAccountCommand::WriteCheck(
                 BankAccountWriteCheckCommandData {
                     check_number: "1170".to_string(),
-                    amount: 100.0,
+                    amount: eur(5000),
                 },
             ))
-            .then_expect_error_message(BankAccountError::InsufficientFunds.to_string().as_str())
+            .then_expect_error(BankAccountError::ChecksNotAllowed);
     }
 
     #[test]
-    fn open_account() {
+    fn cannot_set_an_overdraft_limit_on_savings_accounts() {
         let services = BankAccountServices::new(Box::<MockBankAccountServices>::default());
         AccountTestFramework::with(services)
-            .given_no_previous_events()
-            .when(BankAccountCommand::OpenAccount(
-                BankAccountOpenAccountCommandData {
-                    account_id: "1234".to_string(),
-                },
+            .given(vec![opened_savings_account()])
+            .when(BankAccountCommand::SetOverdraftLimit(
+                BankAccountSetOverdraftLimitCommandData { limit: eur(10000) },
             ))
-            .then_expect_events(vec![BankAccountEvent::AccountOpened {
-                account_id: "1234".to_string(),
+            .then_expect_error(BankAccountError::OverdraftNotAllowed);
+    }
+
+    #[test]
+    fn savings_withdrawals_are_limited_per_calendar_month() {
+        AccountTestFramework::with(services_with_savings_withdrawal_limit(2))
+            .given(vec![
+                opened_savings_account(),
+                deposited_on_march_1st(100000),
+                cash_withdrawn("ATM-1", 1000, "2023-03-02T09:00:00Z".parse().unwrap()),
+                cash_withdrawn("ATM-1", 1000, "2023-03-10T09:00:00Z".parse().unwrap()),
+            ])
+            .when(withdraw_at("ATM-1", 1000))
+            .then_expect_error(BankAccountError::MonthlyWithdrawalLimitReached);
+    }
+
+    #[test]
+    fn savings_withdrawals_from_previous_months_do_not_count() {
+        AccountTestFramework::with(services_with_savings_withdrawal_limit(1))
+            .given(vec![
+                opened_savings_account(),
+                deposited_on_march_1st(100000),
+                cash_withdrawn("ATM-1", 1000, "2023-02-28T09:00:00Z".parse().unwrap()),
+            ])
+            .when(withdraw_at("ATM-1", 1000))
+            .then_expect_events(vec![BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(1000),
+                balance: eur(49000),
+                atm_id: Some("ATM-1".to_string()),
+                conversion: None,
+                occurred_at: now(),
             }]);
     }
 }
@@ -0,0 +1,36 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use crate::domain::{FeeAmount, Money};
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Enum, TS,
+)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum CheckStatus {
+    /// The check has been written and debited but not yet presented for payment
+    #[default]
+    Pending,
+
+    /// The check has been paid
+    Cleared,
+
+    /// The check was returned unpaid and the amount credited back
+    Bounced,
+}
+
+// The fee charged when a check bounces.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CheckClearingPolicy {
+    #[serde(default)]
+    pub returned_item_fee: FeeAmount,
+}
+
+// A check written against an account, as tracked by the aggregate.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WrittenCheck {
+    pub amount: Money,
+    pub status: CheckStatus,
+}
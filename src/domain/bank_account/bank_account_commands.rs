@@ -1,8 +1,11 @@
 use async_graphql::*;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use utoipa::ToSchema;
 
+use crate::domain::{AccountProduct, Currency, Money};
+
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema, OneofObject, TS)]
 #[ts(export, export_to = "frontend/src/bindings/")]
 pub enum BankAccountCommand {
@@ -17,24 +20,101 @@ pub enum BankAccountCommand {
 
     /// WriteCheck
     WriteCheck(BankAccountWriteCheckCommandData),
+
+    /// FreezeAccount
+    FreezeAccount(BankAccountFreezeAccountCommandData),
+
+    /// UnfreezeAccount
+    UnfreezeAccount(BankAccountUnfreezeAccountCommandData),
+
+    /// CloseAccount
+    CloseAccount(BankAccountCloseAccountCommandData),
+
+    /// SetOverdraftLimit
+    SetOverdraftLimit(BankAccountSetOverdraftLimitCommandData),
+
+    /// AccrueInterest
+    AccrueInterest(BankAccountAccrueInterestCommandData),
+
+    /// TransferOut
+    TransferOut(BankAccountTransferOutCommandData),
+
+    /// TransferIn
+    TransferIn(BankAccountTransferInCommandData),
+
+    /// RefundTransfer
+    RefundTransfer(BankAccountRefundTransferCommandData),
+
+    /// CompleteTransfer
+    CompleteTransfer(BankAccountCompleteTransferCommandData),
+
+    /// PlaceHold
+    PlaceHold(BankAccountPlaceHoldCommandData),
+
+    /// CaptureHold
+    CaptureHold(BankAccountCaptureHoldCommandData),
+
+    /// ReleaseHold
+    ReleaseHold(BankAccountReleaseHoldCommandData),
+
+    /// AddHolder
+    AddHolder(BankAccountAddHolderCommandData),
+
+    /// RemoveHolder
+    RemoveHolder(BankAccountRemoveHolderCommandData),
+
+    /// ClearCheck
+    ClearCheck(BankAccountClearCheckCommandData),
+
+    /// BounceCheck
+    BounceCheck(BankAccountBounceCheckCommandData),
+
+    /// ReverseTransaction
+    ReverseTransaction(BankAccountReverseTransactionCommandData),
+
+    /// ChargeMaintenanceFee
+    ChargeMaintenanceFee(BankAccountChargeMaintenanceFeeCommandData),
+
+    /// CreateStandingOrder
+    CreateStandingOrder(BankAccountCreateStandingOrderCommandData),
+
+    /// CancelStandingOrder
+    CancelStandingOrder(BankAccountCancelStandingOrderCommandData),
+
+    /// ExecuteStandingOrder
+    ExecuteStandingOrder(BankAccountExecuteStandingOrderCommandData),
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
 #[ts(export, export_to = "frontend/src/bindings/")]
 pub struct BankAccountOpenAccountCommandData {
     pub account_id: String,
+
+    /// ISO 4217 code of the currency the account is held in, defaults to EUR
+    #[serde(default)]
+    #[graphql(default)]
+    #[schema(value_type = String, example = "EUR")]
+    #[ts(type = "string")]
+    pub currency: Currency,
+    /// Customer who owns the account, further holders can be added later
+    pub primary_holder_id: String,
+
+    /// The product the account is opened as, defaults to checking
+    #[serde(default)]
+    #[graphql(default)]
+    pub product: AccountProduct,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
 #[ts(export, export_to = "frontend/src/bindings/")]
 pub struct BankAccountDepositMoneyCommandData {
-    pub amount: f64,
+    pub amount: Money,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
 #[ts(export, export_to = "frontend/src/bindings/")]
 pub struct BankAccountWithdrawMoneyCommandData {
-    pub amount: f64,
+    pub amount: Money,
     pub atm_id: String,
 }
 
@@ -42,5 +122,164 @@ pub struct BankAccountWithdrawMoneyCommandData {
 #[ts(export, export_to = "frontend/src/bindings/")]
 pub struct BankAccountWriteCheckCommandData {
     pub check_number: String,
-    pub amount: f64,
+    pub amount: Money,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountFreezeAccountCommandData {
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountUnfreezeAccountCommandData {
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountCloseAccountCommandData {
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountSetOverdraftLimitCommandData {
+    pub limit: Money,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountAccrueInterestCommandData {
+    /// Annual interest rate in basis points (hundredths of a percent)
+    pub annual_rate_basis_points: u32,
+
+    /// First day of the accrual period
+    #[schema(value_type = String, example = "2023-03-01")]
+    #[ts(type = "string")]
+    pub period_start: NaiveDate,
+
+    /// Day after the last day of the accrual period
+    #[schema(value_type = String, example = "2023-04-01")]
+    #[ts(type = "string")]
+    pub period_end: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountTransferOutCommandData {
+    pub transfer_id: String,
+    pub destination_account_id: String,
+    pub amount: Money,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountTransferInCommandData {
+    pub transfer_id: String,
+    pub source_account_id: String,
+    pub amount: Money,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountRefundTransferCommandData {
+    pub transfer_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountCompleteTransferCommandData {
+    pub transfer_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountPlaceHoldCommandData {
+    pub hold_id: String,
+    pub amount: Money,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountCaptureHoldCommandData {
+    pub hold_id: String,
+
+    /// Amount to debit, at most the amount held; defaults to the full amount held
+    pub amount: Option<Money>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountReleaseHoldCommandData {
+    pub hold_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountAddHolderCommandData {
+    pub holder_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountRemoveHolderCommandData {
+    pub holder_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountClearCheckCommandData {
+    pub check_number: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountBounceCheckCommandData {
+    pub check_number: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountReverseTransactionCommandData {
+    /// Sequence number of the event that recorded the deposit or withdrawal to reverse
+    pub sequence: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountChargeMaintenanceFeeCommandData {
+    /// The calendar month to charge for, formatted as YYYY-MM
+    #[schema(example = "2023-03")]
+    pub period: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountCreateStandingOrderCommandData {
+    pub standing_order_id: String,
+    pub amount: Money,
+    /// Day of the month to pay on, the last day of shorter months
+    pub day_of_month: u32,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountCancelStandingOrderCommandData {
+    pub standing_order_id: String,
+}
+
+// Issued by the standing order scheduler for each payment that falls due.
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct BankAccountExecuteStandingOrderCommandData {
+    pub standing_order_id: String,
+    #[schema(value_type = String, example = "2023-04-01")]
+    #[ts(type = "string")]
+    pub due_date: NaiveDate,
 }
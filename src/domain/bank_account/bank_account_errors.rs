@@ -1,5 +1,7 @@
 use std::fmt::Debug;
 
+use crate::domain::{Money, MoneyError};
+
 #[derive(thiserror::Error, Debug, PartialEq)]
 pub enum BankAccountError {
     #[error("account already open")]
@@ -35,6 +37,127 @@ pub enum BankAccountError {
     #[error("account not open")]
     AccountNotOpen,
 
+    #[error("fx rate unavailable")]
+    FxRateUnavailable,
+
+    #[error("account frozen")]
+    AccountFrozen,
+
+    #[error("account not frozen")]
+    AccountNotFrozen,
+
+    #[error("account closed")]
+    AccountClosed,
+
+    #[error("cannot close account with non-zero balance")]
+    CannotCloseAccountWithNonZeroBalance,
+
+    #[error("cannot set negative overdraft limit")]
+    CannotSetNegativeOverdraftLimit,
+
+    #[error("invalid interest period")]
+    InvalidInterestPeriod,
+
+    #[error("interest already accrued for period")]
+    InterestAlreadyAccrued,
+
+    #[error("cannot transfer negative amount")]
+    CannotTransferNegativeAmount,
+
+    #[error("duplicate transfer")]
+    DuplicateTransfer,
+
+    #[error("unknown transfer")]
+    UnknownTransfer,
+
+    #[error("duplicate hold")]
+    DuplicateHold,
+
+    #[error("unknown hold")]
+    UnknownHold,
+
+    #[error("hold expired")]
+    HoldExpired,
+
+    #[error("capture exceeds amount held")]
+    CaptureExceedsHold,
+
+    #[error("unknown check")]
+    UnknownCheck,
+
+    #[error("check already settled")]
+    CheckAlreadySettled,
+
+    #[error("invalid holder id")]
+    InvalidHolderId,
+
+    #[error("holder already added")]
+    DuplicateHolder,
+
+    #[error("unknown holder")]
+    UnknownHolder,
+
+    #[error("cannot remove primary holder")]
+    CannotRemovePrimaryHolder,
+
+    #[error("unknown transaction")]
+    UnknownTransaction,
+
+    #[error("transaction already reversed")]
+    TransactionAlreadyReversed,
+
+    #[error("invalid fee period")]
+    InvalidFeePeriod,
+
+    #[error("maintenance fee already charged for period")]
+    MaintenanceFeeAlreadyCharged,
+
+    #[error("maintenance fee period ended before the account was opened")]
+    MaintenanceFeePeriodBeforeOpening,
+
+    #[error("duplicate standing order")]
+    DuplicateStandingOrder,
+
+    #[error("unknown standing order")]
+    UnknownStandingOrder,
+
+    #[error("invalid day of month")]
+    InvalidDayOfMonth,
+
+    #[error("standing order not due")]
+    StandingOrderNotDue,
+
+    #[error("checks not allowed for this account product")]
+    ChecksNotAllowed,
+
+    #[error("overdraft not allowed for this account product")]
+    OverdraftNotAllowed,
+
+    #[error("monthly withdrawal limit reached")]
+    MonthlyWithdrawalLimitReached,
+
+    #[error("interest not earned on this account product")]
+    InterestNotEarned,
+
+    #[error("unknown customer")]
+    UnknownCustomer,
+
+    #[error("customer has not passed kyc")]
+    KycNotPassed,
+
+    #[error("customer lookup unavailable")]
+    CustomerLookupUnavailable,
+
+    // `atm_id` is set when the limit breached is the one for a single ATM.
+    #[error("withdrawal limit exceeded, {remaining} remaining")]
+    WithdrawalLimitExceeded {
+        atm_id: Option<String>,
+        remaining: Money,
+    },
+
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+
     #[error("Unexpected Error: {0}")]
     UnexpectedError(String),
 }
@@ -51,6 +174,47 @@ impl From<&str> for BankAccountError {
             "InvalidCheck" => BankAccountError::InvalidCheck,
             "AtmRuleViolation" => BankAccountError::AtmRuleViolation,
             "CannotWriteNegativeCheckAmount" => BankAccountError::CannotWriteNegativeCheckAmount,
+            "FxRateUnavailable" => BankAccountError::FxRateUnavailable,
+            "AccountFrozen" => BankAccountError::AccountFrozen,
+            "AccountNotFrozen" => BankAccountError::AccountNotFrozen,
+            "AccountClosed" => BankAccountError::AccountClosed,
+            "CannotCloseAccountWithNonZeroBalance" => {
+                BankAccountError::CannotCloseAccountWithNonZeroBalance
+            }
+            "CannotSetNegativeOverdraftLimit" => BankAccountError::CannotSetNegativeOverdraftLimit,
+            "InvalidInterestPeriod" => BankAccountError::InvalidInterestPeriod,
+            "InterestAlreadyAccrued" => BankAccountError::InterestAlreadyAccrued,
+            "CannotTransferNegativeAmount" => BankAccountError::CannotTransferNegativeAmount,
+            "DuplicateTransfer" => BankAccountError::DuplicateTransfer,
+            "UnknownTransfer" => BankAccountError::UnknownTransfer,
+            "DuplicateHold" => BankAccountError::DuplicateHold,
+            "UnknownHold" => BankAccountError::UnknownHold,
+            "HoldExpired" => BankAccountError::HoldExpired,
+            "CaptureExceedsHold" => BankAccountError::CaptureExceedsHold,
+            "UnknownCheck" => BankAccountError::UnknownCheck,
+            "CheckAlreadySettled" => BankAccountError::CheckAlreadySettled,
+            "InvalidHolderId" => BankAccountError::InvalidHolderId,
+            "DuplicateHolder" => BankAccountError::DuplicateHolder,
+            "UnknownHolder" => BankAccountError::UnknownHolder,
+            "CannotRemovePrimaryHolder" => BankAccountError::CannotRemovePrimaryHolder,
+            "UnknownTransaction" => BankAccountError::UnknownTransaction,
+            "TransactionAlreadyReversed" => BankAccountError::TransactionAlreadyReversed,
+            "InvalidFeePeriod" => BankAccountError::InvalidFeePeriod,
+            "MaintenanceFeeAlreadyCharged" => BankAccountError::MaintenanceFeeAlreadyCharged,
+            "MaintenanceFeePeriodBeforeOpening" => {
+                BankAccountError::MaintenanceFeePeriodBeforeOpening
+            }
+            "DuplicateStandingOrder" => BankAccountError::DuplicateStandingOrder,
+            "UnknownStandingOrder" => BankAccountError::UnknownStandingOrder,
+            "InvalidDayOfMonth" => BankAccountError::InvalidDayOfMonth,
+            "StandingOrderNotDue" => BankAccountError::StandingOrderNotDue,
+            "ChecksNotAllowed" => BankAccountError::ChecksNotAllowed,
+            "OverdraftNotAllowed" => BankAccountError::OverdraftNotAllowed,
+            "MonthlyWithdrawalLimitReached" => BankAccountError::MonthlyWithdrawalLimitReached,
+            "InterestNotEarned" => BankAccountError::InterestNotEarned,
+            "UnknownCustomer" => BankAccountError::UnknownCustomer,
+            "KycNotPassed" => BankAccountError::KycNotPassed,
+            "CustomerLookupUnavailable" => BankAccountError::CustomerLookupUnavailable,
             _ => BankAccountError::UnexpectedError(msg.to_string()),
         }
     }
@@ -91,6 +255,120 @@ mod tests {
         let error = BankAccountError::from("CannotWriteNegativeCheckAmount");
         assert_eq!(error, BankAccountError::CannotWriteNegativeCheckAmount);
 
+        let error = BankAccountError::from("FxRateUnavailable");
+        assert_eq!(error, BankAccountError::FxRateUnavailable);
+
+        let error = BankAccountError::from("AccountFrozen");
+        assert_eq!(error, BankAccountError::AccountFrozen);
+
+        let error = BankAccountError::from("AccountNotFrozen");
+        assert_eq!(error, BankAccountError::AccountNotFrozen);
+
+        let error = BankAccountError::from("AccountClosed");
+        assert_eq!(error, BankAccountError::AccountClosed);
+
+        let error = BankAccountError::from("CannotCloseAccountWithNonZeroBalance");
+        assert_eq!(
+            error,
+            BankAccountError::CannotCloseAccountWithNonZeroBalance
+        );
+
+        let error = BankAccountError::from("CannotSetNegativeOverdraftLimit");
+        assert_eq!(error, BankAccountError::CannotSetNegativeOverdraftLimit);
+
+        let error = BankAccountError::from("InvalidInterestPeriod");
+        assert_eq!(error, BankAccountError::InvalidInterestPeriod);
+
+        let error = BankAccountError::from("InterestAlreadyAccrued");
+        assert_eq!(error, BankAccountError::InterestAlreadyAccrued);
+
+        let error = BankAccountError::from("CannotTransferNegativeAmount");
+        assert_eq!(error, BankAccountError::CannotTransferNegativeAmount);
+
+        let error = BankAccountError::from("DuplicateTransfer");
+        assert_eq!(error, BankAccountError::DuplicateTransfer);
+
+        let error = BankAccountError::from("UnknownTransfer");
+        assert_eq!(error, BankAccountError::UnknownTransfer);
+
+        let error = BankAccountError::from("DuplicateHold");
+        assert_eq!(error, BankAccountError::DuplicateHold);
+
+        let error = BankAccountError::from("UnknownHold");
+        assert_eq!(error, BankAccountError::UnknownHold);
+
+        let error = BankAccountError::from("HoldExpired");
+        assert_eq!(error, BankAccountError::HoldExpired);
+
+        let error = BankAccountError::from("CaptureExceedsHold");
+        assert_eq!(error, BankAccountError::CaptureExceedsHold);
+
+        let error = BankAccountError::from("UnknownCheck");
+        assert_eq!(error, BankAccountError::UnknownCheck);
+
+        let error = BankAccountError::from("CheckAlreadySettled");
+        assert_eq!(error, BankAccountError::CheckAlreadySettled);
+
+        let error = BankAccountError::from("InvalidHolderId");
+        assert_eq!(error, BankAccountError::InvalidHolderId);
+
+        let error = BankAccountError::from("DuplicateHolder");
+        assert_eq!(error, BankAccountError::DuplicateHolder);
+
+        let error = BankAccountError::from("UnknownHolder");
+        assert_eq!(error, BankAccountError::UnknownHolder);
+
+        let error = BankAccountError::from("CannotRemovePrimaryHolder");
+        assert_eq!(error, BankAccountError::CannotRemovePrimaryHolder);
+
+        let error = BankAccountError::from("UnknownTransaction");
+        assert_eq!(error, BankAccountError::UnknownTransaction);
+
+        let error = BankAccountError::from("TransactionAlreadyReversed");
+        assert_eq!(error, BankAccountError::TransactionAlreadyReversed);
+
+        let error = BankAccountError::from("InvalidFeePeriod");
+        assert_eq!(error, BankAccountError::InvalidFeePeriod);
+
+        let error = BankAccountError::from("MaintenanceFeeAlreadyCharged");
+        assert_eq!(error, BankAccountError::MaintenanceFeeAlreadyCharged);
+
+        let error = BankAccountError::from("MaintenanceFeePeriodBeforeOpening");
+        assert_eq!(error, BankAccountError::MaintenanceFeePeriodBeforeOpening);
+
+        let error = BankAccountError::from("DuplicateStandingOrder");
+        assert_eq!(error, BankAccountError::DuplicateStandingOrder);
+
+        let error = BankAccountError::from("UnknownStandingOrder");
+        assert_eq!(error, BankAccountError::UnknownStandingOrder);
+
+        let error = BankAccountError::from("InvalidDayOfMonth");
+        assert_eq!(error, BankAccountError::InvalidDayOfMonth);
+
+        let error = BankAccountError::from("StandingOrderNotDue");
+        assert_eq!(error, BankAccountError::StandingOrderNotDue);
+
+        let error = BankAccountError::from("ChecksNotAllowed");
+        assert_eq!(error, BankAccountError::ChecksNotAllowed);
+
+        let error = BankAccountError::from("OverdraftNotAllowed");
+        assert_eq!(error, BankAccountError::OverdraftNotAllowed);
+
+        let error = BankAccountError::from("MonthlyWithdrawalLimitReached");
+        assert_eq!(error, BankAccountError::MonthlyWithdrawalLimitReached);
+
+        let error = BankAccountError::from("InterestNotEarned");
+        assert_eq!(error, BankAccountError::InterestNotEarned);
+
+        let error = BankAccountError::from("UnknownCustomer");
+        assert_eq!(error, BankAccountError::UnknownCustomer);
+
+        let error = BankAccountError::from("KycNotPassed");
+        assert_eq!(error, BankAccountError::KycNotPassed);
+
+        let error = BankAccountError::from("CustomerLookupUnavailable");
+        assert_eq!(error, BankAccountError::CustomerLookupUnavailable);
+
         let error = BankAccountError::from("AnyNonMatchingErrorString");
         assert_eq!(
             error,
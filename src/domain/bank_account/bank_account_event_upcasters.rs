@@ -0,0 +1,185 @@
+use cqrs_es::persist::{EventUpcaster, SemanticVersionEventUpcaster, SerializedEvent};
+use serde_json::{Map, Value};
+
+use crate::domain::{Currency, Money};
+
+// The version recorded with every BankAccountEvent written now. Stored events with an older
+// version are brought up to date by the upcasters below before they are deserialized.
+pub const BANK_ACCOUNT_EVENT_VERSION: &str = "2.0";
+
+// The upcasters for stored BankAccountEvents, in the order they must be applied. Each one
+// rewrites the payload of one event type written before the given version.
+pub fn bank_account_event_upcasters() -> Vec<Box<dyn EventUpcaster>> {
+    vec![
+        // Version 1.0 events may predate `Money` and currencies: accounts had no currency and
+        // amounts were floating point euros. Nothing recorded when they happened either, the
+        // migrations fill that in from the time each event was stored.
+        upcaster("AccountOpened", "2.0", account_opened_v2),
+        upcaster("CustomerDepositedMoney", "2.0", money_movement_v2),
+        upcaster("CustomerWithdrewCash", "2.0", money_movement_v2),
+        upcaster("CustomerWroteCheck", "2.0", money_movement_v2),
+    ]
+}
+
+// Applies the upcasters the same way the persisted event store does when loading events,
+// for code that reads stored events directly.
+pub fn upcast_bank_account_event(mut event: SerializedEvent) -> SerializedEvent {
+    for upcaster in bank_account_event_upcasters() {
+        if upcaster.can_upcast(&event.event_type, &event.event_version) {
+            event = upcaster.upcast(event);
+        }
+    }
+    event
+}
+
+fn upcaster(
+    event_type: &str,
+    event_version: &str,
+    upcast: fn(Value) -> Value,
+) -> Box<dyn EventUpcaster> {
+    Box::new(SemanticVersionEventUpcaster::new(
+        event_type,
+        event_version,
+        Box::new(upcast),
+    ))
+}
+
+fn account_opened_v2(mut payload: Value) -> Value {
+    if let Some(fields) = event_fields(&mut payload) {
+        fields
+            .entry("currency")
+            .or_insert_with(|| Value::String(Currency::default().to_string()));
+    }
+    payload
+}
+
+fn money_movement_v2(mut payload: Value) -> Value {
+    if let Some(fields) = event_fields(&mut payload) {
+        for field in ["amount", "balance"] {
+            if let Some(value) = fields.get_mut(field) {
+                upcast_legacy_money(value);
+            }
+        }
+    }
+    payload
+}
+
+// Events are stored externally tagged, e.g. `{"AccountOpened": {"account_id": "1234"}}`.
+fn event_fields(payload: &mut Value) -> Option<&mut Map<String, Value>> {
+    payload
+        .as_object_mut()?
+        .values_mut()
+        .next()?
+        .as_object_mut()
+}
+
+// Amounts stored as floating point euros become minor units, rounded the same way as when
+// deserializing them directly. Anything else is left for deserialization to judge.
+fn upcast_legacy_money(value: &mut Value) {
+    let Some(major_units) = value.as_f64() else {
+        return;
+    };
+    if let Ok(upcast) = Money::from_major_units(major_units, Currency::default())
+        .map_err(|_| ())
+        .and_then(|money| serde_json::to_value(money).map_err(|_| ()))
+    {
+        *value = upcast;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AccountProduct, BankAccount, BankAccountEvent};
+    use coverage_helper::test;
+    use cqrs_es::{Aggregate, DomainEvent};
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    // Events as they were stored by earlier versions of the application.
+    const FIXTURE_EVENTS: &str = include_str!("../../../tests/fixtures/bank_account_events.json");
+
+    #[derive(Deserialize)]
+    struct FixtureEvent {
+        event_type: String,
+        event_version: String,
+        payload: Value,
+    }
+
+    fn load_fixture_events() -> Vec<BankAccountEvent> {
+        let fixtures: Vec<FixtureEvent> = serde_json::from_str(FIXTURE_EVENTS).unwrap();
+        fixtures
+            .into_iter()
+            .enumerate()
+            .map(|(index, fixture)| {
+                let event = upcast_bank_account_event(SerializedEvent::new(
+                    "1234".to_string(),
+                    index + 1,
+                    BankAccount::aggregate_type(),
+                    fixture.event_type,
+                    fixture.event_version,
+                    fixture.payload,
+                    Value::default(),
+                ));
+                // Upcast events are stored with a normalised version, e.g. `2.0.0`.
+                assert!(!bank_account_event_upcasters()
+                    .iter()
+                    .any(|upcaster| upcaster.can_upcast(&event.event_type, &event.event_version)));
+                serde_json::from_value(event.payload).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn upcast_events_are_at_the_current_version() {
+        let events = load_fixture_events();
+        assert!(events
+            .iter()
+            .all(|event| event.event_version() == BANK_ACCOUNT_EVENT_VERSION));
+    }
+
+    #[test]
+    fn legacy_floating_point_events_are_upcast_to_money() {
+        let events = load_fixture_events();
+        assert_eq!(
+            events[0],
+            BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: None,
+                product: AccountProduct::Checking,
+                occurred_at: chrono::DateTime::default(),
+            }
+        );
+        assert_eq!(
+            events[1],
+            BankAccountEvent::CustomerDepositedMoney {
+                amount: Money::new(100050, Currency::EUR),
+                balance: Money::new(100050, Currency::EUR),
+                conversion: None,
+                occurred_at: chrono::DateTime::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn fixture_events_replay_into_the_aggregate() {
+        let mut account = BankAccount::default();
+        for event in load_fixture_events() {
+            account.apply(event);
+        }
+        let account = serde_json::to_value(account).unwrap();
+        assert_eq!(
+            account["balance"],
+            serde_json::json!({ "minor_units": 87550, "currency": "EUR" })
+        );
+    }
+
+    #[test]
+    fn current_events_are_not_upcast() {
+        let upcasters = bank_account_event_upcasters();
+        assert!(!upcasters
+            .iter()
+            .any(|upcaster| upcaster.can_upcast("AccountOpened", BANK_ACCOUNT_EVENT_VERSION)));
+    }
+}
@@ -1,24 +1,204 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use cqrs_es::DomainEvent;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+use crate::domain::{
+    AccountProduct, Currency, CurrencyConversion, FeeReason, Money, BANK_ACCOUNT_EVENT_VERSION,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum BankAccountEvent {
     AccountOpened {
         account_id: String,
+        #[serde(default)]
+        currency: Currency,
+        // Accounts opened before holders were introduced have no primary holder.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        primary_holder_id: Option<String>,
+        // Accounts opened before products were introduced are checking accounts.
+        #[serde(default)]
+        product: AccountProduct,
+        // Accounts opened before the opening time was recorded are treated as opened at the
+        // epoch.
+        #[serde(default)]
+        occurred_at: DateTime<Utc>,
     },
     CustomerDepositedMoney {
-        amount: f64,
-        balance: f64,
+        amount: Money,
+        balance: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        conversion: Option<CurrencyConversion>,
+        #[serde(default)]
+        occurred_at: DateTime<Utc>,
     },
     CustomerWithdrewCash {
-        amount: f64,
-        balance: f64,
+        amount: Money,
+        balance: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        atm_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        conversion: Option<CurrencyConversion>,
+        #[serde(default)]
+        occurred_at: DateTime<Utc>,
     },
     CustomerWroteCheck {
         check_number: String,
-        amount: f64,
-        balance: f64,
+        amount: Money,
+        balance: Money,
+        #[serde(default)]
+        occurred_at: DateTime<Utc>,
+    },
+    AccountFrozen {
+        reason: String,
+    },
+    AccountUnfrozen {
+        reason: String,
+    },
+    AccountClosed {
+        reason: String,
+    },
+    OverdraftLimitSet {
+        limit: Money,
+    },
+    OverdraftFeeCharged {
+        fee: Money,
+        balance: Money,
+        #[serde(default)]
+        occurred_at: DateTime<Utc>,
+    },
+    InterestAccrued {
+        period_start: NaiveDate,
+        period_end: NaiveDate,
+        annual_rate_basis_points: u32,
+        amount: Money,
+        // Interest earned but too small to post yet, in millionths of a minor unit.
+        unposted_micro_units: i64,
+    },
+    InterestPosted {
+        amount: Money,
+        balance: Money,
+        occurred_at: DateTime<Utc>,
+    },
+    TransferDebited {
+        transfer_id: String,
+        destination_account_id: String,
+        amount: Money,
+        balance: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        conversion: Option<CurrencyConversion>,
+        occurred_at: DateTime<Utc>,
+    },
+    TransferCredited {
+        transfer_id: String,
+        source_account_id: String,
+        amount: Money,
+        balance: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        conversion: Option<CurrencyConversion>,
+        occurred_at: DateTime<Utc>,
+    },
+    TransferRefunded {
+        transfer_id: String,
+        amount: Money,
+        balance: Money,
+        reason: String,
+        occurred_at: DateTime<Utc>,
+    },
+    // The destination account was credited, so the transfer can no longer be refunded.
+    TransferCompleted {
+        transfer_id: String,
+        occurred_at: DateTime<Utc>,
+    },
+    HoldPlaced {
+        hold_id: String,
+        amount: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        conversion: Option<CurrencyConversion>,
+        expires_at: DateTime<Utc>,
+        occurred_at: DateTime<Utc>,
+    },
+    HoldCaptured {
+        hold_id: String,
+        amount: Money,
+        balance: Money,
+        occurred_at: DateTime<Utc>,
+    },
+    HoldReleased {
+        hold_id: String,
+        amount: Money,
+    },
+    // Recorded the next time holds on the account are touched after the hold has lapsed.
+    // Expired holds no longer reserve funds even before this is recorded.
+    HoldExpired {
+        hold_id: String,
+        amount: Money,
+    },
+    HolderAdded {
+        holder_id: String,
+    },
+    HolderRemoved {
+        holder_id: String,
+    },
+    CheckCleared {
+        check_number: String,
+        occurred_at: DateTime<Utc>,
+    },
+    CheckBounced {
+        check_number: String,
+        amount: Money,
+        balance: Money,
+        reason: String,
+        occurred_at: DateTime<Utc>,
+    },
+    ReturnedItemFeeCharged {
+        check_number: String,
+        fee: Money,
+        balance: Money,
+        occurred_at: DateTime<Utc>,
+    },
+    // Undoes the deposit or cash withdrawal recorded by the event with `reversed_sequence`,
+    // `amount` is the amount of that transaction.
+    TransactionReversed {
+        reversed_sequence: usize,
+        amount: Money,
+        balance: Money,
+        reason: String,
+        occurred_at: DateTime<Utc>,
+    },
+    // Charged for one of the fee engine's rules, after the transaction it is for if any.
+    FeeCharged {
+        reason: FeeReason,
+        fee: Money,
+        balance: Money,
+        occurred_at: DateTime<Utc>,
+    },
+    StandingOrderCreated {
+        standing_order_id: String,
+        amount: Money,
+        day_of_month: u32,
+        description: String,
+        first_due_date: NaiveDate,
+    },
+    StandingOrderCancelled {
+        standing_order_id: String,
+    },
+    StandingOrderExecuted {
+        standing_order_id: String,
+        due_date: NaiveDate,
+        amount: Money,
+        balance: Money,
+        next_due_date: NaiveDate,
+        occurred_at: DateTime<Utc>,
+    },
+    // The payment due could not be made, e.g. for lack of funds. It is not retried, the
+    // order carries on with the next payment.
+    StandingOrderFailed {
+        standing_order_id: String,
+        due_date: NaiveDate,
+        reason: String,
+        next_due_date: NaiveDate,
+        occurred_at: DateTime<Utc>,
     },
 }
 
@@ -29,31 +209,67 @@ impl DomainEvent for BankAccountEvent {
             BankAccountEvent::CustomerDepositedMoney { .. } => "CustomerDepositedMoney".to_string(),
             BankAccountEvent::CustomerWithdrewCash { .. } => "CustomerWithdrewCash".to_string(),
             BankAccountEvent::CustomerWroteCheck { .. } => "CustomerWroteCheck".to_string(),
+            BankAccountEvent::AccountFrozen { .. } => "AccountFrozen".to_string(),
+            BankAccountEvent::AccountUnfrozen { .. } => "AccountUnfrozen".to_string(),
+            BankAccountEvent::AccountClosed { .. } => "AccountClosed".to_string(),
+            BankAccountEvent::OverdraftLimitSet { .. } => "OverdraftLimitSet".to_string(),
+            BankAccountEvent::OverdraftFeeCharged { .. } => "OverdraftFeeCharged".to_string(),
+            BankAccountEvent::InterestAccrued { .. } => "InterestAccrued".to_string(),
+            BankAccountEvent::InterestPosted { .. } => "InterestPosted".to_string(),
+            BankAccountEvent::TransferDebited { .. } => "TransferDebited".to_string(),
+            BankAccountEvent::TransferCredited { .. } => "TransferCredited".to_string(),
+            BankAccountEvent::TransferRefunded { .. } => "TransferRefunded".to_string(),
+            BankAccountEvent::TransferCompleted { .. } => "TransferCompleted".to_string(),
+            BankAccountEvent::HoldPlaced { .. } => "HoldPlaced".to_string(),
+            BankAccountEvent::HoldCaptured { .. } => "HoldCaptured".to_string(),
+            BankAccountEvent::HoldReleased { .. } => "HoldReleased".to_string(),
+            BankAccountEvent::HoldExpired { .. } => "HoldExpired".to_string(),
+            BankAccountEvent::HolderAdded { .. } => "HolderAdded".to_string(),
+            BankAccountEvent::HolderRemoved { .. } => "HolderRemoved".to_string(),
+            BankAccountEvent::CheckCleared { .. } => "CheckCleared".to_string(),
+            BankAccountEvent::CheckBounced { .. } => "CheckBounced".to_string(),
+            BankAccountEvent::ReturnedItemFeeCharged { .. } => "ReturnedItemFeeCharged".to_string(),
+            BankAccountEvent::TransactionReversed { .. } => "TransactionReversed".to_string(),
+            BankAccountEvent::FeeCharged { .. } => "FeeCharged".to_string(),
+            BankAccountEvent::StandingOrderCreated { .. } => "StandingOrderCreated".to_string(),
+            BankAccountEvent::StandingOrderCancelled { .. } => "StandingOrderCancelled".to_string(),
+            BankAccountEvent::StandingOrderExecuted { .. } => "StandingOrderExecuted".to_string(),
+            BankAccountEvent::StandingOrderFailed { .. } => "StandingOrderFailed".to_string(),
         }
     }
 
+    // Bump this along with a new upcaster whenever the stored shape of an event changes.
     fn event_version(&self) -> String {
-        "1.0".to_string()
+        BANK_ACCOUNT_EVENT_VERSION.to_string()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::ExchangeRate;
     use pretty_assertions::assert_eq;
 
     #[test]
-    fn bank_account_event_version_is_1_0() {
+    fn bank_account_event_version_is_2_0() {
         let event = BankAccountEvent::AccountOpened {
             account_id: "123".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: Some("customer-1".to_string()),
+            product: AccountProduct::Checking,
+            occurred_at: DateTime::default(),
         };
-        assert_eq!(event.event_version(), "1.0".to_string());
+        assert_eq!(event.event_version(), "2.0".to_string());
     }
 
     #[test]
     fn bank_account_event_type_is_account_opened() {
         let event = BankAccountEvent::AccountOpened {
             account_id: "123".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: Some("customer-1".to_string()),
+            product: AccountProduct::Checking,
+            occurred_at: DateTime::default(),
         };
         assert_eq!(event.event_type(), "AccountOpened".to_string());
     }
@@ -61,8 +277,10 @@ mod tests {
     #[test]
     fn bank_account_event_type_is_customer_deposited_money() {
         let event = BankAccountEvent::CustomerDepositedMoney {
-            amount: 100.0,
-            balance: 100.0,
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(10000, Currency::EUR),
+            conversion: None,
+            occurred_at: DateTime::default(),
         };
         assert_eq!(event.event_type(), "CustomerDepositedMoney".to_string());
     }
@@ -70,8 +288,11 @@ mod tests {
     #[test]
     fn bank_account_event_type_is_customer_withdrew_cash() {
         let event = BankAccountEvent::CustomerWithdrewCash {
-            amount: 100.0,
-            balance: 100.0,
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(10000, Currency::EUR),
+            atm_id: Some("ATM34f1ba3c".to_string()),
+            conversion: None,
+            occurred_at: DateTime::default(),
         };
         assert_eq!(event.event_type(), "CustomerWithdrewCash".to_string());
     }
@@ -80,9 +301,329 @@ mod tests {
     fn bank_account_event_type_is_customer_wrote_check() {
         let event = BankAccountEvent::CustomerWroteCheck {
             check_number: "123".to_string(),
-            amount: 100.0,
-            balance: 100.0,
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(10000, Currency::EUR),
+            occurred_at: DateTime::default(),
         };
         assert_eq!(event.event_type(), "CustomerWroteCheck".to_string());
     }
+
+    #[test]
+    fn bank_account_event_type_is_account_frozen() {
+        let event = BankAccountEvent::AccountFrozen {
+            reason: "suspected fraud".to_string(),
+        };
+        assert_eq!(event.event_type(), "AccountFrozen".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_account_unfrozen() {
+        let event = BankAccountEvent::AccountUnfrozen {
+            reason: "investigation complete".to_string(),
+        };
+        assert_eq!(event.event_type(), "AccountUnfrozen".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_account_closed() {
+        let event = BankAccountEvent::AccountClosed {
+            reason: "customer request".to_string(),
+        };
+        assert_eq!(event.event_type(), "AccountClosed".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_overdraft_limit_set() {
+        let event = BankAccountEvent::OverdraftLimitSet {
+            limit: Money::new(50000, Currency::EUR),
+        };
+        assert_eq!(event.event_type(), "OverdraftLimitSet".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_overdraft_fee_charged() {
+        let event = BankAccountEvent::OverdraftFeeCharged {
+            fee: Money::new(2500, Currency::EUR),
+            balance: Money::new(-12500, Currency::EUR),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "OverdraftFeeCharged".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_interest_accrued() {
+        let event = BankAccountEvent::InterestAccrued {
+            period_start: NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+            period_end: NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+            annual_rate_basis_points: 200,
+            amount: Money::new(170, Currency::EUR),
+            unposted_micro_units: 876_712,
+        };
+        assert_eq!(event.event_type(), "InterestAccrued".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_interest_posted() {
+        let event = BankAccountEvent::InterestPosted {
+            amount: Money::new(170, Currency::EUR),
+            balance: Money::new(10170, Currency::EUR),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "InterestPosted".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_transfer_debited() {
+        let event = BankAccountEvent::TransferDebited {
+            transfer_id: "t-1".to_string(),
+            destination_account_id: "456".to_string(),
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(0, Currency::EUR),
+            conversion: None,
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "TransferDebited".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_transfer_credited() {
+        let event = BankAccountEvent::TransferCredited {
+            transfer_id: "t-1".to_string(),
+            source_account_id: "123".to_string(),
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(10000, Currency::EUR),
+            conversion: None,
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "TransferCredited".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_transfer_refunded() {
+        let event = BankAccountEvent::TransferRefunded {
+            transfer_id: "t-1".to_string(),
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(10000, Currency::EUR),
+            reason: "account closed".to_string(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "TransferRefunded".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_transfer_completed() {
+        let event = BankAccountEvent::TransferCompleted {
+            transfer_id: "t-1".to_string(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "TransferCompleted".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_hold_placed() {
+        let event = BankAccountEvent::HoldPlaced {
+            hold_id: "h-1".to_string(),
+            amount: Money::new(5000, Currency::EUR),
+            conversion: None,
+            expires_at: DateTime::default(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "HoldPlaced".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_hold_captured() {
+        let event = BankAccountEvent::HoldCaptured {
+            hold_id: "h-1".to_string(),
+            amount: Money::new(5000, Currency::EUR),
+            balance: Money::new(5000, Currency::EUR),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "HoldCaptured".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_hold_released() {
+        let event = BankAccountEvent::HoldReleased {
+            hold_id: "h-1".to_string(),
+            amount: Money::new(5000, Currency::EUR),
+        };
+        assert_eq!(event.event_type(), "HoldReleased".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_hold_expired() {
+        let event = BankAccountEvent::HoldExpired {
+            hold_id: "h-1".to_string(),
+            amount: Money::new(5000, Currency::EUR),
+        };
+        assert_eq!(event.event_type(), "HoldExpired".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_holder_added() {
+        let event = BankAccountEvent::HolderAdded {
+            holder_id: "customer-2".to_string(),
+        };
+        assert_eq!(event.event_type(), "HolderAdded".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_holder_removed() {
+        let event = BankAccountEvent::HolderRemoved {
+            holder_id: "customer-2".to_string(),
+        };
+        assert_eq!(event.event_type(), "HolderRemoved".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_check_cleared() {
+        let event = BankAccountEvent::CheckCleared {
+            check_number: "123".to_string(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "CheckCleared".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_check_bounced() {
+        let event = BankAccountEvent::CheckBounced {
+            check_number: "123".to_string(),
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(10000, Currency::EUR),
+            reason: "signature mismatch".to_string(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "CheckBounced".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_returned_item_fee_charged() {
+        let event = BankAccountEvent::ReturnedItemFeeCharged {
+            check_number: "123".to_string(),
+            fee: Money::new(3500, Currency::EUR),
+            balance: Money::new(6500, Currency::EUR),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "ReturnedItemFeeCharged".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_transaction_reversed() {
+        let event = BankAccountEvent::TransactionReversed {
+            reversed_sequence: 2,
+            amount: Money::new(10000, Currency::EUR),
+            balance: Money::new(0, Currency::EUR),
+            reason: "duplicate deposit".to_string(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "TransactionReversed".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_fee_charged() {
+        let event = BankAccountEvent::FeeCharged {
+            reason: FeeReason::MonthlyMaintenance {
+                period: "2023-03".to_string(),
+            },
+            fee: Money::new(500, Currency::EUR),
+            balance: Money::new(9500, Currency::EUR),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "FeeCharged".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_standing_order_created() {
+        let event = BankAccountEvent::StandingOrderCreated {
+            standing_order_id: "so-1".to_string(),
+            amount: Money::new(5000, Currency::EUR),
+            day_of_month: 1,
+            description: "rent".to_string(),
+            first_due_date: NaiveDate::default(),
+        };
+        assert_eq!(event.event_type(), "StandingOrderCreated".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_standing_order_cancelled() {
+        let event = BankAccountEvent::StandingOrderCancelled {
+            standing_order_id: "so-1".to_string(),
+        };
+        assert_eq!(event.event_type(), "StandingOrderCancelled".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_standing_order_executed() {
+        let event = BankAccountEvent::StandingOrderExecuted {
+            standing_order_id: "so-1".to_string(),
+            due_date: NaiveDate::default(),
+            amount: Money::new(5000, Currency::EUR),
+            balance: Money::new(5000, Currency::EUR),
+            next_due_date: NaiveDate::default(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "StandingOrderExecuted".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_type_is_standing_order_failed() {
+        let event = BankAccountEvent::StandingOrderFailed {
+            standing_order_id: "so-1".to_string(),
+            due_date: NaiveDate::default(),
+            reason: "insufficient funds".to_string(),
+            next_due_date: NaiveDate::default(),
+            occurred_at: DateTime::default(),
+        };
+        assert_eq!(event.event_type(), "StandingOrderFailed".to_string());
+    }
+
+    #[test]
+    fn bank_account_event_with_legacy_floating_point_amounts_can_be_deserialized() {
+        let event: BankAccountEvent =
+            serde_json::from_str(r#"{"CustomerDepositedMoney":{"amount":200.0,"balance":350.25}}"#)
+                .unwrap();
+        assert_eq!(
+            event,
+            BankAccountEvent::CustomerDepositedMoney {
+                amount: Money::new(20000, Currency::EUR),
+                balance: Money::new(35025, Currency::EUR),
+                conversion: None,
+                occurred_at: DateTime::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn account_opened_without_a_currency_defaults_to_eur() {
+        let event: BankAccountEvent =
+            serde_json::from_str(r#"{"AccountOpened":{"account_id":"123"}}"#).unwrap();
+        assert_eq!(
+            event,
+            BankAccountEvent::AccountOpened {
+                account_id: "123".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: None,
+                product: AccountProduct::Checking,
+                occurred_at: DateTime::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn converted_deposit_records_the_original_amount_and_rate() {
+        let rate = ExchangeRate::from_decimal_str(Currency::USD, Currency::EUR, "0.92").unwrap();
+        let event = BankAccountEvent::CustomerDepositedMoney {
+            amount: Money::new(9200, Currency::EUR),
+            balance: Money::new(9200, Currency::EUR),
+            conversion: Some(CurrencyConversion {
+                original_amount: Money::new(10000, Currency::USD),
+                rate,
+            }),
+            occurred_at: DateTime::default(),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        let deserialized: BankAccountEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, event);
+    }
 }
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::domain::{Currency, Money};
+
+// What a fee worked out by the fee engine is charged for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum FeeReason {
+    AtmWithdrawal { atm_id: String },
+    Check { check_number: String },
+    // `period` is the calendar month the account is maintained for, e.g. "2023-03".
+    MonthlyMaintenance { period: String },
+}
+
+impl FeeReason {
+    pub fn description(&self) -> String {
+        match self {
+            FeeReason::AtmWithdrawal { atm_id } => format!("atm fee at {atm_id}"),
+            FeeReason::Check { check_number } => format!("check fee for check {check_number}"),
+            FeeReason::MonthlyMaintenance { period } => format!("maintenance fee for {period}"),
+        }
+    }
+}
+
+// A fee in minor units of the account's currency. Either one amount, charged in whatever
+// currency the account is in, or an amount per currency, e.g. `{ EUR: 250, JPY: 40 }`, which
+// leaves accounts in the currencies that are not listed uncharged.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum FeeAmount {
+    Flat(i64),
+    PerCurrency(HashMap<Currency, i64>),
+}
+
+impl Default for FeeAmount {
+    fn default() -> Self {
+        FeeAmount::Flat(0)
+    }
+}
+
+impl FeeAmount {
+    pub fn in_currency(&self, currency: Currency) -> Money {
+        let fee = match self {
+            FeeAmount::Flat(fee) => *fee,
+            FeeAmount::PerCurrency(fees) => fees.get(&currency).copied().unwrap_or(0),
+        };
+        Money::new(fee, currency)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            FeeAmount::Flat(fee) => *fee == 0,
+            FeeAmount::PerCurrency(fees) => fees.values().all(|fee| *fee == 0),
+        }
+    }
+}
+
+// A negative fee would credit the account instead, so it is refused when the configuration
+// is loaded.
+impl<'de> Deserialize<'de> for FeeAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ConfiguredFee {
+            Flat(i64),
+            PerCurrency(HashMap<Currency, i64>),
+        }
+
+        let (fee, negative) = match ConfiguredFee::deserialize(deserializer)? {
+            ConfiguredFee::Flat(fee) => (FeeAmount::Flat(fee), (fee < 0).then_some(fee)),
+            ConfiguredFee::PerCurrency(fees) => {
+                let negative = fees.values().copied().find(|fee| *fee < 0);
+                (FeeAmount::PerCurrency(fees), negative)
+            }
+        };
+        if let Some(negative) = negative {
+            return Err(de::Error::custom(format!(
+                "fees cannot be negative, got {negative}"
+            )));
+        }
+        Ok(fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn flat_fees_are_charged_in_the_account_currency() {
+        let fee: FeeAmount = serde_yaml::from_str("250").unwrap();
+        assert_eq!(
+            fee.in_currency(Currency::GBP),
+            Money::new(250, Currency::GBP)
+        );
+    }
+
+    #[test]
+    fn fees_per_currency_leave_other_currencies_uncharged() {
+        let fee: FeeAmount = serde_yaml::from_str("{ EUR: 250, USD: 300 }").unwrap();
+        assert_eq!(
+            fee.in_currency(Currency::USD),
+            Money::new(300, Currency::USD)
+        );
+        assert!(fee.in_currency(Currency::GBP).is_zero());
+        assert!(!fee.is_zero());
+    }
+
+    #[test]
+    fn negative_fees_are_refused() {
+        assert!(serde_yaml::from_str::<FeeAmount>("-250").is_err());
+        assert!(serde_yaml::from_str::<FeeAmount>("{ EUR: 250, USD: -300 }").is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Money;
+
+// How long an authorization hold reserves funds for before it lapses by itself.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HoldPolicy {
+    #[serde(default = "default_ttl_seconds")]
+    pub ttl_seconds: i64,
+}
+
+fn default_ttl_seconds() -> i64 {
+    7 * 24 * 60 * 60
+}
+
+impl Default for HoldPolicy {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: default_ttl_seconds(),
+        }
+    }
+}
+
+impl HoldPolicy {
+    pub fn expires_at(&self, placed_at: DateTime<Utc>) -> DateTime<Utc> {
+        placed_at + Duration::seconds(self.ttl_seconds)
+    }
+}
+
+// Funds reserved on an account until the hold is captured, released or expires.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Hold {
+    pub amount: Money,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Hold {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        now < self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn holds_expire_after_the_ttl() {
+        let placed_at: DateTime<Utc> = "2023-03-15T12:00:00Z".parse().unwrap();
+        let policy = HoldPolicy { ttl_seconds: 3600 };
+        let hold = Hold {
+            amount: Money::new(5000, Currency::EUR),
+            expires_at: policy.expires_at(placed_at),
+        };
+        let expires_at: DateTime<Utc> = "2023-03-15T13:00:00Z".parse().unwrap();
+        assert_eq!(hold.expires_at, expires_at);
+        assert!(hold.is_active(placed_at));
+        assert!(!hold.is_active(hold.expires_at));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::domain::{Money, MoneyError};
+
+// Interest is accrued in millionths of a minor unit so that daily accruals on small
+// balances are not lost to rounding before they add up to something that can be posted.
+pub const INTEREST_MICRO_UNITS_PER_MINOR_UNIT: i64 = 1_000_000;
+
+// Interest uses the Actual/365 (Fixed) day count convention.
+pub const INTEREST_DAYS_PER_YEAR: i128 = 365;
+
+// The interest earned over a series of days, given the balance at the end of each day.
+// Overdrawn days earn nothing.
+pub fn accrue_daily_interest(
+    end_of_day_balances: impl IntoIterator<Item = Money>,
+    annual_rate_basis_points: u32,
+) -> Result<i64, MoneyError> {
+    let scaled_balance_days: i128 = end_of_day_balances
+        .into_iter()
+        .filter(|balance| !balance.is_negative())
+        .map(|balance| i128::from(balance.minor_units()))
+        .sum();
+    let micro_units = scaled_balance_days
+        * i128::from(annual_rate_basis_points)
+        * i128::from(INTEREST_MICRO_UNITS_PER_MINOR_UNIT)
+        / (10_000 * INTEREST_DAYS_PER_YEAR);
+    i64::try_from(micro_units).map_err(|_| MoneyError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn a_year_at_the_same_balance_earns_the_annual_rate() {
+        let balances = std::iter::repeat_n(Money::new(100_000, Currency::EUR), 365);
+        let interest = accrue_daily_interest(balances, 250).unwrap();
+        assert_eq!(interest, 2_500 * INTEREST_MICRO_UNITS_PER_MINOR_UNIT);
+    }
+
+    #[test]
+    fn overdrawn_days_earn_no_interest() {
+        let balances = vec![
+            Money::new(-36_500, Currency::EUR),
+            Money::new(36_500, Currency::EUR),
+        ];
+        let interest = accrue_daily_interest(balances, 10_000).unwrap();
+        assert_eq!(interest, 100 * INTEREST_MICRO_UNITS_PER_MINOR_UNIT);
+    }
+
+    #[test]
+    fn fractions_of_a_minor_unit_are_kept() {
+        // 1.00 EUR at 1% for a day is 0.0027 cents
+        let interest = accrue_daily_interest(vec![Money::new(100, Currency::EUR)], 100).unwrap();
+        assert_eq!(interest, 2_739);
+    }
+}
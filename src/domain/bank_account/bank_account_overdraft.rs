@@ -0,0 +1,74 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::domain::{Money, MoneyError};
+
+// Determines the fee charged when a debit takes an account's balance below zero.
+// Amounts are in minor units of the account's currency.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OverdraftFeeSchedule {
+    /// Fixed fee charged every time the balance goes below zero
+    #[serde(default, deserialize_with = "non_negative_fee")]
+    pub flat_fee: i64,
+
+    /// Additional fee in basis points (hundredths of a percent) of the amount overdrawn
+    #[serde(default)]
+    pub basis_points: u32,
+}
+
+// A negative fee would credit the account instead, so it is refused when the
+// configuration is loaded.
+fn non_negative_fee<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    let fee = i64::deserialize(deserializer)?;
+    if fee < 0 {
+        return Err(de::Error::custom(format!(
+            "fees cannot be negative, got {fee}"
+        )));
+    }
+    Ok(fee)
+}
+
+impl OverdraftFeeSchedule {
+    pub fn fee_for(&self, overdrawn_by: &Money) -> Result<Money, MoneyError> {
+        let percentage_fee =
+            (i128::from(overdrawn_by.minor_units().abs()) * i128::from(self.basis_points) + 5_000)
+                / 10_000;
+        let percentage_fee = i64::try_from(percentage_fee).map_err(|_| MoneyError::Overflow)?;
+        let fee = self
+            .flat_fee
+            .checked_add(percentage_fee)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(fee, overdrawn_by.currency()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn default_fee_schedule_charges_nothing() {
+        let fee = OverdraftFeeSchedule::default()
+            .fee_for(&Money::new(5000, Currency::EUR))
+            .unwrap();
+        assert!(fee.is_zero());
+    }
+
+    #[test]
+    fn fee_is_flat_fee_plus_percentage_of_amount_overdrawn() {
+        let schedule = OverdraftFeeSchedule {
+            flat_fee: 500,
+            basis_points: 150,
+        };
+        // 5.00 EUR + 1.5% of 123.45 EUR (1.85 EUR, rounded) = 6.85 EUR
+        let fee = schedule.fee_for(&Money::new(12345, Currency::EUR)).unwrap();
+        assert_eq!(fee, Money::new(685, Currency::EUR));
+    }
+
+    #[test]
+    fn negative_flat_fees_are_refused() {
+        assert!(serde_yaml::from_str::<OverdraftFeeSchedule>("flat_fee: -500").is_err());
+    }
+}
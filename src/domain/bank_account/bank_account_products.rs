@@ -0,0 +1,157 @@
+use async_graphql::Enum;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Enum, TS,
+)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum AccountProduct {
+    /// An everyday account, accounts opened before products were introduced are checking accounts
+    #[default]
+    Checking,
+
+    /// An account for putting money aside
+    Savings,
+}
+
+// What an account product allows, loaded from the `products` section of the bank account
+// configuration. Rules that are left out allow everything.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProductRules {
+    #[serde(default = "allowed")]
+    pub checks_allowed: bool,
+
+    #[serde(default = "allowed")]
+    pub overdraft_allowed: bool,
+
+    /// Cash withdrawals, outgoing transfers and standing order payments per calendar month
+    #[serde(default)]
+    pub monthly_withdrawal_limit: Option<u32>,
+
+    /// Annual interest rate in basis points (hundredths of a percent), products that are
+    /// left without one earn no interest
+    #[serde(default)]
+    pub annual_interest_rate_basis_points: Option<u32>,
+}
+
+fn allowed() -> bool {
+    true
+}
+
+impl Default for ProductRules {
+    fn default() -> Self {
+        Self {
+            checks_allowed: true,
+            overdraft_allowed: true,
+            monthly_withdrawal_limit: None,
+            annual_interest_rate_basis_points: None,
+        }
+    }
+}
+
+// The rules of every account product offered.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProductCatalogue {
+    #[serde(default)]
+    pub checking: ProductRules,
+    #[serde(default = "savings_rules")]
+    pub savings: ProductRules,
+}
+
+fn savings_rules() -> ProductRules {
+    ProductRules {
+        checks_allowed: false,
+        overdraft_allowed: false,
+        monthly_withdrawal_limit: Some(6),
+        annual_interest_rate_basis_points: Some(0),
+    }
+}
+
+impl Default for ProductCatalogue {
+    fn default() -> Self {
+        Self {
+            checking: ProductRules::default(),
+            savings: savings_rules(),
+        }
+    }
+}
+
+impl ProductCatalogue {
+    pub fn rules_for(&self, product: AccountProduct) -> &ProductRules {
+        match product {
+            AccountProduct::Checking => &self.checking,
+            AccountProduct::Savings => &self.savings,
+        }
+    }
+}
+
+// The withdrawals counted against the monthly withdrawal limit, as tracked by the aggregate.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MonthlyWithdrawals {
+    // The calendar month counted, formatted as YYYY-MM.
+    pub period: String,
+    pub count: u32,
+}
+
+impl MonthlyWithdrawals {
+    pub fn count_in(&self, now: DateTime<Utc>) -> u32 {
+        if self.period == withdrawal_period(now) {
+            self.count
+        } else {
+            0
+        }
+    }
+
+    pub fn record(&mut self, occurred_at: DateTime<Utc>) {
+        let count = self.count_in(occurred_at) + 1;
+        self.period = withdrawal_period(occurred_at);
+        self.count = count;
+    }
+}
+
+fn withdrawal_period(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    fn at(month: u32, day: u32) -> DateTime<Utc> {
+        format!("2023-{month:02}-{day:02}T12:00:00Z")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn products_left_out_of_the_catalogue_get_the_default_rules() {
+        let catalogue: ProductCatalogue =
+            serde_yaml::from_str("checking: { overdraft_allowed: false }").unwrap();
+        assert!(
+            !catalogue
+                .rules_for(AccountProduct::Checking)
+                .overdraft_allowed
+        );
+        assert!(catalogue.rules_for(AccountProduct::Checking).checks_allowed);
+        assert_eq!(
+            catalogue.rules_for(AccountProduct::Savings),
+            &savings_rules()
+        );
+    }
+
+    #[test]
+    fn withdrawals_are_counted_per_calendar_month() {
+        let mut withdrawals = MonthlyWithdrawals::default();
+        withdrawals.record(at(3, 1));
+        withdrawals.record(at(3, 31));
+        assert_eq!(withdrawals.count_in(at(3, 15)), 2);
+        assert_eq!(withdrawals.count_in(at(4, 1)), 0);
+        withdrawals.record(at(4, 1));
+        assert_eq!(withdrawals.count_in(at(4, 1)), 1);
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Money;
+
+// The kinds of transaction operations can reverse, by the direction the money moved.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReversibleTransactionKind {
+    Deposit,
+    Withdrawal,
+}
+
+// A deposit or cash withdrawal, as tracked by the aggregate under the sequence number of
+// the event that recorded it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReversibleTransaction {
+    pub kind: ReversibleTransactionKind,
+    pub amount: Money,
+    pub reversed: bool,
+}
@@ -0,0 +1,72 @@
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Money;
+
+// A recurring debit set up on the account, as tracked by the aggregate until it is
+// cancelled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StandingOrder {
+    pub amount: Money,
+    pub day_of_month: u32,
+    // The next payment not yet executed or failed.
+    pub next_due_date: NaiveDate,
+}
+
+pub fn is_valid_day_of_month(day_of_month: u32) -> bool {
+    (1..=31).contains(&day_of_month)
+}
+
+// The first payment falls on `day_of_month` in the month the order is created, or in the
+// next month once that day has passed.
+pub fn first_due_date(created_on: NaiveDate, day_of_month: u32) -> NaiveDate {
+    let this_month = due_date_in_month(created_on, day_of_month);
+    if this_month >= created_on {
+        this_month
+    } else {
+        following_due_date(created_on, day_of_month)
+    }
+}
+
+pub fn following_due_date(due_date: NaiveDate, day_of_month: u32) -> NaiveDate {
+    let next_month = first_of_month(due_date) + Months::new(1);
+    due_date_in_month(next_month, day_of_month)
+}
+
+// Orders for a day the month doesn't have are paid on its last day instead.
+fn due_date_in_month(date: NaiveDate, day_of_month: u32) -> NaiveDate {
+    let first = first_of_month(date);
+    let last = first + Months::new(1) - chrono::Duration::days(1);
+    first
+        .with_day(day_of_month.clamp(1, last.day()))
+        .unwrap_or(last)
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn first_payment_is_this_month_unless_the_day_has_passed() {
+        assert_eq!(first_due_date(date(2023, 3, 15), 20), date(2023, 3, 20));
+        assert_eq!(first_due_date(date(2023, 3, 15), 15), date(2023, 3, 15));
+        assert_eq!(first_due_date(date(2023, 3, 15), 1), date(2023, 4, 1));
+    }
+
+    #[test]
+    fn payments_on_days_a_month_lacks_fall_on_its_last_day() {
+        assert_eq!(following_due_date(date(2023, 1, 31), 31), date(2023, 2, 28));
+        assert_eq!(following_due_date(date(2023, 2, 28), 31), date(2023, 3, 31));
+        assert_eq!(following_due_date(date(2023, 12, 1), 1), date(2024, 1, 1));
+    }
+}
@@ -0,0 +1,23 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Enum, TS,
+)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum AccountStatus {
+    /// The account has not been opened yet
+    #[default]
+    NotOpened,
+
+    /// The account accepts credits and debits
+    Open,
+
+    /// The account accepts credits but rejects debits
+    Frozen,
+
+    /// The account no longer accepts any transactions
+    Closed,
+}
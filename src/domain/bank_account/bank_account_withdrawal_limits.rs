@@ -0,0 +1,80 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Money, MoneyError};
+
+// Cash withdrawal limits apply over a rolling window ending at the time of the withdrawal.
+pub const WITHDRAWAL_LIMIT_WINDOW_HOURS: i64 = 24;
+
+// How much cash can be withdrawn from an account over the rolling window, in minor units
+// of the account's currency. Limits that are not set are not enforced.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WithdrawalLimits {
+    /// Across all ATMs
+    #[serde(default)]
+    pub daily_limit: Option<i64>,
+
+    /// At any single ATM
+    #[serde(default)]
+    pub daily_limit_per_atm: Option<i64>,
+}
+
+// A cash withdrawal still inside the rolling window, as tracked by the aggregate.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CashWithdrawal {
+    pub occurred_at: DateTime<Utc>,
+    pub atm_id: Option<String>,
+    pub amount: Money,
+}
+
+pub fn withdrawal_limit_window_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - Duration::hours(WITHDRAWAL_LIMIT_WINDOW_HOURS)
+}
+
+// What is left of a limit after the given withdrawals, never less than zero.
+pub fn remaining_withdrawal_allowance<'a>(
+    limit: Money,
+    withdrawals: impl IntoIterator<Item = &'a CashWithdrawal>,
+) -> Result<Money, MoneyError> {
+    let remaining = withdrawals
+        .into_iter()
+        .try_fold(limit, |remaining, withdrawal| {
+            remaining.checked_sub(&withdrawal.amount)
+        })?;
+    if remaining.is_negative() {
+        return Ok(Money::zero(limit.currency()));
+    }
+    Ok(remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    fn withdrawal(minor_units: i64) -> CashWithdrawal {
+        CashWithdrawal {
+            occurred_at: DateTime::default(),
+            atm_id: None,
+            amount: Money::new(minor_units, Currency::EUR),
+        }
+    }
+
+    #[test]
+    fn remaining_allowance_is_the_limit_less_withdrawals() {
+        let withdrawals = vec![withdrawal(10000), withdrawal(2500)];
+        let remaining =
+            remaining_withdrawal_allowance(Money::new(50000, Currency::EUR), &withdrawals).unwrap();
+        assert_eq!(remaining, Money::new(37500, Currency::EUR));
+    }
+
+    #[test]
+    fn remaining_allowance_is_never_negative() {
+        let withdrawals = vec![withdrawal(60000)];
+        let remaining =
+            remaining_withdrawal_allowance(Money::new(50000, Currency::EUR), &withdrawals).unwrap();
+        assert!(remaining.is_zero());
+    }
+}
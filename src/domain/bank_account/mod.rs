@@ -1,10 +1,32 @@
 pub mod bank_account_aggregate;
+pub mod bank_account_checks;
 pub mod bank_account_commands;
 pub mod bank_account_errors;
+pub mod bank_account_event_upcasters;
 pub mod bank_account_events;
+pub mod bank_account_fees;
+pub mod bank_account_holds;
+pub mod bank_account_interest;
+pub mod bank_account_overdraft;
+pub mod bank_account_products;
+pub mod bank_account_reversals;
+pub mod bank_account_standing_orders;
+pub mod bank_account_status;
+pub mod bank_account_withdrawal_limits;
 
 // Re-exports
 pub use bank_account_aggregate::*;
+pub use bank_account_checks::*;
 pub use bank_account_commands::*;
 pub use bank_account_errors::*;
+pub use bank_account_event_upcasters::*;
 pub use bank_account_events::*;
+pub use bank_account_fees::*;
+pub use bank_account_holds::*;
+pub use bank_account_interest::*;
+pub use bank_account_overdraft::*;
+pub use bank_account_products::*;
+pub use bank_account_reversals::*;
+pub use bank_account_standing_orders::*;
+pub use bank_account_status::*;
+pub use bank_account_withdrawal_limits::*;
@@ -0,0 +1,279 @@
+use super::*;
+use async_trait::async_trait;
+use cqrs_es::Aggregate;
+use serde::{Deserialize, Serialize};
+use tracing::*;
+
+// A customer of the bank. Bank accounts refer to their primary holder by customer id, and may
+// only be opened for customers who passed KYC (know your customer) verification.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Customer {
+    registered: bool,
+    name: String,
+    email: String,
+    address: Option<Address>,
+    kyc_status: KycStatus,
+}
+
+#[async_trait]
+impl Aggregate for Customer {
+    type Command = CustomerCommand;
+    type Event = CustomerEvent;
+    type Error = CustomerError;
+    type Services = ();
+
+    // This identifier should be unique to the system.
+    fn aggregate_type() -> String {
+        "customer".to_string()
+    }
+
+    #[instrument(skip(_services))]
+    async fn handle(
+        &self,
+        command: Self::Command,
+        _services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        match command {
+            CustomerCommand::RegisterCustomer(command) => self.handle_register_customer(command),
+            CustomerCommand::ChangeAddress(command) => {
+                self.ensure_registered()?;
+                ensure_complete(&command.address)?;
+                Ok(vec![CustomerEvent::AddressChanged {
+                    address: command.address,
+                }])
+            }
+            CustomerCommand::SubmitKyc(command) => {
+                self.ensure_registered()?;
+                self.ensure_kyc_status(&[KycStatus::NotSubmitted, KycStatus::Rejected])?;
+                Ok(vec![CustomerEvent::KycSubmitted {
+                    document_reference: command.document_reference,
+                }])
+            }
+            CustomerCommand::VerifyKyc(command) => {
+                self.ensure_registered()?;
+                self.ensure_kyc_status(&[KycStatus::Pending])?;
+                Ok(vec![CustomerEvent::KycVerified {
+                    verified_by: command.verified_by,
+                }])
+            }
+            CustomerCommand::RejectKyc(command) => {
+                self.ensure_registered()?;
+                self.ensure_kyc_status(&[KycStatus::Pending])?;
+                Ok(vec![CustomerEvent::KycRejected {
+                    reason: command.reason,
+                }])
+            }
+        }
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            CustomerEvent::CustomerRegistered {
+                name,
+                email,
+                address,
+            } => {
+                self.registered = true;
+                self.name = name;
+                self.email = email;
+                self.address = Some(address);
+            }
+            CustomerEvent::AddressChanged { address } => {
+                self.address = Some(address);
+            }
+            CustomerEvent::KycSubmitted { .. } => {
+                self.kyc_status = KycStatus::Pending;
+            }
+            CustomerEvent::KycVerified { .. } => {
+                self.kyc_status = KycStatus::Verified;
+            }
+            CustomerEvent::KycRejected { .. } => {
+                self.kyc_status = KycStatus::Rejected;
+            }
+        }
+    }
+}
+
+impl Customer {
+    fn handle_register_customer(
+        &self,
+        command: CustomerRegisterCustomerCommandData,
+    ) -> Result<Vec<CustomerEvent>, CustomerError> {
+        if self.registered {
+            error!("customer already registered");
+            return Err(CustomerError::CustomerAlreadyRegistered);
+        }
+        if command.name.trim().is_empty() {
+            error!("customer name is required");
+            return Err(CustomerError::InvalidName);
+        }
+        ensure_complete(&command.address)?;
+        Ok(vec![CustomerEvent::CustomerRegistered {
+            name: command.name,
+            email: command.email,
+            address: command.address,
+        }])
+    }
+
+    fn ensure_registered(&self) -> Result<(), CustomerError> {
+        if !self.registered {
+            error!("customer not registered");
+            return Err(CustomerError::CustomerNotRegistered);
+        }
+        Ok(())
+    }
+
+    fn ensure_kyc_status(&self, allowed: &[KycStatus]) -> Result<(), CustomerError> {
+        if !allowed.contains(&self.kyc_status) {
+            error!("kyc step not allowed in status {:?}", self.kyc_status);
+            return Err(CustomerError::InvalidKycTransition(format!(
+                "{:?}",
+                self.kyc_status
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn ensure_complete(address: &Address) -> Result<(), CustomerError> {
+    if !address.is_complete() {
+        error!("address is incomplete");
+        return Err(CustomerError::InvalidAddress);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod aggregate_tests {
+    use coverage_helper::test;
+    use cqrs_es::test::TestFramework;
+
+    use super::*;
+
+    type CustomerTestFramework = TestFramework<Customer>;
+
+    fn address() -> Address {
+        Address {
+            line1: "Damrak 1".to_string(),
+            line2: None,
+            city: "Amsterdam".to_string(),
+            postal_code: "1012 LG".to_string(),
+            country: "NL".to_string(),
+        }
+    }
+
+    fn customer_registered() -> CustomerEvent {
+        CustomerEvent::CustomerRegistered {
+            name: "Ada Lovelace".to_string(),
+            email: "ada@example.com".to_string(),
+            address: address(),
+        }
+    }
+
+    fn kyc_submitted() -> CustomerEvent {
+        CustomerEvent::KycSubmitted {
+            document_reference: "passport-1".to_string(),
+        }
+    }
+
+    fn register_customer(name: &str, address: Address) -> CustomerCommand {
+        CustomerCommand::RegisterCustomer(CustomerRegisterCustomerCommandData {
+            name: name.to_string(),
+            email: "ada@example.com".to_string(),
+            address,
+        })
+    }
+
+    fn verify_kyc() -> CustomerCommand {
+        CustomerCommand::VerifyKyc(CustomerVerifyKycCommandData {
+            verified_by: "back-office-1".to_string(),
+        })
+    }
+
+    #[test]
+    fn register_customer_succeeds() {
+        CustomerTestFramework::with(())
+            .given_no_previous_events()
+            .when(register_customer("Ada Lovelace", address()))
+            .then_expect_events(vec![customer_registered()]);
+    }
+
+    #[test]
+    fn cannot_register_customer_twice() {
+        CustomerTestFramework::with(())
+            .given(vec![customer_registered()])
+            .when(register_customer("Ada Lovelace", address()))
+            .then_expect_error(CustomerError::CustomerAlreadyRegistered);
+    }
+
+    #[test]
+    fn cannot_register_customer_without_a_name() {
+        CustomerTestFramework::with(())
+            .given_no_previous_events()
+            .when(register_customer(" ", address()))
+            .then_expect_error(CustomerError::InvalidName);
+    }
+
+    #[test]
+    fn cannot_change_to_an_incomplete_address() {
+        CustomerTestFramework::with(())
+            .given(vec![customer_registered()])
+            .when(CustomerCommand::ChangeAddress(
+                CustomerChangeAddressCommandData {
+                    address: Address {
+                        city: String::new(),
+                        ..address()
+                    },
+                },
+            ))
+            .then_expect_error(CustomerError::InvalidAddress);
+    }
+
+    #[test]
+    fn cannot_submit_kyc_before_registering() {
+        CustomerTestFramework::with(())
+            .given_no_previous_events()
+            .when(CustomerCommand::SubmitKyc(CustomerSubmitKycCommandData {
+                document_reference: "passport-1".to_string(),
+            }))
+            .then_expect_error(CustomerError::CustomerNotRegistered);
+    }
+
+    #[test]
+    fn verify_submitted_kyc_succeeds() {
+        CustomerTestFramework::with(())
+            .given(vec![customer_registered(), kyc_submitted()])
+            .when(verify_kyc())
+            .then_expect_events(vec![CustomerEvent::KycVerified {
+                verified_by: "back-office-1".to_string(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_verify_kyc_that_was_not_submitted() {
+        CustomerTestFramework::with(())
+            .given(vec![customer_registered()])
+            .when(verify_kyc())
+            .then_expect_error(CustomerError::InvalidKycTransition(
+                "NotSubmitted".to_string(),
+            ));
+    }
+
+    #[test]
+    fn can_resubmit_kyc_after_rejection() {
+        CustomerTestFramework::with(())
+            .given(vec![
+                customer_registered(),
+                kyc_submitted(),
+                CustomerEvent::KycRejected {
+                    reason: "document expired".to_string(),
+                },
+            ])
+            .when(CustomerCommand::SubmitKyc(CustomerSubmitKycCommandData {
+                document_reference: "passport-2".to_string(),
+            }))
+            .then_expect_events(vec![CustomerEvent::KycSubmitted {
+                document_reference: "passport-2".to_string(),
+            }]);
+    }
+}
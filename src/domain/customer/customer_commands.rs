@@ -0,0 +1,82 @@
+use async_graphql::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema, OneofObject, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum CustomerCommand {
+    /// RegisterCustomer
+    RegisterCustomer(CustomerRegisterCustomerCommandData),
+
+    /// ChangeAddress
+    ChangeAddress(CustomerChangeAddressCommandData),
+
+    /// SubmitKyc
+    SubmitKyc(CustomerSubmitKycCommandData),
+
+    /// VerifyKyc
+    VerifyKyc(CustomerVerifyKycCommandData),
+
+    /// RejectKyc
+    RejectKyc(CustomerRejectKycCommandData),
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, SimpleObject, InputObject, TS,
+)]
+#[graphql(input_name = "AddressInput")]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct Address {
+    pub line1: String,
+    #[serde(default)]
+    pub line2: Option<String>,
+    pub city: String,
+    pub postal_code: String,
+    /// ISO 3166-1 alpha-2 country code
+    #[schema(example = "NL")]
+    pub country: String,
+}
+
+impl Address {
+    // Every line but the second is required.
+    pub fn is_complete(&self) -> bool {
+        [&self.line1, &self.city, &self.postal_code, &self.country]
+            .iter()
+            .all(|line| !line.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct CustomerRegisterCustomerCommandData {
+    pub name: String,
+    pub email: String,
+    pub address: Address,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct CustomerChangeAddressCommandData {
+    pub address: Address,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct CustomerSubmitKycCommandData {
+    /// Reference to the identity documents in the document store
+    pub document_reference: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct CustomerVerifyKycCommandData {
+    /// Who checked the documents
+    pub verified_by: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct CustomerRejectKycCommandData {
+    pub reason: String,
+}
@@ -0,0 +1,62 @@
+use std::fmt::Debug;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum CustomerError {
+    #[error("customer already registered")]
+    CustomerAlreadyRegistered,
+
+    #[error("customer not registered")]
+    CustomerNotRegistered,
+
+    #[error("customer name is required")]
+    InvalidName,
+
+    #[error("address is incomplete")]
+    InvalidAddress,
+
+    #[error("kyc verification not allowed in status {0}")]
+    InvalidKycTransition(String),
+
+    #[error("Unexpected Error: {0}")]
+    UnexpectedError(String),
+}
+
+impl From<&str> for CustomerError {
+    fn from(msg: &str) -> Self {
+        match msg {
+            "CustomerAlreadyRegistered" => CustomerError::CustomerAlreadyRegistered,
+            "CustomerNotRegistered" => CustomerError::CustomerNotRegistered,
+            "InvalidName" => CustomerError::InvalidName,
+            "InvalidAddress" => CustomerError::InvalidAddress,
+            _ => CustomerError::UnexpectedError(msg.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn can_create_customer_error_from_string() {
+        let error = CustomerError::from("CustomerAlreadyRegistered");
+        assert_eq!(error, CustomerError::CustomerAlreadyRegistered);
+
+        let error = CustomerError::from("CustomerNotRegistered");
+        assert_eq!(error, CustomerError::CustomerNotRegistered);
+
+        let error = CustomerError::from("InvalidName");
+        assert_eq!(error, CustomerError::InvalidName);
+
+        let error = CustomerError::from("InvalidAddress");
+        assert_eq!(error, CustomerError::InvalidAddress);
+
+        let error = CustomerError::from("AnyNonMatchingErrorString");
+        assert_eq!(
+            error,
+            CustomerError::UnexpectedError("AnyNonMatchingErrorString".to_string())
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use cqrs_es::DomainEvent;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+use crate::domain::Address;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CustomerEvent {
+    CustomerRegistered {
+        name: String,
+        email: String,
+        address: Address,
+    },
+    AddressChanged {
+        address: Address,
+    },
+    KycSubmitted {
+        document_reference: String,
+    },
+    KycVerified {
+        verified_by: String,
+    },
+    KycRejected {
+        reason: String,
+    },
+}
+
+impl DomainEvent for CustomerEvent {
+    fn event_type(&self) -> String {
+        match self {
+            CustomerEvent::CustomerRegistered { .. } => "CustomerRegistered".to_string(),
+            CustomerEvent::AddressChanged { .. } => "AddressChanged".to_string(),
+            CustomerEvent::KycSubmitted { .. } => "KycSubmitted".to_string(),
+            CustomerEvent::KycVerified { .. } => "KycVerified".to_string(),
+            CustomerEvent::KycRejected { .. } => "KycRejected".to_string(),
+        }
+    }
+
+    fn event_version(&self) -> String {
+        "1.0".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn customer_event_version_is_1_0() {
+        let event = CustomerEvent::KycVerified {
+            verified_by: "back-office-1".to_string(),
+        };
+        assert_eq!(event.event_version(), "1.0".to_string());
+    }
+
+    #[test]
+    fn customer_event_type_is_kyc_rejected() {
+        let event = CustomerEvent::KycRejected {
+            reason: "document expired".to_string(),
+        };
+        assert_eq!(event.event_type(), "KycRejected".to_string());
+    }
+}
@@ -0,0 +1,23 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Enum, TS,
+)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum KycStatus {
+    /// The customer has not submitted anything to be verified yet
+    #[default]
+    NotSubmitted,
+
+    /// The customer's documents are being checked
+    Pending,
+
+    /// The customer passed KYC and may open accounts
+    Verified,
+
+    /// The customer failed KYC, they may submit new documents
+    Rejected,
+}
@@ -0,0 +1,12 @@
+pub mod customer_aggregate;
+pub mod customer_commands;
+pub mod customer_errors;
+pub mod customer_events;
+pub mod customer_kyc_status;
+
+// Re-exports
+pub use customer_aggregate::*;
+pub use customer_commands::*;
+pub use customer_errors::*;
+pub use customer_events::*;
+pub use customer_kyc_status::*;
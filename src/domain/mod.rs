@@ -1,4 +1,10 @@
 pub mod bank_account;
+pub mod customer;
+pub mod money;
+pub mod transfer;
 
 // Re-exports
 pub use bank_account::*;
+pub use customer::*;
+pub use money::*;
+pub use transfer::*;
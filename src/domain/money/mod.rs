@@ -0,0 +1,10 @@
+pub mod money_currency;
+pub mod money_errors;
+pub mod money_exchange_rate;
+pub mod money_value;
+
+// Re-exports
+pub use money_currency::*;
+pub use money_errors::*;
+pub use money_exchange_rate::*;
+pub use money_value::*;
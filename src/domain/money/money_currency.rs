@@ -0,0 +1,133 @@
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display};
+
+use super::MoneyError;
+
+// An ISO 4217 currency code, e.g. "EUR". Stored as three ASCII bytes so that
+// `Currency` (and therefore `Money`) can be `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const EUR: Currency = Currency(*b"EUR");
+    pub const USD: Currency = Currency(*b"USD");
+    pub const GBP: Currency = Currency(*b"GBP");
+
+    pub fn new(code: &str) -> Result<Self, MoneyError> {
+        let bytes = code.as_bytes();
+        if bytes.len() != 3 || !bytes.iter().all(|b| b.is_ascii_uppercase()) {
+            return Err(MoneyError::InvalidCurrencyCode(code.to_string()));
+        }
+        Ok(Currency([bytes[0], bytes[1], bytes[2]]))
+    }
+
+    pub fn code(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("currency codes are validated as ASCII")
+    }
+
+    // The number of decimal places used by the currency's minor unit, e.g. 2 for EUR (cents).
+    pub fn minor_unit_exponent(&self) -> u32 {
+        match self.code() {
+            "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF"
+            | "UGX" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+            "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+            _ => 2,
+        }
+    }
+}
+
+// Accounts and events created before currencies were introduced are assumed to be in euros.
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::EUR
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl Debug for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Currency({})", self.code())
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = MoneyError;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        Currency::new(&code)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.code().to_string()
+    }
+}
+
+// Currencies are exposed in GraphQL as their ISO 4217 code.
+#[Scalar]
+impl ScalarType for Currency {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::String(code) => Currency::new(&code).map_err(InputValueError::custom),
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.code().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn can_create_currency_from_valid_code() {
+        let currency = Currency::new("USD").unwrap();
+        assert_eq!(currency, Currency::USD);
+        assert_eq!(currency.code(), "USD");
+    }
+
+    #[test]
+    fn cannot_create_currency_from_invalid_code() {
+        assert_eq!(
+            Currency::new("usd"),
+            Err(MoneyError::InvalidCurrencyCode("usd".to_string()))
+        );
+        assert_eq!(
+            Currency::new("EURO"),
+            Err(MoneyError::InvalidCurrencyCode("EURO".to_string()))
+        );
+    }
+
+    #[test]
+    fn minor_unit_exponent_matches_iso_4217() {
+        assert_eq!(Currency::EUR.minor_unit_exponent(), 2);
+        assert_eq!(Currency::new("JPY").unwrap().minor_unit_exponent(), 0);
+        assert_eq!(Currency::new("KWD").unwrap().minor_unit_exponent(), 3);
+    }
+
+    #[test]
+    fn currency_serializes_as_code() {
+        let json = serde_json::to_string(&Currency::GBP).unwrap();
+        assert_eq!(json, "\"GBP\"");
+        let currency: Currency = serde_json::from_str(&json).unwrap();
+        assert_eq!(currency, Currency::GBP);
+    }
+
+    #[test]
+    fn default_currency_is_eur() {
+        assert_eq!(Currency::default(), Currency::EUR);
+    }
+}
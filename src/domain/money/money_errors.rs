@@ -0,0 +1,35 @@
+use std::fmt::Debug;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum MoneyError {
+    #[error("invalid currency code: {0}")]
+    InvalidCurrencyCode(String),
+
+    #[error("currency mismatch: expected {expected}, found {found}")]
+    CurrencyMismatch { expected: String, found: String },
+
+    #[error("invalid exchange rate: {0}")]
+    InvalidExchangeRate(String),
+
+    #[error("amount overflow")]
+    Overflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn currency_mismatch_error_message_contains_both_currencies() {
+        let error = MoneyError::CurrencyMismatch {
+            expected: "EUR".to_string(),
+            found: "USD".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "currency mismatch: expected EUR, found USD"
+        );
+    }
+}
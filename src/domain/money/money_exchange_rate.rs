@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display};
+
+use super::{Currency, Money, MoneyError};
+
+// Exchange rates are stored as integers scaled by 10^8, i.e. with eight decimal places.
+pub const EXCHANGE_RATE_SCALE: u32 = 8;
+
+// The number of `to` major units received for one `from` major unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    from: Currency,
+    to: Currency,
+    scaled_rate: i64,
+}
+
+// Records how an amount in a foreign currency was converted into the account's currency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrencyConversion {
+    pub original_amount: Money,
+    pub rate: ExchangeRate,
+}
+
+impl ExchangeRate {
+    pub fn new(from: Currency, to: Currency, scaled_rate: i64) -> Result<Self, MoneyError> {
+        if scaled_rate <= 0 {
+            return Err(MoneyError::InvalidExchangeRate(scaled_rate.to_string()));
+        }
+        Ok(Self {
+            from,
+            to,
+            scaled_rate,
+        })
+    }
+
+    // Parses a decimal rate such as "1.0845" without going through floating point.
+    pub fn from_decimal_str(from: Currency, to: Currency, rate: &str) -> Result<Self, MoneyError> {
+        let invalid = || MoneyError::InvalidExchangeRate(rate.to_string());
+        let (whole, fraction) = rate.trim().split_once('.').unwrap_or((rate.trim(), ""));
+        if whole.is_empty()
+            || fraction.len() > EXCHANGE_RATE_SCALE as usize
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let padded_fraction = format!("{fraction:0<width$}", width = EXCHANGE_RATE_SCALE as usize);
+        let scaled_rate = format!("{whole}{padded_fraction}")
+            .parse::<i64>()
+            .map_err(|_| invalid())?;
+        Self::new(from, to, scaled_rate).map_err(|_| invalid())
+    }
+
+    pub fn source_currency(&self) -> Currency {
+        self.from
+    }
+
+    pub fn target_currency(&self) -> Currency {
+        self.to
+    }
+
+    pub fn scaled_rate(&self) -> i64 {
+        self.scaled_rate
+    }
+
+    // Converts an amount in the `from` currency into the `to` currency, rounding half away
+    // from zero to the nearest minor unit of the `to` currency.
+    pub fn convert(&self, amount: &Money) -> Result<Money, MoneyError> {
+        if amount.currency() != self.from {
+            return Err(MoneyError::CurrencyMismatch {
+                expected: self.from.to_string(),
+                found: amount.currency().to_string(),
+            });
+        }
+        let numerator = i128::from(amount.minor_units())
+            .checked_mul(i128::from(self.scaled_rate))
+            .and_then(|n| n.checked_mul(10_i128.pow(self.to.minor_unit_exponent())))
+            .ok_or(MoneyError::Overflow)?;
+        let denominator =
+            10_i128.pow(EXCHANGE_RATE_SCALE + amount.currency().minor_unit_exponent());
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        let rounded = if remainder.abs() * 2 >= denominator {
+            quotient + numerator.signum()
+        } else {
+            quotient
+        };
+        let minor_units = i64::try_from(rounded).map_err(|_| MoneyError::Overflow)?;
+        Ok(Money::new(minor_units, self.to))
+    }
+}
+
+impl Display for ExchangeRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let factor = 10_i64.pow(EXCHANGE_RATE_SCALE);
+        write!(
+            f,
+            "1 {} = {}.{:0width$} {}",
+            self.from,
+            self.scaled_rate / factor,
+            self.scaled_rate % factor,
+            self.to,
+            width = EXCHANGE_RATE_SCALE as usize
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn can_parse_decimal_exchange_rate() {
+        let rate = ExchangeRate::from_decimal_str(Currency::USD, Currency::EUR, "0.92").unwrap();
+        assert_eq!(rate.scaled_rate(), 92_000_000);
+        assert_eq!(rate.to_string(), "1 USD = 0.92000000 EUR");
+    }
+
+    #[test]
+    fn cannot_parse_invalid_exchange_rates() {
+        for rate in ["", "-1.2", "abc", "1.123456789", "0", "0.0"] {
+            assert_eq!(
+                ExchangeRate::from_decimal_str(Currency::USD, Currency::EUR, rate),
+                Err(MoneyError::InvalidExchangeRate(rate.to_string())),
+                "expected {rate:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn convert_rounds_half_away_from_zero() {
+        let rate = ExchangeRate::from_decimal_str(Currency::USD, Currency::EUR, "0.925").unwrap();
+        // 0.10 USD * 0.925 = 0.0925 EUR, which rounds to 0.09 EUR
+        assert_eq!(
+            rate.convert(&Money::new(10, Currency::USD)).unwrap(),
+            Money::new(9, Currency::EUR)
+        );
+        // 0.30 USD * 0.925 = 0.2775 EUR, which rounds to 0.28 EUR
+        assert_eq!(
+            rate.convert(&Money::new(30, Currency::USD)).unwrap(),
+            Money::new(28, Currency::EUR)
+        );
+    }
+
+    #[test]
+    fn convert_accounts_for_different_minor_units() {
+        let jpy = Currency::new("JPY").unwrap();
+        let rate = ExchangeRate::from_decimal_str(jpy, Currency::EUR, "0.0062").unwrap();
+        assert_eq!(
+            rate.convert(&Money::new(10_000, jpy)).unwrap(),
+            Money::new(6200, Currency::EUR)
+        );
+    }
+
+    #[test]
+    fn convert_rejects_amounts_in_the_wrong_currency() {
+        let rate = ExchangeRate::from_decimal_str(Currency::USD, Currency::EUR, "0.92").unwrap();
+        assert_eq!(
+            rate.convert(&Money::new(100, Currency::GBP)),
+            Err(MoneyError::CurrencyMismatch {
+                expected: "USD".to_string(),
+                found: "GBP".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use super::{Currency, MoneyError};
+
+// A monetary amount stored as an integer number of minor units (e.g. cents) in a given
+// currency, so that balances never suffer from floating point rounding drift.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+#[serde(try_from = "MoneyRepresentation")]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct Money {
+    /// The amount in the currency's minor unit, e.g. 1050 is 10.50 EUR
+    #[ts(type = "number")]
+    minor_units: i64,
+
+    /// ISO 4217 currency code
+    #[schema(value_type = String, example = "EUR")]
+    #[ts(type = "string")]
+    currency: Currency,
+}
+
+// Events persisted before `Money` was introduced store amounts as bare floating point
+// numbers in major units, so both representations are accepted when deserializing.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MoneyRepresentation {
+    MinorUnits {
+        minor_units: i64,
+        currency: Currency,
+    },
+    LegacyMajorUnits(f64),
+}
+
+impl TryFrom<MoneyRepresentation> for Money {
+    type Error = MoneyError;
+
+    fn try_from(representation: MoneyRepresentation) -> Result<Self, Self::Error> {
+        match representation {
+            MoneyRepresentation::MinorUnits {
+                minor_units,
+                currency,
+            } => Ok(Money::new(minor_units, currency)),
+            MoneyRepresentation::LegacyMajorUnits(amount) => {
+                Money::from_major_units(amount, Currency::default())
+            }
+        }
+    }
+}
+
+impl Money {
+    pub const fn new(minor_units: i64, currency: Currency) -> Self {
+        Self {
+            minor_units,
+            currency,
+        }
+    }
+
+    pub const fn zero(currency: Currency) -> Self {
+        Self::new(0, currency)
+    }
+
+    // Converts a floating point amount in major units (e.g. 10.5 EUR) to minor units,
+    // rounding to the nearest minor unit.
+    pub fn from_major_units(amount: f64, currency: Currency) -> Result<Self, MoneyError> {
+        let scaled = (amount * 10_f64.powi(currency.minor_unit_exponent() as i32)).round();
+        if !scaled.is_finite() || scaled.abs() >= i64::MAX as f64 {
+            return Err(MoneyError::Overflow);
+        }
+        Ok(Self::new(scaled as i64, currency))
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.minor_units < 0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor_units == 0
+    }
+
+    /// The amount in major units without the currency code, e.g. "-10.50"
+    pub fn to_major_units_string(self) -> String {
+        let sign = if self.is_negative() { "-" } else { "" };
+        let absolute = self.minor_units.unsigned_abs();
+        let exponent = self.currency.minor_unit_exponent();
+        if exponent == 0 {
+            return format!("{sign}{absolute}");
+        }
+        let factor = 10_u64.pow(exponent);
+        format!(
+            "{sign}{}.{:0width$}",
+            absolute / factor,
+            absolute % factor,
+            width = exponent as usize
+        )
+    }
+
+    pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.ensure_same_currency(other)?;
+        self.minor_units
+            .checked_add(other.minor_units)
+            .map(|minor_units| Money::new(minor_units, self.currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.ensure_same_currency(other)?;
+        self.minor_units
+            .checked_sub(other.minor_units)
+            .map(|minor_units| Money::new(minor_units, self.currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    pub fn checked_neg(&self) -> Result<Money, MoneyError> {
+        self.minor_units
+            .checked_neg()
+            .map(|minor_units| Money::new(minor_units, self.currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    fn ensure_same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch {
+                expected: self.currency.to_string(),
+                found: other.currency.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.to_major_units_string(), self.currency)
+    }
+}
+
+// Money is exposed in GraphQL as a scalar using the same shape as its JSON representation.
+#[Scalar]
+impl ScalarType for Money {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        async_graphql::from_value(value).map_err(InputValueError::custom)
+    }
+
+    fn to_value(&self) -> Value {
+        async_graphql::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn can_add_money_of_the_same_currency() {
+        let sum = Money::new(1050, Currency::EUR)
+            .checked_add(&Money::new(250, Currency::EUR))
+            .unwrap();
+        assert_eq!(sum, Money::new(1300, Currency::EUR));
+    }
+
+    #[test]
+    fn can_subtract_money_of_the_same_currency() {
+        let difference = Money::new(1050, Currency::EUR)
+            .checked_sub(&Money::new(2000, Currency::EUR))
+            .unwrap();
+        assert_eq!(difference, Money::new(-950, Currency::EUR));
+        assert!(difference.is_negative());
+    }
+
+    #[test]
+    fn cannot_add_money_of_different_currencies() {
+        let result = Money::new(100, Currency::EUR).checked_add(&Money::new(100, Currency::USD));
+        assert_eq!(
+            result,
+            Err(MoneyError::CurrencyMismatch {
+                expected: "EUR".to_string(),
+                found: "USD".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn addition_overflow_returns_error() {
+        let result = Money::new(i64::MAX, Currency::EUR).checked_add(&Money::new(1, Currency::EUR));
+        assert_eq!(result, Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn from_major_units_rounds_to_nearest_minor_unit() {
+        assert_eq!(
+            Money::from_major_units(0.1 + 0.2, Currency::EUR).unwrap(),
+            Money::new(30, Currency::EUR)
+        );
+        assert_eq!(
+            Money::from_major_units(1500.0, Currency::new("JPY").unwrap()).unwrap(),
+            Money::new(1500, Currency::new("JPY").unwrap())
+        );
+    }
+
+    #[test]
+    fn from_major_units_rejects_non_finite_amounts() {
+        assert_eq!(
+            Money::from_major_units(f64::INFINITY, Currency::EUR),
+            Err(MoneyError::Overflow)
+        );
+    }
+
+    #[test]
+    fn money_displays_in_major_units() {
+        assert_eq!(Money::new(1050, Currency::EUR).to_string(), "10.50 EUR");
+        assert_eq!(Money::new(-5, Currency::USD).to_string(), "-0.05 USD");
+        assert_eq!(
+            Money::new(1500, Currency::new("JPY").unwrap()).to_string(),
+            "1500 JPY"
+        );
+    }
+
+    #[test]
+    fn money_serializes_as_minor_units_and_currency() {
+        let json = serde_json::to_string(&Money::new(1050, Currency::EUR)).unwrap();
+        assert_eq!(json, r#"{"minor_units":1050,"currency":"EUR"}"#);
+        let money: Money = serde_json::from_str(&json).unwrap();
+        assert_eq!(money, Money::new(1050, Currency::EUR));
+    }
+
+    #[test]
+    fn money_deserializes_legacy_floating_point_amounts_as_euros() {
+        let money: Money = serde_json::from_str("200.5").unwrap();
+        assert_eq!(money, Money::new(20050, Currency::EUR));
+    }
+}
@@ -0,0 +1,12 @@
+pub mod transfer_aggregate;
+pub mod transfer_commands;
+pub mod transfer_errors;
+pub mod transfer_events;
+pub mod transfer_status;
+
+// Re-exports
+pub use transfer_aggregate::*;
+pub use transfer_commands::*;
+pub use transfer_errors::*;
+pub use transfer_events::*;
+pub use transfer_status::*;
@@ -0,0 +1,226 @@
+use super::*;
+use async_trait::async_trait;
+use cqrs_es::Aggregate;
+use serde::{Deserialize, Serialize};
+use tracing::*;
+
+use crate::domain::Money;
+
+// A transfer of money between two bank accounts. The aggregate only records the progress of
+// the transfer, the money itself is moved by the transfer process manager, which issues
+// commands against the bank accounts involved and reports each outcome back here.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Transfer {
+    source_account_id: String,
+    destination_account_id: String,
+    amount: Money,
+    status: TransferStatus,
+}
+
+#[async_trait]
+impl Aggregate for Transfer {
+    type Command = TransferCommand;
+    type Event = TransferEvent;
+    type Error = TransferError;
+    type Services = ();
+
+    // This identifier should be unique to the system.
+    fn aggregate_type() -> String {
+        "transfer".to_string()
+    }
+
+    #[instrument(skip(_services))]
+    async fn handle(
+        &self,
+        command: Self::Command,
+        _services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        match command {
+            TransferCommand::RequestTransfer(command) => self.handle_request_transfer(command),
+            TransferCommand::RecordSourceDebited => {
+                self.ensure_status(TransferStatus::Requested)?;
+                Ok(vec![TransferEvent::SourceAccountDebited {
+                    account_id: self.source_account_id.clone(),
+                }])
+            }
+            TransferCommand::RecordSourceDebitFailed { reason } => {
+                self.ensure_status(TransferStatus::Requested)?;
+                Ok(vec![TransferEvent::SourceAccountDebitFailed {
+                    account_id: self.source_account_id.clone(),
+                    reason,
+                }])
+            }
+            TransferCommand::RecordDestinationCredited => {
+                self.ensure_status(TransferStatus::SourceDebited)?;
+                Ok(vec![TransferEvent::DestinationAccountCredited {
+                    account_id: self.destination_account_id.clone(),
+                }])
+            }
+            TransferCommand::RecordDestinationCreditFailed { reason } => {
+                self.ensure_status(TransferStatus::SourceDebited)?;
+                Ok(vec![TransferEvent::DestinationAccountCreditFailed {
+                    account_id: self.destination_account_id.clone(),
+                    reason,
+                }])
+            }
+            TransferCommand::RecordSourceRefunded => {
+                self.ensure_status(TransferStatus::Compensating)?;
+                Ok(vec![TransferEvent::SourceAccountRefunded {
+                    account_id: self.source_account_id.clone(),
+                }])
+            }
+        }
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            TransferEvent::TransferRequested {
+                source_account_id,
+                destination_account_id,
+                amount,
+            } => {
+                self.source_account_id = source_account_id;
+                self.destination_account_id = destination_account_id;
+                self.amount = amount;
+                self.status = TransferStatus::Requested;
+            }
+            TransferEvent::SourceAccountDebited { .. } => {
+                self.status = TransferStatus::SourceDebited;
+            }
+            TransferEvent::SourceAccountDebitFailed { .. } => {
+                self.status = TransferStatus::Failed;
+            }
+            TransferEvent::DestinationAccountCredited { .. } => {
+                self.status = TransferStatus::Completed;
+            }
+            TransferEvent::DestinationAccountCreditFailed { .. } => {
+                self.status = TransferStatus::Compensating;
+            }
+            TransferEvent::SourceAccountRefunded { .. } => {
+                self.status = TransferStatus::Compensated;
+            }
+        }
+    }
+}
+
+impl Transfer {
+    fn handle_request_transfer(
+        &self,
+        command: TransferRequestTransferCommandData,
+    ) -> Result<Vec<TransferEvent>, TransferError> {
+        if self.status != TransferStatus::NotRequested {
+            error!("transfer already requested");
+            return Err(TransferError::TransferAlreadyRequested);
+        }
+        if command.amount.is_negative() || command.amount.is_zero() {
+            error!("invalid transfer amount");
+            return Err(TransferError::InvalidAmount);
+        }
+        if command.source_account_id == command.destination_account_id {
+            error!("cannot transfer to the same account");
+            return Err(TransferError::CannotTransferToSameAccount);
+        }
+        Ok(vec![TransferEvent::TransferRequested {
+            source_account_id: command.source_account_id,
+            destination_account_id: command.destination_account_id,
+            amount: command.amount,
+        }])
+    }
+
+    fn ensure_status(&self, expected: TransferStatus) -> Result<(), TransferError> {
+        if self.status != expected {
+            error!("transfer step not allowed in status {:?}", self.status);
+            return Err(TransferError::InvalidTransferStep(format!(
+                "{:?}",
+                self.status
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod aggregate_tests {
+    use coverage_helper::test;
+    use cqrs_es::test::TestFramework;
+
+    use super::*;
+    use crate::domain::Currency;
+
+    type TransferTestFramework = TestFramework<Transfer>;
+
+    fn transfer_requested() -> TransferEvent {
+        TransferEvent::TransferRequested {
+            source_account_id: "1234".to_string(),
+            destination_account_id: "5678".to_string(),
+            amount: Money::new(5000, Currency::EUR),
+        }
+    }
+
+    fn request_transfer(source: &str, destination: &str, amount: i64) -> TransferCommand {
+        TransferCommand::RequestTransfer(TransferRequestTransferCommandData {
+            source_account_id: source.to_string(),
+            destination_account_id: destination.to_string(),
+            amount: Money::new(amount, Currency::EUR),
+        })
+    }
+
+    #[test]
+    fn request_transfer_succeeds() {
+        TransferTestFramework::with(())
+            .given_no_previous_events()
+            .when(request_transfer("1234", "5678", 5000))
+            .then_expect_events(vec![transfer_requested()]);
+    }
+
+    #[test]
+    fn cannot_request_transfer_twice() {
+        TransferTestFramework::with(())
+            .given(vec![transfer_requested()])
+            .when(request_transfer("1234", "5678", 5000))
+            .then_expect_error(TransferError::TransferAlreadyRequested);
+    }
+
+    #[test]
+    fn cannot_transfer_zero_amount() {
+        TransferTestFramework::with(())
+            .given_no_previous_events()
+            .when(request_transfer("1234", "5678", 0))
+            .then_expect_error(TransferError::InvalidAmount);
+    }
+
+    #[test]
+    fn cannot_transfer_to_the_same_account() {
+        TransferTestFramework::with(())
+            .given_no_previous_events()
+            .when(request_transfer("1234", "1234", 5000))
+            .then_expect_error(TransferError::CannotTransferToSameAccount);
+    }
+
+    #[test]
+    fn failed_credit_is_compensated_by_refunding_the_source() {
+        TransferTestFramework::with(())
+            .given(vec![
+                transfer_requested(),
+                TransferEvent::SourceAccountDebited {
+                    account_id: "1234".to_string(),
+                },
+                TransferEvent::DestinationAccountCreditFailed {
+                    account_id: "5678".to_string(),
+                    reason: "account closed".to_string(),
+                },
+            ])
+            .when(TransferCommand::RecordSourceRefunded)
+            .then_expect_events(vec![TransferEvent::SourceAccountRefunded {
+                account_id: "1234".to_string(),
+            }]);
+    }
+
+    #[test]
+    fn cannot_credit_destination_before_source_is_debited() {
+        TransferTestFramework::with(())
+            .given(vec![transfer_requested()])
+            .when(TransferCommand::RecordDestinationCredited)
+            .then_expect_error(TransferError::InvalidTransferStep("Requested".to_string()));
+    }
+}
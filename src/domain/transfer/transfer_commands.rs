@@ -0,0 +1,26 @@
+use async_graphql::InputObject;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use crate::domain::Money;
+
+// `RequestTransfer` is issued by clients, the remaining commands are issued by the transfer
+// process manager to record the outcome of each step against the bank accounts involved.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum TransferCommand {
+    RequestTransfer(TransferRequestTransferCommandData),
+    RecordSourceDebited,
+    RecordSourceDebitFailed { reason: String },
+    RecordDestinationCredited,
+    RecordDestinationCreditFailed { reason: String },
+    RecordSourceRefunded,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, InputObject, Clone, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct TransferRequestTransferCommandData {
+    pub source_account_id: String,
+    pub destination_account_id: String,
+    pub amount: Money,
+}
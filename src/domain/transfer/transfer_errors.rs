@@ -0,0 +1,55 @@
+use std::fmt::Debug;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum TransferError {
+    #[error("transfer already requested")]
+    TransferAlreadyRequested,
+
+    #[error("transfer amount must be positive")]
+    InvalidAmount,
+
+    #[error("cannot transfer to the same account")]
+    CannotTransferToSameAccount,
+
+    #[error("transfer step not allowed in status {0}")]
+    InvalidTransferStep(String),
+
+    #[error("Unexpected Error: {0}")]
+    UnexpectedError(String),
+}
+
+impl From<&str> for TransferError {
+    fn from(msg: &str) -> Self {
+        match msg {
+            "TransferAlreadyRequested" => TransferError::TransferAlreadyRequested,
+            "InvalidAmount" => TransferError::InvalidAmount,
+            "CannotTransferToSameAccount" => TransferError::CannotTransferToSameAccount,
+            _ => TransferError::UnexpectedError(msg.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coverage_helper::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn can_create_transfer_error_from_string() {
+        let error = TransferError::from("TransferAlreadyRequested");
+        assert_eq!(error, TransferError::TransferAlreadyRequested);
+
+        let error = TransferError::from("InvalidAmount");
+        assert_eq!(error, TransferError::InvalidAmount);
+
+        let error = TransferError::from("CannotTransferToSameAccount");
+        assert_eq!(error, TransferError::CannotTransferToSameAccount);
+
+        let error = TransferError::from("AnyNonMatchingErrorString");
+        assert_eq!(
+            error,
+            TransferError::UnexpectedError("AnyNonMatchingErrorString".to_string())
+        );
+    }
+}
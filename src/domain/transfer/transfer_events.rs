@@ -0,0 +1,91 @@
+use cqrs_es::DomainEvent;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+use crate::domain::Money;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TransferEvent {
+    TransferRequested {
+        source_account_id: String,
+        destination_account_id: String,
+        amount: Money,
+    },
+    SourceAccountDebited {
+        account_id: String,
+    },
+    SourceAccountDebitFailed {
+        account_id: String,
+        reason: String,
+    },
+    DestinationAccountCredited {
+        account_id: String,
+    },
+    DestinationAccountCreditFailed {
+        account_id: String,
+        reason: String,
+    },
+    SourceAccountRefunded {
+        account_id: String,
+    },
+}
+
+impl DomainEvent for TransferEvent {
+    fn event_type(&self) -> String {
+        match self {
+            TransferEvent::TransferRequested { .. } => "TransferRequested".to_string(),
+            TransferEvent::SourceAccountDebited { .. } => "SourceAccountDebited".to_string(),
+            TransferEvent::SourceAccountDebitFailed { .. } => {
+                "SourceAccountDebitFailed".to_string()
+            }
+            TransferEvent::DestinationAccountCredited { .. } => {
+                "DestinationAccountCredited".to_string()
+            }
+            TransferEvent::DestinationAccountCreditFailed { .. } => {
+                "DestinationAccountCreditFailed".to_string()
+            }
+            TransferEvent::SourceAccountRefunded { .. } => "SourceAccountRefunded".to_string(),
+        }
+    }
+
+    fn event_version(&self) -> String {
+        "1.0".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn transfer_event_version_is_1_0() {
+        let event = TransferEvent::SourceAccountDebited {
+            account_id: "123".to_string(),
+        };
+        assert_eq!(event.event_version(), "1.0".to_string());
+    }
+
+    #[test]
+    fn transfer_event_type_is_transfer_requested() {
+        let event = TransferEvent::TransferRequested {
+            source_account_id: "123".to_string(),
+            destination_account_id: "456".to_string(),
+            amount: Money::new(10000, Currency::EUR),
+        };
+        assert_eq!(event.event_type(), "TransferRequested".to_string());
+    }
+
+    #[test]
+    fn transfer_event_type_is_destination_account_credit_failed() {
+        let event = TransferEvent::DestinationAccountCreditFailed {
+            account_id: "456".to_string(),
+            reason: "account closed".to_string(),
+        };
+        assert_eq!(
+            event.event_type(),
+            "DestinationAccountCreditFailed".to_string()
+        );
+    }
+}
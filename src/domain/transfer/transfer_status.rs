@@ -0,0 +1,32 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Enum, TS,
+)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum TransferStatus {
+    /// The transfer has not been requested yet
+    #[default]
+    NotRequested,
+
+    /// The transfer has been accepted and the source account is about to be debited
+    Requested,
+
+    /// The source account has been debited, the destination account is about to be credited
+    SourceDebited,
+
+    /// The destination account has been credited
+    Completed,
+
+    /// The source account could not be debited, no money has moved
+    Failed,
+
+    /// The destination account could not be credited, the source account is being refunded
+    Compensating,
+
+    /// The destination account could not be credited and the source account has been refunded
+    Compensated,
+}
@@ -21,11 +21,29 @@ pub enum Error {
     #[error(transparent)]
     Sqlx(#[from] sqlx::Error),
 
+    #[error(transparent)]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+
     #[error(transparent)]
     SetLoggerError(#[from] log::SetLoggerError),
 
     #[error(transparent)]
     ParseIntError(#[from] std::num::ParseIntError),
+
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    SerdeYamlError(#[from] serde_yaml::Error),
+
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    MoneyError(#[from] crate::domain::MoneyError),
+
+    #[error(transparent)]
+    Persistence(#[from] cqrs_es::persist::PersistenceError),
 }
 
 #[cfg(test)]
@@ -1,8 +1,13 @@
 #![cfg_attr(coverage_nightly, feature(no_coverage))]
 
+pub mod any_es;
 pub mod application;
 pub mod configuration;
 pub mod domain;
 pub mod error;
+#[cfg(feature = "in-memory")]
+pub mod memory_es;
 pub mod prelude;
 pub mod presentation;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_es;
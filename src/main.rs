@@ -6,7 +6,10 @@
 
 use axum::{routing::get, Extension, Router, Server};
 use axum_prometheus::PrometheusMetricLayer;
-use hyper::{header::CONTENT_TYPE, Method};
+use hyper::{
+    header::{HeaderName, CONTENT_TYPE},
+    Method,
+};
 use presentation::ApiDoc;
 
 use tower::ServiceBuilder;
@@ -15,32 +18,20 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::prelude::*;
+mod any_es;
 mod error;
-use cfg_if::cfg_if;
 use dotenvy::dotenv;
 mod application;
 mod configuration;
 mod domain;
+#[cfg(feature = "in-memory")]
+mod memory_es;
 mod prelude;
 mod presentation;
+#[cfg(feature = "sqlite")]
+mod sqlite_es;
 use tracing_log::LogTracer;
 
-cfg_if! {
-    if #[cfg(feature = "postgres")] {
-        use sqlx::{Pool, Postgres};
-        async fn get_db_connection() -> crate::prelude::Result<Pool<Postgres>> {
-            configuration::get_db_connection_postgres_sqlx().await
-        }
-    } else if #[cfg(feature = "mysql")] {
-        use sqlx::{Pool, mysql};
-        async fn get_db_connection() -> crate::prelude::Result<Pool<mysql::MySql>> {
-            configuration::get_db_connection_mysql_sqlx().await
-        }
-    } else {
-        compile_error!("Must specify either mysql or postgres feature");
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
@@ -54,39 +45,188 @@ async fn main() -> Result<()> {
         }
     };
 
-    let pool = get_db_connection().await?;
-    let (cqrs, account_query) = presentation::get_bank_account_cqrs_framework(pool);
+    // Load the exchange rates used to convert foreign currency transactions
+    let fx_rates = match dotenvy::var("FX_RATES_FILE_PATH") {
+        Ok(path) => application::FxRateTable::load(&path)?,
+        Err(_) => {
+            tracing::warn!(
+                "FX_RATES_FILE_PATH not set, foreign currency transactions will be rejected"
+            );
+            application::FxRateTable::default()
+        }
+    };
+    // Load the tunable bank account rules, such as overdraft fees, how long idempotency
+    // keys are kept and which database to run against
+    let (bank_account_configuration, idempotency_policy, database_configuration) =
+        match dotenvy::var("CONFIGURATION_FILE_PATH") {
+            Ok(_) => {
+                let app_configuration = configuration::load_app_configuration().await?;
+                (
+                    app_configuration.bank_account,
+                    app_configuration.idempotency,
+                    app_configuration.database,
+                )
+            }
+            Err(_) => {
+                tracing::warn!("CONFIGURATION_FILE_PATH not set, using default bank account rules");
+                (
+                    application::BankAccountConfiguration::default(),
+                    application::IdempotencyPolicy::default(),
+                    configuration::DatabaseConfiguration::default(),
+                )
+            }
+        };
+    let db = configuration::get_db_connection(&database_configuration).await?;
+    // `veloxide migrate` brings the schema up to date and exits. `veloxide backfill-ledger`
+    // posts the stored bank account events to the general ledger and exits, it needs the
+    // schema to be up to date
+    let command = std::env::args().nth(1);
+    match command.as_deref() {
+        Some("migrate") => {
+            db.migrate().await?;
+            tracing::info!("{} database migrated", db.backend());
+            return Ok(());
+        }
+        Some("backfill-ledger") | None => {}
+        Some(command) => {
+            return Err(Error::Generic(format!(
+                "unknown command `{command}`, the commands are `migrate` and `backfill-ledger`"
+            )));
+        }
+    }
+    if database_configuration.run_migrations {
+        db.migrate().await?;
+    }
+    let pending_migrations = db.pending_migrations().await?;
+    if !pending_migrations.is_empty() {
+        return Err(Error::Generic(format!(
+            "the {} database is missing migrations {pending_migrations:?}, run the `migrate` \
+             command or set `database.run_migrations` in the configuration file",
+            db.backend()
+        )));
+    }
+    if command.as_deref() == Some("backfill-ledger") {
+        let events = presentation::backfill_general_ledger(db.clone()).await?;
+        tracing::info!("{events} bank account events posted to the general ledger");
+        return Ok(());
+    }
+    let interest_configuration = bank_account_configuration.interest.clone();
+    let products = bank_account_configuration.products.clone();
+    let charges_maintenance_fees = bank_account_configuration.fees.charges_maintenance_fees();
+    let (customer_cqrs, customer_query) = presentation::get_customer_cqrs_framework(db.clone());
+    let services = application::BankAccountServices::new(Box::new(
+        application::HappyPathBankAccountServices::new(fx_rates),
+    ))
+    .with_configuration(bank_account_configuration)
+    // Accounts are only opened for customers who passed KYC
+    .with_customers(Box::new(presentation::CustomerViewKycLookup::new(
+        customer_query.clone(),
+    )));
+    let (cqrs, account_query, statement_query, standing_order_query) =
+        presentation::get_bank_account_cqrs_framework(db.clone(), services);
+    let (transfer_cqrs, transfer_query, transfer_requested) =
+        presentation::get_transfer_cqrs_framework(db.clone());
+    let general_ledger = presentation::new_ledger_store(db.clone());
+    let idempotent_commands = std::sync::Arc::new(application::IdempotentCommands::new(
+        presentation::new_idempotency_store(db.clone()),
+        std::sync::Arc::new(presentation::EventStoreCommittedRequests::new(db.clone())),
+        idempotency_policy,
+    ));
+
+    // Carry out requested transfers in the background, picking up those left unfinished
+    let transfer_saga = application::TransferSaga::new(
+        cqrs.clone(),
+        transfer_cqrs.clone(),
+        std::sync::Arc::new(presentation::TransferViewUnfinishedTransfersLookup::new(
+            db.clone(),
+        )),
+    );
+    tokio::spawn(transfer_saga.run(transfer_requested));
+
+    // Accrue and post interest for open accounts in the background
+    if interest_configuration.enabled {
+        let scheduler = application::InterestAccrualScheduler::new(
+            cqrs.clone(),
+            std::sync::Arc::new(presentation::AccountViewOpenAccountsLookup::new(db.clone())),
+            products,
+            interest_configuration,
+        );
+        tokio::spawn(scheduler.run());
+    }
+
+    // Charge monthly maintenance fees for open accounts in the background
+    if charges_maintenance_fees {
+        let scheduler = application::MaintenanceFeeScheduler::new(
+            cqrs.clone(),
+            std::sync::Arc::new(presentation::AccountViewOpenAccountsLookup::new(db.clone())),
+        );
+        tokio::spawn(scheduler.run());
+    }
+
+    // Forget lapsed idempotency keys in the background
+    tokio::spawn(idempotent_commands.clone().run());
+
+    // Retry posting the events that could not be posted to the general ledger in the background
+    tokio::spawn(presentation::new_general_ledger(db.clone()).run());
+
+    // Execute standing orders as their payments fall due in the background
+    let standing_order_scheduler = application::StandingOrderScheduler::new(
+        cqrs.clone(),
+        std::sync::Arc::new(presentation::StandingOrderViewDueStandingOrdersLookup::new(
+            db.clone(),
+        )),
+    );
+    tokio::spawn(standing_order_scheduler.run());
 
     // Set up Axum
 
     // Configure prometheus layer for Axum
     let (prometheus_layer, metric_handle) = PrometheusMetricLayer::pair();
+    // Connection pool gauges are rendered along with the request metrics
+    tokio::spawn(db.record_pool_metrics());
 
     // Configure CORS middleware for axum
     let cors = CorsLayer::new()
         .allow_methods([Method::GET, Method::POST])
-        .allow_headers([CONTENT_TYPE])
+        .allow_headers([
+            CONTENT_TYPE,
+            HeaderName::from_static("x-holder-id"),
+            HeaderName::from_static("x-operator-id"),
+            HeaderName::from_static("idempotency-key"),
+        ])
         // allow requests from any origin TODO: Make me more secure
         .allow_origin(Any);
 
     // Set up the GraphQL router
-    let graphql_router =
-        presentation::graphql::new_graphql_router(cqrs.clone(), account_query.clone()).await;
+    let graphql_router = presentation::graphql::new_graphql_router(
+        cqrs.clone(),
+        account_query.clone(),
+        transfer_cqrs.clone(),
+        transfer_query.clone(),
+        customer_cqrs.clone(),
+        customer_query.clone(),
+        idempotent_commands.clone(),
+    )
+    .await;
 
     // Set up the router
     let app = Router::new()
         .merge(SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi()))
-        .route(
-            "/api/bank-accounts/:id",
-            get(presentation::bank_account::query_handler)
-                .post(presentation::bank_account::command_handler),
-        )
+        .merge(presentation::new_api_router())
         .route("/metrics", get(|| async move { metric_handle.render() }))
         .nest("/graphql", graphql_router)
         .layer(
             ServiceBuilder::new()
                 .layer(Extension(cqrs.clone()))
                 .layer(Extension(account_query.clone()))
+                .layer(Extension(statement_query.clone()))
+                .layer(Extension(standing_order_query.clone()))
+                .layer(Extension(transfer_cqrs.clone()))
+                .layer(Extension(transfer_query.clone()))
+                .layer(Extension(customer_cqrs.clone()))
+                .layer(Extension(customer_query.clone()))
+                .layer(Extension(general_ledger.clone()))
+                .layer(Extension(idempotent_commands.clone()))
                 .layer(prometheus_layer)
                 .layer(cors),
         )
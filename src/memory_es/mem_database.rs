@@ -0,0 +1,95 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use cqrs_es::persist::SerializedEvent;
+use serde_json::Value;
+
+// A row of a table, the `version` is only used by view tables.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemRow {
+    pub version: i64,
+    pub payload: Value,
+}
+
+// Rows keyed by their id, kept in order so that scans are deterministic.
+pub type MemTable = BTreeMap<String, MemRow>;
+
+// The latest snapshot of an aggregate, as in the `snapshots` table of the other backends.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct MemSnapshot {
+    pub(super) last_sequence: usize,
+    pub(super) current_snapshot: usize,
+    pub(super) payload: Value,
+}
+
+// Event streams and snapshots keyed by aggregate type and aggregate id.
+#[derive(Default)]
+pub(super) struct MemEventTables {
+    pub(super) streams: HashMap<(String, String), Vec<SerializedEvent>>,
+    pub(super) snapshots: HashMap<(String, String), MemSnapshot>,
+}
+
+// Stands in for a connection pool: clones share the same events and tables, so the
+// frameworks, view repositories and stores built from one database see each other's writes.
+#[derive(Clone, Default)]
+pub struct MemDatabase {
+    events: Arc<RwLock<MemEventTables>>,
+    tables: Arc<RwLock<HashMap<String, MemTable>>>,
+}
+
+impl MemDatabase {
+    pub(super) fn read_events<R>(&self, f: impl FnOnce(&MemEventTables) -> R) -> R {
+        f(&self.events.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    // Events are appended while holding the lock, so that concurrent commands cannot both
+    // commit the same sequence number.
+    pub(super) fn write_events<R>(&self, f: impl FnOnce(&mut MemEventTables) -> R) -> R {
+        f(&mut self.events.write().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    // A table that was never written to reads as empty.
+    pub fn read<R>(&self, table: &str, f: impl FnOnce(&MemTable) -> R) -> R {
+        let tables = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+        match tables.get(table) {
+            Some(rows) => f(rows),
+            None => f(&MemTable::new()),
+        }
+    }
+
+    // Changes to a table are made while holding the lock, which makes a check followed by a
+    // write atomic.
+    pub fn write<R>(&self, table: &str, f: impl FnOnce(&mut MemTable) -> R) -> R {
+        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+        f(tables.entry(table.to_string()).or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn clones_share_their_tables() {
+        let db = MemDatabase::default();
+        db.clone().write("account_query", |rows| {
+            rows.insert(
+                "1234".to_string(),
+                MemRow {
+                    version: 1,
+                    payload: json!({ "status": "Open" }),
+                },
+            )
+        });
+
+        let statuses = db.read("account_query", |rows| {
+            rows.values()
+                .map(|row| row.payload["status"].clone())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(statuses, vec![json!("Open")]);
+        assert!(db.read("transfer_query", |rows| rows.is_empty()));
+    }
+}
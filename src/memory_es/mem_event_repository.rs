@@ -0,0 +1,281 @@
+use async_trait::async_trait;
+use cqrs_es::persist::{
+    PersistedEventRepository, PersistenceError, ReplayStream, SerializedEvent, SerializedSnapshot,
+};
+use cqrs_es::Aggregate;
+use serde_json::Value;
+
+use super::{MemDatabase, MemSnapshot};
+
+// How many events are buffered while replaying them to a query.
+const REPLAY_QUEUE_SIZE: usize = 1000;
+
+// Events and snapshots kept in a `MemDatabase`, with the same optimistic locking as the
+// database backends: committing a sequence number or snapshot that another command
+// committed first fails.
+pub struct MemEventRepository {
+    db: MemDatabase,
+}
+
+impl MemEventRepository {
+    pub fn new(db: MemDatabase) -> Self {
+        Self { db }
+    }
+
+    fn select_events(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: Option<&str>,
+        after_sequence: usize,
+    ) -> Vec<SerializedEvent> {
+        self.db.read_events(|tables| {
+            let mut events: Vec<SerializedEvent> = tables
+                .streams
+                .iter()
+                .filter(|((stream_type, stream_id), _)| {
+                    stream_type == aggregate_type
+                        && aggregate_id.is_none_or(|aggregate_id| stream_id == aggregate_id)
+                })
+                .flat_map(|(_, stream)| stream.iter())
+                .filter(|event| event.sequence > after_sequence)
+                .cloned()
+                .collect();
+            events
+                .sort_by(|a, b| (&a.aggregate_id, a.sequence).cmp(&(&b.aggregate_id, b.sequence)));
+            events
+        })
+    }
+
+    // The events of an aggregate, for lookups that only know its type by name.
+    pub fn aggregate_events(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: &str,
+    ) -> Vec<SerializedEvent> {
+        self.select_events(aggregate_type, Some(aggregate_id), 0)
+    }
+
+    // The ids of the aggregates of the type that have events, in order.
+    pub fn aggregate_ids(&self, aggregate_type: &str) -> Vec<String> {
+        self.db.read_events(|tables| {
+            let mut aggregate_ids: Vec<String> = tables
+                .streams
+                .keys()
+                .filter(|(stream_type, _)| stream_type == aggregate_type)
+                .map(|(_, stream_id)| stream_id.clone())
+                .collect();
+            aggregate_ids.sort();
+            aggregate_ids
+        })
+    }
+
+    async fn replay(&self, events: Vec<SerializedEvent>) -> Result<ReplayStream, PersistenceError> {
+        let (mut feed, stream) = ReplayStream::new(REPLAY_QUEUE_SIZE);
+        tokio::spawn(async move {
+            for event in events {
+                if feed.push(Ok(event)).await.is_err() {
+                    // The stream was dropped.
+                    return;
+                }
+            }
+        });
+        Ok(stream)
+    }
+}
+
+#[async_trait]
+impl PersistedEventRepository for MemEventRepository {
+    async fn get_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<Vec<SerializedEvent>, PersistenceError> {
+        Ok(self.select_events(&A::aggregate_type(), Some(aggregate_id), 0))
+    }
+
+    async fn get_last_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+        last_sequence: usize,
+    ) -> Result<Vec<SerializedEvent>, PersistenceError> {
+        Ok(self.select_events(&A::aggregate_type(), Some(aggregate_id), last_sequence))
+    }
+
+    async fn get_snapshot<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<Option<SerializedSnapshot>, PersistenceError> {
+        let key = (A::aggregate_type(), aggregate_id.to_string());
+        Ok(self.db.read_events(|tables| {
+            tables
+                .snapshots
+                .get(&key)
+                .map(|snapshot| SerializedSnapshot {
+                    aggregate_id: aggregate_id.to_string(),
+                    aggregate: snapshot.payload.clone(),
+                    current_sequence: snapshot.last_sequence,
+                    current_snapshot: snapshot.current_snapshot,
+                })
+        }))
+    }
+
+    async fn persist<A: Aggregate>(
+        &self,
+        events: &[SerializedEvent],
+        snapshot_update: Option<(String, Value, usize)>,
+    ) -> Result<(), PersistenceError> {
+        self.db.write_events(|tables| {
+            // Everything is checked before anything is written, so a failed commit leaves
+            // no trace.
+            for event in events {
+                let key = (event.aggregate_type.clone(), event.aggregate_id.clone());
+                let committed = tables.streams.get(&key).is_some_and(|stream| {
+                    stream
+                        .iter()
+                        .any(|committed| committed.sequence == event.sequence)
+                });
+                if committed {
+                    return Err(PersistenceError::OptimisticLockError);
+                }
+            }
+            let snapshot_update = match snapshot_update {
+                Some((aggregate_id, payload, current_snapshot)) => {
+                    let key = (A::aggregate_type(), aggregate_id);
+                    let previous_snapshot = tables
+                        .snapshots
+                        .get(&key)
+                        .map(|snapshot| snapshot.current_snapshot);
+                    // The first snapshot is inserted, later ones replace their predecessor.
+                    let expected_snapshot = match current_snapshot {
+                        1 => None,
+                        n => Some(n - 1),
+                    };
+                    if previous_snapshot != expected_snapshot {
+                        return Err(PersistenceError::OptimisticLockError);
+                    }
+                    let snapshot = MemSnapshot {
+                        last_sequence: events.last().map_or(0, |event| event.sequence),
+                        current_snapshot,
+                        payload,
+                    };
+                    Some((key, snapshot))
+                }
+                None => None,
+            };
+
+            for event in events {
+                tables
+                    .streams
+                    .entry((event.aggregate_type.clone(), event.aggregate_id.clone()))
+                    .or_default()
+                    .push(event.clone());
+            }
+            if let Some((key, snapshot)) = snapshot_update {
+                tables.snapshots.insert(key, snapshot);
+            }
+            Ok(())
+        })
+    }
+
+    async fn stream_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<ReplayStream, PersistenceError> {
+        let events = self.select_events(&A::aggregate_type(), Some(aggregate_id), 0);
+        self.replay(events).await
+    }
+
+    async fn stream_all_events<A: Aggregate>(&self) -> Result<ReplayStream, PersistenceError> {
+        let events = self.select_events(&A::aggregate_type(), None, 0);
+        self.replay(events).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Transfer;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn event(sequence: usize) -> SerializedEvent {
+        SerializedEvent {
+            aggregate_id: "transfer-1".to_string(),
+            sequence,
+            aggregate_type: "transfer".to_string(),
+            event_type: "SourceAccountDebited".to_string(),
+            event_version: "1.0".to_string(),
+            payload: json!({ "SourceAccountDebited": { "account_id": "1234" } }),
+            metadata: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn persisted_events_are_loaded_in_order() {
+        let repository = MemEventRepository::new(MemDatabase::default());
+        repository
+            .persist::<Transfer>(&[event(1), event(2)], None)
+            .await
+            .unwrap();
+        repository
+            .persist::<Transfer>(&[event(3)], None)
+            .await
+            .unwrap();
+
+        let events = repository
+            .get_events::<Transfer>("transfer-1")
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event(1), event(2), event(3)]);
+        let events = repository
+            .get_last_events::<Transfer>("transfer-1", 2)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event(3)]);
+    }
+
+    #[tokio::test]
+    async fn committing_an_existing_sequence_number_is_an_optimistic_lock_error() {
+        let db = MemDatabase::default();
+        let repository = MemEventRepository::new(db.clone());
+        repository
+            .persist::<Transfer>(&[event(1)], None)
+            .await
+            .unwrap();
+        let result = MemEventRepository::new(db)
+            .persist::<Transfer>(&[event(2), event(1)], None)
+            .await;
+        assert!(matches!(result, Err(PersistenceError::OptimisticLockError)));
+        let events = repository
+            .get_events::<Transfer>("transfer-1")
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event(1)]);
+    }
+
+    #[tokio::test]
+    async fn snapshots_replace_the_previous_snapshot() {
+        let repository = MemEventRepository::new(MemDatabase::default());
+        let snapshot = |n: usize| Some(("transfer-1".to_string(), json!({ "n": n }), n));
+        repository
+            .persist::<Transfer>(&[event(1)], snapshot(1))
+            .await
+            .unwrap();
+        repository
+            .persist::<Transfer>(&[event(2)], snapshot(2))
+            .await
+            .unwrap();
+        let result = repository
+            .persist::<Transfer>(&[event(3)], snapshot(2))
+            .await;
+        assert!(matches!(result, Err(PersistenceError::OptimisticLockError)));
+
+        let snapshot = repository
+            .get_snapshot::<Transfer>("transfer-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.aggregate, json!({ "n": 2 }));
+        assert_eq!(snapshot.current_sequence, 2);
+        assert_eq!(snapshot.current_snapshot, 2);
+    }
+}
@@ -0,0 +1,123 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use cqrs_es::persist::{PersistenceError, ViewContext, ViewRepository};
+use cqrs_es::{Aggregate, View};
+
+use super::{MemDatabase, MemRow};
+
+// Stores views as JSON in a table of their own, named like the view tables of the other
+// backends so that lookups can find them.
+pub struct MemViewRepository<V, A> {
+    view_name: String,
+    db: MemDatabase,
+    _phantom: PhantomData<(V, A)>,
+}
+
+impl<V, A> MemViewRepository<V, A>
+where
+    V: View<A>,
+    A: Aggregate,
+{
+    pub fn new(view_name: &str, db: MemDatabase) -> Self {
+        Self {
+            view_name: view_name.to_string(),
+            db,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<V, A> ViewRepository<V, A> for MemViewRepository<V, A>
+where
+    V: View<A>,
+    A: Aggregate,
+{
+    async fn load(&self, view_id: &str) -> Result<Option<V>, PersistenceError> {
+        Ok(self.load_with_context(view_id).await?.map(|(view, _)| view))
+    }
+
+    async fn load_with_context(
+        &self,
+        view_id: &str,
+    ) -> Result<Option<(V, ViewContext)>, PersistenceError> {
+        let Some(row) = self
+            .db
+            .read(&self.view_name, |rows| rows.get(view_id).cloned())
+        else {
+            return Ok(None);
+        };
+        let view = serde_json::from_value(row.payload)
+            .map_err(|err| PersistenceError::DeserializationError(Box::new(err)))?;
+        Ok(Some((
+            view,
+            ViewContext::new(view_id.to_string(), row.version),
+        )))
+    }
+
+    async fn update_view(&self, view: V, context: ViewContext) -> Result<(), PersistenceError> {
+        let payload = serde_json::to_value(&view)
+            .map_err(|err| PersistenceError::UnknownError(Box::new(err)))?;
+        self.db.write(&self.view_name, |rows| {
+            // A view is only updated from the version it was loaded at, version 0 being a
+            // view that was not stored yet.
+            let stored_version = rows
+                .get(&context.view_instance_id)
+                .map_or(0, |row| row.version);
+            if stored_version != context.version {
+                return Err(PersistenceError::OptimisticLockError);
+            }
+            rows.insert(
+                context.view_instance_id.clone(),
+                MemRow {
+                    version: context.version + 1,
+                    payload,
+                },
+            );
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Customer, CustomerEvent};
+    use crate::presentation::CustomerView;
+    use cqrs_es::EventEnvelope;
+    use std::collections::HashMap;
+
+    fn kyc_submitted() -> EventEnvelope<Customer> {
+        EventEnvelope {
+            aggregate_id: "customer-1".to_string(),
+            sequence: 1,
+            payload: CustomerEvent::KycSubmitted {
+                document_reference: "passport-1".to_string(),
+            },
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn views_are_updated_from_the_version_they_were_loaded_at() {
+        let repository =
+            MemViewRepository::<CustomerView, Customer>::new("customer_query", Default::default());
+        assert!(repository.load("customer-1").await.unwrap().is_none());
+
+        let mut view = CustomerView::default();
+        view.update(&kyc_submitted());
+        let context = ViewContext::new("customer-1".to_string(), 0);
+        repository.update_view(view, context).await.unwrap();
+
+        let (view, context) = repository
+            .load_with_context("customer-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(context.version, 1);
+        let stale_context = ViewContext::new("customer-1".to_string(), 0);
+        let result = repository.update_view(view, stale_context).await;
+        assert!(matches!(result, Err(PersistenceError::OptimisticLockError)));
+    }
+}
@@ -0,0 +1,11 @@
+//! An event store and view repository kept in memory, for exercising the REST and GraphQL
+//! APIs in tests and running demos without a database. Nothing survives a restart.
+
+pub mod mem_database;
+pub mod mem_event_repository;
+pub mod mem_view_repository;
+
+// Re-exports
+pub use mem_database::*;
+pub use mem_event_repository::*;
+pub use mem_view_repository::*;
@@ -0,0 +1,235 @@
+use super::*;
+
+// Who may issue a command against an account through the API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CommandIssuer {
+    // The holders of the account, and operators on their behalf.
+    Holder,
+    // Operators only, e.g. for credit decisions, corrections and the settlement of card
+    // payments and checks.
+    Operator,
+    // Nobody, the command is only issued by the service itself, e.g. by a background job or
+    // by the transfer saga, which debits and credits both sides of a transfer together.
+    Service,
+}
+
+fn command_issuer(command: &BankAccountCommand) -> CommandIssuer {
+    match command {
+        BankAccountCommand::OpenAccount(_)
+        | BankAccountCommand::DepositMoney(_)
+        | BankAccountCommand::WithdrawMoney(_)
+        | BankAccountCommand::WriteCheck(_)
+        | BankAccountCommand::FreezeAccount(_)
+        | BankAccountCommand::CloseAccount(_)
+        | BankAccountCommand::AddHolder(_)
+        | BankAccountCommand::RemoveHolder(_)
+        | BankAccountCommand::CreateStandingOrder(_)
+        | BankAccountCommand::CancelStandingOrder(_) => CommandIssuer::Holder,
+        BankAccountCommand::UnfreezeAccount(_)
+        | BankAccountCommand::SetOverdraftLimit(_)
+        | BankAccountCommand::PlaceHold(_)
+        | BankAccountCommand::CaptureHold(_)
+        | BankAccountCommand::ReleaseHold(_)
+        | BankAccountCommand::ClearCheck(_)
+        | BankAccountCommand::BounceCheck(_)
+        | BankAccountCommand::ReverseTransaction(_) => CommandIssuer::Operator,
+        BankAccountCommand::AccrueInterest(_)
+        | BankAccountCommand::ChargeMaintenanceFee(_)
+        | BankAccountCommand::ExecuteStandingOrder(_)
+        | BankAccountCommand::TransferOut(_)
+        | BankAccountCommand::TransferIn(_)
+        | BankAccountCommand::RefundTransfer(_)
+        | BankAccountCommand::CompleteTransfer(_) => CommandIssuer::Service,
+    }
+}
+
+// Customers may only open accounts for themselves and use the accounts they hold, and
+// commands against accounts that do not exist are left to operators.
+pub fn authorize_command(
+    view: Option<&BankAccountView>,
+    caller: &Caller,
+    command: &BankAccountCommand,
+) -> bool {
+    match (command_issuer(command), command) {
+        (CommandIssuer::Service, _) => false,
+        (CommandIssuer::Operator, _) => caller.is_operator(),
+        (CommandIssuer::Holder, BankAccountCommand::OpenAccount(command)) => {
+            caller.is_operator() || caller.customer_id() == Some(command.primary_holder_id.as_str())
+        }
+        (CommandIssuer::Holder, _) => {
+            caller.is_operator() || view.is_some_and(|view| authorize_read(view, caller))
+        }
+    }
+}
+
+// Accounts can be read by their holders and by operators.
+pub fn authorize_read(view: &BankAccountView, caller: &Caller) -> bool {
+    caller.is_operator() || caller.customer_id().is_some_and(|id| view.is_held_by(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn view_held_by(holder_id: Option<&str>) -> BankAccountView {
+        let mut view = BankAccountView::default();
+        view.update(&EventEnvelope {
+            aggregate_id: "1234".to_string(),
+            sequence: 1,
+            payload: BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: holder_id.map(str::to_string),
+                product: AccountProduct::Checking,
+                occurred_at: chrono::DateTime::default(),
+            },
+            metadata: HashMap::new(),
+        });
+        view
+    }
+
+    fn customer(customer_id: &str) -> Caller {
+        Caller::Customer(customer_id.to_string())
+    }
+
+    fn operator() -> Caller {
+        Caller::Operator("operator-1".to_string())
+    }
+
+    fn freeze_account() -> BankAccountCommand {
+        BankAccountCommand::FreezeAccount(BankAccountFreezeAccountCommandData {
+            reason: "lost card".to_string(),
+        })
+    }
+
+    fn unfreeze_account() -> BankAccountCommand {
+        BankAccountCommand::UnfreezeAccount(BankAccountUnfreezeAccountCommandData {
+            reason: "card found".to_string(),
+        })
+    }
+
+    fn open_account_for(primary_holder_id: &str) -> BankAccountCommand {
+        BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+            account_id: "1234".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: primary_holder_id.to_string(),
+            product: AccountProduct::Checking,
+        })
+    }
+
+    #[test]
+    fn holders_and_operators_are_authorized() {
+        let view = view_held_by(Some("customer-1"));
+        assert!(authorize_command(
+            Some(&view),
+            &customer("customer-1"),
+            &freeze_account()
+        ));
+        assert!(authorize_command(
+            Some(&view),
+            &operator(),
+            &freeze_account()
+        ));
+    }
+
+    #[test]
+    fn others_are_not_authorized() {
+        let view = view_held_by(Some("customer-1"));
+        assert!(!authorize_command(
+            Some(&view),
+            &customer("customer-2"),
+            &freeze_account()
+        ));
+        assert!(!authorize_command(
+            Some(&view),
+            &Caller::Anonymous,
+            &freeze_account()
+        ));
+        assert!(!authorize_read(&view, &customer("customer-2")));
+    }
+
+    #[test]
+    fn accounts_without_holders_are_left_to_operators() {
+        let view = view_held_by(None);
+        assert!(!authorize_command(
+            Some(&view),
+            &customer("customer-1"),
+            &freeze_account()
+        ));
+        assert!(!authorize_read(&view, &customer("customer-1")));
+        assert!(authorize_read(&view, &operator()));
+    }
+
+    #[test]
+    fn customers_only_open_accounts_for_themselves() {
+        let caller = customer("customer-1");
+        assert!(authorize_command(
+            None,
+            &caller,
+            &open_account_for("customer-1")
+        ));
+        assert!(!authorize_command(
+            None,
+            &caller,
+            &open_account_for("customer-2")
+        ));
+        assert!(!authorize_command(None, &caller, &freeze_account()));
+    }
+
+    #[test]
+    fn operator_commands_are_refused_to_holders() {
+        let view = view_held_by(Some("customer-1"));
+        assert!(!authorize_command(
+            Some(&view),
+            &customer("customer-1"),
+            &unfreeze_account()
+        ));
+        assert!(authorize_command(
+            Some(&view),
+            &operator(),
+            &unfreeze_account()
+        ));
+    }
+
+    #[test]
+    fn overdraft_limits_are_set_by_operators() {
+        let view = view_held_by(Some("customer-1"));
+        let command =
+            BankAccountCommand::SetOverdraftLimit(BankAccountSetOverdraftLimitCommandData {
+                limit: Money::new(50000, Currency::EUR),
+            });
+        assert!(!authorize_command(
+            Some(&view),
+            &customer("customer-1"),
+            &command
+        ));
+        assert!(authorize_command(Some(&view), &operator(), &command));
+    }
+
+    #[test]
+    fn service_commands_are_refused_to_everyone() {
+        let view = view_held_by(Some("customer-1"));
+        let command =
+            BankAccountCommand::ChargeMaintenanceFee(BankAccountChargeMaintenanceFeeCommandData {
+                period: "2023-03".to_string(),
+            });
+        assert!(!authorize_command(Some(&view), &operator(), &command));
+    }
+
+    #[test]
+    fn transfers_only_move_money_through_the_saga() {
+        let view = view_held_by(Some("customer-1"));
+        let command = BankAccountCommand::TransferIn(BankAccountTransferInCommandData {
+            transfer_id: "t-1".to_string(),
+            source_account_id: "5678".to_string(),
+            amount: Money::new(50000, Currency::EUR),
+        });
+        assert!(!authorize_command(
+            Some(&view),
+            &customer("customer-1"),
+            &command
+        ));
+        assert!(!authorize_command(Some(&view), &operator(), &command));
+    }
+}
@@ -1,8 +1,9 @@
 use super::*;
 
 use async_graphql::{Context, Object};
+use cqrs_es::Aggregate;
 
-use cfg_if::cfg_if;
+use crate::application::{CommandOutcome, IdempotentCommands, IdempotentRequest};
 
 #[derive(Default)]
 pub struct BankAccountGraphQlQuery {}
@@ -10,64 +11,6 @@ pub struct BankAccountGraphQlQuery {}
 #[derive(Default)]
 pub struct BankAccountGraphQlMutation {}
 
-cfg_if! {
-    if #[cfg(feature = "postgres")] {
-        #[Object]
-        impl BankAccountGraphQlQuery {
-            #[instrument(skip(self, ctx))]
-            /// Get a bank account by its ID
-            async fn bank_account_query<'ctx>(
-                &self,
-                ctx: &Context<'ctx>,
-                id: String,
-            ) -> async_graphql::Result<BankAccountView> {
-                let view_repo = ctx.data::<Arc<PostgresViewRepository<BankAccountView, BankAccount>>>()?;
-                let view = match view_repo.load(&id).await? {
-                    Some(view) => view,
-                    None => {
-                        return Err(async_graphql::Error::new("Bank account not found"));
-                    }
-                };
-                tracing::debug!("Loaded view in GraphQL response: {:?}", view);
-                Ok(view)
-            }
-        }
-
-        #[Object]
-impl BankAccountGraphQlMutation {
-    #[instrument(skip(self, ctx))]
-    /// Issue a command on the bank account aggregate
-    async fn bank_account_mutation<'ctx>(
-        &self,
-        ctx: &Context<'ctx>,
-        id: String,
-        command: BankAccountCommand,
-    ) -> async_graphql::Result<BankAccountView> {
-        let cqrs = ctx.data::<Arc<PostgresCqrs<BankAccount>>>()?;
-        let view_repo = ctx.data::<Arc<PostgresViewRepository<BankAccountView, BankAccount>>>()?;
-
-        match cqrs.execute(&id, command).await {
-            Ok(_) => {}
-            Err(err) => {
-                return Err(async_graphql::Error::new(err.to_string()));
-            }
-        }
-        let view = match view_repo.load(&id).await {
-            Ok(view) => match view {
-                Some(view) => view,
-                None => {
-                    return Err(async_graphql::Error::new("Bank account not found"));
-                }
-            },
-            Err(err) => {
-                return Err(async_graphql::Error::new(err.to_string()));
-            }
-        };
-        Ok(view)
-    }
-}
-
-    } else if #[cfg(feature = "mysql")] {
 #[Object]
 impl BankAccountGraphQlQuery {
     #[instrument(skip(self, ctx))]
@@ -77,15 +20,20 @@ impl BankAccountGraphQlQuery {
         ctx: &Context<'ctx>,
         id: String,
     ) -> async_graphql::Result<BankAccountView> {
-        let view_repo = ctx.data::<Arc<MysqlViewRepository<BankAccountView, BankAccount>>>()?;
+        let view_repo = ctx.data::<Arc<AnyViewRepository<BankAccountView, BankAccount>>>()?;
         let view = match view_repo.load(&id).await? {
             Some(view) => view,
             None => {
                 return Err(async_graphql::Error::new("Bank account not found"));
             }
         };
+        if !authorize_read(&view, ctx.data::<Caller>()?) {
+            return Err(async_graphql::Error::new(
+                "Not a holder of the bank account",
+            ));
+        }
         tracing::debug!("Loaded view in GraphQL response: {:?}", view);
-        Ok(view)
+        Ok(view.in_effect_at(chrono::Utc::now()))
     }
 }
 
@@ -98,12 +46,39 @@ impl BankAccountGraphQlMutation {
         ctx: &Context<'ctx>,
         id: String,
         command: BankAccountCommand,
+        idempotency_key: Option<String>,
     ) -> async_graphql::Result<BankAccountView> {
-        let cqrs = ctx.data::<Arc<MysqlCqrs<BankAccount>>>()?;
-        let view_repo = ctx.data::<Arc<MysqlViewRepository<BankAccountView, BankAccount>>>()?;
+        let cqrs = ctx.data::<Arc<AnyCqrs<BankAccount>>>()?;
+        let view_repo = ctx.data::<Arc<AnyViewRepository<BankAccountView, BankAccount>>>()?;
 
-        match cqrs.execute(&id, command).await {
-            Ok(_) => {}
+        let caller = ctx.data::<Caller>()?;
+        let view = view_repo.load(&id).await?;
+        if !authorize_command(view.as_ref(), caller, &command) {
+            return Err(async_graphql::Error::new(
+                "Not a holder of the bank account",
+            ));
+        }
+        // Retried mutations with the same key get the original outcome, as on the REST API.
+        let metadata = graphql_metadata(idempotency_key.as_deref());
+        let idempotent_commands = ctx.data::<Arc<IdempotentCommands>>()?;
+        let request = IdempotentRequest {
+            aggregate_type: &BankAccount::aggregate_type(),
+            aggregate_id: Some(&id),
+            scope: &idempotency_scope(&id, caller),
+            request: &idempotent_request(&id, &command),
+            metadata,
+        };
+        match idempotent_commands
+            .execute(request, |_, metadata| {
+                cqrs.execute_with_metadata(&id, command, metadata)
+            })
+            .await
+            .map(|executed| executed.outcome)
+        {
+            Ok(CommandOutcome::Succeeded) => {}
+            Ok(CommandOutcome::Rejected(err)) => {
+                return Err(async_graphql::Error::new(err));
+            }
             Err(err) => {
                 return Err(async_graphql::Error::new(err.to_string()));
             }
@@ -119,10 +94,6 @@ impl BankAccountGraphQlMutation {
                 return Err(async_graphql::Error::new(err.to_string()));
             }
         };
-        Ok(view)
-    }
-}
-    } else {
-        compile_error!("Must specify either mysql or postgres feature");
+        Ok(view.in_effect_at(chrono::Utc::now()))
     }
 }
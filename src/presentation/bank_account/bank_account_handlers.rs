@@ -1,119 +1,212 @@
 use super::*;
+use axum::http::HeaderMap;
 
-cfg_if::cfg_if! {
-    if #[cfg(feature = "postgres")] {
-        // Serves as our query endpoint to respond with the materialized `BankAccountView`
+use cqrs_es::Aggregate;
+
+use crate::application::{CommandOutcome, IdempotencyError, IdempotentCommands, IdempotentRequest};
+
+// Serves as our query endpoint to respond with the materialized `BankAccountView`
 // for the requested account.
 #[utoipa::path(
     get,
     tag = "Bank Accounts",
     path = "/api/bank-accounts/{id}",
     params(
-        ("id" = i32, Path, description = "Bank account ID")
+        ("id" = i32, Path, description = "Bank account ID"),
+        ("X-Holder-Id" = Option<String>, Header, description = "Holder the account is read on behalf of"),
+        ("X-Operator-Id" = Option<String>, Header, description = "Operator the account is read on behalf of")
     ),
     responses(
-        (status = 200, description = "Get bank account details", body = [BankAccountView])
+        (status = 200, description = "Get bank account details", body = [BankAccountView]),
+        (status = 403, description = "Not a holder of the bank account"),
+        (status = 404, description = "Bank account not found")
     )
-  )]
-  #[instrument(skip(view_repo))]
-  pub async fn query_handler(
-      Path(id): Path<String>,
-      Extension(view_repo): Extension<Arc<PostgresViewRepository<BankAccountView, BankAccount>>>,
-  ) -> Response {
-      let view = match view_repo.load(&id).await {
-          Ok(view) => view,
-          Err(err) => {
-              return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
-          }
-      };
-      match view {
-          None => StatusCode::NOT_FOUND.into_response(),
-          Some(account_view) => (StatusCode::OK, Json(account_view)).into_response(),
-      }
-  }
+)]
+#[instrument(skip(view_repo))]
+pub async fn query_handler(
+    Path(id): Path<String>,
+    Extension(view_repo): Extension<Arc<AnyViewRepository<BankAccountView, BankAccount>>>,
+    CallerExtension(caller): CallerExtension,
+) -> Response {
+    match load_readable_view(&view_repo, &id, &caller).await {
+        Ok(account_view) => {
+            let account_view = account_view.in_effect_at(chrono::Utc::now());
+            (StatusCode::OK, Json(account_view)).into_response()
+        }
+        Err(response) => response,
+    }
+}
 
-  // Serves as our command endpoint to make changes in a `BankAccount` aggregate.
-  #[utoipa::path(
-    post,
+// Loads the view of an account, unless it does not exist or the caller may not read it.
+async fn load_readable_view(
+    view_repo: &AnyViewRepository<BankAccountView, BankAccount>,
+    id: &str,
+    caller: &Caller,
+) -> Result<BankAccountView, Response> {
+    match view_repo.load(id).await {
+        Ok(Some(view)) if authorize_read(&view, caller) => Ok(view),
+        Ok(Some(_)) => Err(StatusCode::FORBIDDEN.into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND.into_response()),
+        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()),
+    }
+}
+
+// Serves the statement of an account for a calendar month as JSON, CSV or PDF.
+#[utoipa::path(
+    get,
     tag = "Bank Accounts",
-    path = "/api/bank-accounts/{id}",
-    responses(
-      (status = 204, description = "Command issued successfully"),
-      (status = 400, description = "Command failed", body = [String])
-    ),
-    request_body(content = BankAccountCommand, description = "Bank account command to execute, see the Bank Account Command schema at the bottom of the page for details", content_type = "application/json"),
+    path = "/api/bank-accounts/{id}/statements/{period}",
     params(
       ("id" = i32, Path, description = "Bank account ID"),
+      ("period" = String, Path, description = "Calendar month of the statement, e.g. 2023-03"),
+      ("format" = Option<String>, Query, description = "json, csv or pdf, defaults to the Accept header"),
+      ("X-Holder-Id" = Option<String>, Header, description = "Holder the statement is read on behalf of"),
+      ("X-Operator-Id" = Option<String>, Header, description = "Operator the statement is read on behalf of"),
     ),
-  )]
-  #[instrument(skip(cqrs))]
-  pub async fn command_handler(
-      Path(id): Path<String>,
-      Extension(cqrs): Extension<Arc<PostgresCqrs<BankAccount>>>,
-      MetadataExtension(metadata): MetadataExtension,
-      Json(command): Json<BankAccountCommand>,
-  ) -> Response {
-      match cqrs.execute_with_metadata(&id, command, metadata).await {
-          Ok(_) => StatusCode::NO_CONTENT.into_response(),
-          Err(err) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
-      }
-  }
+    responses(
+      (status = 200, description = "Get the statement for the period as JSON, CSV or PDF", body = Statement),
+      (status = 400, description = "Invalid period", body = [String]),
+      (status = 403, description = "Not a holder of the bank account"),
+      (status = 404, description = "No statement for the period")
+    )
+)]
+#[instrument(skip(view_repo, statement_repo))]
+pub async fn statement_handler(
+    Path((id, period)): Path<(String, String)>,
+    axum::extract::Query(params): axum::extract::Query<StatementParams>,
+    headers: HeaderMap,
+    Extension(view_repo): Extension<Arc<AnyViewRepository<BankAccountView, BankAccount>>>,
+    Extension(statement_repo): Extension<
+        Arc<AnyViewRepository<BankAccountStatementsView, BankAccount>>,
+    >,
+    CallerExtension(caller): CallerExtension,
+) -> Response {
+    if let Err(response) = load_readable_view(&view_repo, &id, &caller).await {
+        return response;
+    }
+    let view = match statement_repo.load(&id).await {
+        Ok(view) => view,
+        Err(err) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+        }
+    };
+    let format = params
+        .format
+        .unwrap_or_else(|| StatementFormat::from_accept(&headers));
+    statement_response(&id, &period, format, view)
+}
 
-    } else if #[cfg(feature = "mysql")] {
-// for the requested account.
+// Serves the standing orders of an account along with the outcome of their payments.
 #[utoipa::path(
     get,
     tag = "Bank Accounts",
-    path = "/api/bank-accounts/{id}",
+    path = "/api/bank-accounts/{id}/standing-orders",
     params(
-        ("id" = i32, Path, description = "Bank account ID")
+      ("id" = i32, Path, description = "Bank account ID"),
+      ("X-Holder-Id" = Option<String>, Header, description = "Holder the standing orders are read on behalf of"),
+      ("X-Operator-Id" = Option<String>, Header, description = "Operator the standing orders are read on behalf of")
     ),
     responses(
-        (status = 200, description = "Get bank account details", body = [BankAccountView])
+      (status = 200, description = "Get the standing orders of the bank account", body = StandingOrdersView),
+      (status = 403, description = "Not a holder of the bank account"),
+      (status = 404, description = "Bank account not found")
     )
-  )]
-  #[instrument(skip(view_repo))]
-  pub async fn query_handler(
-      Path(id): Path<String>,
-      Extension(view_repo): Extension<Arc<MysqlViewRepository<BankAccountView, BankAccount>>>,
-  ) -> Response {
-      let view = match view_repo.load(&id).await {
-          Ok(view) => view,
-          Err(err) => {
-              return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
-          }
-      };
-      match view {
-          None => StatusCode::NOT_FOUND.into_response(),
-          Some(account_view) => (StatusCode::OK, Json(account_view)).into_response(),
-      }
-  }
+)]
+#[instrument(skip(view_repo, standing_order_repo))]
+pub async fn standing_orders_handler(
+    Path(id): Path<String>,
+    Extension(view_repo): Extension<Arc<AnyViewRepository<BankAccountView, BankAccount>>>,
+    Extension(standing_order_repo): Extension<
+        Arc<AnyViewRepository<StandingOrdersView, BankAccount>>,
+    >,
+    CallerExtension(caller): CallerExtension,
+) -> Response {
+    if let Err(response) = load_readable_view(&view_repo, &id, &caller).await {
+        return response;
+    }
+    let view = match standing_order_repo.load(&id).await {
+        Ok(view) => view,
+        Err(err) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+        }
+    };
+    match view {
+        None => StatusCode::NOT_FOUND.into_response(),
+        Some(standing_orders_view) => (StatusCode::OK, Json(standing_orders_view)).into_response(),
+    }
+}
 
-  // Serves as our command endpoint to make changes in a `BankAccount` aggregate.
-  #[utoipa::path(
+// Serves as our command endpoint to make changes in a `BankAccount` aggregate.
+#[utoipa::path(
     post,
     tag = "Bank Accounts",
     path = "/api/bank-accounts/{id}",
     responses(
       (status = 204, description = "Command issued successfully"),
-      (status = 400, description = "Command failed", body = [String])
+      (status = 400, description = "Command failed", body = [String]),
+      (status = 403, description = "Not a holder of the bank account, or a command reserved for operators"),
+      (status = 409, description = "Idempotency key reused for a different request, or the original request is still in progress", body = [String])
     ),
     request_body(content = BankAccountCommand, description = "Bank account command to execute, see the Bank Account Command schema at the bottom of the page for details", content_type = "application/json"),
     params(
       ("id" = i32, Path, description = "Bank account ID"),
+      ("X-Holder-Id" = Option<String>, Header, description = "Holder the command is issued on behalf of"),
+      ("X-Operator-Id" = Option<String>, Header, description = "Operator the command is issued by"),
+      ("Idempotency-Key" = Option<String>, Header, description = "Retried requests with the same key get the original outcome instead of executing the command again"),
     ),
-  )]
-  #[instrument(skip(cqrs))]
-  pub async fn command_handler(
-      Path(id): Path<String>,
-      Extension(cqrs): Extension<Arc<MysqlCqrs<BankAccount>>>,
-      MetadataExtension(metadata): MetadataExtension,
-      Json(command): Json<BankAccountCommand>,
-  ) -> Response {
-      match cqrs.execute_with_metadata(&id, command, metadata).await {
-          Ok(_) => StatusCode::NO_CONTENT.into_response(),
-          Err(err) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
-      }
-  }
+)]
+#[instrument(skip(cqrs, view_repo, idempotent_commands))]
+pub async fn command_handler(
+    Path(id): Path<String>,
+    Extension(cqrs): Extension<Arc<AnyCqrs<BankAccount>>>,
+    Extension(idempotent_commands): Extension<Arc<IdempotentCommands>>,
+    Extension(view_repo): Extension<Arc<AnyViewRepository<BankAccountView, BankAccount>>>,
+    CallerExtension(caller): CallerExtension,
+    MetadataExtension(metadata): MetadataExtension,
+    Json(command): Json<BankAccountCommand>,
+) -> Response {
+    let view = match view_repo.load(&id).await {
+        Ok(view) => view,
+        Err(err) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+        }
+    };
+    if !authorize_command(view.as_ref(), &caller, &command) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let request = IdempotentRequest {
+        aggregate_type: &BankAccount::aggregate_type(),
+        aggregate_id: Some(&id),
+        scope: &idempotency_scope(&id, &caller),
+        request: &idempotent_request(&id, &command),
+        metadata,
+    };
+    let outcome = idempotent_commands
+        .execute(request, |_, metadata| {
+            cqrs.execute_with_metadata(&id, command, metadata)
+        })
+        .await;
+    command_response(outcome.map(|executed| executed.outcome))
+}
+
+// What a retried request must match to be given the outcome of the original one.
+pub fn idempotent_request(id: &str, command: &BankAccountCommand) -> String {
+    serde_json::json!({ "id": id, "command": command }).to_string()
+}
+
+fn command_response(outcome: Result<CommandOutcome, IdempotencyError>) -> Response {
+    match outcome {
+        Ok(CommandOutcome::Succeeded) => StatusCode::NO_CONTENT.into_response(),
+        Ok(CommandOutcome::Rejected(err)) => (StatusCode::BAD_REQUEST, err).into_response(),
+        Err(err @ (IdempotencyError::KeyReused | IdempotencyError::InProgress)) => {
+            (StatusCode::CONFLICT, err.to_string()).into_response()
+        }
+        Err(err @ IdempotencyError::CommandFailed(_)) => {
+            (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+        }
+        Err(err @ IdempotencyError::Store(_)) => {
+            tracing::error!("{err}");
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        }
     }
 }
@@ -0,0 +1,238 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use ts_rs::TS;
+
+use super::*;
+use crate::application::DueStandingOrder;
+
+// Persists the standing orders of each account along with the outcome of their payments.
+pub type StandingOrderQuery =
+    GenericQuery<StandingOrderViewRepository, StandingOrdersView, BankAccount>;
+
+pub type StandingOrderViewRepository = AnyViewRepository<StandingOrdersView, BankAccount>;
+
+// The standing orders set up on an account, including cancelled ones.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct StandingOrdersView {
+    account_id: Option<String>,
+    standing_orders: Vec<StandingOrderRecord>,
+}
+
+impl View<BankAccount> for StandingOrdersView {
+    fn update(&mut self, event: &EventEnvelope<BankAccount>) {
+        match &event.payload {
+            BankAccountEvent::AccountOpened { account_id, .. } => {
+                self.account_id = Some(account_id.clone());
+            }
+            BankAccountEvent::StandingOrderCreated {
+                standing_order_id,
+                amount,
+                day_of_month,
+                description,
+                first_due_date,
+            } => {
+                self.standing_orders.push(StandingOrderRecord {
+                    standing_order_id: standing_order_id.clone(),
+                    amount: *amount,
+                    day_of_month: *day_of_month,
+                    description: description.clone(),
+                    status: StandingOrderStatus::Active,
+                    next_due_date: Some(*first_due_date),
+                    payments: Vec::new(),
+                });
+            }
+            BankAccountEvent::StandingOrderCancelled { standing_order_id } => {
+                if let Some(order) = self.active_order(standing_order_id) {
+                    order.status = StandingOrderStatus::Cancelled;
+                    order.next_due_date = None;
+                }
+            }
+            BankAccountEvent::StandingOrderExecuted {
+                standing_order_id,
+                due_date,
+                next_due_date,
+                occurred_at,
+                ..
+            } => {
+                if let Some(order) = self.active_order(standing_order_id) {
+                    order.next_due_date = Some(*next_due_date);
+                    order.payments.push(StandingOrderPayment {
+                        due_date: *due_date,
+                        occurred_at: *occurred_at,
+                        failure_reason: None,
+                    });
+                }
+            }
+            BankAccountEvent::StandingOrderFailed {
+                standing_order_id,
+                due_date,
+                reason,
+                next_due_date,
+                occurred_at,
+            } => {
+                if let Some(order) = self.active_order(standing_order_id) {
+                    order.next_due_date = Some(*next_due_date);
+                    order.payments.push(StandingOrderPayment {
+                        due_date: *due_date,
+                        occurred_at: *occurred_at,
+                        failure_reason: Some(reason.clone()),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl StandingOrdersView {
+    // The payments of active orders that are due by `today` and have not been made yet.
+    pub fn due_standing_orders(&self, today: NaiveDate) -> Vec<DueStandingOrder> {
+        let Some(account_id) = &self.account_id else {
+            return Vec::new();
+        };
+        self.standing_orders
+            .iter()
+            .filter_map(|order| {
+                let due_date = order.next_due_date.filter(|due_date| *due_date <= today)?;
+                Some(DueStandingOrder {
+                    account_id: account_id.clone(),
+                    standing_order_id: order.standing_order_id.clone(),
+                    due_date,
+                })
+            })
+            .collect()
+    }
+
+    // An id can be reused once its order is cancelled, so only the active order is updated.
+    fn active_order(&mut self, standing_order_id: &str) -> Option<&mut StandingOrderRecord> {
+        self.standing_orders.iter_mut().find(|order| {
+            order.status == StandingOrderStatus::Active
+                && order.standing_order_id == standing_order_id
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct StandingOrderRecord {
+    standing_order_id: String,
+    amount: Money,
+    day_of_month: u32,
+    description: String,
+    status: StandingOrderStatus,
+    /// Not set once the order is cancelled
+    #[schema(value_type = Option<String>, example = "2023-04-01")]
+    #[ts(type = "string | null")]
+    next_due_date: Option<NaiveDate>,
+    payments: Vec<StandingOrderPayment>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum StandingOrderStatus {
+    Active,
+    Cancelled,
+}
+
+// A payment made for an order, or attempted and recorded as failed.
+#[derive(Debug, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct StandingOrderPayment {
+    #[schema(value_type = String, example = "2023-04-01")]
+    #[ts(type = "string")]
+    due_date: NaiveDate,
+    #[schema(value_type = String, example = "2023-04-01T00:05:00Z")]
+    #[ts(type = "string")]
+    occurred_at: DateTime<Utc>,
+    /// Why the payment could not be made, not set for payments that were made
+    failure_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use chrono::Datelike;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 3, day).unwrap()
+    }
+
+    fn standing_orders_view(events: Vec<BankAccountEvent>) -> StandingOrdersView {
+        let mut view = StandingOrdersView::default();
+        for (index, payload) in events.into_iter().enumerate() {
+            view.update(&EventEnvelope {
+                aggregate_id: "1234".to_string(),
+                sequence: index + 1,
+                payload,
+                metadata: HashMap::new(),
+            });
+        }
+        view
+    }
+
+    fn created(standing_order_id: &str, first_due_date: NaiveDate) -> BankAccountEvent {
+        BankAccountEvent::StandingOrderCreated {
+            standing_order_id: standing_order_id.to_string(),
+            amount: Money::new(5000, Currency::EUR),
+            day_of_month: first_due_date.day(),
+            description: "rent".to_string(),
+            first_due_date,
+        }
+    }
+
+    fn opened() -> BankAccountEvent {
+        BankAccountEvent::AccountOpened {
+            account_id: "1234".to_string(),
+            currency: Currency::EUR,
+            primary_holder_id: None,
+            product: AccountProduct::Checking,
+            occurred_at: DateTime::default(),
+        }
+    }
+
+    #[test]
+    fn due_standing_orders_are_those_of_active_orders_due_by_today() {
+        let view = standing_orders_view(vec![
+            opened(),
+            created("so-1", date(1)),
+            created("so-2", date(20)),
+            created("so-3", date(2)),
+            BankAccountEvent::StandingOrderCancelled {
+                standing_order_id: "so-3".to_string(),
+            },
+        ]);
+        assert_eq!(
+            view.due_standing_orders(date(15)),
+            vec![DueStandingOrder {
+                account_id: "1234".to_string(),
+                standing_order_id: "so-1".to_string(),
+                due_date: date(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn failed_payments_are_recorded_and_the_order_moves_on() {
+        let view = standing_orders_view(vec![
+            opened(),
+            created("so-1", date(1)),
+            BankAccountEvent::StandingOrderFailed {
+                standing_order_id: "so-1".to_string(),
+                due_date: date(1),
+                reason: "insufficient funds".to_string(),
+                next_due_date: NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+                occurred_at: DateTime::default(),
+            },
+        ]);
+        let order = &view.standing_orders[0];
+        assert_eq!(order.next_due_date, NaiveDate::from_ymd_opt(2023, 4, 1));
+        assert_eq!(
+            order.payments[0].failure_reason.as_deref(),
+            Some("insufficient funds")
+        );
+        assert!(view.due_standing_orders(date(15)).is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+use axum::http::{
+    header::{ACCEPT, CONTENT_DISPOSITION, CONTENT_TYPE},
+    HeaderMap,
+};
+
+use super::*;
+
+// The representations a statement can be downloaded in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Json,
+    Csv,
+    Pdf,
+}
+
+impl StatementFormat {
+    // Picks the format from the Accept header when the request does not name one.
+    pub fn from_accept(headers: &HeaderMap) -> Self {
+        let accept = headers
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if accept.contains("text/csv") {
+            StatementFormat::Csv
+        } else if accept.contains("application/pdf") {
+            StatementFormat::Pdf
+        } else {
+            StatementFormat::Json
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            StatementFormat::Json => "application/json",
+            StatementFormat::Csv => "text/csv; charset=utf-8",
+            StatementFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            StatementFormat::Json => "json",
+            StatementFormat::Csv => "csv",
+            StatementFormat::Pdf => "pdf",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StatementParams {
+    pub format: Option<StatementFormat>,
+}
+
+// Builds the response for a statement request once the account's statements are loaded.
+pub fn statement_response(
+    account_id: &str,
+    period: &str,
+    format: StatementFormat,
+    view: Option<BankAccountStatementsView>,
+) -> Response {
+    let Some(period) = parse_statement_period(period) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "period must be formatted as YYYY-MM",
+        )
+            .into_response();
+    };
+    let Some(statement) = view.and_then(|view| view.statement(&period)) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let body = match format {
+        StatementFormat::Json => return (StatusCode::OK, Json(statement)).into_response(),
+        StatementFormat::Csv => render_statement_csv(&statement).into_bytes(),
+        StatementFormat::Pdf => render_statement_pdf(account_id, &statement),
+    };
+    let disposition = format!(
+        "attachment; filename=\"statement-{account_id}-{period}.{}\"",
+        format.file_extension()
+    );
+    (
+        StatusCode::OK,
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+// One row per transaction, framed by the opening and closing balance. Amounts are in major
+// units of the currency in the last column.
+pub fn render_statement_csv(statement: &Statement) -> String {
+    let currency = statement.opening_balance().currency().to_string();
+    let mut csv = String::from("date,type,description,amount,balance,currency\n");
+    let mut push_row = |fields: [&str; 6]| {
+        let row: Vec<String> = fields.iter().map(|field| escape_csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    };
+    push_row([
+        "",
+        "",
+        "opening balance",
+        "",
+        &statement.opening_balance().to_major_units_string(),
+        &currency,
+    ]);
+    for line in statement.lines() {
+        push_row([
+            &line.occurred_at.to_rfc3339(),
+            &format!("{:?}", line.transaction_type),
+            &line.description,
+            &line.amount.to_major_units_string(),
+            &line.balance.to_major_units_string(),
+            &currency,
+        ]);
+    }
+    push_row([
+        "",
+        "",
+        "closing balance",
+        "",
+        &statement.closing_balance().to_major_units_string(),
+        &currency,
+    ]);
+    csv
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// A4 in points, with the statement laid out as plain Helvetica text lines.
+const PDF_PAGE_WIDTH: u32 = 595;
+const PDF_PAGE_HEIGHT: u32 = 842;
+const PDF_MARGIN: u32 = 50;
+const PDF_FONT_SIZE: u32 = 10;
+const PDF_LINE_HEIGHT: u32 = 14;
+const PDF_LINES_PER_PAGE: usize = ((PDF_PAGE_HEIGHT - 2 * PDF_MARGIN) / PDF_LINE_HEIGHT) as usize;
+
+// Renders the statement as a minimal PDF 1.4 document. Only the standard Helvetica font is
+// used so that nothing needs to be embedded.
+pub fn render_statement_pdf(account_id: &str, statement: &Statement) -> Vec<u8> {
+    let mut text = vec![
+        format!("Statement for account {account_id}"),
+        format!("Period: {}", statement.period()),
+        String::new(),
+        format!("Opening balance: {}", statement.opening_balance()),
+        format!("Closing balance: {}", statement.closing_balance()),
+        String::new(),
+        "Totals by transaction type".to_string(),
+    ];
+    text.extend(
+        statement
+            .totals()
+            .iter()
+            .map(|total| format!("  {:?}: {}", total.transaction_type, total.amount)),
+    );
+    text.push(String::new());
+    text.push("Transactions".to_string());
+    text.extend(statement.lines().iter().map(|line| {
+        format!(
+            "  {}  {}  {}  balance {}",
+            line.occurred_at.format("%Y-%m-%d"),
+            line.description,
+            line.amount,
+            line.balance
+        )
+    }));
+
+    let pages: Vec<&[String]> = text.chunks(PDF_LINES_PER_PAGE).collect();
+    // Objects 1 and 2 are the catalog and page tree, 3 is the font, then each page is
+    // followed by its content stream.
+    let page_object_ids: Vec<usize> = (0..pages.len()).map(|page| 4 + 2 * page).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_object_ids
+                .iter()
+                .map(|id| format!("{id} 0 R"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    for (page, lines) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PDF_PAGE_WIDTH} {PDF_PAGE_HEIGHT}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            page_object_ids[page] + 1
+        ));
+        let mut content = format!(
+            "BT\n/F1 {PDF_FONT_SIZE} Tf\n{PDF_LINE_HEIGHT} TL\n{PDF_MARGIN} {} Td\n",
+            PDF_PAGE_HEIGHT - PDF_MARGIN
+        );
+        for line in lines.iter() {
+            content.push_str(&format!("({}) '\n", escape_pdf_text(line)));
+        }
+        content.push_str("ET");
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{content}\nendstream",
+            content.len()
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{object}\nendobj\n", index + 1));
+    }
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    pdf.into_bytes()
+}
+
+// Text in a PDF string is delimited by parentheses, and the standard fonts only cover ASCII
+// reliably, so anything else is replaced.
+fn escape_pdf_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{c}"),
+            ' '..='~' => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn statement_with_lines(lines: usize) -> Statement {
+        let line = serde_json::json!({
+            "occurred_at": "2023-03-15T12:00:00Z",
+            "transaction_type": "Withdrawal",
+            "description": "atm withdrawal, (main street)",
+            "amount": { "minor_units": -2500, "currency": "EUR" },
+            "balance": { "minor_units": 7500, "currency": "EUR" }
+        });
+        serde_json::from_value(serde_json::json!({
+            "period": "2023-03",
+            "opening_balance": { "minor_units": 10000, "currency": "EUR" },
+            "closing_balance": { "minor_units": 7500, "currency": "EUR" },
+            "totals": [
+                { "transaction_type": "Withdrawal", "amount": { "minor_units": -2500, "currency": "EUR" } }
+            ],
+            "lines": vec![line; lines],
+        }))
+        .unwrap()
+    }
+
+    fn statement() -> Statement {
+        statement_with_lines(1)
+    }
+
+    #[test]
+    fn statements_render_as_csv() {
+        assert_eq!(
+            render_statement_csv(&statement()),
+            "date,type,description,amount,balance,currency\n\
+             ,,opening balance,,100.00,EUR\n\
+             2023-03-15T12:00:00+00:00,Withdrawal,\"atm withdrawal, (main street)\",-25.00,75.00,EUR\n\
+             ,,closing balance,,75.00,EUR\n"
+        );
+    }
+
+    #[test]
+    fn statements_render_as_pdf_with_a_valid_cross_reference_table() {
+        let pdf = String::from_utf8(render_statement_pdf("account-1", &statement())).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains(
+            "(  2023-03-15  atm withdrawal, \\(main street\\)  -25.00 EUR  balance 75.00 EUR) '"
+        ));
+
+        let startxref: usize = pdf
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[startxref..].starts_with("xref\n"));
+        // Every object offset in the table points at the start of that object.
+        for (index, entry) in pdf[startxref..].lines().skip(3).take(5).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", index + 1)));
+        }
+    }
+
+    #[test]
+    fn long_statements_span_several_pdf_pages() {
+        let pdf = String::from_utf8(render_statement_pdf(
+            "account-1",
+            &statement_with_lines(100),
+        ))
+        .unwrap();
+        assert!(pdf.contains("/Count 3"));
+    }
+
+    #[test]
+    fn statement_format_falls_back_to_the_accept_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            StatementFormat::from_accept(&headers),
+            StatementFormat::Json
+        );
+        headers.insert(ACCEPT, "application/pdf".parse().unwrap());
+        assert_eq!(StatementFormat::from_accept(&headers), StatementFormat::Pdf);
+        headers.insert(ACCEPT, "text/csv".parse().unwrap());
+        assert_eq!(StatementFormat::from_accept(&headers), StatementFormat::Csv);
+    }
+}
@@ -0,0 +1,561 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+use super::*;
+
+// Persists the statements of each account, cut into calendar months as events arrive.
+pub type StatementQuery =
+    GenericQuery<StatementViewRepository, BankAccountStatementsView, BankAccount>;
+
+pub type StatementViewRepository = AnyViewRepository<BankAccountStatementsView, BankAccount>;
+
+// Statement periods are calendar months, identified as e.g. "2023-03".
+const STATEMENT_PERIOD_FORMAT: &str = "%Y-%m";
+
+// Returns the period normalized to "YYYY-MM", or None if it is not a calendar month.
+pub fn parse_statement_period(period: &str) -> Option<String> {
+    NaiveDate::parse_from_str(&format!("{period}-01"), "%Y-%m-%d")
+        .ok()
+        .map(|date| date.format(STATEMENT_PERIOD_FORMAT).to_string())
+}
+
+// The statements of an account, one per calendar month with activity on the account.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BankAccountStatementsView {
+    account_id: Option<String>,
+    opened_in: Option<String>,
+    balance: Money,
+    statements: Vec<Statement>,
+}
+
+impl View<BankAccount> for BankAccountStatementsView {
+    fn update(&mut self, event: &EventEnvelope<BankAccount>) {
+        if let BankAccountEvent::AccountOpened {
+            account_id,
+            currency,
+            ..
+        } = &event.payload
+        {
+            self.account_id = Some(account_id.clone());
+            self.opened_in = Some(statement_period(DateTime::default(), &event.metadata));
+            self.balance = Money::zero(*currency);
+            return;
+        }
+        let Some(entry) = StatementEntry::from_event(&event.payload) else {
+            return;
+        };
+        let period = statement_period(entry.occurred_at, &event.metadata);
+        // The amount is taken from the change in balance, so that it is signed and matches
+        // the running balance even when the event recorded a conversion.
+        let amount = entry
+            .balance
+            .checked_sub(&self.balance)
+            .unwrap_or(entry.amount);
+        let line = StatementLine {
+            occurred_at: entry.occurred_at,
+            transaction_type: entry.transaction_type,
+            description: entry.description,
+            amount,
+            balance: entry.balance,
+        };
+        self.statement_for_update(period).add_line(line);
+        self.balance = entry.balance;
+    }
+}
+
+impl BankAccountStatementsView {
+    pub fn account_id(&self) -> Option<&str> {
+        self.account_id.as_deref()
+    }
+
+    // The statement for a period. Periods without activity since the account was opened
+    // get an empty statement carrying the balance forward.
+    pub fn statement(&self, period: &str) -> Option<Statement> {
+        if let Some(statement) = self.statements.iter().find(|s| s.period == period) {
+            return Some(statement.clone());
+        }
+        let opened_in = self
+            .opened_in
+            .as_deref()
+            .or_else(|| self.statements.first().map(|s| s.period.as_str()))?;
+        if period < opened_in {
+            return None;
+        }
+        let balance = self
+            .statements
+            .iter()
+            .rev()
+            .find(|s| s.period.as_str() < period)
+            .map_or(Money::zero(self.balance.currency()), |s| s.closing_balance);
+        Some(Statement::new(period.to_string(), balance))
+    }
+
+    // Events are applied in order, so they land in the latest statement unless they start a
+    // new period. Events timestamped earlier than the latest statement are kept in it.
+    fn statement_for_update(&mut self, period: String) -> &mut Statement {
+        let starts_new_period = self
+            .statements
+            .last()
+            .is_none_or(|latest| latest.period < period);
+        if starts_new_period {
+            self.statements.push(Statement::new(period, self.balance));
+        }
+        self.statements
+            .last_mut()
+            .expect("a statement was just added")
+    }
+}
+
+// Events from before timestamps were recorded fall back to the time the command was issued.
+fn statement_period(occurred_at: DateTime<Utc>, metadata: &HashMap<String, String>) -> String {
+    let occurred_at = if occurred_at == DateTime::<Utc>::default() {
+        metadata
+            .get("time")
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map_or(occurred_at, |time| time.with_timezone(&Utc))
+    } else {
+        occurred_at
+    };
+    occurred_at.format(STATEMENT_PERIOD_FORMAT).to_string()
+}
+
+// The parts of an event that move money on the account.
+struct StatementEntry {
+    occurred_at: DateTime<Utc>,
+    transaction_type: AccountTransactionType,
+    description: String,
+    amount: Money,
+    balance: Money,
+}
+
+impl StatementEntry {
+    fn new(
+        occurred_at: DateTime<Utc>,
+        transaction_type: AccountTransactionType,
+        description: &str,
+        amount: Money,
+        balance: Money,
+    ) -> Self {
+        Self {
+            occurred_at,
+            transaction_type,
+            description: description.to_string(),
+            amount,
+            balance,
+        }
+    }
+
+    fn from_event(event: &BankAccountEvent) -> Option<Self> {
+        let entry = match event {
+            BankAccountEvent::CustomerDepositedMoney {
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Deposit,
+                "deposit",
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::CustomerWithdrewCash {
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Withdrawal,
+                "atm withdrawal",
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::CustomerWroteCheck {
+                check_number,
+                amount,
+                balance,
+                occurred_at,
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Check,
+                &format!("check {check_number}"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::OverdraftFeeCharged {
+                fee,
+                balance,
+                occurred_at,
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Fee,
+                "overdraft fee",
+                *fee,
+                *balance,
+            ),
+            BankAccountEvent::InterestPosted {
+                amount,
+                balance,
+                occurred_at,
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Interest,
+                "interest",
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::TransferDebited {
+                destination_account_id,
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::TransferOut,
+                &format!("transfer to {destination_account_id}"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::TransferCredited {
+                source_account_id,
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::TransferIn,
+                &format!("transfer from {source_account_id}"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::TransferRefunded {
+                amount,
+                balance,
+                reason,
+                occurred_at,
+                ..
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::TransferRefund,
+                &format!("transfer refund: {reason}"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::HoldCaptured {
+                hold_id,
+                amount,
+                balance,
+                occurred_at,
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::HoldCapture,
+                &format!("hold {hold_id}"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::CheckBounced {
+                check_number,
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::CheckReturned,
+                &format!("check {check_number} returned"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::ReturnedItemFeeCharged {
+                check_number,
+                fee,
+                balance,
+                occurred_at,
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Fee,
+                &format!("returned item fee for check {check_number}"),
+                *fee,
+                *balance,
+            ),
+            BankAccountEvent::TransactionReversed {
+                reversed_sequence,
+                amount,
+                balance,
+                reason,
+                occurred_at,
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Reversal,
+                &format!("reversal of transaction {reversed_sequence}: {reason}"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::FeeCharged {
+                reason,
+                fee,
+                balance,
+                occurred_at,
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::Fee,
+                &reason.description(),
+                *fee,
+                *balance,
+            ),
+            BankAccountEvent::StandingOrderExecuted {
+                standing_order_id,
+                amount,
+                balance,
+                occurred_at,
+                ..
+            } => Self::new(
+                *occurred_at,
+                AccountTransactionType::StandingOrder,
+                &format!("standing order {standing_order_id}"),
+                *amount,
+                *balance,
+            ),
+            BankAccountEvent::AccountOpened { .. }
+            | BankAccountEvent::AccountFrozen { .. }
+            | BankAccountEvent::AccountUnfrozen { .. }
+            | BankAccountEvent::AccountClosed { .. }
+            | BankAccountEvent::OverdraftLimitSet { .. }
+            | BankAccountEvent::InterestAccrued { .. }
+            | BankAccountEvent::TransferCompleted { .. }
+            | BankAccountEvent::HoldPlaced { .. }
+            | BankAccountEvent::HoldReleased { .. }
+            | BankAccountEvent::HoldExpired { .. }
+            | BankAccountEvent::HolderAdded { .. }
+            | BankAccountEvent::HolderRemoved { .. }
+            | BankAccountEvent::CheckCleared { .. }
+            | BankAccountEvent::StandingOrderCreated { .. }
+            | BankAccountEvent::StandingOrderCancelled { .. }
+            | BankAccountEvent::StandingOrderFailed { .. } => return None,
+        };
+        Some(entry)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct Statement {
+    /// The calendar month covered, e.g. "2023-03"
+    period: String,
+    opening_balance: Money,
+    closing_balance: Money,
+    /// The net amount per transaction type over the period
+    totals: Vec<StatementTotal>,
+    lines: Vec<StatementLine>,
+}
+
+impl Statement {
+    fn new(period: String, opening_balance: Money) -> Self {
+        Self {
+            period,
+            opening_balance,
+            closing_balance: opening_balance,
+            totals: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn period(&self) -> &str {
+        &self.period
+    }
+
+    pub fn opening_balance(&self) -> Money {
+        self.opening_balance
+    }
+
+    pub fn closing_balance(&self) -> Money {
+        self.closing_balance
+    }
+
+    pub fn totals(&self) -> &[StatementTotal] {
+        &self.totals
+    }
+
+    pub fn lines(&self) -> &[StatementLine] {
+        &self.lines
+    }
+
+    fn add_line(&mut self, line: StatementLine) {
+        match self
+            .totals
+            .iter_mut()
+            .find(|total| total.transaction_type == line.transaction_type)
+        {
+            Some(total) => {
+                total.amount = total
+                    .amount
+                    .checked_add(&line.amount)
+                    .unwrap_or(total.amount)
+            }
+            None => self.totals.push(StatementTotal {
+                transaction_type: line.transaction_type,
+                amount: line.amount,
+            }),
+        }
+        self.closing_balance = line.balance;
+        self.lines.push(line);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct StatementTotal {
+    pub transaction_type: AccountTransactionType,
+    /// Credits are positive and debits negative
+    pub amount: Money,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct StatementLine {
+    #[schema(value_type = String, example = "2023-03-15T12:00:00Z")]
+    #[ts(type = "string")]
+    pub occurred_at: DateTime<Utc>,
+    pub transaction_type: AccountTransactionType,
+    pub description: String,
+    /// Credits are positive and debits negative
+    pub amount: Money,
+    /// The balance after the transaction
+    pub balance: Money,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use pretty_assertions::assert_eq;
+
+    fn eur(minor_units: i64) -> Money {
+        Money::new(minor_units, Currency::EUR)
+    }
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    fn envelope(sequence: usize, payload: BankAccountEvent) -> EventEnvelope<BankAccount> {
+        EventEnvelope {
+            aggregate_id: "account-1".to_string(),
+            sequence,
+            payload,
+            metadata: HashMap::from([("time".to_string(), "2023-02-01T09:00:00Z".to_string())]),
+        }
+    }
+
+    fn statements_view() -> BankAccountStatementsView {
+        let events = vec![
+            BankAccountEvent::AccountOpened {
+                account_id: "account-1".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: None,
+                product: AccountProduct::Checking,
+                occurred_at: DateTime::default(),
+            },
+            BankAccountEvent::CustomerDepositedMoney {
+                amount: eur(10000),
+                balance: eur(10000),
+                conversion: None,
+                occurred_at: at("2023-02-10T12:00:00Z"),
+            },
+            BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(2500),
+                balance: eur(7500),
+                atm_id: None,
+                conversion: None,
+                occurred_at: at("2023-03-01T08:00:00Z"),
+            },
+            BankAccountEvent::CustomerWroteCheck {
+                check_number: "1170".to_string(),
+                amount: eur(1500),
+                balance: eur(6000),
+                occurred_at: at("2023-03-15T12:00:00Z"),
+            },
+            BankAccountEvent::CustomerWithdrewCash {
+                amount: eur(1000),
+                balance: eur(5000),
+                atm_id: None,
+                conversion: None,
+                occurred_at: at("2023-03-20T12:00:00Z"),
+            },
+        ];
+        let mut view = BankAccountStatementsView::default();
+        for (sequence, event) in events.into_iter().enumerate() {
+            view.update(&envelope(sequence + 1, event));
+        }
+        view
+    }
+
+    #[test]
+    fn statements_are_cut_into_calendar_months() {
+        let view = statements_view();
+        let february = view.statement("2023-02").unwrap();
+        let march = view.statement("2023-03").unwrap();
+        assert_eq!(february.opening_balance(), eur(0));
+        assert_eq!(february.closing_balance(), eur(10000));
+        assert_eq!(march.opening_balance(), eur(10000));
+        assert_eq!(march.closing_balance(), eur(5000));
+        assert_eq!(march.lines().len(), 3);
+    }
+
+    #[test]
+    fn statements_total_signed_amounts_by_transaction_type() {
+        let march = statements_view().statement("2023-03").unwrap();
+        assert_eq!(
+            march.totals(),
+            &[
+                StatementTotal {
+                    transaction_type: AccountTransactionType::Withdrawal,
+                    amount: eur(-3500),
+                },
+                StatementTotal {
+                    transaction_type: AccountTransactionType::Check,
+                    amount: eur(-1500),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn periods_without_activity_carry_the_balance_forward() {
+        let view = statements_view();
+        let april = view.statement("2023-04").unwrap();
+        assert_eq!(april.opening_balance(), eur(5000));
+        assert_eq!(april.closing_balance(), eur(5000));
+        assert!(april.lines().is_empty());
+        assert_eq!(view.statement("2023-01"), None);
+    }
+
+    #[test]
+    fn events_without_a_timestamp_use_the_command_time() {
+        let mut view = BankAccountStatementsView::default();
+        view.update(&envelope(
+            1,
+            BankAccountEvent::CustomerDepositedMoney {
+                amount: eur(100),
+                balance: eur(100),
+                conversion: None,
+                occurred_at: DateTime::default(),
+            },
+        ));
+        assert_eq!(view.statement("2023-02").unwrap().lines().len(), 1);
+    }
+
+    #[test]
+    fn statement_periods_must_be_calendar_months() {
+        assert_eq!(
+            parse_statement_period("2023-03"),
+            Some("2023-03".to_string())
+        );
+        assert_eq!(parse_statement_period("2023-13"), None);
+        assert_eq!(parse_statement_period("march"), None);
+    }
+}
@@ -1,33 +1,16 @@
-use async_graphql::SimpleObject;
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Utc};
 use ts_rs::TS;
 
 use super::*;
 
-cfg_if::cfg_if! {
-    if #[cfg(feature = "postgres")] {
-
-// Our second query, this one will be handled with Postgres `GenericQuery`
-// which will serialize and persist our view after it is updated. It also
-// provides a `load` method to deserialize the view on request.
-pub type AccountQuery = GenericQuery<
-PostgresViewRepository<BankAccountView, BankAccount>,
-BankAccountView,
-BankAccount,
->;
-    } else if #[cfg(feature = "mysql")] {
-
-// Our second query, this one will be handled with Mysql `GenericQuery`
-// which will serialize and persist our view after it is updated. It also
-// provides a `load` method to deserialize the view on request.
-pub type AccountQuery = GenericQuery<
-    MysqlViewRepository<BankAccountView, BankAccount>,
-    BankAccountView,
-    BankAccount,
->;
-} else {
-        compile_error!("Must specify either mysql or postgres feature");
-    }
-}
+// Our second query, this one will be handled with a `GenericQuery` over the
+// database the service runs against, which will serialize and persist our view
+// after it is updated. It also provides a `load` method to deserialize the view
+// on request.
+pub type AccountQuery = GenericQuery<AccountViewRepository, BankAccountView, BankAccount>;
+
+pub type AccountViewRepository = AnyViewRepository<BankAccountView, BankAccount>;
 
 // The view for a BankAccount query, for a standard http application this should
 // be designed to reflect the response dto that will be returned to a user.
@@ -37,9 +20,23 @@ pub type AccountQuery = GenericQuery<
 #[ts(export)]
 pub struct BankAccountView {
     account_id: Option<String>,
-    balance: f64,
-    written_checks: Vec<String>,
+    #[serde(default)]
+    product: AccountProduct,
+    #[serde(default)]
+    primary_holder_id: Option<String>,
+    #[serde(default)]
+    holder_ids: Vec<String>,
+    #[serde(default)]
+    status: AccountStatus,
+    balance: Money,
+    #[serde(default)]
+    overdraft_limit: Money,
+    #[serde(default)]
+    available_balance: Money,
+    written_checks: Vec<CheckRecord>,
     account_transactions: Vec<AccountTransaction>,
+    #[serde(default)]
+    pending_holds: Vec<PendingHold>,
 }
 
 // This updates the view with events as they are committed.
@@ -47,33 +44,319 @@ pub struct BankAccountView {
 // design the events to carry the balance information instead.
 impl View<BankAccount> for BankAccountView {
     fn update(&mut self, event: &EventEnvelope<BankAccount>) {
+        let recorded_transactions = self.account_transactions.len();
         match &event.payload {
-            BankAccountEvent::AccountOpened { account_id } => {
+            BankAccountEvent::AccountOpened {
+                account_id,
+                currency,
+                primary_holder_id,
+                product,
+                ..
+            } => {
                 self.account_id = Some(account_id.clone());
+                self.product = *product;
+                self.primary_holder_id = primary_holder_id.clone();
+                self.holder_ids.extend(primary_holder_id.clone());
+                self.status = AccountStatus::Open;
+                self.balance = Money::zero(*currency);
+                self.overdraft_limit = Money::zero(*currency);
+                self.available_balance = Money::zero(*currency);
             }
 
-            BankAccountEvent::CustomerDepositedMoney { amount, balance } => {
-                self.account_transactions
-                    .push(AccountTransaction::new("deposit", *amount));
-                self.balance = *balance;
+            BankAccountEvent::CustomerDepositedMoney {
+                amount, balance, ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Deposit,
+                    "deposit",
+                    *amount,
+                ));
+                self.set_balance(*balance);
             }
 
-            BankAccountEvent::CustomerWithdrewCash { amount, balance } => {
-                self.account_transactions
-                    .push(AccountTransaction::new("atm withdrawal", *amount));
-                self.balance = *balance;
+            BankAccountEvent::CustomerWithdrewCash {
+                amount, balance, ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Withdrawal,
+                    "atm withdrawal",
+                    *amount,
+                ));
+                self.set_balance(*balance);
             }
 
             BankAccountEvent::CustomerWroteCheck {
                 check_number,
                 amount,
                 balance,
+                ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Check,
+                    check_number,
+                    *amount,
+                ));
+                self.written_checks.push(CheckRecord {
+                    check_number: check_number.clone(),
+                    amount: *amount,
+                    status: CheckStatus::Pending,
+                });
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::AccountFrozen { .. } => {
+                self.status = AccountStatus::Frozen;
+            }
+
+            BankAccountEvent::AccountUnfrozen { .. } => {
+                self.status = AccountStatus::Open;
+            }
+
+            BankAccountEvent::AccountClosed { .. } => {
+                self.status = AccountStatus::Closed;
+            }
+
+            BankAccountEvent::OverdraftLimitSet { limit } => {
+                self.overdraft_limit = *limit;
+                self.set_balance(self.balance);
+            }
+
+            BankAccountEvent::OverdraftFeeCharged { fee, balance, .. } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Fee,
+                    "overdraft fee",
+                    *fee,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::InterestAccrued { .. } => {}
+
+            BankAccountEvent::InterestPosted {
+                amount, balance, ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Interest,
+                    "interest",
+                    *amount,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::TransferDebited {
+                destination_account_id,
+                amount,
+                balance,
+                ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::TransferOut,
+                    &format!("transfer to {destination_account_id}"),
+                    *amount,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::TransferCredited {
+                source_account_id,
+                amount,
+                balance,
+                ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::TransferIn,
+                    &format!("transfer from {source_account_id}"),
+                    *amount,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::TransferRefunded {
+                amount,
+                balance,
+                reason,
+                ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::TransferRefund,
+                    &format!("transfer refund: {reason}"),
+                    *amount,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::HoldPlaced {
+                hold_id,
+                amount,
+                expires_at,
+                ..
+            } => {
+                self.pending_holds.push(PendingHold {
+                    hold_id: hold_id.clone(),
+                    amount: *amount,
+                    expires_at: *expires_at,
+                });
+                self.set_balance(self.balance);
+            }
+
+            BankAccountEvent::HoldCaptured {
+                hold_id,
+                amount,
+                balance,
+                ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::HoldCapture,
+                    &format!("hold {hold_id}"),
+                    *amount,
+                ));
+                self.remove_pending_hold(hold_id);
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::HoldReleased { hold_id, .. }
+            | BankAccountEvent::HoldExpired { hold_id, .. } => {
+                self.remove_pending_hold(hold_id);
+                self.set_balance(self.balance);
+            }
+
+            BankAccountEvent::HolderAdded { holder_id } => {
+                self.holder_ids.push(holder_id.clone());
+            }
+
+            BankAccountEvent::HolderRemoved { holder_id } => {
+                self.holder_ids.retain(|existing| existing != holder_id);
+            }
+
+            BankAccountEvent::CheckCleared { check_number, .. } => {
+                self.set_check_status(check_number, CheckStatus::Cleared);
+            }
+
+            BankAccountEvent::CheckBounced {
+                check_number,
+                amount,
+                balance,
+                ..
             } => {
-                self.account_transactions
-                    .push(AccountTransaction::new(check_number, *amount));
-                self.written_checks.push(check_number.clone());
-                self.balance = *balance;
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::CheckReturned,
+                    check_number,
+                    *amount,
+                ));
+                self.set_check_status(check_number, CheckStatus::Bounced);
+                self.set_balance(*balance);
             }
+
+            BankAccountEvent::ReturnedItemFeeCharged { fee, balance, .. } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Fee,
+                    "returned item fee",
+                    *fee,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::TransactionReversed {
+                reversed_sequence,
+                amount,
+                balance,
+                reason,
+                ..
+            } => {
+                if let Some(reversed) = self
+                    .account_transactions
+                    .iter_mut()
+                    .find(|transaction| transaction.sequence == Some(*reversed_sequence))
+                {
+                    reversed.reversed = true;
+                }
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Reversal,
+                    &format!("reversal of transaction {reversed_sequence}: {reason}"),
+                    *amount,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::FeeCharged {
+                reason,
+                fee,
+                balance,
+                ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::Fee,
+                    &reason.description(),
+                    *fee,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::StandingOrderExecuted {
+                standing_order_id,
+                amount,
+                balance,
+                ..
+            } => {
+                self.account_transactions.push(AccountTransaction::new(
+                    AccountTransactionType::StandingOrder,
+                    &format!("standing order {standing_order_id}"),
+                    *amount,
+                ));
+                self.set_balance(*balance);
+            }
+
+            BankAccountEvent::TransferCompleted { .. }
+            | BankAccountEvent::StandingOrderCreated { .. }
+            | BankAccountEvent::StandingOrderCancelled { .. }
+            | BankAccountEvent::StandingOrderFailed { .. } => {}
+        }
+        // Transactions are referred to by the sequence number of the event recording them.
+        for transaction in &mut self.account_transactions[recorded_transactions..] {
+            transaction.sequence = Some(event.sequence);
+        }
+    }
+}
+
+impl BankAccountView {
+    // Accounts opened before holders were introduced have none, until an operator adds them.
+    pub fn is_held_by(&self, holder_id: &str) -> bool {
+        self.holder_ids.iter().any(|existing| existing == holder_id)
+    }
+
+    // Expired holds stop reserving funds whether or not their expiry has been recorded yet, so
+    // the view is served with the holds and available balance as of the time it is read.
+    pub fn in_effect_at(mut self, now: DateTime<Utc>) -> Self {
+        self.pending_holds.retain(|hold| now < hold.expires_at);
+        self.set_balance(self.balance);
+        self
+    }
+
+    // The available balance is what the customer can still spend, including their overdraft
+    // and less any funds reserved by pending holds.
+    fn set_balance(&mut self, balance: Money) {
+        self.balance = balance;
+        self.available_balance = self
+            .pending_holds
+            .iter()
+            .try_fold(balance, |available, hold| {
+                available.checked_sub(&hold.amount)
+            })
+            .and_then(|available| available.checked_add(&self.overdraft_limit))
+            .unwrap_or(balance);
+    }
+
+    fn remove_pending_hold(&mut self, hold_id: &str) {
+        self.pending_holds.retain(|hold| hold.hold_id != hold_id);
+    }
+
+    fn set_check_status(&mut self, check_number: &str, status: CheckStatus) {
+        if let Some(check) = self
+            .written_checks
+            .iter_mut()
+            .find(|check| check.check_number == check_number)
+        {
+            check.status = status;
         }
     }
 }
@@ -81,18 +364,104 @@ impl View<BankAccount> for BankAccountView {
 #[derive(Debug, Serialize, Deserialize, ToSchema, ToResponse, SimpleObject, TS)]
 #[ts(export, export_to = "frontend/src/bindings/")]
 pub struct AccountTransaction {
+    #[serde(default)]
+    transaction_type: AccountTransactionType,
     description: String,
-    amount: f64,
+    amount: Money,
+    /// Sequence number of the event that recorded the transaction, e.g. to reverse it
+    #[serde(default)]
+    sequence: Option<usize>,
+    #[serde(default)]
+    reversed: bool,
 }
 impl AccountTransaction {
-    fn new(description: &str, amount: f64) -> Self {
+    fn new(transaction_type: AccountTransactionType, description: &str, amount: Money) -> Self {
         Self {
+            transaction_type,
             description: description.to_string(),
             amount,
+            sequence: None,
+            reversed: false,
         }
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema, ToResponse, SimpleObject, TS)]
+#[serde(from = "StoredCheckRecord")]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct CheckRecord {
+    check_number: String,
+    amount: Money,
+    status: CheckStatus,
+}
+
+// Views stored before checks were tracked individually only hold the check numbers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCheckRecord {
+    Record {
+        check_number: String,
+        amount: Money,
+        status: CheckStatus,
+    },
+    CheckNumber(String),
+}
+
+impl From<StoredCheckRecord> for CheckRecord {
+    fn from(stored: StoredCheckRecord) -> Self {
+        match stored {
+            StoredCheckRecord::Record {
+                check_number,
+                amount,
+                status,
+            } => Self {
+                check_number,
+                amount,
+                status,
+            },
+            StoredCheckRecord::CheckNumber(check_number) => Self {
+                check_number,
+                amount: Money::default(),
+                status: CheckStatus::Pending,
+            },
+        }
+    }
+}
+
+// Funds reserved on the account that have not been captured or released yet. A hold stops
+// reserving funds once it expires, even if the expiry has not been recorded yet.
+#[derive(Debug, Serialize, Deserialize, ToSchema, ToResponse, SimpleObject, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct PendingHold {
+    hold_id: String,
+    amount: Money,
+    #[schema(value_type = String, example = "2023-03-22T12:00:00Z")]
+    #[ts(type = "string")]
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, Enum, TS,
+)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub enum AccountTransactionType {
+    Deposit,
+    Withdrawal,
+    Check,
+    Fee,
+    Interest,
+    TransferOut,
+    TransferIn,
+    TransferRefund,
+    HoldCapture,
+    CheckReturned,
+    Reversal,
+    StandingOrder,
+    // Transactions recorded before transaction types were introduced.
+    #[default]
+    Other,
+}
+
 pub struct SimpleLoggingQuery;
 
 #[async_trait]
@@ -103,3 +472,122 @@ impl Query<BankAccount> for SimpleLoggingQuery {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Currency;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn envelope(sequence: usize, payload: BankAccountEvent) -> EventEnvelope<BankAccount> {
+        EventEnvelope {
+            aggregate_id: "1234".to_string(),
+            sequence,
+            payload,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn reversed_transactions_are_marked_as_reversed() {
+        let eur = |minor_units| Money::new(minor_units, Currency::EUR);
+        let mut view = BankAccountView::default();
+        for event in [
+            envelope(
+                1,
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: None,
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+            ),
+            envelope(
+                2,
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: DateTime::default(),
+                },
+            ),
+            envelope(
+                3,
+                BankAccountEvent::TransactionReversed {
+                    reversed_sequence: 2,
+                    amount: eur(20000),
+                    balance: eur(0),
+                    reason: "recorded in error".to_string(),
+                    occurred_at: DateTime::default(),
+                },
+            ),
+        ] {
+            view.update(&event);
+        }
+        let [deposit, reversal] = &view.account_transactions[..] else {
+            panic!("expected a deposit and its reversal");
+        };
+        assert_eq!(deposit.sequence, Some(2));
+        assert!(deposit.reversed);
+        assert_eq!(reversal.transaction_type, AccountTransactionType::Reversal);
+        assert_eq!(reversal.sequence, Some(3));
+        assert!(!reversal.reversed);
+        assert_eq!(view.balance, eur(0));
+    }
+
+    #[test]
+    fn expired_holds_no_longer_reduce_the_available_balance() {
+        let eur = |minor_units| Money::new(minor_units, Currency::EUR);
+        let placed_at = DateTime::<Utc>::default();
+        let expires_at = placed_at + chrono::Duration::days(7);
+        let mut view = BankAccountView::default();
+        for event in [
+            envelope(
+                1,
+                BankAccountEvent::AccountOpened {
+                    account_id: "1234".to_string(),
+                    currency: Currency::EUR,
+                    primary_holder_id: None,
+                    product: AccountProduct::Checking,
+                    occurred_at: DateTime::default(),
+                },
+            ),
+            envelope(
+                2,
+                BankAccountEvent::CustomerDepositedMoney {
+                    amount: eur(20000),
+                    balance: eur(20000),
+                    conversion: None,
+                    occurred_at: placed_at,
+                },
+            ),
+            envelope(
+                3,
+                BankAccountEvent::HoldPlaced {
+                    hold_id: "hold-1".to_string(),
+                    amount: eur(5000),
+                    conversion: None,
+                    expires_at,
+                    occurred_at: placed_at,
+                },
+            ),
+        ] {
+            view.update(&event);
+        }
+        let before_expiry = view.in_effect_at(expires_at - chrono::Duration::seconds(1));
+        assert_eq!(before_expiry.pending_holds.len(), 1);
+        assert_eq!(before_expiry.available_balance, eur(15000));
+        let after_expiry = before_expiry.in_effect_at(expires_at);
+        assert!(after_expiry.pending_holds.is_empty());
+        assert_eq!(after_expiry.available_balance, eur(20000));
+    }
+
+    #[test]
+    fn written_checks_stored_as_check_numbers_can_be_deserialized() {
+        let check: CheckRecord = serde_json::from_str(r#""1170""#).unwrap();
+        assert_eq!(check.check_number, "1170");
+        assert_eq!(check.status, CheckStatus::Pending);
+    }
+}
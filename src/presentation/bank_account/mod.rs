@@ -1,87 +1,260 @@
 use super::*;
-use cqrs_es::persist::ViewRepository;
-use cqrs_es::Query;
+use cqrs_es::persist::{PersistedEventRepository, ViewRepository};
 use cqrs_es::{persist::GenericQuery, EventEnvelope, View};
+use cqrs_es::{CqrsFramework, Query};
 
-use crate::application::{BankAccountServices, HappyPathBankAccountServices};
+use crate::any_es::AnyEventRepository;
+use crate::application::{
+    BankAccountEventLookup, BankAccountServices, DueStandingOrder, DueStandingOrdersLookup,
+    OpenAccount, OpenAccountsLookup,
+};
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use cfg_if::cfg_if;
 
+pub mod bank_account_authorization;
 pub mod bank_account_graphql;
 pub mod bank_account_handlers;
+pub mod bank_account_standing_orders;
+pub mod bank_account_statement_documents;
+pub mod bank_account_statements;
 pub mod bank_account_views;
 
 // Re-exports
 
+pub use bank_account_authorization::*;
 pub use bank_account_graphql::*;
 pub use bank_account_handlers::*;
+pub use bank_account_standing_orders::*;
+pub use bank_account_statement_documents::*;
+pub use bank_account_statements::*;
 pub use bank_account_views::*;
 
-cfg_if! {
-    if #[cfg(feature = "postgres")] {
-        use postgres_es::{PostgresCqrs, PostgresViewRepository};
-        use sqlx::{Pool, Postgres};
-        pub fn get_bank_account_cqrs_framework(
-            pool: Pool<Postgres>,
-        ) -> (
-            Arc<PostgresCqrs<BankAccount>>,
-            Arc<PostgresViewRepository<BankAccountView, BankAccount>>,
-        ) {
-            // A very simple query that writes each event to stdout.
-            let simple_query = SimpleLoggingQuery {};
-
-            // A query that stores the current state of an individual account.
-            let account_view_repo = Arc::new(PostgresViewRepository::new("account_query", pool.clone()));
-            let mut account_query = AccountQuery::new(account_view_repo.clone());
-
-            // Without a query error handler there will be no indication if an
-            // error occurs (e.g., database connection failure, missing columns or table).
-            // Consider logging an error or panicking in your own application.
-            account_query.use_error_handler(Box::new(|e| println!("{}", e)));
-
-            // Create and return an event-sourced `CqrsFramework`.
-            let queries: Vec<Box<dyn Query<BankAccount>>> =
-                vec![Box::new(simple_query), Box::new(account_query)];
-            let services = BankAccountServices::new(Box::new(HappyPathBankAccountServices));
-            (
-                Arc::new(postgres_es::postgres_cqrs(pool, queries, services)),
-                account_view_repo,
-            )
-        }
-    } else if #[cfg(feature = "mysql")] {
-        use sqlx::{Pool, MySql};
-        use mysql_es::{MysqlCqrs, MysqlViewRepository};
-
-        pub fn get_bank_account_cqrs_framework(
-            pool: Pool<MySql>,
-        ) -> (
-            Arc<MysqlCqrs<BankAccount>>,
-            Arc<MysqlViewRepository<BankAccountView, BankAccount>>,
-        ) {
-            // A very simple query that writes each event to stdout.
-            let simple_query = SimpleLoggingQuery {};
-
-            // A query that stores the current state of an individual account.
-            let account_view_repo = Arc::new(MysqlViewRepository::new("account_query", pool.clone()));
-            let mut account_query = AccountQuery::new(account_view_repo.clone());
-
-            // Without a query error handler there will be no indication if an
-            // error occurs (e.g., database connection failure, missing columns or table).
-            // Consider logging an error or panicking in your own application.
-            account_query.use_error_handler(Box::new(|e| println!("{}", e)));
-
-            // Create and return an event-sourced `CqrsFramework`.
-            let queries: Vec<Box<dyn Query<BankAccount>>> =
-                vec![Box::new(simple_query), Box::new(account_query)];
-            let services = BankAccountServices::new(Box::new(HappyPathBankAccountServices));
-            (
-                Arc::new(mysql_es::mysql_cqrs(pool, queries, services)),
-                account_view_repo,
-            )
+pub fn get_bank_account_cqrs_framework(
+    db: AnyDatabase,
+    services: BankAccountServices,
+) -> (
+    Arc<AnyCqrs<BankAccount>>,
+    Arc<AccountViewRepository>,
+    Arc<StatementViewRepository>,
+    Arc<StandingOrderViewRepository>,
+) {
+    // A very simple query that writes each event to stdout.
+    let simple_query = SimpleLoggingQuery {};
+
+    // A query that stores the current state of an individual account.
+    let account_view_repo = Arc::new(AnyViewRepository::new("account_query", db.clone()));
+    let mut account_query = AccountQuery::new(account_view_repo.clone());
+
+    // Without a query error handler there will be no indication if an
+    // error occurs (e.g., database connection failure, missing columns or table).
+    account_query.use_error_handler(Box::new(|e| {
+        tracing::error!("could not update the account view: {e}")
+    }));
+
+    // A query that stores the monthly statements of an individual account.
+    let statement_view_repo = Arc::new(AnyViewRepository::new("statement_query", db.clone()));
+    let mut statement_query = StatementQuery::new(statement_view_repo.clone());
+    statement_query.use_error_handler(Box::new(|e| {
+        tracing::error!("could not update the statement view: {e}")
+    }));
+
+    // A query that stores the standing orders of an individual account, the scheduler
+    // finds the payments that have fallen due from it.
+    let standing_order_view_repo =
+        Arc::new(AnyViewRepository::new("standing_order_query", db.clone()));
+    let mut standing_order_query = StandingOrderQuery::new(standing_order_view_repo.clone());
+    standing_order_query.use_error_handler(Box::new(|e| {
+        tracing::error!("could not update the standing order view: {e}")
+    }));
+
+    // A query that posts every movement of money to the general ledger.
+    let general_ledger = new_general_ledger(db.clone());
+
+    // Create and return an event-sourced `CqrsFramework`.
+    let queries: Vec<Box<dyn Query<BankAccount>>> = vec![
+        Box::new(simple_query),
+        Box::new(account_query),
+        Box::new(statement_query),
+        Box::new(standing_order_query),
+        Box::new(general_ledger),
+    ];
+
+    // Older events are upcast to the current event version as they are loaded, and
+    // busy accounts are snapshotted so that commands don't replay their whole history.
+    let events = AnyEventRepository::new(db).with_snapshot_version(BANK_ACCOUNT_SNAPSHOT_VERSION);
+    let event_store = services
+        .configuration
+        .snapshots
+        .event_store(events)
+        .with_upcasters(bank_account_event_upcasters());
+    (
+        Arc::new(CqrsFramework::new(event_store, queries, services)),
+        account_view_repo,
+        statement_view_repo,
+        standing_order_view_repo,
+    )
+}
+
+// Finds stored events in the event store, upcast to the current event version.
+pub struct EventStoreBankAccountEventLookup {
+    events: AnyEventRepository,
+}
+
+impl EventStoreBankAccountEventLookup {
+    pub fn new(db: AnyDatabase) -> Self {
+        Self {
+            events: AnyEventRepository::new(db),
         }
-    } else {
-        compile_error!("Must specify either mysql or postgres feature");
+    }
+}
+
+#[async_trait]
+impl BankAccountEventLookup for EventStoreBankAccountEventLookup {
+    async fn event(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<Option<BankAccountEvent>> {
+        let events = self.events.get_events::<BankAccount>(account_id).await?;
+        let Some(event) = events.into_iter().find(|event| event.sequence == sequence) else {
+            return Ok(None);
+        };
+        let event = upcast_bank_account_event(event);
+        Ok(Some(serde_json::from_value(event.payload)?))
+    }
+}
+
+// Finds open accounts from the views stored by the account query.
+pub struct AccountViewOpenAccountsLookup {
+    db: AnyDatabase,
+}
+
+impl AccountViewOpenAccountsLookup {
+    pub fn new(db: AnyDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl OpenAccountsLookup for AccountViewOpenAccountsLookup {
+    async fn open_accounts(&self) -> crate::prelude::Result<Vec<OpenAccount>> {
+        // Every backend has a JSON syntax of its own.
+        let accounts: Vec<(String, Option<String>)> = match &self.db {
+            #[cfg(feature = "postgres")]
+            AnyDatabase::Postgres(pool) => {
+                sqlx::query_as(
+                    "SELECT view_id, payload->>'product' FROM account_query WHERE payload->>'status' IN ('Open', 'Frozen')",
+                )
+                .fetch_all(pool)
+                .await?
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabase::Mysql(pool) => {
+                sqlx::query_as(
+                    "SELECT view_id, JSON_UNQUOTE(JSON_EXTRACT(payload, '$.product')) FROM account_query WHERE JSON_UNQUOTE(JSON_EXTRACT(payload, '$.status')) IN ('Open', 'Frozen')",
+                )
+                .fetch_all(pool)
+                .await?
+            }
+            #[cfg(feature = "sqlite")]
+            AnyDatabase::Sqlite(pool) => {
+                sqlx::query_as(
+                    "SELECT view_id, json_extract(payload, '$.product') FROM account_query WHERE json_extract(payload, '$.status') IN ('Open', 'Frozen')",
+                )
+                .fetch_all(pool)
+                .await?
+            }
+            #[cfg(feature = "in-memory")]
+            AnyDatabase::InMemory(db) => db.read("account_query", |views| {
+                views
+                    .iter()
+                    .filter(|(_, view)| {
+                        matches!(view.payload["status"].as_str(), Some("Open" | "Frozen"))
+                    })
+                    .map(|(account_id, view)| {
+                        (
+                            account_id.clone(),
+                            view.payload["product"].as_str().map(str::to_string),
+                        )
+                    })
+                    .collect()
+            }),
+        };
+        // Views of accounts opened before products were introduced have no product.
+        accounts
+            .into_iter()
+            .map(|(account_id, product)| {
+                let product = match product {
+                    Some(product) => serde_json::from_value(serde_json::Value::String(product))?,
+                    None => AccountProduct::default(),
+                };
+                Ok(OpenAccount {
+                    account_id,
+                    product,
+                })
+            })
+            .collect()
+    }
+}
+
+// Finds the due standing order payments from the views stored by the standing order
+// query.
+pub struct StandingOrderViewDueStandingOrdersLookup {
+    db: AnyDatabase,
+}
+
+impl StandingOrderViewDueStandingOrdersLookup {
+    pub fn new(db: AnyDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl DueStandingOrdersLookup for StandingOrderViewDueStandingOrdersLookup {
+    async fn due_standing_orders(
+        &self,
+        today: chrono::NaiveDate,
+    ) -> crate::prelude::Result<Vec<DueStandingOrder>> {
+        const SELECT_VIEWS: &str = "SELECT payload FROM standing_order_query";
+        let views: Vec<StandingOrdersView> = match &self.db {
+            #[cfg(feature = "postgres")]
+            AnyDatabase::Postgres(pool) => {
+                sqlx::query_scalar::<_, sqlx::types::Json<StandingOrdersView>>(SELECT_VIEWS)
+                    .fetch_all(pool)
+                    .await?
+                    .into_iter()
+                    .map(|view| view.0)
+                    .collect()
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabase::Mysql(pool) => {
+                sqlx::query_scalar::<_, sqlx::types::Json<StandingOrdersView>>(SELECT_VIEWS)
+                    .fetch_all(pool)
+                    .await?
+                    .into_iter()
+                    .map(|view| view.0)
+                    .collect()
+            }
+            // The sqlite view repository stores views as JSON text.
+            #[cfg(feature = "sqlite")]
+            AnyDatabase::Sqlite(pool) => sqlx::query_scalar::<_, String>(SELECT_VIEWS)
+                .fetch_all(pool)
+                .await?
+                .iter()
+                .map(|view| serde_json::from_str(view))
+                .collect::<Result<_, _>>()?,
+            #[cfg(feature = "in-memory")]
+            AnyDatabase::InMemory(db) => db.read("standing_order_query", |views| {
+                views
+                    .values()
+                    .map(|view| serde_json::from_value(view.payload.clone()))
+                    .collect::<Result<_, _>>()
+            })?,
+        };
+        Ok(views
+            .iter()
+            .flat_map(|view| view.due_standing_orders(today))
+            .collect())
     }
 }
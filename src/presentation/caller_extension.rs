@@ -0,0 +1,96 @@
+use super::*;
+use axum::extract::FromRequestParts;
+use axum::http::{request::Parts, HeaderMap};
+use std::convert::Infallible;
+
+pub const HOLDER_ID_HDR: &str = "X-Holder-Id";
+pub const OPERATOR_ID_HDR: &str = "X-Operator-Id";
+
+// Who a request is made by. Authenticating callers is left to whatever sits in front of the
+// API, which sets the `X-Operator-Id` header for back office staff and `X-Holder-Id` for
+// customers, and must drop both from requests it did not authenticate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Caller {
+    // Back office staff, trusted with every command that is not reserved for the service.
+    Operator(String),
+    // A customer, who may only use their own records and the accounts they hold.
+    Customer(String),
+    Anonymous,
+}
+
+impl Caller {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        if let Some(operator_id) = header(OPERATOR_ID_HDR) {
+            return Caller::Operator(operator_id);
+        }
+        match header(HOLDER_ID_HDR) {
+            Some(customer_id) => Caller::Customer(customer_id),
+            None => Caller::Anonymous,
+        }
+    }
+
+    pub fn is_operator(&self) -> bool {
+        matches!(self, Caller::Operator(_))
+    }
+
+    pub fn customer_id(&self) -> Option<&str> {
+        match self {
+            Caller::Customer(customer_id) => Some(customer_id),
+            _ => None,
+        }
+    }
+}
+
+// Extracts the `Caller` of a request from its headers.
+pub struct CallerExtension(pub Caller);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CallerExtension
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(CallerExtension(Caller::from_headers(&parts.headers)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn operators_take_precedence_over_customers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Caller::from_headers(&headers), Caller::Anonymous);
+
+        headers.insert(HOLDER_ID_HDR, HeaderValue::from_static("customer-1"));
+        assert_eq!(
+            Caller::from_headers(&headers),
+            Caller::Customer("customer-1".to_string())
+        );
+
+        headers.insert(OPERATOR_ID_HDR, HeaderValue::from_static("operator-1"));
+        assert_eq!(
+            Caller::from_headers(&headers),
+            Caller::Operator("operator-1".to_string())
+        );
+    }
+
+    #[test]
+    fn empty_headers_are_ignored() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOLDER_ID_HDR, HeaderValue::from_static(""));
+        assert_eq!(Caller::from_headers(&headers), Caller::Anonymous);
+    }
+}
@@ -0,0 +1,74 @@
+use super::*;
+
+// Customers may register and update themselves, but only operators record the outcome of
+// the KYC check on their documents.
+pub fn authorize_customer_command(id: &str, caller: &Caller, command: &CustomerCommand) -> bool {
+    match command {
+        CustomerCommand::VerifyKyc(_) | CustomerCommand::RejectKyc(_) => caller.is_operator(),
+        CustomerCommand::RegisterCustomer(_)
+        | CustomerCommand::ChangeAddress(_)
+        | CustomerCommand::SubmitKyc(_) => authorize_customer_read(id, caller),
+    }
+}
+
+// Customers can be read by themselves and by operators.
+pub fn authorize_customer_read(id: &str, caller: &Caller) -> bool {
+    caller.is_operator() || caller.customer_id() == Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn customer(customer_id: &str) -> Caller {
+        Caller::Customer(customer_id.to_string())
+    }
+
+    fn operator() -> Caller {
+        Caller::Operator("operator-1".to_string())
+    }
+
+    fn submit_kyc() -> CustomerCommand {
+        CustomerCommand::SubmitKyc(CustomerSubmitKycCommandData {
+            document_reference: "passport-1".to_string(),
+        })
+    }
+
+    fn verify_kyc() -> CustomerCommand {
+        CustomerCommand::VerifyKyc(CustomerVerifyKycCommandData {
+            verified_by: "compliance".to_string(),
+        })
+    }
+
+    #[test]
+    fn customers_only_use_their_own_records() {
+        let caller = customer("customer-1");
+        assert!(authorize_customer_command(
+            "customer-1",
+            &caller,
+            &submit_kyc()
+        ));
+        assert!(!authorize_customer_command(
+            "customer-2",
+            &caller,
+            &submit_kyc()
+        ));
+        assert!(!authorize_customer_read("customer-2", &caller));
+        assert!(!authorize_customer_read("customer-1", &Caller::Anonymous));
+        assert!(authorize_customer_read("customer-2", &operator()));
+    }
+
+    #[test]
+    fn only_operators_record_the_outcome_of_kyc() {
+        assert!(!authorize_customer_command(
+            "customer-1",
+            &customer("customer-1"),
+            &verify_kyc()
+        ));
+        assert!(authorize_customer_command(
+            "customer-1",
+            &operator(),
+            &verify_kyc()
+        ));
+    }
+}
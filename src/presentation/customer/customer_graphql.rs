@@ -0,0 +1,59 @@
+use super::*;
+
+use async_graphql::{Context, Object};
+
+#[derive(Default)]
+pub struct CustomerGraphQlQuery {}
+
+#[derive(Default)]
+pub struct CustomerGraphQlMutation {}
+
+#[Object]
+impl CustomerGraphQlQuery {
+    #[instrument(skip(self, ctx))]
+    /// Get the details and KYC status of a customer by their ID
+    async fn customer_query<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        id: String,
+    ) -> async_graphql::Result<CustomerView> {
+        if !authorize_customer_read(&id, ctx.data::<Caller>()?) {
+            return Err(async_graphql::Error::new(
+                "Neither the customer nor an operator",
+            ));
+        }
+        let view_repo = ctx.data::<Arc<AnyViewRepository<CustomerView, Customer>>>()?;
+        match view_repo.load(&id).await? {
+            Some(view) => Ok(view),
+            None => Err(async_graphql::Error::new("Customer not found")),
+        }
+    }
+}
+
+#[Object]
+impl CustomerGraphQlMutation {
+    #[instrument(skip(self, ctx))]
+    /// Execute a command against a customer, such as registering them or recording the outcome of their KYC check
+    async fn customer_mutation<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        id: String,
+        command: CustomerCommand,
+    ) -> async_graphql::Result<CustomerView> {
+        let cqrs = ctx.data::<Arc<AnyCqrs<Customer>>>()?;
+        let view_repo = ctx.data::<Arc<AnyViewRepository<CustomerView, Customer>>>()?;
+
+        if !authorize_customer_command(&id, ctx.data::<Caller>()?, &command) {
+            return Err(async_graphql::Error::new(
+                "Neither the customer nor an operator",
+            ));
+        }
+        if let Err(err) = cqrs.execute(&id, command).await {
+            return Err(async_graphql::Error::new(err.to_string()));
+        }
+        match view_repo.load(&id).await? {
+            Some(view) => Ok(view),
+            None => Err(async_graphql::Error::new("Customer not found")),
+        }
+    }
+}
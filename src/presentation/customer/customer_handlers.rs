@@ -0,0 +1,73 @@
+use super::*;
+
+// Serves as our query endpoint to respond with the materialized `CustomerView`
+// for the requested customer.
+#[utoipa::path(
+    get,
+    tag = "Customers",
+    path = "/api/customers/{id}",
+    params(
+        ("id" = String, Path, description = "Customer ID"),
+        ("X-Holder-Id" = Option<String>, Header, description = "Customer the request is made by"),
+        ("X-Operator-Id" = Option<String>, Header, description = "Operator the request is made by")
+    ),
+    responses(
+        (status = 200, description = "Get customer details and KYC status", body = [CustomerView]),
+        (status = 403, description = "Neither the customer nor an operator"),
+        (status = 404, description = "Customer not found")
+    )
+)]
+#[instrument(skip(view_repo))]
+pub async fn customer_query_handler(
+    Path(id): Path<String>,
+    Extension(view_repo): Extension<Arc<AnyViewRepository<CustomerView, Customer>>>,
+    CallerExtension(caller): CallerExtension,
+) -> Response {
+    if !authorize_customer_read(&id, &caller) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let view = match view_repo.load(&id).await {
+        Ok(view) => view,
+        Err(err) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+        }
+    };
+    match view {
+        None => StatusCode::NOT_FOUND.into_response(),
+        Some(customer_view) => (StatusCode::OK, Json(customer_view)).into_response(),
+    }
+}
+
+// Serves as our command endpoint to make changes to a customer.
+#[utoipa::path(
+    post,
+    tag = "Customers",
+    path = "/api/customers/{id}",
+    responses(
+      (status = 204, description = "Command issued successfully"),
+      (status = 400, description = "Command failed", body = [String]),
+      (status = 403, description = "Neither the customer nor an operator, or a command reserved for operators")
+    ),
+    request_body(content = CustomerCommand, description = "Customer command to execute, see the Customer Command schema at the bottom of the page for details", content_type = "application/json"),
+    params(
+      ("id" = String, Path, description = "Customer ID"),
+      ("X-Holder-Id" = Option<String>, Header, description = "Customer the command is issued by"),
+      ("X-Operator-Id" = Option<String>, Header, description = "Operator the command is issued by")
+    ),
+)]
+#[instrument(skip(cqrs))]
+pub async fn customer_command_handler(
+    Path(id): Path<String>,
+    Extension(cqrs): Extension<Arc<AnyCqrs<Customer>>>,
+    CallerExtension(caller): CallerExtension,
+    MetadataExtension(metadata): MetadataExtension,
+    Json(command): Json<CustomerCommand>,
+) -> Response {
+    if !authorize_customer_command(&id, &caller, &command) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match cqrs.execute_with_metadata(&id, command, metadata).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+    }
+}
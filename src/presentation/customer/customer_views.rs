@@ -0,0 +1,89 @@
+use async_graphql::SimpleObject;
+use ts_rs::TS;
+
+use super::*;
+
+// Stores the current details and KYC status of each customer, keyed by customer id.
+pub type CustomerQuery =
+    GenericQuery<AnyViewRepository<CustomerView, Customer>, CustomerView, Customer>;
+
+#[derive(SimpleObject, Debug, Default, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct CustomerView {
+    customer_id: String,
+    name: String,
+    email: String,
+    address: Option<Address>,
+    pub(crate) kyc_status: KycStatus,
+    kyc_rejection_reason: Option<String>,
+}
+
+impl View<Customer> for CustomerView {
+    fn update(&mut self, event: &EventEnvelope<Customer>) {
+        match &event.payload {
+            CustomerEvent::CustomerRegistered {
+                name,
+                email,
+                address,
+            } => {
+                self.customer_id = event.aggregate_id.clone();
+                self.name = name.clone();
+                self.email = email.clone();
+                self.address = Some(address.clone());
+            }
+
+            CustomerEvent::AddressChanged { address } => {
+                self.address = Some(address.clone());
+            }
+
+            CustomerEvent::KycSubmitted { .. } => {
+                self.kyc_status = KycStatus::Pending;
+                self.kyc_rejection_reason = None;
+            }
+
+            CustomerEvent::KycVerified { .. } => {
+                self.kyc_status = KycStatus::Verified;
+            }
+
+            CustomerEvent::KycRejected { reason } => {
+                self.kyc_status = KycStatus::Rejected;
+                self.kyc_rejection_reason = Some(reason.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn envelope(payload: CustomerEvent) -> EventEnvelope<Customer> {
+        EventEnvelope {
+            aggregate_id: "customer-1".to_string(),
+            sequence: 1,
+            payload,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn resubmitting_kyc_clears_the_rejection_reason() {
+        let mut view = CustomerView::default();
+        view.update(&envelope(CustomerEvent::KycRejected {
+            reason: "document expired".to_string(),
+        }));
+        assert_eq!(view.kyc_status, KycStatus::Rejected);
+        assert_eq!(
+            view.kyc_rejection_reason,
+            Some("document expired".to_string())
+        );
+
+        view.update(&envelope(CustomerEvent::KycSubmitted {
+            document_reference: "passport-2".to_string(),
+        }));
+        assert_eq!(view.kyc_status, KycStatus::Pending);
+        assert_eq!(view.kyc_rejection_reason, None);
+    }
+}
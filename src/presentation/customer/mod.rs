@@ -0,0 +1,66 @@
+use super::*;
+use cqrs_es::persist::ViewRepository;
+use cqrs_es::Query;
+use cqrs_es::{persist::GenericQuery, EventEnvelope, View};
+
+use crate::application::{CustomerKycLookup, CustomerLookupError};
+use std::sync::Arc;
+
+pub mod customer_authorization;
+pub mod customer_graphql;
+pub mod customer_handlers;
+pub mod customer_views;
+
+// Re-exports
+
+pub use customer_authorization::*;
+pub use customer_graphql::*;
+pub use customer_handlers::*;
+pub use customer_views::*;
+
+pub fn get_customer_cqrs_framework(
+    db: AnyDatabase,
+) -> (
+    Arc<AnyCqrs<Customer>>,
+    Arc<AnyViewRepository<CustomerView, Customer>>,
+) {
+    // A query that stores the current details and KYC status of each customer.
+    let customer_view_repo = Arc::new(AnyViewRepository::new("customer_query", db.clone()));
+    let mut customer_query = CustomerQuery::new(customer_view_repo.clone());
+    customer_query.use_error_handler(Box::new(|e| {
+        tracing::error!("could not update the customer view: {e}")
+    }));
+
+    let queries: Vec<Box<dyn Query<Customer>>> = vec![Box::new(customer_query)];
+    (
+        Arc::new(crate::any_es::any_cqrs(db, queries, ())),
+        customer_view_repo,
+    )
+}
+
+// Looks up the KYC status of the primary holder of a new bank account in the customer view.
+pub struct CustomerViewKycLookup {
+    view_repo: Arc<AnyViewRepository<CustomerView, Customer>>,
+}
+
+impl CustomerViewKycLookup {
+    pub fn new(view_repo: Arc<AnyViewRepository<CustomerView, Customer>>) -> Self {
+        Self { view_repo }
+    }
+}
+
+#[async_trait]
+impl CustomerKycLookup for CustomerViewKycLookup {
+    async fn kyc_status(
+        &self,
+        customer_id: &str,
+    ) -> Result<Option<KycStatus>, CustomerLookupError> {
+        match self.view_repo.load(customer_id).await {
+            Ok(view) => Ok(view.map(|customer| customer.kyc_status)),
+            Err(err) => {
+                tracing::error!("could not look up customer {customer_id}: {err}");
+                Err(CustomerLookupError)
+            }
+        }
+    }
+}
@@ -13,21 +13,15 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
-use crate::domain::BankAccount;
-
-use super::{BankAccountGraphQlMutation, BankAccountGraphQlQuery, BankAccountView};
-
-use cfg_if::cfg_if;
-
-cfg_if! {
-    if #[cfg(feature = "postgres")] {
-        use postgres_es::{PostgresCqrs, PostgresViewRepository};
-    } else if #[cfg(feature = "mysql")] {
-        use mysql_es::{MysqlCqrs, MysqlViewRepository};
-    } else {
-        compile_error!("Must specify either mysql or postgres feature");
-    }
-}
+use crate::any_es::{AnyCqrs, AnyViewRepository};
+use crate::application::IdempotentCommands;
+use crate::domain::{BankAccount, Customer, Transfer};
+
+use super::{
+    BankAccountGraphQlMutation, BankAccountGraphQlQuery, BankAccountView, CallerExtension,
+    CustomerGraphQlMutation, CustomerGraphQlQuery, CustomerView, TransferGraphQlMutation,
+    TransferGraphQlQuery, TransferView,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GraphQlConfiguration {
@@ -53,63 +47,55 @@ async fn graphql_playground() -> impl IntoResponse {
 #[instrument(skip(schema, req))]
 async fn graphql_handler(
     schema: Extension<Schema<QueryRoot, MutationRoot, EmptySubscription>>,
+    CallerExtension(caller): CallerExtension,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    // Resolvers authorize against the caller the same way as the REST handlers.
+    schema.execute(req.into_inner().data(caller)).await.into()
 }
 
 #[derive(MergedObject, Default)]
-struct QueryRoot(BankAccountGraphQlQuery);
+struct QueryRoot(
+    BankAccountGraphQlQuery,
+    TransferGraphQlQuery,
+    CustomerGraphQlQuery,
+);
 
 #[derive(MergedObject, Default)]
-struct MutationRoot(BankAccountGraphQlMutation);
-
-cfg_if! {
-    if #[cfg(feature = "postgres")] {
-        #[instrument(skip(bank_account_view_repsitory, bank_account_cqrs_framework))]
-        pub async fn new_graphql_router(
-            bank_account_cqrs_framework: Arc<PostgresCqrs<BankAccount>>,
-            bank_account_view_repsitory: Arc<PostgresViewRepository<BankAccountView, BankAccount>>,
-        ) -> Router {
-            tracing::debug!("Starting graphql server");
-
-            // create the schema
-            let schema = Schema::build(
-                QueryRoot::default(),
-                MutationRoot::default(),
-                EmptySubscription,
-            )
-            .data(bank_account_view_repsitory)
-            .data(bank_account_cqrs_framework)
-            .finish();
-
-            Router::new()
-                .route("/", get(graphql_playground).post(graphql_handler))
-                .layer(Extension(schema))
-        }
-    } else if #[cfg(feature = "mysql")] {
-        #[instrument(skip(bank_account_view_repsitory, bank_account_cqrs_framework))]
-        pub async fn new_graphql_router(
-            bank_account_cqrs_framework: Arc<MysqlCqrs<BankAccount>>,
-            bank_account_view_repsitory: Arc<MysqlViewRepository<BankAccountView, BankAccount>>,
-        ) -> Router {
-            tracing::debug!("Starting graphql server");
-
-            // create the schema
-            let schema = Schema::build(
-                QueryRoot::default(),
-                MutationRoot::default(),
-                EmptySubscription,
-            )
-            .data(bank_account_view_repsitory)
-            .data(bank_account_cqrs_framework)
-            .finish();
-
-            Router::new()
-                .route("/", get(graphql_playground).post(graphql_handler))
-                .layer(Extension(schema))
-        }
-    } else {
-        compile_error!("Must specify either mysql or postgres feature");
-    }
+struct MutationRoot(
+    BankAccountGraphQlMutation,
+    TransferGraphQlMutation,
+    CustomerGraphQlMutation,
+);
+
+#[instrument(skip_all)]
+pub async fn new_graphql_router(
+    bank_account_cqrs_framework: Arc<AnyCqrs<BankAccount>>,
+    bank_account_view_repsitory: Arc<AnyViewRepository<BankAccountView, BankAccount>>,
+    transfer_cqrs_framework: Arc<AnyCqrs<Transfer>>,
+    transfer_view_repository: Arc<AnyViewRepository<TransferView, Transfer>>,
+    customer_cqrs_framework: Arc<AnyCqrs<Customer>>,
+    customer_view_repository: Arc<AnyViewRepository<CustomerView, Customer>>,
+    idempotent_commands: Arc<IdempotentCommands>,
+) -> Router {
+    tracing::debug!("Starting graphql server");
+
+    // create the schema
+    let schema = Schema::build(
+        QueryRoot::default(),
+        MutationRoot::default(),
+        EmptySubscription,
+    )
+    .data(bank_account_view_repsitory)
+    .data(bank_account_cqrs_framework)
+    .data(transfer_view_repository)
+    .data(transfer_cqrs_framework)
+    .data(customer_view_repository)
+    .data(customer_cqrs_framework)
+    .data(idempotent_commands)
+    .finish();
+
+    Router::new()
+        .route("/", get(graphql_playground).post(graphql_handler))
+        .layer(Extension(schema))
 }
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::application::{CommandOutcome, CommittedRequests, IdempotencyStore};
+#[cfg(feature = "in-memory")]
+use crate::memory_es::MemEventRepository;
+
+use super::*;
+
+// Outcomes are stored as JSON text, they are only ever read back by the application.
+pub(super) fn decode_outcome(
+    outcome: Option<String>,
+) -> crate::prelude::Result<Option<CommandOutcome>> {
+    Ok(outcome
+        .map(|outcome| serde_json::from_str(&outcome))
+        .transpose()?)
+}
+
+// Idempotency keys are scoped to the account a command is for and the caller who issued it,
+// so that a key chosen by one client never replays the outcome of another client's command.
+pub fn idempotency_scope(account_id: &str, caller: &Caller) -> String {
+    let caller = match caller {
+        Caller::Operator(operator_id) => serde_json::json!({ "operator": operator_id }),
+        Caller::Customer(customer_id) => serde_json::json!({ "customer": customer_id }),
+        Caller::Anonymous => serde_json::Value::Null,
+    };
+    serde_json::json!({ "account_id": account_id, "caller": caller }).to_string()
+}
+
+// The idempotency keys in the database the service runs against.
+pub fn new_idempotency_store(db: AnyDatabase) -> Arc<dyn IdempotencyStore> {
+    match db {
+        #[cfg(feature = "postgres")]
+        AnyDatabase::Postgres(pool) => Arc::new(PostgresIdempotencyStore::new(pool)),
+        #[cfg(feature = "mysql")]
+        AnyDatabase::Mysql(pool) => Arc::new(MysqlIdempotencyStore::new(pool)),
+        #[cfg(feature = "sqlite")]
+        AnyDatabase::Sqlite(pool) => Arc::new(SqliteIdempotencyStore::new(pool)),
+        #[cfg(feature = "in-memory")]
+        AnyDatabase::InMemory(db) => Arc::new(MemIdempotencyStore::new(db)),
+    }
+}
+
+// Finds the events committed for a request in the events table of the database the service
+// runs against.
+pub struct EventStoreCommittedRequests {
+    db: AnyDatabase,
+}
+
+impl EventStoreCommittedRequests {
+    pub fn new(db: AnyDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl CommittedRequests for EventStoreCommittedRequests {
+    async fn is_committed(
+        &self,
+        aggregate_type: &str,
+        aggregate_id: &str,
+        request_id: &str,
+    ) -> crate::prelude::Result<bool> {
+        // Every backend has a JSON syntax of its own.
+        let events: i64 = match &self.db {
+            #[cfg(feature = "postgres")]
+            AnyDatabase::Postgres(pool) => {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM events WHERE aggregate_type = $1 AND aggregate_id = $2 AND metadata->>'request_id' = $3",
+                )
+                .bind(aggregate_type)
+                .bind(aggregate_id)
+                .bind(request_id)
+                .fetch_one(pool)
+                .await?
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabase::Mysql(pool) => {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM events WHERE aggregate_type = ? AND aggregate_id = ? AND JSON_UNQUOTE(JSON_EXTRACT(metadata, '$.request_id')) = ?",
+                )
+                .bind(aggregate_type)
+                .bind(aggregate_id)
+                .bind(request_id)
+                .fetch_one(pool)
+                .await?
+            }
+            #[cfg(feature = "sqlite")]
+            AnyDatabase::Sqlite(pool) => {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM events WHERE aggregate_type = ? AND aggregate_id = ? AND json_extract(metadata, '$.request_id') = ?",
+                )
+                .bind(aggregate_type)
+                .bind(aggregate_id)
+                .bind(request_id)
+                .fetch_one(pool)
+                .await?
+            }
+            #[cfg(feature = "in-memory")]
+            AnyDatabase::InMemory(db) => MemEventRepository::new(db.clone())
+                .aggregate_events(aggregate_type, aggregate_id)
+                .iter()
+                .filter(|event| {
+                    event.metadata[crate::application::REQUEST_ID_METADATA].as_str() == Some(request_id)
+                })
+                .count() as i64,
+        };
+        Ok(events > 0)
+    }
+}
@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::application::{
+    CommandOutcome, IdempotencyClaim, IdempotencyReservation, IdempotencyStore,
+};
+use crate::memory_es::{MemDatabase, MemRow};
+
+use super::idempotency_store::decode_outcome;
+
+// An idempotency key as stored in memory, like a row of the `idempotency_keys` table.
+#[derive(Serialize, Deserialize)]
+struct MemIdempotencyKey {
+    request: String,
+    request_id: String,
+    created_at: DateTime<Utc>,
+    claimed_at: Option<DateTime<Utc>>,
+    outcome: Option<String>,
+}
+
+// Idempotency keys kept in the `idempotency_keys` table of a `MemDatabase`.
+pub struct MemIdempotencyStore {
+    db: MemDatabase,
+}
+
+impl MemIdempotencyStore {
+    pub fn new(db: MemDatabase) -> Self {
+        Self { db }
+    }
+}
+
+// Rows are keyed by scope and key together, as the table's primary key is.
+fn scoped_key(scope: &str, key: &str) -> String {
+    serde_json::json!([scope, key]).to_string()
+}
+
+#[async_trait]
+impl IdempotencyStore for MemIdempotencyStore {
+    async fn reserve(
+        &self,
+        claim: &IdempotencyClaim<'_>,
+        now: DateTime<Utc>,
+        expired_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> crate::prelude::Result<IdempotencyReservation> {
+        let reserved = serde_json::to_value(MemIdempotencyKey {
+            request: claim.request.to_string(),
+            request_id: claim.request_id.to_string(),
+            created_at: now,
+            claimed_at: Some(now),
+            outcome: None,
+        })?;
+        // The key is looked up and claimed under the same lock, so that only one of two
+        // concurrent requests gets the reservation.
+        self.db.write(
+            "idempotency_keys",
+            |keys| -> crate::prelude::Result<IdempotencyReservation> {
+                let row_key = scoped_key(claim.scope, claim.key);
+                let existing = keys.get(&row_key).and_then(|existing| {
+                    serde_json::from_value::<MemIdempotencyKey>(existing.payload.clone()).ok()
+                });
+                match existing {
+                    Some(mut existing) if existing.created_at >= expired_before => {
+                        let reclaimable = existing.outcome.is_none()
+                            && existing.request == claim.request
+                            && existing
+                                .claimed_at
+                                .is_none_or(|claimed_at| claimed_at < abandoned_before);
+                        if !reclaimable {
+                            return Ok(IdempotencyReservation::Existing {
+                                request: existing.request,
+                                request_id: existing.request_id,
+                                outcome: decode_outcome(existing.outcome)?,
+                            });
+                        }
+                        existing.claimed_at = Some(now);
+                        let request_id = existing.request_id.clone();
+                        keys.insert(
+                            row_key,
+                            MemRow {
+                                version: 0,
+                                payload: serde_json::to_value(existing)?,
+                            },
+                        );
+                        Ok(IdempotencyReservation::Reserved {
+                            request_id,
+                            reclaimed: true,
+                        })
+                    }
+                    _ => {
+                        keys.insert(
+                            row_key,
+                            MemRow {
+                                version: 0,
+                                payload: reserved,
+                            },
+                        );
+                        Ok(IdempotencyReservation::Reserved {
+                            request_id: claim.request_id.to_string(),
+                            reclaimed: false,
+                        })
+                    }
+                }
+            },
+        )
+    }
+
+    async fn record_outcome(
+        &self,
+        scope: &str,
+        key: &str,
+        outcome: &CommandOutcome,
+    ) -> crate::prelude::Result<()> {
+        let outcome = serde_json::to_string(outcome)?;
+        self.db.write("idempotency_keys", |keys| {
+            if let Some(existing) = keys.get_mut(&scoped_key(scope, key)) {
+                existing.payload["outcome"] = serde_json::Value::String(outcome);
+            }
+        });
+        Ok(())
+    }
+
+    async fn release(&self, scope: &str, key: &str) -> crate::prelude::Result<()> {
+        self.db.write("idempotency_keys", |keys| {
+            if let Some(existing) = keys.get_mut(&scoped_key(scope, key)) {
+                if existing.payload["outcome"].is_null() {
+                    existing.payload["claimed_at"] = serde_json::Value::Null;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn forget_expired(&self, expired_before: DateTime<Utc>) -> crate::prelude::Result<u64> {
+        let forgotten = self.db.write("idempotency_keys", |keys| {
+            let stored = keys.len();
+            keys.retain(|_, existing| {
+                serde_json::from_value::<MemIdempotencyKey>(existing.payload.clone())
+                    .map_or(true, |existing| existing.created_at >= expired_before)
+            });
+            stored - keys.len()
+        });
+        Ok(forgotten as u64)
+    }
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+pub mod idempotency_store;
+#[cfg(feature = "in-memory")]
+pub mod mem_idempotency_store;
+#[cfg(feature = "mysql")]
+pub mod mysql_idempotency_store;
+#[cfg(feature = "postgres")]
+pub mod postgres_idempotency_store;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_idempotency_store;
+
+// Re-exports
+
+pub use idempotency_store::*;
+#[cfg(feature = "in-memory")]
+pub use mem_idempotency_store::*;
+#[cfg(feature = "mysql")]
+pub use mysql_idempotency_store::*;
+#[cfg(feature = "postgres")]
+pub use postgres_idempotency_store::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_idempotency_store::*;
@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, Pool};
+
+use crate::application::{
+    CommandOutcome, IdempotencyClaim, IdempotencyReservation, IdempotencyStore,
+};
+
+use super::idempotency_store::decode_outcome;
+
+// Idempotency keys kept in the `idempotency_keys` table.
+pub struct MysqlIdempotencyStore {
+    pool: Pool<MySql>,
+}
+
+impl MysqlIdempotencyStore {
+    pub fn new(pool: Pool<MySql>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MysqlIdempotencyStore {
+    async fn reserve(
+        &self,
+        claim: &IdempotencyClaim<'_>,
+        now: DateTime<Utc>,
+        expired_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> crate::prelude::Result<IdempotencyReservation> {
+        let inserted = sqlx::query(
+            "INSERT IGNORE INTO idempotency_keys (scope, idempotency_key, request, request_id, created_at, claimed_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(claim.scope)
+        .bind(claim.key)
+        .bind(claim.request)
+        .bind(claim.request_id)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        if inserted.rows_affected() == 1 {
+            return Ok(IdempotencyReservation::Reserved {
+                request_id: claim.request_id.to_string(),
+                reclaimed: false,
+            });
+        }
+        // Only one of two concurrent requests claims an expired key, the other no longer
+        // matches once the creation time has been moved on.
+        let claimed = sqlx::query(
+            "UPDATE idempotency_keys SET request = ?, request_id = ?, outcome = NULL, created_at = ?, claimed_at = ? WHERE scope = ? AND idempotency_key = ? AND created_at < ?",
+        )
+        .bind(claim.request)
+        .bind(claim.request_id)
+        .bind(now)
+        .bind(now)
+        .bind(claim.scope)
+        .bind(claim.key)
+        .bind(expired_before)
+        .execute(&self.pool)
+        .await?;
+        if claimed.rows_affected() == 1 {
+            return Ok(IdempotencyReservation::Reserved {
+                request_id: claim.request_id.to_string(),
+                reclaimed: false,
+            });
+        }
+        // The same goes for taking over a released or abandoned attempt at the request.
+        let reclaimed = sqlx::query(
+            "UPDATE idempotency_keys SET claimed_at = ? WHERE scope = ? AND idempotency_key = ? AND request = ? AND outcome IS NULL AND (claimed_at IS NULL OR claimed_at < ?)",
+        )
+        .bind(now)
+        .bind(claim.scope)
+        .bind(claim.key)
+        .bind(claim.request)
+        .bind(abandoned_before)
+        .execute(&self.pool)
+        .await?;
+        let (request, request_id, outcome): (String, String, Option<String>) = sqlx::query_as(
+            "SELECT request, request_id, outcome FROM idempotency_keys WHERE scope = ? AND idempotency_key = ?",
+        )
+        .bind(claim.scope)
+        .bind(claim.key)
+        .fetch_one(&self.pool)
+        .await?;
+        if reclaimed.rows_affected() == 1 {
+            return Ok(IdempotencyReservation::Reserved {
+                request_id,
+                reclaimed: true,
+            });
+        }
+        Ok(IdempotencyReservation::Existing {
+            request,
+            request_id,
+            outcome: decode_outcome(outcome)?,
+        })
+    }
+
+    async fn record_outcome(
+        &self,
+        scope: &str,
+        key: &str,
+        outcome: &CommandOutcome,
+    ) -> crate::prelude::Result<()> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET outcome = ? WHERE scope = ? AND idempotency_key = ?",
+        )
+        .bind(serde_json::to_string(outcome)?)
+        .bind(scope)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn release(&self, scope: &str, key: &str) -> crate::prelude::Result<()> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET claimed_at = NULL WHERE scope = ? AND idempotency_key = ? AND outcome IS NULL",
+        )
+        .bind(scope)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn forget_expired(&self, expired_before: DateTime<Utc>) -> crate::prelude::Result<u64> {
+        let forgotten = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ?")
+            .bind(expired_before)
+            .execute(&self.pool)
+            .await?;
+        Ok(forgotten.rows_affected())
+    }
+}
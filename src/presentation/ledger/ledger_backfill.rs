@@ -0,0 +1,127 @@
+use cqrs_es::persist::PersistedEventRepository;
+use cqrs_es::{Aggregate, EventEnvelope, Query};
+
+use crate::any_es::AnyEventRepository;
+use crate::application::GeneralLedger;
+
+use super::*;
+
+// The general ledger of the database the service runs against, which looks up the events it
+// posts late in the event store.
+pub fn new_general_ledger(db: AnyDatabase) -> GeneralLedger {
+    GeneralLedger::new(
+        new_ledger_store(db.clone()),
+        Arc::new(EventStoreBankAccountEventLookup::new(db)),
+    )
+}
+
+// Posts every stored bank account event to the general ledger and returns how many events
+// were read, e.g. for the events stored before the ledger was. Events already posted are left
+// as they are and events that cannot be posted are recorded as unposted, so it can be run
+// again at any time.
+pub async fn backfill_general_ledger(db: AnyDatabase) -> crate::prelude::Result<usize> {
+    let ledger = new_general_ledger(db.clone());
+    let repository = AnyEventRepository::new(db.clone());
+    let mut read = 0;
+    // Events are read an account at a time, as the event store loads them, so that legacy
+    // events are upcast before they are deserialized.
+    for account_id in bank_account_ids(&db).await? {
+        let events = repository
+            .get_events::<BankAccount>(&account_id)
+            .await?
+            .into_iter()
+            .map(|event| EventEnvelope::try_from(upcast_bank_account_event(event)))
+            .collect::<Result<Vec<EventEnvelope<BankAccount>>, _>>()?;
+        read += events.len();
+        ledger.dispatch(&account_id, &events).await;
+    }
+    Ok(read)
+}
+
+// The ids of every bank account with stored events, open or not.
+async fn bank_account_ids(db: &AnyDatabase) -> crate::prelude::Result<Vec<String>> {
+    let aggregate_type = BankAccount::aggregate_type();
+    let account_ids: Vec<String> = match db {
+        #[cfg(feature = "postgres")]
+        AnyDatabase::Postgres(pool) => {
+            sqlx::query_scalar(
+                "SELECT DISTINCT aggregate_id FROM events WHERE aggregate_type = $1 ORDER BY aggregate_id",
+            )
+            .bind(aggregate_type)
+            .fetch_all(pool)
+            .await?
+        }
+        #[cfg(feature = "mysql")]
+        AnyDatabase::Mysql(pool) => {
+            sqlx::query_scalar(
+                "SELECT DISTINCT aggregate_id FROM events WHERE aggregate_type = ? ORDER BY aggregate_id",
+            )
+            .bind(aggregate_type)
+            .fetch_all(pool)
+            .await?
+        }
+        #[cfg(feature = "sqlite")]
+        AnyDatabase::Sqlite(pool) => {
+            sqlx::query_scalar(
+                "SELECT DISTINCT aggregate_id FROM events WHERE aggregate_type = ? ORDER BY aggregate_id",
+            )
+            .bind(aggregate_type)
+            .fetch_all(pool)
+            .await?
+        }
+        #[cfg(feature = "in-memory")]
+        AnyDatabase::InMemory(db) => {
+            crate::memory_es::MemEventRepository::new(db.clone()).aggregate_ids(&aggregate_type)
+        }
+    };
+    Ok(account_ids)
+}
+
+#[cfg(all(test, feature = "in-memory"))]
+mod tests {
+    use super::*;
+    use crate::application::{
+        BankAccountServices, HappyPathBankAccountServices, HappyPathCustomerKycLookup,
+        LedgerAccount,
+    };
+    use crate::memory_es::MemDatabase;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn events_stored_before_the_ledger_are_backfilled() {
+        let db = AnyDatabase::InMemory(MemDatabase::default());
+        // Without the general ledger query, as before the ledger was added.
+        let cqrs = crate::any_es::any_cqrs::<BankAccount>(
+            db.clone(),
+            vec![],
+            BankAccountServices::new(Box::<HappyPathBankAccountServices>::default())
+                .with_customers(Box::new(HappyPathCustomerKycLookup)),
+        );
+        cqrs.execute(
+            "1234",
+            BankAccountCommand::OpenAccount(BankAccountOpenAccountCommandData {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: "customer-1".to_string(),
+                product: AccountProduct::Checking,
+            }),
+        )
+        .await
+        .unwrap();
+        cqrs.execute(
+            "1234",
+            BankAccountCommand::DepositMoney(BankAccountDepositMoneyCommandData {
+                amount: Money::new(20000, Currency::EUR),
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(backfill_general_ledger(db.clone()).await.unwrap(), 2);
+        assert_eq!(backfill_general_ledger(db.clone()).await.unwrap(), 2);
+        let trial_balance = new_ledger_store(db).trial_balance().await.unwrap();
+        let deposits = &trial_balance.accounts[0];
+        assert_eq!(deposits.ledger_account, LedgerAccount::CustomerDeposits);
+        assert_eq!(deposits.credits, Money::new(20000, Currency::EUR));
+    }
+}
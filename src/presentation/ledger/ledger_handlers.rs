@@ -0,0 +1,59 @@
+use super::*;
+
+use crate::application::LedgerStore;
+
+// Serves the trial balance of the general ledger, in which the debits and credits of every
+// currency are equal. The ledger spans every account, so only operators can read it.
+#[utoipa::path(
+    get,
+    tag = "General Ledger",
+    path = "/api/ledger/trial-balance",
+    params(
+        ("X-Operator-Id" = Option<String>, Header, description = "Operator the trial balance is read by")
+    ),
+    responses(
+        (status = 200, description = "Get the trial balance of the general ledger", body = TrialBalance),
+        (status = 403, description = "Not an operator")
+    )
+)]
+#[instrument(skip(ledger))]
+pub async fn trial_balance_handler(
+    Extension(ledger): Extension<Arc<dyn LedgerStore>>,
+    CallerExtension(caller): CallerExtension,
+) -> Response {
+    if !caller.is_operator() {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match ledger.trial_balance().await {
+        Ok(trial_balance) => (StatusCode::OK, Json(trial_balance)).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+// Serves the bank account events that could not be posted to the general ledger yet, and why.
+// The trial balance leaves them out until they are posted.
+#[utoipa::path(
+    get,
+    tag = "General Ledger",
+    path = "/api/ledger/unposted-events",
+    params(
+        ("X-Operator-Id" = Option<String>, Header, description = "Operator the unposted events are read by")
+    ),
+    responses(
+        (status = 200, description = "Get the events not posted to the general ledger yet", body = [UnpostedEvent]),
+        (status = 403, description = "Not an operator")
+    )
+)]
+#[instrument(skip(ledger))]
+pub async fn unposted_events_handler(
+    Extension(ledger): Extension<Arc<dyn LedgerStore>>,
+    CallerExtension(caller): CallerExtension,
+) -> Response {
+    if !caller.is_operator() {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match ledger.unposted_events().await {
+        Ok(unposted) => (StatusCode::OK, Json(unposted)).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,68 @@
+use crate::application::{
+    LedgerAccount, LedgerBalance, LedgerPosting, LedgerStore, TrialBalance, UnpostedEvent,
+};
+use crate::domain::{Currency, Money};
+
+use super::*;
+
+fn ledger_account(code: &str) -> crate::prelude::Result<LedgerAccount> {
+    LedgerAccount::from_code(code)
+        .ok_or_else(|| crate::prelude::Error::Generic(format!("unknown ledger account {code}")))
+}
+
+pub(super) fn decode_posting(
+    (code, currency, amount): (String, String, i64),
+) -> crate::prelude::Result<LedgerPosting> {
+    Ok(LedgerPosting {
+        ledger_account: ledger_account(&code)?,
+        amount: Money::new(amount, Currency::try_from(currency)?),
+    })
+}
+
+// Sorted by currency, then in chart of accounts order.
+pub(super) fn decode_trial_balance(
+    rows: Vec<(String, String, i64, i64)>,
+) -> crate::prelude::Result<TrialBalance> {
+    let mut balances = rows
+        .into_iter()
+        .map(|(code, currency, debits, credits)| {
+            let currency = Currency::try_from(currency)?;
+            Ok(LedgerBalance {
+                ledger_account: ledger_account(&code)?,
+                debits: Money::new(debits, currency),
+                credits: Money::new(credits, currency),
+            })
+        })
+        .collect::<crate::prelude::Result<Vec<_>>>()?;
+    balances.sort_by_key(|balance| {
+        (
+            String::from(balance.debits.currency()),
+            balance.ledger_account,
+        )
+    });
+    TrialBalance::new(balances)
+}
+
+pub(super) fn decode_unposted_event(
+    (account_id, sequence, error): (String, i64, String),
+) -> UnpostedEvent {
+    UnpostedEvent {
+        account_id,
+        sequence: sequence as usize,
+        error,
+    }
+}
+
+// The general ledger in the database the service runs against.
+pub fn new_ledger_store(db: AnyDatabase) -> Arc<dyn LedgerStore> {
+    match db {
+        #[cfg(feature = "postgres")]
+        AnyDatabase::Postgres(pool) => Arc::new(PostgresLedgerStore::new(pool)),
+        #[cfg(feature = "mysql")]
+        AnyDatabase::Mysql(pool) => Arc::new(MysqlLedgerStore::new(pool)),
+        #[cfg(feature = "sqlite")]
+        AnyDatabase::Sqlite(pool) => Arc::new(SqliteLedgerStore::new(pool)),
+        #[cfg(feature = "in-memory")]
+        AnyDatabase::InMemory(db) => Arc::new(MemLedgerStore::new(db)),
+    }
+}
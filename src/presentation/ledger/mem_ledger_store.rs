@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::application::{JournalEntry, LedgerStore, TrialBalance, UnpostedEvent};
+use crate::memory_es::{MemDatabase, MemRow};
+
+use super::ledger_store::{decode_posting, decode_trial_balance, decode_unposted_event};
+
+// A journal entry as stored in memory, with its postings as (ledger account, currency,
+// amount) like the rows of the `ledger_postings` table.
+#[derive(Serialize, Deserialize)]
+struct MemJournalEntry {
+    account_id: String,
+    sequence: usize,
+    reference: Option<String>,
+    postings: Vec<(String, String, i64)>,
+}
+
+impl MemJournalEntry {
+    fn decode(self) -> crate::prelude::Result<JournalEntry> {
+        Ok(JournalEntry {
+            account_id: self.account_id,
+            sequence: self.sequence,
+            reference: self.reference,
+            postings: self
+                .postings
+                .into_iter()
+                .map(decode_posting)
+                .collect::<crate::prelude::Result<_>>()?,
+        })
+    }
+}
+
+// Keys sort by account and then by sequence number.
+fn entry_key(account_id: &str, sequence: usize) -> String {
+    format!("{account_id}/{sequence:020}")
+}
+
+// The general ledger kept in the `ledger_entries` table of a `MemDatabase`, and the events not
+// posted to it yet in `ledger_unposted_events`.
+pub struct MemLedgerStore {
+    db: MemDatabase,
+}
+
+impl MemLedgerStore {
+    pub fn new(db: MemDatabase) -> Self {
+        Self { db }
+    }
+
+    fn entries(&self) -> crate::prelude::Result<Vec<MemJournalEntry>> {
+        let payloads: Vec<serde_json::Value> = self.db.read("ledger_entries", |entries| {
+            entries
+                .values()
+                .map(|entry| entry.payload.clone())
+                .collect()
+        });
+        Ok(payloads
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+#[async_trait]
+impl LedgerStore for MemLedgerStore {
+    async fn record(&self, entry: &JournalEntry) -> crate::prelude::Result<()> {
+        let payload = serde_json::to_value(MemJournalEntry {
+            account_id: entry.account_id.clone(),
+            sequence: entry.sequence,
+            reference: entry.reference.clone(),
+            postings: entry
+                .postings
+                .iter()
+                .map(|posting| {
+                    (
+                        posting.ledger_account.code().to_string(),
+                        String::from(posting.amount.currency()),
+                        posting.amount.minor_units(),
+                    )
+                })
+                .collect(),
+        })?;
+        self.db.write("ledger_entries", |entries| {
+            entries
+                .entry(entry_key(&entry.account_id, entry.sequence))
+                .or_insert(MemRow {
+                    version: 0,
+                    payload,
+                });
+        });
+        Ok(())
+    }
+
+    async fn entry(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<Option<JournalEntry>> {
+        let payload = self.db.read("ledger_entries", |entries| {
+            entries
+                .get(&entry_key(account_id, sequence))
+                .map(|entry| entry.payload.clone())
+        });
+        payload
+            .map(|payload| serde_json::from_value::<MemJournalEntry>(payload)?.decode())
+            .transpose()
+    }
+
+    async fn entry_with_reference(
+        &self,
+        account_id: &str,
+        reference: &str,
+    ) -> crate::prelude::Result<Option<JournalEntry>> {
+        self.entries()?
+            .into_iter()
+            .find(|entry| {
+                entry.account_id == account_id && entry.reference.as_deref() == Some(reference)
+            })
+            .map(MemJournalEntry::decode)
+            .transpose()
+    }
+
+    async fn trial_balance(&self) -> crate::prelude::Result<TrialBalance> {
+        let mut balances: BTreeMap<(String, String), (i64, i64)> = BTreeMap::new();
+        for entry in self.entries()? {
+            for (code, currency, amount) in entry.postings {
+                let (debits, credits) = balances.entry((code, currency)).or_default();
+                if amount > 0 {
+                    *debits += amount;
+                } else {
+                    *credits -= amount;
+                }
+            }
+        }
+        decode_trial_balance(
+            balances
+                .into_iter()
+                .map(|((code, currency), (debits, credits))| (code, currency, debits, credits))
+                .collect(),
+        )
+    }
+
+    async fn record_unposted(&self, event: &UnpostedEvent) -> crate::prelude::Result<()> {
+        let payload = serde_json::to_value((&event.account_id, event.sequence, &event.error))?;
+        self.db.write("ledger_unposted_events", |events| {
+            events.insert(
+                entry_key(&event.account_id, event.sequence),
+                MemRow {
+                    version: 0,
+                    payload,
+                },
+            );
+        });
+        Ok(())
+    }
+
+    async fn remove_unposted(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<()> {
+        self.db.write("ledger_unposted_events", |events| {
+            events.remove(&entry_key(account_id, sequence));
+        });
+        Ok(())
+    }
+
+    async fn unposted_events(&self) -> crate::prelude::Result<Vec<UnpostedEvent>> {
+        let payloads: Vec<serde_json::Value> = self.db.read("ledger_unposted_events", |events| {
+            events.values().map(|event| event.payload.clone()).collect()
+        });
+        Ok(payloads
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(decode_unposted_event)
+            .collect())
+    }
+}
@@ -0,0 +1,29 @@
+use super::*;
+
+use std::sync::Arc;
+
+pub mod ledger_backfill;
+pub mod ledger_handlers;
+pub mod ledger_store;
+#[cfg(feature = "in-memory")]
+pub mod mem_ledger_store;
+#[cfg(feature = "mysql")]
+pub mod mysql_ledger_store;
+#[cfg(feature = "postgres")]
+pub mod postgres_ledger_store;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_ledger_store;
+
+// Re-exports
+
+pub use ledger_backfill::*;
+pub use ledger_handlers::*;
+pub use ledger_store::*;
+#[cfg(feature = "in-memory")]
+pub use mem_ledger_store::*;
+#[cfg(feature = "mysql")]
+pub use mysql_ledger_store::*;
+#[cfg(feature = "postgres")]
+pub use postgres_ledger_store::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_ledger_store::*;
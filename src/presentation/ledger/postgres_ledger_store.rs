@@ -0,0 +1,145 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use crate::application::{JournalEntry, LedgerStore, TrialBalance, UnpostedEvent};
+
+use super::ledger_store::{decode_posting, decode_trial_balance, decode_unposted_event};
+
+// The general ledger kept in the `ledger_entries` and `ledger_postings` tables, and the events
+// not posted to it yet in `ledger_unposted_events`.
+pub struct PostgresLedgerStore {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresLedgerStore {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LedgerStore for PostgresLedgerStore {
+    async fn record(&self, entry: &JournalEntry) -> crate::prelude::Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        let inserted = sqlx::query(
+            "INSERT INTO ledger_entries (account_id, sequence, reference) VALUES ($1, $2, $3) ON CONFLICT (account_id, sequence) DO NOTHING",
+        )
+        .bind(&entry.account_id)
+        .bind(entry.sequence as i64)
+        .bind(&entry.reference)
+        .execute(&mut transaction)
+        .await?;
+        if inserted.rows_affected() == 0 {
+            return Ok(());
+        }
+        for (line, posting) in entry.postings.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO ledger_postings (account_id, sequence, line, ledger_account, currency, amount) VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(&entry.account_id)
+            .bind(entry.sequence as i64)
+            .bind(line as i32)
+            .bind(posting.ledger_account.code())
+            .bind(String::from(posting.amount.currency()))
+            .bind(posting.amount.minor_units())
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn entry(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<Option<JournalEntry>> {
+        let reference: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT reference FROM ledger_entries WHERE account_id = $1 AND sequence = $2",
+        )
+        .bind(account_id)
+        .bind(sequence as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(reference) = reference else {
+            return Ok(None);
+        };
+        let postings: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT ledger_account, currency, amount FROM ledger_postings WHERE account_id = $1 AND sequence = $2 ORDER BY line",
+        )
+        .bind(account_id)
+        .bind(sequence as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(Some(JournalEntry {
+            account_id: account_id.to_string(),
+            sequence,
+            reference,
+            postings: postings
+                .into_iter()
+                .map(decode_posting)
+                .collect::<crate::prelude::Result<_>>()?,
+        }))
+    }
+
+    async fn entry_with_reference(
+        &self,
+        account_id: &str,
+        reference: &str,
+    ) -> crate::prelude::Result<Option<JournalEntry>> {
+        let sequence: Option<i64> = sqlx::query_scalar(
+            "SELECT sequence FROM ledger_entries WHERE account_id = $1 AND reference = $2 ORDER BY sequence LIMIT 1",
+        )
+        .bind(account_id)
+        .bind(reference)
+        .fetch_optional(&self.pool)
+        .await?;
+        match sequence {
+            Some(sequence) => self.entry(account_id, sequence as usize).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn trial_balance(&self) -> crate::prelude::Result<TrialBalance> {
+        let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+            "SELECT ledger_account, currency, CAST(SUM(CASE WHEN amount > 0 THEN amount ELSE 0 END) AS BIGINT), CAST(SUM(CASE WHEN amount < 0 THEN -amount ELSE 0 END) AS BIGINT) FROM ledger_postings GROUP BY ledger_account, currency",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        decode_trial_balance(rows)
+    }
+
+    async fn record_unposted(&self, event: &UnpostedEvent) -> crate::prelude::Result<()> {
+        sqlx::query(
+            "INSERT INTO ledger_unposted_events (account_id, sequence, error) VALUES ($1, $2, $3) ON CONFLICT (account_id, sequence) DO UPDATE SET error = excluded.error",
+        )
+        .bind(&event.account_id)
+        .bind(event.sequence as i64)
+        .bind(&event.error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_unposted(
+        &self,
+        account_id: &str,
+        sequence: usize,
+    ) -> crate::prelude::Result<()> {
+        sqlx::query("DELETE FROM ledger_unposted_events WHERE account_id = $1 AND sequence = $2")
+            .bind(account_id)
+            .bind(sequence as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn unposted_events(&self) -> crate::prelude::Result<Vec<UnpostedEvent>> {
+        let rows: Vec<(String, i64, String)> = sqlx::query_as(
+            "SELECT account_id, sequence, error FROM ledger_unposted_events ORDER BY account_id, sequence",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(decode_unposted_event).collect())
+    }
+}
@@ -1,13 +1,38 @@
 use super::*;
 use axum::extract::{FromRequest, FromRequestParts};
-use axum::http::{request::Parts, Request, StatusCode};
+use axum::http::{request::Parts, HeaderMap, Request, StatusCode};
 use std::collections::HashMap;
 use std::convert::Infallible;
 
+use crate::application::IDEMPOTENCY_KEY_METADATA;
+
 // This is a custom Axum extension that builds metadata from the inbound request.
 pub struct MetadataExtension(pub HashMap<String, String>);
 
 const USER_AGENT_HDR: &str = "User-Agent";
+pub const IDEMPOTENCY_KEY_HDR: &str = "Idempotency-Key";
+
+// Retried requests carrying the same key are only executed once.
+fn insert_idempotency_key(metadata: &mut HashMap<String, String>, key: Option<&str>) {
+    if let Some(key) = key.filter(|key| !key.is_empty()) {
+        metadata.insert(IDEMPOTENCY_KEY_METADATA.to_string(), key.to_string());
+    }
+}
+
+fn idempotency_key_header(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(IDEMPOTENCY_KEY_HDR)
+        .and_then(|value| value.to_str().ok())
+}
+
+// The metadata submitted with commands issued through GraphQL, where the idempotency key is
+// an argument of the mutation rather than a header.
+pub fn graphql_metadata(idempotency_key: Option<&str>) -> HashMap<String, String> {
+    let mut metadata = HashMap::default();
+    metadata.insert("time".to_string(), chrono::Utc::now().to_rfc3339());
+    insert_idempotency_key(&mut metadata, idempotency_key);
+    metadata
+}
 
 #[async_trait]
 impl<S, B> FromRequest<S, B> for MetadataExtension
@@ -28,6 +53,7 @@ where
                 metadata.insert(USER_AGENT_HDR.to_string(), value.to_string());
             }
         }
+        insert_idempotency_key(&mut metadata, idempotency_key_header(req.headers()));
         Ok(MetadataExtension(metadata))
     }
 }
@@ -48,6 +74,41 @@ where
         let mut metadata = HashMap::default();
         metadata.insert("time".to_string(), chrono::Utc::now().to_rfc3339());
         metadata.insert("path".to_string(), parts.uri.path().to_string());
+        insert_idempotency_key(&mut metadata, idempotency_key_header(&parts.headers));
         Ok(MetadataExtension(metadata))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn idempotency_keys_are_carried_in_the_metadata() {
+        let (mut parts, _) = Request::builder()
+            .uri("/api/bank-accounts/1234")
+            .header(IDEMPOTENCY_KEY_HDR, "deposit-1")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let MetadataExtension(metadata) = MetadataExtension::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        assert_eq!(
+            metadata.get(IDEMPOTENCY_KEY_METADATA),
+            Some(&"deposit-1".to_string())
+        );
+    }
+
+    #[test]
+    fn graphql_metadata_carries_the_idempotency_key_argument() {
+        let metadata = graphql_metadata(Some("deposit-1"));
+        assert_eq!(
+            metadata.get(IDEMPOTENCY_KEY_METADATA),
+            Some(&"deposit-1".to_string())
+        );
+        assert!(metadata.contains_key("time"));
+        assert!(!graphql_metadata(Some("")).contains_key(IDEMPOTENCY_KEY_METADATA));
+    }
+}
@@ -2,6 +2,7 @@
 use mockall::predicate::*;
 
 use super::domain::*;
+use crate::any_es::{AnyCqrs, AnyDatabase, AnyViewRepository};
 use async_trait::async_trait;
 use axum::{
     extract::Path, http::StatusCode, response::IntoResponse, response::Response, Extension, Json,
@@ -10,13 +11,25 @@ use serde::{Deserialize, Serialize};
 use tracing::instrument;
 use utoipa::{ToResponse, ToSchema};
 
+pub mod caller_extension;
+pub mod idempotency;
 pub mod metadata_extension;
 
 pub mod bank_account;
+pub mod customer;
 pub mod graphql;
+pub mod ledger;
 pub mod openapi;
+pub mod router;
+pub mod transfer;
 
 // Re-exports
 pub use bank_account::*;
+pub use caller_extension::*;
+pub use customer::*;
+pub use idempotency::*;
+pub use ledger::*;
 pub use metadata_extension::*;
 pub use openapi::*;
+pub use router::*;
+pub use transfer::*;
@@ -3,7 +3,10 @@ use utoipa::{
     Modify, OpenApi,
 };
 
-use crate::domain::BankAccountCommand;
+use crate::application::{
+    LedgerAccount, LedgerBalance, TrialBalance, TrialBalanceTotal, UnpostedEvent,
+};
+use crate::domain::{BankAccountCommand, Money};
 use crate::presentation::*;
 
 #[derive(OpenApi)]
@@ -11,6 +14,14 @@ use crate::presentation::*;
       paths(
           bank_account::query_handler,
           bank_account::command_handler,
+          bank_account::statement_handler,
+          bank_account::standing_orders_handler,
+          transfer::transfer_query_handler,
+          transfer::transfer_command_handler,
+          customer::customer_query_handler,
+          customer::customer_command_handler,
+          ledger::trial_balance_handler,
+          ledger::unposted_events_handler,
       ),
       components(
           schemas(
@@ -20,11 +31,67 @@ use crate::presentation::*;
             BankAccountDepositMoneyCommandData,
             BankAccountWithdrawMoneyCommandData,
             BankAccountWriteCheckCommandData,
-            AccountTransaction),
+            BankAccountFreezeAccountCommandData,
+            BankAccountUnfreezeAccountCommandData,
+            BankAccountCloseAccountCommandData,
+            BankAccountSetOverdraftLimitCommandData,
+            BankAccountAccrueInterestCommandData,
+            BankAccountTransferOutCommandData,
+            BankAccountTransferInCommandData,
+            BankAccountRefundTransferCommandData,
+            BankAccountCompleteTransferCommandData,
+            BankAccountPlaceHoldCommandData,
+            BankAccountCaptureHoldCommandData,
+            BankAccountReleaseHoldCommandData,
+            BankAccountAddHolderCommandData,
+            BankAccountRemoveHolderCommandData,
+            BankAccountClearCheckCommandData,
+            BankAccountBounceCheckCommandData,
+            BankAccountReverseTransactionCommandData,
+            BankAccountChargeMaintenanceFeeCommandData,
+            BankAccountCreateStandingOrderCommandData,
+            BankAccountCancelStandingOrderCommandData,
+            BankAccountExecuteStandingOrderCommandData,
+            AccountStatus,
+            AccountProduct,
+            AccountTransaction,
+            AccountTransactionType,
+            PendingHold,
+            CheckRecord,
+            CheckStatus,
+            Statement,
+            StatementTotal,
+            StatementLine,
+            StandingOrdersView,
+            StandingOrderRecord,
+            StandingOrderStatus,
+            StandingOrderPayment,
+            TransferView,
+            TransferStatus,
+            TransferRequestTransferCommandData,
+            TransferAccepted,
+            CustomerView,
+            CustomerCommand,
+            CustomerRegisterCustomerCommandData,
+            CustomerChangeAddressCommandData,
+            CustomerSubmitKycCommandData,
+            CustomerVerifyKycCommandData,
+            CustomerRejectKycCommandData,
+            Address,
+            KycStatus,
+            TrialBalance,
+            TrialBalanceTotal,
+            UnpostedEvent,
+            LedgerBalance,
+            LedgerAccount,
+            Money),
     ),
       modifiers(&SecurityAddon),
       tags(
-          (name = "Bank Accounts", description = "Bank Account Management API")
+          (name = "Bank Accounts", description = "Bank Account Management API"),
+          (name = "Transfers", description = "Transfers Between Bank Accounts API"),
+          (name = "Customers", description = "Customer Registration and KYC API"),
+          (name = "General Ledger", description = "Double-Entry Bookkeeping of All Bank Accounts API")
       ),
         info(
             title = "Bank Account API: built with Veloxide",
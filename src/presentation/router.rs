@@ -0,0 +1,305 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use super::*;
+
+// The REST API. The frameworks, view repositories and stores that the handlers use are
+// added by the caller as `Extension` layers.
+pub fn new_api_router() -> Router {
+    Router::new()
+        .route(
+            "/api/bank-accounts/:id",
+            get(bank_account::query_handler).post(bank_account::command_handler),
+        )
+        .route(
+            "/api/bank-accounts/:id/statements/:period",
+            get(bank_account::statement_handler),
+        )
+        .route(
+            "/api/bank-accounts/:id/standing-orders",
+            get(bank_account::standing_orders_handler),
+        )
+        .route("/api/transfers", post(transfer::transfer_command_handler))
+        .route("/api/transfers/:id", get(transfer::transfer_query_handler))
+        .route(
+            "/api/customers/:id",
+            get(customer::customer_query_handler).post(customer::customer_command_handler),
+        )
+        .route(
+            "/api/ledger/trial-balance",
+            get(ledger::trial_balance_handler),
+        )
+        .route(
+            "/api/ledger/unposted-events",
+            get(ledger::unposted_events_handler),
+        )
+}
+
+#[cfg(all(test, feature = "in-memory"))]
+mod tests {
+    use super::*;
+    use crate::any_es::AnyDatabase;
+    use crate::application::{
+        BankAccountServices, HappyPathBankAccountServices, IdempotencyPolicy, IdempotentCommands,
+    };
+    use crate::memory_es::MemDatabase;
+    use axum::body::Body;
+    use axum::http::Request;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    // The API as it is served, with everything kept in memory.
+    async fn app() -> Router {
+        let db = AnyDatabase::InMemory(MemDatabase::default());
+        let (customer_cqrs, customer_query) = get_customer_cqrs_framework(db.clone());
+        let services = BankAccountServices::new(Box::<HappyPathBankAccountServices>::default())
+            .with_customers(Box::new(CustomerViewKycLookup::new(customer_query.clone())));
+        let (cqrs, account_query, statement_query, standing_order_query) =
+            get_bank_account_cqrs_framework(db.clone(), services);
+        let (transfer_cqrs, transfer_query, _transfer_requested) =
+            get_transfer_cqrs_framework(db.clone());
+        let general_ledger = new_ledger_store(db.clone());
+        let idempotent_commands = Arc::new(IdempotentCommands::new(
+            new_idempotency_store(db.clone()),
+            Arc::new(EventStoreCommittedRequests::new(db)),
+            IdempotencyPolicy::default(),
+        ));
+        let graphql_router = graphql::new_graphql_router(
+            cqrs.clone(),
+            account_query.clone(),
+            transfer_cqrs.clone(),
+            transfer_query.clone(),
+            customer_cqrs.clone(),
+            customer_query.clone(),
+            idempotent_commands.clone(),
+        )
+        .await;
+
+        new_api_router()
+            .nest("/graphql", graphql_router)
+            .layer(Extension(cqrs))
+            .layer(Extension(account_query))
+            .layer(Extension(statement_query))
+            .layer(Extension(standing_order_query))
+            .layer(Extension(transfer_cqrs))
+            .layer(Extension(transfer_query))
+            .layer(Extension(customer_cqrs))
+            .layer(Extension(customer_query))
+            .layer(Extension(general_ledger))
+            .layer(Extension(idempotent_commands))
+    }
+
+    async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    async fn get_json(app: &Router, uri: &str) -> (StatusCode, Value) {
+        get_json_as(app, uri, HOLDER_ID_HDR, "customer-1").await
+    }
+
+    async fn get_json_as(
+        app: &Router,
+        uri: &str,
+        header: &str,
+        caller: &str,
+    ) -> (StatusCode, Value) {
+        let request = Request::get(uri).header(header, caller).body(Body::empty());
+        send(app, request.unwrap()).await
+    }
+
+    async fn post_json(app: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+        post_json_as(app, uri, HOLDER_ID_HDR, "customer-1", body).await
+    }
+
+    async fn post_json_as(
+        app: &Router,
+        uri: &str,
+        header: &str,
+        caller: &str,
+        body: Value,
+    ) -> (StatusCode, Value) {
+        let request = Request::post(uri)
+            .header("content-type", "application/json")
+            .header(header, caller)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        send(app, request).await
+    }
+
+    async fn register_customer(app: &Router, verify_kyc: bool) {
+        let commands = [
+            json!({ "RegisterCustomer": {
+                "name": "Ada Lovelace",
+                "email": "ada@example.com",
+                "address": {
+                    "line1": "1 Main Street",
+                    "city": "Amsterdam",
+                    "postal_code": "1011 AB",
+                    "country": "NL"
+                }
+            } }),
+            json!({ "SubmitKyc": { "document_reference": "passport-1" } }),
+        ];
+        for command in commands {
+            let (status, _) = post_json(app, "/api/customers/customer-1", command).await;
+            assert_eq!(status, StatusCode::NO_CONTENT);
+        }
+        // Customers cannot pass their own KYC check, an operator records its outcome.
+        let verify = json!({ "VerifyKyc": { "verified_by": "compliance" } });
+        let (status, _) = post_json(app, "/api/customers/customer-1", verify.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        if verify_kyc {
+            let (status, _) = post_json_as(
+                app,
+                "/api/customers/customer-1",
+                OPERATOR_ID_HDR,
+                "operator-1",
+                verify,
+            )
+            .await;
+            assert_eq!(status, StatusCode::NO_CONTENT);
+        }
+    }
+
+    fn open_account() -> Value {
+        json!({ "OpenAccount": { "account_id": "1234", "primary_holder_id": "customer-1" } })
+    }
+
+    #[tokio::test]
+    async fn deposits_show_up_in_the_account_view_and_the_general_ledger() {
+        let app = app().await;
+        register_customer(&app, true).await;
+        let (status, _) = post_json(&app, "/api/bank-accounts/1234", open_account()).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let deposit =
+            json!({ "DepositMoney": { "amount": { "minor_units": 5000, "currency": "EUR" } } });
+        let (status, _) = post_json(&app, "/api/bank-accounts/1234", deposit).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, account) = get_json(&app, "/api/bank-accounts/1234").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(account["balance"]["minor_units"], json!(5000));
+        for uri in ["/api/ledger/trial-balance", "/api/ledger/unposted-events"] {
+            let (status, _) = get_json(&app, uri).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+        let (status, trial_balance) = get_json_as(
+            &app,
+            "/api/ledger/trial-balance",
+            OPERATOR_ID_HDR,
+            "operator-1",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            trial_balance["totals"][0]["debits"]["minor_units"],
+            json!(5000)
+        );
+        assert_eq!(
+            trial_balance["totals"][0]["credits"]["minor_units"],
+            json!(5000)
+        );
+        let (status, _) = get_json(&app, "/api/bank-accounts/5678").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn accounts_and_customers_are_only_read_by_their_owners_and_operators() {
+        let app = app().await;
+        register_customer(&app, true).await;
+        post_json(&app, "/api/bank-accounts/1234", open_account()).await;
+
+        for uri in [
+            "/api/bank-accounts/1234",
+            "/api/bank-accounts/1234/standing-orders",
+            "/api/customers/customer-1",
+        ] {
+            let (status, _) = get_json_as(&app, uri, HOLDER_ID_HDR, "customer-2").await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            let (status, _) = get_json_as(&app, uri, OPERATOR_ID_HDR, "operator-1").await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, _) = get_json_as(
+            &app,
+            "/api/bank-accounts/1234/statements/2023-03",
+            HOLDER_ID_HDR,
+            "customer-2",
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn transfers_are_requested_by_holders_of_the_source_account_once_per_key() {
+        let app = app().await;
+        register_customer(&app, true).await;
+        post_json(&app, "/api/bank-accounts/1234", open_account()).await;
+        let transfer = json!({
+            "source_account_id": "1234",
+            "destination_account_id": "5678",
+            "amount": { "minor_units": 2500, "currency": "EUR" }
+        });
+        let request_transfer = |caller: &str| {
+            Request::post("/api/transfers")
+                .header("content-type", "application/json")
+                .header(HOLDER_ID_HDR, caller)
+                .header(IDEMPOTENCY_KEY_HDR, "transfer-1")
+                .body(Body::from(transfer.to_string()))
+                .unwrap()
+        };
+
+        let (status, _) = send(&app, request_transfer("customer-2")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let mut transfer_ids = Vec::new();
+        for _ in 0..2 {
+            let (status, accepted) = send(&app, request_transfer("customer-1")).await;
+            assert_eq!(status, StatusCode::ACCEPTED);
+            transfer_ids.push(accepted["transfer_id"].as_str().unwrap().to_string());
+        }
+        assert_eq!(transfer_ids[0], transfer_ids[1]);
+        assert_ne!(transfer_ids[0], "transfer-1");
+
+        let transfer_uri = format!("/api/transfers/{}", transfer_ids[0]);
+        let (status, _) = get_json(&app, &transfer_uri).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = get_json_as(&app, &transfer_uri, HOLDER_ID_HDR, "customer-2").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn accounts_are_only_opened_for_customers_who_passed_kyc() {
+        let app = app().await;
+        register_customer(&app, false).await;
+        let (status, _) = post_json(&app, "/api/bank-accounts/1234", open_account()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn graphql_queries_read_the_same_views() {
+        let app = app().await;
+        register_customer(&app, true).await;
+        post_json(&app, "/api/bank-accounts/1234", open_account()).await;
+
+        let query = r#"{
+            bankAccountQuery(id: "1234") { accountId status }
+            customerQuery(id: "customer-1") { kycStatus }
+        }"#;
+        let (status, response) = post_json(&app, "/graphql", json!({ "query": query })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            response["data"],
+            json!({
+                "bankAccountQuery": { "accountId": "1234", "status": "OPEN" },
+                "customerQuery": { "kycStatus": "VERIFIED" }
+            })
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use super::*;
+use cqrs_es::persist::ViewRepository;
+use cqrs_es::Query;
+use cqrs_es::{persist::GenericQuery, EventEnvelope, View};
+
+use crate::application::{TransferProcessManager, UnfinishedTransfer, UnfinishedTransfersLookup};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+pub mod transfer_authorization;
+pub mod transfer_graphql;
+pub mod transfer_handlers;
+pub mod transfer_views;
+
+// Re-exports
+
+pub use transfer_authorization::*;
+pub use transfer_graphql::*;
+pub use transfer_handlers::*;
+pub use transfer_views::*;
+
+// The `Notify` wakes the `TransferSaga` up whenever a transfer is requested.
+pub fn get_transfer_cqrs_framework(
+    db: AnyDatabase,
+) -> (
+    Arc<AnyCqrs<Transfer>>,
+    Arc<TransferViewRepository>,
+    Arc<Notify>,
+) {
+    let transfer_requested = Arc::new(Notify::new());
+    let process_manager = TransferProcessManager::new(transfer_requested.clone());
+
+    // A query that stores the current status of each transfer.
+    let transfer_view_repo = Arc::new(AnyViewRepository::new("transfer_query", db.clone()));
+    let mut transfer_query = TransferQuery::new(transfer_view_repo.clone());
+    transfer_query.use_error_handler(Box::new(|e| {
+        tracing::error!("could not update the transfer view: {e}")
+    }));
+
+    let queries: Vec<Box<dyn Query<Transfer>>> =
+        vec![Box::new(transfer_query), Box::new(process_manager)];
+    (
+        Arc::new(crate::any_es::any_cqrs(db, queries, ())),
+        transfer_view_repo,
+        transfer_requested,
+    )
+}
+
+// Finds unfinished transfers from the views stored by the transfer query.
+pub struct TransferViewUnfinishedTransfersLookup {
+    db: AnyDatabase,
+}
+
+impl TransferViewUnfinishedTransfersLookup {
+    pub fn new(db: AnyDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl UnfinishedTransfersLookup for TransferViewUnfinishedTransfersLookup {
+    async fn unfinished_transfers(&self) -> crate::prelude::Result<Vec<UnfinishedTransfer>> {
+        // Every backend has a JSON syntax of its own.
+        let views: Vec<TransferView> = match &self.db {
+            #[cfg(feature = "postgres")]
+            AnyDatabase::Postgres(pool) => {
+                sqlx::query_scalar::<_, sqlx::types::Json<TransferView>>(
+                    "SELECT payload FROM transfer_query WHERE payload->>'status' IN ('Requested', 'SourceDebited', 'Compensating')",
+                )
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|view| view.0)
+                .collect()
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabase::Mysql(pool) => {
+                sqlx::query_scalar::<_, sqlx::types::Json<TransferView>>(
+                    "SELECT payload FROM transfer_query WHERE JSON_UNQUOTE(JSON_EXTRACT(payload, '$.status')) IN ('Requested', 'SourceDebited', 'Compensating')",
+                )
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|view| view.0)
+                .collect()
+            }
+            // The sqlite view repository stores views as JSON text.
+            #[cfg(feature = "sqlite")]
+            AnyDatabase::Sqlite(pool) => sqlx::query_scalar::<_, String>(
+                "SELECT payload FROM transfer_query WHERE json_extract(payload, '$.status') IN ('Requested', 'SourceDebited', 'Compensating')",
+            )
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .map(|view| serde_json::from_str(view))
+            .collect::<Result<_, _>>()?,
+            #[cfg(feature = "in-memory")]
+            AnyDatabase::InMemory(db) => db.read("transfer_query", |views| {
+                views
+                    .values()
+                    .map(|view| serde_json::from_value(view.payload.clone()))
+                    .collect::<Result<_, _>>()
+            })?,
+        };
+        Ok(views
+            .into_iter()
+            .filter_map(TransferView::unfinished)
+            .collect())
+    }
+}
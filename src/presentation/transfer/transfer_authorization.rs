@@ -0,0 +1,44 @@
+use super::*;
+
+// Transfers are requested and read by the holders of the source account and by operators.
+pub fn authorize_transfer(source_account: Option<&BankAccountView>, caller: &Caller) -> bool {
+    caller.is_operator() || source_account.is_some_and(|view| authorize_read(view, caller))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn account_held_by(holder_id: &str) -> BankAccountView {
+        let mut view = BankAccountView::default();
+        view.update(&EventEnvelope {
+            aggregate_id: "1234".to_string(),
+            sequence: 1,
+            payload: BankAccountEvent::AccountOpened {
+                account_id: "1234".to_string(),
+                currency: Currency::EUR,
+                primary_holder_id: Some(holder_id.to_string()),
+                product: AccountProduct::Checking,
+                occurred_at: chrono::DateTime::default(),
+            },
+            metadata: HashMap::new(),
+        });
+        view
+    }
+
+    #[test]
+    fn only_holders_of_the_source_account_and_operators_transfer() {
+        let account = account_held_by("customer-1");
+        let holder = Caller::Customer("customer-1".to_string());
+        let operator = Caller::Operator("operator-1".to_string());
+        assert!(authorize_transfer(Some(&account), &holder));
+        assert!(authorize_transfer(Some(&account), &operator));
+        assert!(authorize_transfer(None, &operator));
+        assert!(!authorize_transfer(
+            Some(&account),
+            &Caller::Customer("customer-2".to_string())
+        ));
+        assert!(!authorize_transfer(None, &holder));
+    }
+}
@@ -0,0 +1,104 @@
+use super::*;
+
+use async_graphql::{Context, Object};
+use cqrs_es::Aggregate;
+
+use crate::application::{
+    CommandOutcome, IdempotentCommands, IdempotentOutcome, IdempotentRequest,
+};
+
+#[derive(Default)]
+pub struct TransferGraphQlQuery {}
+
+#[derive(Default)]
+pub struct TransferGraphQlMutation {}
+
+#[Object]
+impl TransferGraphQlQuery {
+    #[instrument(skip(self, ctx))]
+    /// Get the status of a transfer by its ID
+    async fn transfer_query<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        id: String,
+    ) -> async_graphql::Result<TransferView> {
+        let view_repo = ctx.data::<Arc<AnyViewRepository<TransferView, Transfer>>>()?;
+        let account_view_repo =
+            ctx.data::<Arc<AnyViewRepository<BankAccountView, BankAccount>>>()?;
+        let view = match view_repo.load(&id).await? {
+            Some(view) => view,
+            None => return Err(async_graphql::Error::new("Transfer not found")),
+        };
+        let source_account = account_view_repo.load(&view.source_account_id).await?;
+        if !authorize_transfer(source_account.as_ref(), ctx.data::<Caller>()?) {
+            return Err(async_graphql::Error::new(
+                "Not a holder of the source account",
+            ));
+        }
+        Ok(view)
+    }
+}
+
+#[Object]
+impl TransferGraphQlMutation {
+    #[instrument(skip(self, ctx))]
+    /// Request a transfer between two bank accounts, the transfer completes in the background.
+    /// Retried mutations with the same idempotency key are given the transfer already requested.
+    async fn transfer_mutation<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        command: TransferRequestTransferCommandData,
+        idempotency_key: Option<String>,
+    ) -> async_graphql::Result<TransferView> {
+        let cqrs = ctx.data::<Arc<AnyCqrs<Transfer>>>()?;
+        let view_repo = ctx.data::<Arc<AnyViewRepository<TransferView, Transfer>>>()?;
+        let account_view_repo =
+            ctx.data::<Arc<AnyViewRepository<BankAccountView, BankAccount>>>()?;
+
+        let caller = ctx.data::<Caller>()?;
+        let source_account = account_view_repo.load(&command.source_account_id).await?;
+        if !authorize_transfer(source_account.as_ref(), caller) {
+            return Err(async_graphql::Error::new(
+                "Not a holder of the source account",
+            ));
+        }
+        let metadata = graphql_metadata(idempotency_key.as_deref());
+        let idempotent_commands = ctx.data::<Arc<IdempotentCommands>>()?;
+        let request = IdempotentRequest {
+            aggregate_type: &Transfer::aggregate_type(),
+            aggregate_id: None,
+            scope: &idempotency_scope(&command.source_account_id, caller),
+            request: &idempotent_transfer_request(&command),
+            metadata,
+        };
+        let transfer_id = match idempotent_commands
+            .execute(request, |transfer_id, metadata| async move {
+                cqrs.execute_with_metadata(
+                    &transfer_id,
+                    TransferCommand::RequestTransfer(command),
+                    metadata,
+                )
+                .await
+            })
+            .await
+        {
+            Ok(IdempotentOutcome {
+                request_id: transfer_id,
+                outcome: CommandOutcome::Succeeded,
+            }) => transfer_id,
+            Ok(IdempotentOutcome {
+                outcome: CommandOutcome::Rejected(err),
+                ..
+            }) => {
+                return Err(async_graphql::Error::new(err));
+            }
+            Err(err) => {
+                return Err(async_graphql::Error::new(err.to_string()));
+            }
+        };
+        match view_repo.load(&transfer_id).await? {
+            Some(view) => Ok(view),
+            None => Err(async_graphql::Error::new("Transfer not found")),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use super::*;
+
+use cqrs_es::Aggregate;
+
+use crate::application::{
+    CommandOutcome, IdempotencyError, IdempotentCommands, IdempotentOutcome, IdempotentRequest,
+};
+
+// Returned when a transfer is accepted, the transfer then completes in the background.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TransferAccepted {
+    pub transfer_id: String,
+}
+
+// Serves as our query endpoint to respond with the materialized `TransferView`
+// for the requested transfer.
+#[utoipa::path(
+    get,
+    tag = "Transfers",
+    path = "/api/transfers/{id}",
+    params(
+        ("id" = String, Path, description = "Transfer ID"),
+        ("X-Holder-Id" = Option<String>, Header, description = "Holder of the source account the transfer is read on behalf of"),
+        ("X-Operator-Id" = Option<String>, Header, description = "Operator the transfer is read on behalf of")
+    ),
+    responses(
+        (status = 200, description = "Get transfer status", body = [TransferView]),
+        (status = 403, description = "Not a holder of the source account"),
+        (status = 404, description = "Transfer not found")
+    )
+)]
+#[instrument(skip(view_repo, account_view_repo))]
+pub async fn transfer_query_handler(
+    Path(id): Path<String>,
+    Extension(view_repo): Extension<Arc<AnyViewRepository<TransferView, Transfer>>>,
+    Extension(account_view_repo): Extension<Arc<AnyViewRepository<BankAccountView, BankAccount>>>,
+    CallerExtension(caller): CallerExtension,
+) -> Response {
+    let transfer_view = match view_repo.load(&id).await {
+        Ok(Some(view)) => view,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+        }
+    };
+    let source_account = match account_view_repo
+        .load(&transfer_view.source_account_id)
+        .await
+    {
+        Ok(view) => view,
+        Err(err) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+        }
+    };
+    if !authorize_transfer(source_account.as_ref(), &caller) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    (StatusCode::OK, Json(transfer_view)).into_response()
+}
+
+// Requests a transfer between two bank accounts. The transfer is given the id of the request,
+// so that a retried request is given the transfer it already requested.
+#[utoipa::path(
+    post,
+    tag = "Transfers",
+    path = "/api/transfers",
+    params(
+      ("X-Holder-Id" = Option<String>, Header, description = "Holder of the source account the transfer is requested on behalf of"),
+      ("X-Operator-Id" = Option<String>, Header, description = "Operator the transfer is requested by"),
+      ("Idempotency-Key" = Option<String>, Header, description = "Retried requests with the same key get the original outcome and transfer instead of requesting another transfer"),
+    ),
+    responses(
+      (status = 202, description = "Transfer accepted", body = TransferAccepted),
+      (status = 400, description = "Transfer rejected", body = [String]),
+      (status = 403, description = "Not a holder of the source account"),
+      (status = 409, description = "Idempotency key reused for a different request, or the original request is still in progress", body = [String])
+    ),
+    request_body(content = TransferRequestTransferCommandData, description = "Transfer to carry out", content_type = "application/json"),
+)]
+#[instrument(skip(cqrs, account_view_repo, idempotent_commands))]
+pub async fn transfer_command_handler(
+    Extension(cqrs): Extension<Arc<AnyCqrs<Transfer>>>,
+    Extension(account_view_repo): Extension<Arc<AnyViewRepository<BankAccountView, BankAccount>>>,
+    Extension(idempotent_commands): Extension<Arc<IdempotentCommands>>,
+    CallerExtension(caller): CallerExtension,
+    MetadataExtension(metadata): MetadataExtension,
+    Json(command): Json<TransferRequestTransferCommandData>,
+) -> Response {
+    let source_account = match account_view_repo.load(&command.source_account_id).await {
+        Ok(view) => view,
+        Err(err) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+        }
+    };
+    if !authorize_transfer(source_account.as_ref(), &caller) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let request = IdempotentRequest {
+        aggregate_type: &Transfer::aggregate_type(),
+        aggregate_id: None,
+        scope: &idempotency_scope(&command.source_account_id, &caller),
+        request: &idempotent_transfer_request(&command),
+        metadata,
+    };
+    let outcome = idempotent_commands
+        .execute(request, |transfer_id, metadata| async move {
+            cqrs.execute_with_metadata(
+                &transfer_id,
+                TransferCommand::RequestTransfer(command),
+                metadata,
+            )
+            .await
+        })
+        .await;
+    match outcome {
+        Ok(IdempotentOutcome {
+            request_id: transfer_id,
+            outcome: CommandOutcome::Succeeded,
+        }) => (StatusCode::ACCEPTED, Json(TransferAccepted { transfer_id })).into_response(),
+        Ok(IdempotentOutcome {
+            outcome: CommandOutcome::Rejected(err),
+            ..
+        }) => (StatusCode::BAD_REQUEST, err).into_response(),
+        Err(err @ (IdempotencyError::KeyReused | IdempotencyError::InProgress)) => {
+            (StatusCode::CONFLICT, err.to_string()).into_response()
+        }
+        Err(err @ IdempotencyError::CommandFailed(_)) => {
+            (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+        }
+        Err(err @ IdempotencyError::Store(_)) => {
+            tracing::error!("{err}");
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        }
+    }
+}
+
+// What a retried transfer request must match to be given the outcome of the original one.
+pub fn idempotent_transfer_request(command: &TransferRequestTransferCommandData) -> String {
+    serde_json::json!({ "transfer": command }).to_string()
+}
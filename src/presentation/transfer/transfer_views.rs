@@ -0,0 +1,82 @@
+use async_graphql::SimpleObject;
+use ts_rs::TS;
+
+use super::*;
+
+// Stores the current status of each transfer, keyed by transfer id.
+pub type TransferQuery = GenericQuery<TransferViewRepository, TransferView, Transfer>;
+
+pub type TransferViewRepository = AnyViewRepository<TransferView, Transfer>;
+
+#[derive(SimpleObject, Debug, Default, Serialize, Deserialize, ToSchema, ToResponse, TS)]
+#[ts(export, export_to = "frontend/src/bindings/")]
+pub struct TransferView {
+    transfer_id: String,
+    pub(crate) source_account_id: String,
+    destination_account_id: String,
+    amount: Money,
+    status: TransferStatus,
+    failure_reason: Option<String>,
+}
+
+impl TransferView {
+    // The transfer as the `TransferSaga` carries it on, unless there is nothing left to do.
+    pub fn unfinished(self) -> Option<UnfinishedTransfer> {
+        match self.status {
+            TransferStatus::Requested
+            | TransferStatus::SourceDebited
+            | TransferStatus::Compensating => Some(UnfinishedTransfer {
+                transfer_id: self.transfer_id,
+                source_account_id: self.source_account_id,
+                destination_account_id: self.destination_account_id,
+                amount: self.amount,
+                status: self.status,
+                failure_reason: self.failure_reason,
+            }),
+            TransferStatus::NotRequested
+            | TransferStatus::Completed
+            | TransferStatus::Failed
+            | TransferStatus::Compensated => None,
+        }
+    }
+}
+
+impl View<Transfer> for TransferView {
+    fn update(&mut self, event: &EventEnvelope<Transfer>) {
+        match &event.payload {
+            TransferEvent::TransferRequested {
+                source_account_id,
+                destination_account_id,
+                amount,
+            } => {
+                self.transfer_id = event.aggregate_id.clone();
+                self.source_account_id = source_account_id.clone();
+                self.destination_account_id = destination_account_id.clone();
+                self.amount = *amount;
+                self.status = TransferStatus::Requested;
+            }
+
+            TransferEvent::SourceAccountDebited { .. } => {
+                self.status = TransferStatus::SourceDebited;
+            }
+
+            TransferEvent::SourceAccountDebitFailed { reason, .. } => {
+                self.status = TransferStatus::Failed;
+                self.failure_reason = Some(reason.clone());
+            }
+
+            TransferEvent::DestinationAccountCredited { .. } => {
+                self.status = TransferStatus::Completed;
+            }
+
+            TransferEvent::DestinationAccountCreditFailed { reason, .. } => {
+                self.status = TransferStatus::Compensating;
+                self.failure_reason = Some(reason.clone());
+            }
+
+            TransferEvent::SourceAccountRefunded { .. } => {
+                self.status = TransferStatus::Compensated;
+            }
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! An event store and view repository backed by SQLite, for running the service against a
+//! file or in-memory database. It follows the `postgres-es` and `mysql-es` crates used by the
+//! other backends, including their table layout.
+
+pub mod sqlite_errors;
+pub mod sqlite_event_repository;
+pub mod sqlite_view_repository;
+
+// Re-exports
+pub use sqlite_errors::*;
+pub use sqlite_event_repository::*;
+pub use sqlite_view_repository::*;
@@ -0,0 +1,25 @@
+use cqrs_es::persist::PersistenceError;
+
+// SQLITE_CONSTRAINT_PRIMARYKEY and SQLITE_CONSTRAINT_UNIQUE, raised when another command
+// committed the same event sequence number first.
+const CONSTRAINT_VIOLATION_CODES: [&str; 2] = ["1555", "2067"];
+
+pub fn persistence_error(err: sqlx::Error) -> PersistenceError {
+    match &err {
+        sqlx::Error::Database(database_error)
+            if database_error
+                .code()
+                .is_some_and(|code| CONSTRAINT_VIOLATION_CODES.contains(&code.as_ref())) =>
+        {
+            PersistenceError::OptimisticLockError
+        }
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => {
+            PersistenceError::ConnectionError(Box::new(err))
+        }
+        _ => PersistenceError::UnknownError(Box::new(err)),
+    }
+}
+
+pub fn deserialization_error(err: serde_json::Error) -> PersistenceError {
+    PersistenceError::DeserializationError(Box::new(err))
+}
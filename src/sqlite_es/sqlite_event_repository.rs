@@ -0,0 +1,300 @@
+use async_trait::async_trait;
+use cqrs_es::persist::{
+    PersistedEventRepository, PersistenceError, ReplayStream, SerializedEvent, SerializedSnapshot,
+};
+use cqrs_es::Aggregate;
+use serde_json::Value;
+use sqlx::{Pool, Sqlite, Transaction};
+
+use super::{deserialization_error, persistence_error};
+
+const INSERT_EVENT: &str = "INSERT INTO events (aggregate_type, aggregate_id, sequence, event_type, event_version, payload, metadata) VALUES (?, ?, ?, ?, ?, ?, ?)";
+const SELECT_EVENTS: &str = "SELECT aggregate_type, aggregate_id, sequence, event_type, event_version, payload, metadata FROM events WHERE aggregate_type = ? AND aggregate_id = ? ORDER BY sequence";
+const SELECT_LAST_EVENTS: &str = "SELECT aggregate_type, aggregate_id, sequence, event_type, event_version, payload, metadata FROM events WHERE aggregate_type = ? AND aggregate_id = ? AND sequence > ? ORDER BY sequence";
+const SELECT_ALL_EVENTS: &str = "SELECT aggregate_type, aggregate_id, sequence, event_type, event_version, payload, metadata FROM events WHERE aggregate_type = ? ORDER BY aggregate_id, sequence";
+const SELECT_SNAPSHOT: &str = "SELECT aggregate_type, aggregate_id, last_sequence, current_snapshot, payload FROM snapshots WHERE aggregate_type = ? AND aggregate_id = ?";
+const INSERT_SNAPSHOT: &str = "INSERT INTO snapshots (aggregate_type, aggregate_id, last_sequence, current_snapshot, payload) VALUES (?, ?, ?, ?, ?)";
+const UPDATE_SNAPSHOT: &str = "UPDATE snapshots SET last_sequence = ?, payload = ?, current_snapshot = ? WHERE aggregate_type = ? AND aggregate_id = ? AND current_snapshot = ?";
+
+// How many events are buffered while replaying them to a query.
+const REPLAY_QUEUE_SIZE: usize = 1000;
+
+type EventRow = (String, String, i64, String, String, String, String);
+
+// Events in the `events` table and snapshots in the `snapshots` table.
+pub struct SqliteEventRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteEventRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    async fn select_events(
+        &self,
+        sql: &str,
+        binds: &[&str],
+        after_sequence: Option<usize>,
+    ) -> Result<Vec<SerializedEvent>, PersistenceError> {
+        let mut query = sqlx::query_as::<_, EventRow>(sql);
+        for bind in binds {
+            query = query.bind(*bind);
+        }
+        if let Some(sequence) = after_sequence {
+            query = query.bind(sequence as i64);
+        }
+        query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(persistence_error)?
+            .into_iter()
+            .map(serialized_event)
+            .collect()
+    }
+
+    async fn insert_events(
+        transaction: &mut Transaction<'_, Sqlite>,
+        events: &[SerializedEvent],
+    ) -> Result<(), PersistenceError> {
+        for event in events {
+            sqlx::query(INSERT_EVENT)
+                .bind(&event.aggregate_type)
+                .bind(&event.aggregate_id)
+                .bind(event.sequence as i64)
+                .bind(&event.event_type)
+                .bind(&event.event_version)
+                .bind(event.payload.to_string())
+                .bind(event.metadata.to_string())
+                .execute(&mut *transaction)
+                .await
+                .map_err(persistence_error)?;
+        }
+        Ok(())
+    }
+
+    async fn replay(
+        &self,
+        events: Result<Vec<SerializedEvent>, PersistenceError>,
+    ) -> Result<ReplayStream, PersistenceError> {
+        let events = events?;
+        let (mut feed, stream) = ReplayStream::new(REPLAY_QUEUE_SIZE);
+        tokio::spawn(async move {
+            for event in events {
+                if feed.push(Ok(event)).await.is_err() {
+                    // The stream was dropped.
+                    return;
+                }
+            }
+        });
+        Ok(stream)
+    }
+}
+
+fn serialized_event(
+    (aggregate_type, aggregate_id, sequence, event_type, event_version, payload, metadata): EventRow,
+) -> Result<SerializedEvent, PersistenceError> {
+    Ok(SerializedEvent {
+        aggregate_id,
+        sequence: sequence as usize,
+        aggregate_type,
+        event_type,
+        event_version,
+        payload: serde_json::from_str(&payload).map_err(deserialization_error)?,
+        metadata: serde_json::from_str(&metadata).map_err(deserialization_error)?,
+    })
+}
+
+#[async_trait]
+impl PersistedEventRepository for SqliteEventRepository {
+    async fn get_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<Vec<SerializedEvent>, PersistenceError> {
+        self.select_events(
+            SELECT_EVENTS,
+            &[A::aggregate_type().as_str(), aggregate_id],
+            None,
+        )
+        .await
+    }
+
+    async fn get_last_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+        last_sequence: usize,
+    ) -> Result<Vec<SerializedEvent>, PersistenceError> {
+        self.select_events(
+            SELECT_LAST_EVENTS,
+            &[A::aggregate_type().as_str(), aggregate_id],
+            Some(last_sequence),
+        )
+        .await
+    }
+
+    async fn get_snapshot<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<Option<SerializedSnapshot>, PersistenceError> {
+        let row: Option<(String, String, i64, i64, String)> = sqlx::query_as(SELECT_SNAPSHOT)
+            .bind(A::aggregate_type())
+            .bind(aggregate_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(persistence_error)?;
+        let Some((_, aggregate_id, last_sequence, current_snapshot, payload)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(SerializedSnapshot {
+            aggregate_id,
+            aggregate: serde_json::from_str(&payload).map_err(deserialization_error)?,
+            current_sequence: last_sequence as usize,
+            current_snapshot: current_snapshot as usize,
+        }))
+    }
+
+    async fn persist<A: Aggregate>(
+        &self,
+        events: &[SerializedEvent],
+        snapshot_update: Option<(String, Value, usize)>,
+    ) -> Result<(), PersistenceError> {
+        let mut transaction = self.pool.begin().await.map_err(persistence_error)?;
+        Self::insert_events(&mut transaction, events).await?;
+        if let Some((aggregate_id, aggregate, current_snapshot)) = snapshot_update {
+            let last_sequence = events.last().map_or(0, |event| event.sequence) as i64;
+            let updated = if current_snapshot == 1 {
+                sqlx::query(INSERT_SNAPSHOT)
+                    .bind(A::aggregate_type())
+                    .bind(&aggregate_id)
+                    .bind(last_sequence)
+                    .bind(current_snapshot as i64)
+                    .bind(aggregate.to_string())
+                    .execute(&mut transaction)
+                    .await
+            } else {
+                sqlx::query(UPDATE_SNAPSHOT)
+                    .bind(last_sequence)
+                    .bind(aggregate.to_string())
+                    .bind(current_snapshot as i64)
+                    .bind(A::aggregate_type())
+                    .bind(&aggregate_id)
+                    .bind((current_snapshot - 1) as i64)
+                    .execute(&mut transaction)
+                    .await
+            }
+            .map_err(persistence_error)?;
+            if updated.rows_affected() != 1 {
+                return Err(PersistenceError::OptimisticLockError);
+            }
+        }
+        transaction.commit().await.map_err(persistence_error)?;
+        Ok(())
+    }
+
+    async fn stream_events<A: Aggregate>(
+        &self,
+        aggregate_id: &str,
+    ) -> Result<ReplayStream, PersistenceError> {
+        let events = self.get_events::<A>(aggregate_id).await;
+        self.replay(events).await
+    }
+
+    async fn stream_all_events<A: Aggregate>(&self) -> Result<ReplayStream, PersistenceError> {
+        let events = self
+            .select_events(SELECT_ALL_EVENTS, &[A::aggregate_type().as_str()], None)
+            .await;
+        self.replay(events).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Transfer;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn repository() -> SqliteEventRepository {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&pool)
+            .await
+            .unwrap();
+        SqliteEventRepository::new(pool)
+    }
+
+    fn event(sequence: usize) -> SerializedEvent {
+        SerializedEvent {
+            aggregate_id: "transfer-1".to_string(),
+            sequence,
+            aggregate_type: "transfer".to_string(),
+            event_type: "SourceAccountDebited".to_string(),
+            event_version: "1.0".to_string(),
+            payload: json!({ "SourceAccountDebited": { "account_id": "1234" } }),
+            metadata: json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn persisted_events_are_loaded_in_order() {
+        let repository = repository().await;
+        repository
+            .persist::<Transfer>(&[event(1), event(2)], None)
+            .await
+            .unwrap();
+        repository
+            .persist::<Transfer>(&[event(3)], None)
+            .await
+            .unwrap();
+
+        let events = repository
+            .get_events::<Transfer>("transfer-1")
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event(1), event(2), event(3)]);
+        let events = repository
+            .get_last_events::<Transfer>("transfer-1", 2)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event(3)]);
+    }
+
+    #[tokio::test]
+    async fn committing_an_existing_sequence_number_is_an_optimistic_lock_error() {
+        let repository = repository().await;
+        repository
+            .persist::<Transfer>(&[event(1)], None)
+            .await
+            .unwrap();
+        let result = repository.persist::<Transfer>(&[event(1)], None).await;
+        assert!(matches!(result, Err(PersistenceError::OptimisticLockError)));
+    }
+
+    #[tokio::test]
+    async fn snapshots_replace_the_previous_snapshot() {
+        let repository = repository().await;
+        let snapshot = |n: usize| Some(("transfer-1".to_string(), json!({ "n": n }), n));
+        repository
+            .persist::<Transfer>(&[event(1)], snapshot(1))
+            .await
+            .unwrap();
+        repository
+            .persist::<Transfer>(&[event(2)], snapshot(2))
+            .await
+            .unwrap();
+
+        let snapshot = repository
+            .get_snapshot::<Transfer>("transfer-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.aggregate, json!({ "n": 2 }));
+        assert_eq!(snapshot.current_sequence, 2);
+        assert_eq!(snapshot.current_snapshot, 2);
+    }
+}
@@ -0,0 +1,92 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use cqrs_es::persist::{PersistenceError, ViewContext, ViewRepository};
+use cqrs_es::{Aggregate, View};
+use sqlx::{Pool, Sqlite};
+
+use super::{deserialization_error, persistence_error};
+
+// Stores views as JSON in a table of their own, with the `view_id`, `version` and `payload`
+// columns of the other backends' view tables.
+pub struct SqliteViewRepository<V, A> {
+    insert_sql: String,
+    update_sql: String,
+    select_sql: String,
+    pool: Pool<Sqlite>,
+    _phantom: PhantomData<(V, A)>,
+}
+
+impl<V, A> SqliteViewRepository<V, A>
+where
+    V: View<A>,
+    A: Aggregate,
+{
+    pub fn new(view_name: &str, pool: Pool<Sqlite>) -> Self {
+        Self {
+            insert_sql: format!(
+                "INSERT INTO {view_name} (payload, version, view_id) VALUES (?, ?, ?)"
+            ),
+            update_sql: format!(
+                "UPDATE {view_name} SET payload = ?, version = ? WHERE view_id = ? AND version = ?"
+            ),
+            select_sql: format!("SELECT version, payload FROM {view_name} WHERE view_id = ?"),
+            pool,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<V, A> ViewRepository<V, A> for SqliteViewRepository<V, A>
+where
+    V: View<A>,
+    A: Aggregate,
+{
+    async fn load(&self, view_id: &str) -> Result<Option<V>, PersistenceError> {
+        Ok(self.load_with_context(view_id).await?.map(|(view, _)| view))
+    }
+
+    async fn load_with_context(
+        &self,
+        view_id: &str,
+    ) -> Result<Option<(V, ViewContext)>, PersistenceError> {
+        let row: Option<(i64, String)> = sqlx::query_as(&self.select_sql)
+            .bind(view_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(persistence_error)?;
+        let Some((version, payload)) = row else {
+            return Ok(None);
+        };
+        let view = serde_json::from_str(&payload).map_err(deserialization_error)?;
+        Ok(Some((view, ViewContext::new(view_id.to_string(), version))))
+    }
+
+    async fn update_view(&self, view: V, context: ViewContext) -> Result<(), PersistenceError> {
+        let payload = serde_json::to_string(&view)
+            .map_err(|err| PersistenceError::UnknownError(Box::new(err)))?;
+        let version = context.version + 1;
+        let updated = if context.version == 0 {
+            sqlx::query(&self.insert_sql)
+                .bind(payload)
+                .bind(version)
+                .bind(&context.view_instance_id)
+                .execute(&self.pool)
+                .await
+        } else {
+            sqlx::query(&self.update_sql)
+                .bind(payload)
+                .bind(version)
+                .bind(&context.view_instance_id)
+                .bind(context.version)
+                .execute(&self.pool)
+                .await
+        }
+        .map_err(persistence_error)?;
+        if updated.rows_affected() != 1 {
+            return Err(PersistenceError::OptimisticLockError);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+//! Runs the service binary against the in-memory backend, so that the wiring in `main` is
+//! exercised by the test suite and not only by the build.
+
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+const BINARY: &str = env!("CARGO_BIN_EXE_example-veloxide-api");
+
+// Kills the service when the test is over, whether it passed or not.
+struct Service(Child);
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn service_command(port: u16) -> Command {
+    let mut command = Command::new(BINARY);
+    command
+        .env("DATABASE_URL", "memory:")
+        .env("CONFIGURATION_FILE_PATH", "veloxide-config.yaml")
+        .env("FX_RATES_FILE_PATH", "fx-rates.yaml")
+        .env("HTTP_PORT", port.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+#[tokio::test]
+async fn service_starts_and_reports_healthy() {
+    let port = free_port();
+    let mut service = Service(service_command(port).spawn().unwrap());
+    let client = hyper::Client::new();
+    let uri: hyper::Uri = format!("http://127.0.0.1:{port}/health").parse().unwrap();
+
+    for _ in 0..100 {
+        if let Some(status) = service.0.try_wait().unwrap() {
+            panic!("the service exited on startup with {status}");
+        }
+        if let Ok(response) = client.get(uri.clone()).await {
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(body.as_ref(), b"HEALTHY");
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("the service did not report healthy within 10 seconds");
+}
+
+#[test]
+fn migrate_command_exits_once_migrated() {
+    let status = service_command(free_port())
+        .arg("migrate")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn unknown_commands_are_rejected() {
+    let status = service_command(free_port()).arg("serve").status().unwrap();
+    assert!(!status.success());
+}